@@ -0,0 +1,85 @@
+//! כיול הביטחון מתוצאות העריכה שנצברו ביומן הכיול:
+//! `cargo run --release --bin fit_calibration -- [--samples models/calibration_samples.jsonl]
+//!  [--output models/calibration.json] [--report <קובץ .svg או .json>] [--config <קובץ>]`
+//! הממשק טוען את המיפויים מ-models/calibration.json בהפעלה הבאה
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use rustohebru::evaluation::calibration::{
+    load_samples, CalibrationConfig, ConfidenceCalibrator, DEFAULT_CALIBRATION_PATH, DEFAULT_SAMPLES_PATH,
+};
+
+struct Args {
+    samples: PathBuf,
+    output: PathBuf,
+    report: Option<PathBuf>,
+    config: Option<PathBuf>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        samples: PathBuf::from(DEFAULT_SAMPLES_PATH),
+        output: PathBuf::from(DEFAULT_CALIBRATION_PATH),
+        report: None,
+        config: None,
+    };
+    let mut iter = std::env::args().skip(1);
+    while let Some(flag) = iter.next() {
+        let value = iter.next().map(PathBuf::from).ok_or_else(|| format!("חסר ערך ל-{}", flag))?;
+        match flag.as_str() {
+            "--samples" => args.samples = value,
+            "--output" => args.output = value,
+            "--report" => args.report = Some(value),
+            "--config" => args.config = Some(value),
+            other => return Err(format!("פרמטר לא מוכר: {}", other)),
+        }
+    }
+    Ok(args)
+}
+
+fn run() -> Result<(), String> {
+    let args = parse_args()?;
+    let config = match args.config {
+        Some(path) => {
+            let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+            serde_json::from_str(&content).map_err(|e| e.to_string())?
+        }
+        None => CalibrationConfig::default(),
+    };
+
+    let samples = load_samples(&args.samples).map_err(|e| e.to_string())?;
+    let (calibrator, report) = ConfidenceCalibrator::fit(&samples, &config).map_err(|e| e.to_string())?;
+
+    for calibration in &report.calibrations {
+        let backend = calibration.backend.map_or("כל המנועים".to_string(), |backend| format!("{:?}", backend));
+        println!(
+            "{}: ECE {:.3} -> {:.3}, Brier {:.3} -> {:.3} ({} דוגמאות בצד)",
+            backend,
+            calibration.ece_before,
+            calibration.ece_after,
+            calibration.brier_before,
+            calibration.brier_after,
+            calibration.held_out
+        );
+    }
+
+    if let Some(parent) = args.output.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    calibrator.save(&args.output).map_err(|e| e.to_string())?;
+    if let Some(path) = args.report {
+        report.save(&path).map_err(|e| e.to_string())?;
+    }
+    println!("{} דוגמאות, המיפויים נשמרו ב-{}", report.samples, args.output.display());
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
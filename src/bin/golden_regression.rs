@@ -0,0 +1,120 @@
+//! בדיקת רגרסיה מול סט הזהב דרך TranslationEngine, כמו בממשק; --model מוסיף מודל ONNX לכיוון ru-he:
+//! `cargo run --release --bin golden_regression -- [--model <תיקייה>] [--golden golden/ru-he.json]
+//!  [--baseline golden/ru-he.baseline.json] [--thresholds <קובץ>] [--diff <קובץ>] [--write-baseline <קובץ>]`
+//! קוד היציאה 1 כשאחד המדדים ירד מעבר לסף. בלי מודל המנוע מחזיר את המקור, וההרצה נכשלת במקום ליצור בסיס
+//! שאינו מוכיח דבר; את הבסיס הראשון יוצרים עם --model ו---write-baseline
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+use rustohebru::evaluation::regression::{
+    translate_golden, GoldenSet, RegressionBaseline, RegressionRunner, RegressionThresholds, DEFAULT_BASELINE_PATH,
+};
+use rustohebru::evaluation::{Evaluator, FormalityLevel, StyleGuide};
+use rustohebru::language_detection::Language;
+use rustohebru::neural::onnx::OnnxTranslator;
+use rustohebru::translation::TranslationEngine;
+
+#[derive(Default)]
+struct Args {
+    model: Option<PathBuf>,
+    golden: PathBuf,
+    baseline: PathBuf,
+    thresholds: Option<PathBuf>,
+    diff: Option<PathBuf>,
+    write_baseline: Option<PathBuf>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        golden: PathBuf::from("golden/ru-he.json"),
+        baseline: PathBuf::from(DEFAULT_BASELINE_PATH),
+        ..Default::default()
+    };
+    let mut iter = std::env::args().skip(1);
+    while let Some(flag) = iter.next() {
+        let value = iter.next().map(PathBuf::from).ok_or_else(|| format!("חסר ערך ל-{}", flag))?;
+        match flag.as_str() {
+            "--model" => args.model = Some(value),
+            "--golden" => args.golden = value,
+            "--baseline" => args.baseline = value,
+            "--thresholds" => args.thresholds = Some(value),
+            "--diff" => args.diff = Some(value),
+            "--write-baseline" => args.write_baseline = Some(value),
+            other => return Err(format!("פרמטר לא מוכר: {}", other)),
+        }
+    }
+    Ok(args)
+}
+
+async fn run() -> Result<bool, String> {
+    let args = parse_args()?;
+
+    let golden = GoldenSet::load(&args.golden).map_err(|e| e.to_string())?;
+    // בלי בסיס שמור ההרצה רק יוצרת בסיס חדש
+    let baseline = if args.baseline.exists() {
+        Some(RegressionBaseline::load(&args.baseline).map_err(|e| e.to_string())?)
+    } else {
+        println!("אין בסיס ב-{}, ההרצה לא משווה", args.baseline.display());
+        None
+    };
+    let thresholds = match args.thresholds {
+        Some(path) => {
+            let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+            serde_json::from_str(&content).map_err(|e| e.to_string())?
+        }
+        None => RegressionThresholds::default(),
+    };
+
+    let mut engine = TranslationEngine::new();
+    if let Some(model) = args.model {
+        let translator = OnnxTranslator::load(&model).map_err(|e| e.to_string())?;
+        engine.register_backend(Language::Russian, Language::Hebrew, Arc::new(translator));
+    }
+    let outputs = translate_golden(&engine, &golden).await.map_err(|e| e.to_string())?;
+
+    let evaluator = Evaluator::new(HashMap::new(), HashMap::new(), StyleGuide::new(FormalityLevel::Formal));
+    let report = RegressionRunner::new(evaluator)
+        .with_thresholds(thresholds)
+        .compare(&golden, env!("CARGO_PKG_VERSION"), baseline.as_ref(), outputs)
+        .map_err(|e| e.to_string())?;
+
+    println!("{}", report.summary());
+    if report.golden_version_changed {
+        println!("אזהרה: הבסיס נוצר מגרסה אחרת של סט הזהב, ההשוואה דולגה; יש לשמור בסיס חדש עם --write-baseline");
+    }
+    for change in report.changed_segments.iter().filter(|c| c.regressed) {
+        println!(
+            "\n[{}] chrF {:.1} -> {:.1}\n  מקור: {}\n  ייחוס: {}\n  לפני: {}\n  אחרי: {}",
+            change.id,
+            change.baseline_chrf.unwrap_or_default(),
+            change.chrf,
+            change.source,
+            change.reference,
+            change.baseline_output.as_deref().unwrap_or("-"),
+            change.output
+        );
+    }
+
+    if let Some(path) = args.diff {
+        let diff = serde_json::to_string_pretty(&report.changed_segments).map_err(|e| e.to_string())?;
+        std::fs::write(path, diff).map_err(|e| e.to_string())?;
+    }
+    if let Some(path) = args.write_baseline {
+        report.baseline.save(&path).map_err(|e| e.to_string())?;
+    }
+    Ok(report.passed())
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    match run().await {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,43 @@
+//! מריץ את הדוגמאות שבקבצי כללי QA, לכותבי כללים:
+//! `cargo run --bin qa_rules -- [--no-defaults] <קובץ או תיקייה>...`
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use rustohebru::quality_control::rules::RuleEngine;
+
+fn main() -> ExitCode {
+    let mut include_defaults = true;
+    let mut paths = Vec::new();
+    for arg in std::env::args().skip(1) {
+        if arg == "--no-defaults" {
+            include_defaults = false;
+        } else {
+            paths.push(PathBuf::from(arg));
+        }
+    }
+
+    let engine = match RuleEngine::load(paths, include_defaults) {
+        Ok(engine) => engine,
+        Err(e) => {
+            eprintln!("שגיאה בטעינת הכללים: {:#}", e);
+            return ExitCode::from(2);
+        }
+    };
+
+    let results = engine.run_tests();
+    let failed = results.iter().filter(|r| !r.passed).count();
+    for result in &results {
+        if result.passed {
+            println!("ok    {} #{}", result.rule_id, result.test);
+        } else {
+            println!("FAIL  {} #{}: {}", result.rule_id, result.test, result.detail);
+        }
+    }
+    println!("{} דוגמאות, {} נכשלו", results.len(), failed);
+
+    if failed == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
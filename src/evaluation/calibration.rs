@@ -0,0 +1,636 @@
+//! כיול הביטחון של סגמנטים מתורגמים: הציון הגולמי של כל מנוע ממופה להסתברות שהמתרגם יקבל את הסגמנט
+//! בלי עריכה מהותית. המיפוי מאומן על תוצאות העריכה שנרשמו במנהל הלמידה, בסקלת טמפרטורה או ברגרסיה
+//! איזוטונית לכל מנוע, והדוח כולל דיאגרמות אמינות ו-ECE לפני הכיול ואחריו
+
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Serialize, Deserialize};
+use thiserror::Error;
+
+use super::metrics::{CorpusMetric, Ter};
+use crate::learning_manager::{LearningEvent, LearningEventType};
+use crate::system_combination::CandidateSource;
+
+/// המיפויים שהמנוע טוען בהפעלה
+pub const DEFAULT_CALIBRATION_PATH: &str = "models/calibration.json";
+
+/// יומן תוצאות העריכה שהממשק צובר לכיול הבא
+pub const DEFAULT_SAMPLES_PATH: &str = "models/calibration_samples.jsonl";
+
+/// דירוג משוב (1-5) שממנו הסגמנט נחשב מקובל
+const ACCEPTED_RATING: i32 = 4;
+
+/// הביטחון נחסם מ-0 ו-1 לפני חישוב logit
+const CONFIDENCE_EPSILON: f64 = 1e-4;
+
+#[derive(Error, Debug)]
+pub enum CalibrationError {
+    #[error("אין מספיק תוצאות עריכה לכיול: {found}, נדרשות לפחות {required}")]
+    NotEnoughSamples { found: usize, required: usize },
+
+    #[error("שגיאת קובץ: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("קובץ לא תקין: {0}")]
+    Format(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CalibrationMethod {
+    Temperature,
+    Isotonic,
+    /// איזוטונית כשיש מספיק דוגמאות למנוע, אחרת טמפרטורה
+    #[default]
+    Auto,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CalibrationConfig {
+    pub method: CalibrationMethod,
+    /// HTER מקסימלי שבו הסגמנט עדיין נחשב כמקובל בלי עריכה מהותית
+    pub accept_hter: f64,
+    /// מינימום דוגמאות למיפוי נפרד למנוע; מנוע עם פחות משתמש במיפוי המשותף
+    pub min_samples: usize,
+    pub min_isotonic_samples: usize,
+    /// מספר הסלים בדיאגרמת האמינות וב-ECE
+    pub bins: usize,
+    /// חלק הדוגמאות שלא משתתף באימון המיפוי, ועליו נמדדים ECE ו-Brier
+    pub holdout_ratio: f64,
+    /// זרע לחלוקה בין אימון לבדיקה, כך שהדוח ניתן לשחזור
+    pub seed: u64,
+}
+
+impl Default for CalibrationConfig {
+    fn default() -> Self {
+        Self {
+            method: CalibrationMethod::Auto,
+            accept_hter: 0.1,
+            min_samples: 30,
+            min_isotonic_samples: 200,
+            bins: 10,
+            holdout_ratio: 0.2,
+            seed: 42,
+        }
+    }
+}
+
+/// תוצאת עריכה אחת: הביטחון הגולמי שהמנוע נתן והאם הסגמנט התקבל
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalibrationSample {
+    pub backend: Option<CandidateSource>,
+    pub confidence: f64,
+    pub accepted: bool,
+}
+
+impl CalibrationSample {
+    pub fn new(backend: Option<CandidateSource>, confidence: f64, accepted: bool) -> Self {
+        Self { backend, confidence, accepted }
+    }
+}
+
+/// דוגמאות כיול מהיסטוריית האירועים: תיקון נמדד ב-HTER מול התרגום המקורי, ומשוב לפי הדירוג.
+/// אירוע בלי ביטחון גולמי (0) לא נכלל
+pub fn samples_from_events(events: &[LearningEvent], accept_hter: f64) -> Vec<CalibrationSample> {
+    events
+        .iter()
+        .filter(|event| event.metrics.confidence_score > 0.0)
+        .filter_map(|event| {
+            let accepted = match &event.event_type {
+                LearningEventType::Correction { original, corrected, .. } => {
+                    let machine_translation = if original.trim().is_empty() { &event.target_text } else { original };
+                    if machine_translation.trim().is_empty() {
+                        return None;
+                    }
+                    let hter = Ter::default().sentence_score(machine_translation, &[corrected.as_str()]) / 100.0;
+                    hter <= accept_hter
+                }
+                LearningEventType::UserFeedback { rating, .. } => *rating >= ACCEPTED_RATING,
+                _ => return None,
+            };
+            Some(CalibrationSample::new(event.backend, event.metrics.confidence_score as f64, accepted))
+        })
+        .collect()
+}
+
+/// הוספת דוגמאות ליומן הכיול (שורת JSON לכל דוגמה), שנצבר בין הרצות של הממשק
+pub fn append_samples(path: &Path, samples: &[CalibrationSample]) -> Result<(), CalibrationError> {
+    if samples.is_empty() {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    for sample in samples {
+        writeln!(file, "{}", serde_json::to_string(sample)?)?;
+    }
+    Ok(())
+}
+
+pub fn load_samples(path: &Path) -> Result<Vec<CalibrationSample>, CalibrationError> {
+    let file = fs::File::open(path)?;
+    let mut samples = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            samples.push(serde_json::from_str(&line)?);
+        }
+    }
+    Ok(samples)
+}
+
+/// מיפוי מביטחון גולמי לביטחון מכויל; נשמר כ-JSON
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum CalibrationMap {
+    /// sigmoid(logit(c) / T): מעל 1 מרכך ביטחון יתר, מתחת ל-1 מחדד
+    Temperature { temperature: f64 },
+    /// נקודות (ביטחון גולמי, שיעור קבלה) עולות, עם אינטרפולציה לינארית ביניהן
+    Isotonic { points: Vec<(f64, f64)> },
+}
+
+impl CalibrationMap {
+    pub fn apply(&self, confidence: f64) -> f64 {
+        match self {
+            CalibrationMap::Temperature { temperature } => sigmoid(logit(confidence) / temperature),
+            CalibrationMap::Isotonic { points } => interpolate(points, confidence),
+        }
+    }
+
+    /// T שממזער את ה-log loss, בחיפוש חתך הזהב על log T; ה-loss קמור ב-1/T
+    pub fn fit_temperature(samples: &[CalibrationSample]) -> Self {
+        let loss = |log_temperature: f64| {
+            let temperature = log_temperature.exp();
+            samples
+                .iter()
+                .map(|sample| {
+                    let p = sigmoid(logit(sample.confidence) / temperature).clamp(CONFIDENCE_EPSILON, 1.0 - CONFIDENCE_EPSILON);
+                    if sample.accepted { -p.ln() } else { -(1.0 - p).ln() }
+                })
+                .sum::<f64>()
+        };
+
+        let ratio = (5f64.sqrt() - 1.0) / 2.0;
+        let (mut low, mut high) = (0.05f64.ln(), 20f64.ln());
+        for _ in 0..80 {
+            let a = high - ratio * (high - low);
+            let b = low + ratio * (high - low);
+            if loss(a) < loss(b) {
+                high = b;
+            } else {
+                low = a;
+            }
+        }
+        CalibrationMap::Temperature { temperature: ((low + high) / 2.0).exp() }
+    }
+
+    /// רגרסיה איזוטונית של הקבלה על הביטחון
+    pub fn fit_isotonic(samples: &[CalibrationSample]) -> Self {
+        CalibrationMap::Isotonic {
+            points: isotonic_fit(
+                samples
+                    .iter()
+                    .map(|sample| (sample.confidence, if sample.accepted { 1.0 } else { 0.0 }))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// רגרסיה איזוטונית (pool adjacent violators): מיפוי מונוטוני עולה מציון גולמי לערך הנצפי.
+/// נקודות עם אותו x תמיד באותו בלוק; מחזירה את מרכזי הבלוקים לאינטרפולציה
+pub fn isotonic_fit(mut points: Vec<(f64, f64)>) -> Vec<(f64, f64)> {
+    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    // כל בלוק: (סכום x, סכום y, מספר נקודות)
+    let mut blocks: Vec<(f64, f64, f64)> = Vec::new();
+    for (x, y) in points {
+        match blocks.last_mut() {
+            Some(last) if last.0 / last.2 == x => {
+                last.0 += x;
+                last.1 += y;
+                last.2 += 1.0;
+            }
+            _ => blocks.push((x, y, 1.0)),
+        }
+        while blocks.len() > 1 {
+            let (x2, y2, n2) = blocks[blocks.len() - 1];
+            let (x1, y1, n1) = blocks[blocks.len() - 2];
+            if y1 / n1 <= y2 / n2 {
+                break;
+            }
+            blocks.truncate(blocks.len() - 2);
+            blocks.push((x1 + x2, y1 + y2, n1 + n2));
+        }
+    }
+
+    blocks.into_iter().map(|(x, y, n)| (x / n, y / n)).collect()
+}
+
+fn sigmoid(z: f64) -> f64 {
+    1.0 / (1.0 + (-z).exp())
+}
+
+fn logit(p: f64) -> f64 {
+    let p = p.clamp(CONFIDENCE_EPSILON, 1.0 - CONFIDENCE_EPSILON);
+    (p / (1.0 - p)).ln()
+}
+
+fn interpolate(points: &[(f64, f64)], x: f64) -> f64 {
+    let (Some(first), Some(last)) = (points.first(), points.last()) else {
+        return x;
+    };
+    if x <= first.0 {
+        return first.1;
+    }
+    if x >= last.0 {
+        return last.1;
+    }
+
+    let upper = points.iter().position(|(px, _)| *px >= x).unwrap_or(points.len() - 1);
+    let (x0, y0) = points[upper - 1];
+    let (x1, y1) = points[upper];
+    if x1 - x0 <= f64::EPSILON {
+        return y1;
+    }
+    y0 + (y1 - y0) * (x - x0) / (x1 - x0)
+}
+
+/// סל אחד בדיאגרמת האמינות: הביטחון הממוצע מול שיעור הקבלה בפועל
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReliabilityBin {
+    pub lower: f64,
+    pub upper: f64,
+    pub count: usize,
+    pub mean_confidence: f64,
+    pub accuracy: f64,
+}
+
+/// סלים ברוחב שווה על 0-1; סל ריק נשאר עם count 0
+pub fn reliability_bins(predictions: &[(f64, bool)], bins: usize) -> Vec<ReliabilityBin> {
+    let bins = bins.max(1);
+    let mut sums = vec![(0usize, 0.0f64, 0usize); bins];
+    for &(confidence, accepted) in predictions {
+        let index = ((confidence.clamp(0.0, 1.0) * bins as f64) as usize).min(bins - 1);
+        sums[index].0 += 1;
+        sums[index].1 += confidence;
+        sums[index].2 += accepted as usize;
+    }
+
+    sums.into_iter()
+        .enumerate()
+        .map(|(index, (count, confidence_sum, accepted))| {
+            let lower = index as f64 / bins as f64;
+            let upper = (index + 1) as f64 / bins as f64;
+            ReliabilityBin {
+                lower,
+                upper,
+                count,
+                mean_confidence: if count > 0 { confidence_sum / count as f64 } else { (lower + upper) / 2.0 },
+                accuracy: if count > 0 { accepted as f64 / count as f64 } else { 0.0 },
+            }
+        })
+        .collect()
+}
+
+/// ECE: הפער הממוצע בין ביטחון לשיעור קבלה, משוקלל בגודל הסל
+pub fn expected_calibration_error(bins: &[ReliabilityBin]) -> f64 {
+    let total: usize = bins.iter().map(|bin| bin.count).sum();
+    if total == 0 {
+        return 0.0;
+    }
+    bins.iter()
+        .map(|bin| bin.count as f64 * (bin.accuracy - bin.mean_confidence).abs())
+        .sum::<f64>()
+        / total as f64
+}
+
+fn brier_score(predictions: &[(f64, bool)]) -> f64 {
+    if predictions.is_empty() {
+        return 0.0;
+    }
+    predictions
+        .iter()
+        .map(|&(confidence, accepted)| (confidence - if accepted { 1.0 } else { 0.0 }).powi(2))
+        .sum::<f64>()
+        / predictions.len() as f64
+}
+
+/// חלוקה אקראית קבועה (לפי הזרע) לדוגמאות אימון ודוגמאות בצד; נשארת תמיד לפחות דוגמת אימון אחת
+fn split_holdout(samples: &[CalibrationSample], ratio: f64, seed: u64) -> (Vec<CalibrationSample>, Vec<CalibrationSample>) {
+    let mut shuffled = samples.to_vec();
+    shuffled.shuffle(&mut ChaCha8Rng::seed_from_u64(seed));
+    let held_out = ((shuffled.len() as f64 * ratio.clamp(0.0, 1.0)).round() as usize).min(shuffled.len().saturating_sub(1));
+    let held_out_samples = shuffled.split_off(shuffled.len() - held_out);
+    (shuffled, held_out_samples)
+}
+
+/// מדדי הכיול של מנוע אחד; backend ריק הוא המיפוי המשותף לכל המנועים
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackendCalibration {
+    pub backend: Option<CandidateSource>,
+    pub samples: usize,
+    /// הדוגמאות שהוחזקו בצד; המדדים לפני ואחרי נמדדים רק עליהן
+    #[serde(default)]
+    pub held_out: usize,
+    pub map: CalibrationMap,
+    pub ece_before: f64,
+    pub ece_after: f64,
+    pub brier_before: f64,
+    pub brier_after: f64,
+    pub reliability_before: Vec<ReliabilityBin>,
+    pub reliability_after: Vec<ReliabilityBin>,
+}
+
+impl BackendCalibration {
+    fn fit(backend: Option<CandidateSource>, samples: &[CalibrationSample], config: &CalibrationConfig) -> Self {
+        // המיפוי מאומן בלי הדוגמאות שבצד; על דוגמאות האימון עצמן איזוטונית מגיעה ל-ECE כמעט אפס בהגדרה
+        let (training, held_out) = split_holdout(samples, config.holdout_ratio, config.seed);
+        let isotonic = match config.method {
+            CalibrationMethod::Temperature => false,
+            CalibrationMethod::Isotonic => true,
+            CalibrationMethod::Auto => training.len() >= config.min_isotonic_samples,
+        };
+        let map = if isotonic {
+            CalibrationMap::fit_isotonic(&training)
+        } else {
+            CalibrationMap::fit_temperature(&training)
+        };
+
+        let before: Vec<(f64, bool)> = held_out.iter().map(|s| (s.confidence, s.accepted)).collect();
+        let after: Vec<(f64, bool)> = held_out.iter().map(|s| (map.apply(s.confidence), s.accepted)).collect();
+        let reliability_before = reliability_bins(&before, config.bins);
+        let reliability_after = reliability_bins(&after, config.bins);
+
+        Self {
+            backend,
+            samples: samples.len(),
+            held_out: held_out.len(),
+            ece_before: expected_calibration_error(&reliability_before),
+            ece_after: expected_calibration_error(&reliability_after),
+            brier_before: brier_score(&before),
+            brier_after: brier_score(&after),
+            map,
+            reliability_before,
+            reliability_after,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self.backend {
+            None => "all",
+            Some(CandidateSource::TranslationMemory) => "translation_memory",
+            Some(CandidateSource::Dictionary) => "dictionary",
+            Some(CandidateSource::Rules) => "rules",
+            Some(CandidateSource::Neural) => "neural",
+        }
+    }
+}
+
+/// דוח הכיול לצד דוחות ההערכה האחרים: המיפוי המשותף ואחריו מיפוי לכל מנוע שהיו לו מספיק דוגמאות
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalibrationReport {
+    pub samples: usize,
+    pub calibrations: Vec<BackendCalibration>,
+}
+
+impl CalibrationReport {
+    pub fn to_json(&self) -> Result<String, CalibrationError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// דיאגרמת אמינות לכל מיפוי: עמודות שיעור הקבלה אחרי הכיול, נקודות לפני הכיול, והאלכסון
+    pub fn to_svg(&self) -> String {
+        const SIZE: f64 = 240.0;
+        const MARGIN: f64 = 30.0;
+        let panel = SIZE + 2.0 * MARGIN;
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-family=\"sans-serif\" font-size=\"11\">\n",
+            panel * self.calibrations.len().max(1) as f64,
+            panel + 20.0
+        );
+        for (index, calibration) in self.calibrations.iter().enumerate() {
+            let left = index as f64 * panel + MARGIN;
+            let bottom = MARGIN + SIZE;
+            let x = |value: f64| left + value * SIZE;
+            let y = |value: f64| bottom - value * SIZE;
+
+            svg.push_str(&format!(
+                "<text x=\"{}\" y=\"{}\">{} (n={}) ECE {:.3} → {:.3}</text>\n",
+                left, MARGIN - 10.0, calibration.label(), calibration.samples, calibration.ece_before, calibration.ece_after
+            ));
+            svg.push_str(&format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"none\" stroke=\"#999\"/>\n",
+                left, MARGIN, SIZE, SIZE
+            ));
+            for bin in calibration.reliability_after.iter().filter(|bin| bin.count > 0) {
+                svg.push_str(&format!(
+                    "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"#4a7ebb\" fill-opacity=\"0.6\"/>\n",
+                    x(bin.lower), y(bin.accuracy), (bin.upper - bin.lower) * SIZE, bin.accuracy * SIZE
+                ));
+            }
+            for bin in calibration.reliability_before.iter().filter(|bin| bin.count > 0) {
+                svg.push_str(&format!(
+                    "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"3\" fill=\"#c0392b\"/>\n",
+                    x(bin.mean_confidence), y(bin.accuracy)
+                ));
+            }
+            svg.push_str(&format!(
+                "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"#333\" stroke-dasharray=\"4\"/>\n",
+                x(0.0), y(0.0), x(1.0), y(1.0)
+            ));
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// שמירה לפי הסיומת: svg לדיאגרמה, אחרת JSON
+    pub fn save(&self, path: &Path) -> Result<(), CalibrationError> {
+        let content = match path.extension().and_then(|e| e.to_str()) {
+            Some("svg") => self.to_svg(),
+            _ => self.to_json()?,
+        };
+        fs::write(path, content)?;
+        Ok(())
+    }
+}
+
+/// המיפויים שהמנוע מפעיל על ביטחון הסגמנטים
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConfidenceCalibrator {
+    pub backends: HashMap<CandidateSource, CalibrationMap>,
+    /// מיפוי על כל הדוגמאות, למנוע שלא היו לו מספיק דוגמאות משלו
+    pub fallback: Option<CalibrationMap>,
+}
+
+impl ConfidenceCalibrator {
+    pub fn fit(samples: &[CalibrationSample], config: &CalibrationConfig) -> Result<(Self, CalibrationReport), CalibrationError> {
+        let required = config.min_samples.max(1);
+        if samples.len() < required {
+            return Err(CalibrationError::NotEnoughSamples { found: samples.len(), required });
+        }
+
+        let overall = BackendCalibration::fit(None, samples, config);
+        let mut calibrator = Self { backends: HashMap::new(), fallback: Some(overall.map.clone()) };
+        let mut calibrations = vec![overall];
+
+        for backend in [CandidateSource::TranslationMemory, CandidateSource::Dictionary, CandidateSource::Rules, CandidateSource::Neural] {
+            let backend_samples: Vec<CalibrationSample> = samples
+                .iter()
+                .filter(|sample| sample.backend == Some(backend))
+                .cloned()
+                .collect();
+            if backend_samples.len() < required {
+                continue;
+            }
+            let calibration = BackendCalibration::fit(Some(backend), &backend_samples, config);
+            calibrator.backends.insert(backend, calibration.map.clone());
+            calibrations.push(calibration);
+        }
+
+        Ok((calibrator, CalibrationReport { samples: samples.len(), calibrations }))
+    }
+
+    /// כיול על תוצאות העריכה שנרשמו במנהל הלמידה
+    pub fn fit_from_events(events: &[LearningEvent], config: &CalibrationConfig) -> Result<(Self, CalibrationReport), CalibrationError> {
+        Self::fit(&samples_from_events(events, config.accept_hter), config)
+    }
+
+    pub fn calibrate(&self, backend: Option<CandidateSource>, confidence: f64) -> f64 {
+        let map = backend
+            .and_then(|backend| self.backends.get(&backend))
+            .or(self.fallback.as_ref());
+        match map {
+            Some(map) => map.apply(confidence).clamp(0.0, 1.0),
+            None => confidence,
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, CalibrationError> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), CalibrationError> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::learning_manager::EventMetrics;
+
+    /// מנוע בטוח מדי: מדווח 0.9 אבל רק 60% מהסגמנטים שלו מתקבלים
+    fn overconfident(backend: CandidateSource, count: usize) -> Vec<CalibrationSample> {
+        (0..count)
+            .map(|i| CalibrationSample::new(Some(backend), if i % 2 == 0 { 0.9 } else { 0.8 }, i % 5 < 3))
+            .collect()
+    }
+
+    #[test]
+    fn test_temperature_scaling_reduces_overconfidence() {
+        let samples = overconfident(CandidateSource::Neural, 100);
+        let config = CalibrationConfig { method: CalibrationMethod::Temperature, ..Default::default() };
+        let (calibrator, report) = ConfidenceCalibrator::fit(&samples, &config).unwrap();
+
+        let neural = &report.calibrations[1];
+        assert_eq!(neural.backend, Some(CandidateSource::Neural));
+        assert!(matches!(neural.map, CalibrationMap::Temperature { temperature } if temperature > 1.0));
+        assert!(neural.ece_after < neural.ece_before);
+        assert!(neural.brier_after < neural.brier_before);
+        assert!(calibrator.calibrate(Some(CandidateSource::Neural), 0.9) < 0.8);
+    }
+
+    #[test]
+    fn test_isotonic_is_monotonic_and_falls_back_per_backend() {
+        let mut samples: Vec<CalibrationSample> = (0..300)
+            .map(|i| {
+                let confidence = (i % 10) as f64 / 10.0 + 0.05;
+                CalibrationSample::new(Some(CandidateSource::Rules), confidence, (i * 7) % 10 < (i % 10) / 2)
+            })
+            .collect();
+        samples.extend(overconfident(CandidateSource::Dictionary, 10));
+
+        let (calibrator, report) = ConfidenceCalibrator::fit(&samples, &CalibrationConfig::default()).unwrap();
+        assert!(matches!(report.calibrations[1].map, CalibrationMap::Isotonic { .. }));
+        // ECE אחרי הכיול נמדד רק על הדוגמאות שהמיפוי לא ראה
+        assert_eq!(report.calibrations[1].held_out, 60);
+        assert!(report.calibrations[1].ece_after > 0.0);
+
+        let calibrated: Vec<f64> = (0..=10)
+            .map(|i| calibrator.calibrate(Some(CandidateSource::Rules), i as f64 / 10.0))
+            .collect();
+        assert!(calibrated.windows(2).all(|pair| pair[0] <= pair[1]));
+
+        // למילון אין מספיק דוגמאות, ולכן הוא מכויל במיפוי המשותף
+        assert!(!calibrator.backends.contains_key(&CandidateSource::Dictionary));
+        assert_eq!(
+            calibrator.calibrate(Some(CandidateSource::Dictionary), 0.9),
+            calibrator.fallback.as_ref().unwrap().apply(0.9)
+        );
+        assert!(report.to_svg().contains("rules (n=300)"));
+    }
+
+    #[test]
+    fn test_samples_from_post_edits() {
+        let event = |event_type, confidence: f32| {
+            let metrics = EventMetrics { confidence_score: confidence, ..Default::default() };
+            LearningEvent::new(event_type, "Закрыть задвижку".into(), "לסגור את המגוף".into(), metrics, String::new(), String::new())
+                .with_backend(CandidateSource::Neural)
+        };
+        let correction = |corrected: &str| LearningEventType::Correction {
+            original: String::new(),
+            corrected: corrected.to_string(),
+            error_type: String::new(),
+        };
+
+        let samples = samples_from_events(
+            &[
+                event(correction("לסגור את המגוף"), 0.9),
+                event(correction("יש לסגור את השסתום הראשי"), 0.8),
+                event(LearningEventType::UserFeedback { rating: 5, comments: String::new() }, 0.7),
+                event(correction("לסגור את המגוף"), 0.0),
+            ],
+            0.1,
+        );
+
+        let outcomes: Vec<(f64, bool)> = samples.iter().map(|s| ((s.confidence * 10.0).round() / 10.0, s.accepted)).collect();
+        assert_eq!(outcomes, vec![(0.9, true), (0.8, false), (0.7, true)]);
+        assert!(samples.iter().all(|s| s.backend == Some(CandidateSource::Neural)));
+    }
+
+    #[test]
+    fn test_sample_log_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("models").join("calibration_samples.jsonl");
+        append_samples(&path, &overconfident(CandidateSource::Neural, 3)).unwrap();
+        append_samples(&path, &[CalibrationSample::new(None, 0.5, true)]).unwrap();
+
+        let samples = load_samples(&path).unwrap();
+        assert_eq!(samples.len(), 4);
+        assert_eq!(samples[0].backend, Some(CandidateSource::Neural));
+        assert_eq!(samples[3].backend, None);
+    }
+
+    #[test]
+    fn test_reliability_bins_and_ece() {
+        let predictions = [(0.95, true), (0.95, false), (0.15, false), (0.15, false)];
+        let bins = reliability_bins(&predictions, 10);
+        assert_eq!(bins.len(), 10);
+        assert_eq!(bins[9].count, 2);
+        assert!((bins[9].accuracy - 0.5).abs() < 1e-9);
+        assert!((expected_calibration_error(&bins) - 0.3).abs() < 1e-9);
+
+        let too_few = ConfidenceCalibrator::fit(&overconfident(CandidateSource::Neural, 5), &CalibrationConfig::default());
+        assert!(matches!(too_few, Err(CalibrationError::NotEnoughSamples { found: 5, .. })));
+    }
+}
@@ -0,0 +1,971 @@
+//! מדדי הערכה ברמת הקורפוס התואמים ל-sacreBLEU: BLEU, chrF/chrF++ ו-TER עם הזזות,
+//! ובדיקת מובהקות בשיטת paired bootstrap resampling
+
+use std::collections::HashMap;
+use std::fmt;
+use lazy_static::lazy_static;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+use thiserror::Error;
+
+lazy_static! {
+    /// הטוקנייזר 13a של mteval-v13a, ברירת המחדל של sacreBLEU
+    static ref TOKENIZER_13A: Vec<(Regex, &'static str)> = vec![
+        (Regex::new(r"([\{-~\[-` -&\(-\+:-@/])").unwrap(), " ${1} "),
+        (Regex::new(r"([^0-9])([\.,])").unwrap(), "${1} ${2} "),
+        (Regex::new(r"([\.,])([^0-9])").unwrap(), " ${1} ${2}"),
+        (Regex::new(r"([0-9])(-)").unwrap(), "${1} ${2} "),
+    ];
+
+    /// הטוקנייזר intl (mteval-v14 international): מפריד סימני פיסוק וסמלים לפי קטגוריות Unicode,
+    /// ולכן מתאים לעברית (מקף, גרש, גרשיים) ולרוסית
+    static ref TOKENIZER_INTL: Vec<(Regex, &'static str)> = vec![
+        (Regex::new(r"(\P{N})(\p{P})").unwrap(), "${1} ${2} "),
+        (Regex::new(r"(\p{P})(\P{N})").unwrap(), " ${1} ${2}"),
+        (Regex::new(r"(\p{S})").unwrap(), " ${1} "),
+    ];
+}
+
+/// סימני הפיסוק שמופרדים ממילים ב-chrF++
+const CHRF_PUNCTUATION: &str = "!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+
+const TER_MAX_SHIFT_SIZE: usize = 10;
+const TER_MAX_SHIFT_DISTANCE: usize = 50;
+const TER_MAX_SHIFT_CANDIDATES: usize = 1000;
+const TER_BEAM_WIDTH: usize = 25;
+
+#[derive(Error, Debug)]
+pub enum MetricsError {
+    #[error("מספר התרגומים ({hypotheses}) שונה ממספר סגמנטי הייחוס ({references})")]
+    LengthMismatch { hypotheses: usize, references: usize },
+
+    #[error("לא סופקו תרגומי ייחוס")]
+    NoReferences,
+
+    #[error("חסר תרגום ייחוס עבור: {0}")]
+    MissingReference(String),
+}
+
+/// מדד שמחושב מסכום סטטיסטיקות של סגמנטים, כמו ב-sacreBLEU
+pub trait CorpusMetric {
+    fn name(&self) -> &'static str;
+
+    /// סטטיסטיקות הסגמנט מול כל תרגומי הייחוס שלו; ציון הקורפוס מחושב מסכומן
+    fn segment_statistics(&self, hypothesis: &str, references: &[&str]) -> Vec<f64>;
+
+    fn score_from_statistics(&self, statistics: &[f64]) -> f64;
+
+    fn higher_is_better(&self) -> bool {
+        true
+    }
+
+    fn sentence_score(&self, hypothesis: &str, references: &[&str]) -> f64 {
+        self.score_from_statistics(&self.segment_statistics(hypothesis, references))
+    }
+
+    /// הסטטיסטיקות של כל הסגמנטים; references הם זרמי ייחוס, references[k][i] הוא הייחוס ה-k של הסגמנט i
+    fn corpus_statistics(&self, hypotheses: &[String], references: &[Vec<String>]) -> Result<Vec<Vec<f64>>, MetricsError> {
+        validate(hypotheses, references)?;
+        Ok(hypotheses
+            .iter()
+            .enumerate()
+            .map(|(i, hypothesis)| {
+                let segment_references: Vec<&str> = references.iter().map(|stream| stream[i].as_str()).collect();
+                self.segment_statistics(hypothesis, &segment_references)
+            })
+            .collect())
+    }
+
+    fn corpus_score(&self, hypotheses: &[String], references: &[Vec<String>]) -> Result<f64, MetricsError> {
+        let statistics = self.corpus_statistics(hypotheses, references)?;
+        Ok(self.score_from_statistics(&sum_statistics(statistics.iter())))
+    }
+}
+
+fn validate(hypotheses: &[String], references: &[Vec<String>]) -> Result<(), MetricsError> {
+    if references.is_empty() {
+        return Err(MetricsError::NoReferences);
+    }
+    for stream in references {
+        if stream.len() != hypotheses.len() {
+            return Err(MetricsError::LengthMismatch {
+                hypotheses: hypotheses.len(),
+                references: stream.len(),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn sum_statistics<'a, I>(statistics: I) -> Vec<f64>
+where
+    I: Iterator<Item = &'a Vec<f64>>,
+{
+    let mut total: Vec<f64> = Vec::new();
+    for segment in statistics {
+        if total.is_empty() {
+            total = vec![0.0; segment.len()];
+        }
+        for (sum, value) in total.iter_mut().zip(segment) {
+            *sum += value;
+        }
+    }
+    total
+}
+
+/// הטוקנייזר של BLEU
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BleuTokenizer {
+    #[serde(rename = "none")]
+    None,
+    #[serde(rename = "13a")]
+    Mteval13a,
+    #[serde(rename = "intl")]
+    Intl,
+    #[serde(rename = "char")]
+    Char,
+}
+
+impl BleuTokenizer {
+    pub fn tokenize(&self, line: &str) -> String {
+        match self {
+            BleuTokenizer::None => line.to_string(),
+            BleuTokenizer::Mteval13a => {
+                let mut line = line.replace("<skipped>", "").replace("-\n", "").replace('\n', " ");
+                if line.contains('&') {
+                    line = line
+                        .replace("&quot;", "\"")
+                        .replace("&amp;", "&")
+                        .replace("&lt;", "<")
+                        .replace("&gt;", ">");
+                }
+                apply_rules(&format!(" {} ", line), &TOKENIZER_13A)
+            }
+            BleuTokenizer::Intl => apply_rules(line, &TOKENIZER_INTL),
+            BleuTokenizer::Char => line
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .map(String::from)
+                .collect::<Vec<_>>()
+                .join(" "),
+        }
+    }
+}
+
+fn apply_rules(line: &str, rules: &[(Regex, &'static str)]) -> String {
+    let mut line = line.to_string();
+    for (pattern, replacement) in rules {
+        line = pattern.replace_all(&line, *replacement).into_owned();
+    }
+    line.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// החלקה לדיוק n-gram ללא התאמות
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", content = "value", rename_all = "kebab-case")]
+pub enum BleuSmoothing {
+    None,
+    Floor(f64),
+    AddK(f64),
+    Exp,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BleuConfig {
+    pub tokenizer: BleuTokenizer,
+    pub lowercase: bool,
+    pub max_ngram_order: usize,
+    pub smoothing: BleuSmoothing,
+    /// ממוצע רק על סדרי n-gram שיש להם מועמדים; מקובל ל-BLEU ברמת המשפט
+    pub effective_order: bool,
+}
+
+impl Default for BleuConfig {
+    fn default() -> Self {
+        Self {
+            tokenizer: BleuTokenizer::Mteval13a,
+            lowercase: false,
+            max_ngram_order: 4,
+            smoothing: BleuSmoothing::Exp,
+            effective_order: false,
+        }
+    }
+}
+
+/// ציון BLEU עם הפירוט המקובל בדיווח של sacreBLEU
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BleuScore {
+    pub score: f64,
+    pub precisions: Vec<f64>,
+    pub brevity_penalty: f64,
+    pub ratio: f64,
+    pub hyp_len: usize,
+    pub ref_len: usize,
+}
+
+impl fmt::Display for BleuScore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let precisions: Vec<String> = self.precisions.iter().map(|p| format!("{:.1}", p)).collect();
+        write!(
+            f,
+            "BLEU = {:.2} {} (BP = {:.3} ratio = {:.3} hyp_len = {} ref_len = {})",
+            self.score,
+            precisions.join("/"),
+            self.brevity_penalty,
+            self.ratio,
+            self.hyp_len,
+            self.ref_len,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Bleu {
+    pub config: BleuConfig,
+}
+
+impl Bleu {
+    pub fn new(config: BleuConfig) -> Self {
+        Self { config }
+    }
+
+    /// BLEU ברמת הקורפוס עם הפירוט המלא
+    pub fn corpus_bleu(&self, hypotheses: &[String], references: &[Vec<String>]) -> Result<BleuScore, MetricsError> {
+        let statistics = self.corpus_statistics(hypotheses, references)?;
+        Ok(self.bleu_from_statistics(&sum_statistics(statistics.iter())))
+    }
+
+    fn preprocess(&self, line: &str) -> Vec<String> {
+        let line = if self.config.lowercase { line.to_lowercase() } else { line.to_string() };
+        self.config.tokenizer
+            .tokenize(line.trim())
+            .split_whitespace()
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// הסטטיסטיקות: [hyp_len, ref_len, correct_1..n, total_1..n]
+    pub fn bleu_from_statistics(&self, statistics: &[f64]) -> BleuScore {
+        let order = self.config.max_ngram_order;
+        if statistics.len() < 2 + 2 * order {
+            return BleuScore {
+                score: 0.0,
+                precisions: vec![0.0; order],
+                brevity_penalty: 0.0,
+                ratio: 0.0,
+                hyp_len: 0,
+                ref_len: 0,
+            };
+        }
+
+        let hyp_len = statistics[0];
+        let ref_len = statistics[1];
+        let mut correct = statistics[2..2 + order].to_vec();
+        let mut total = statistics[2 + order..2 + 2 * order].to_vec();
+
+        let mut precisions = vec![0.0; order];
+        let mut smooth_mteval = 1.0;
+        let mut effective_order = order;
+
+        for n in 1..=order {
+            if let BleuSmoothing::AddK(k) = self.config.smoothing {
+                if n > 1 {
+                    correct[n - 1] += k;
+                    total[n - 1] += k;
+                }
+            }
+            if total[n - 1] == 0.0 {
+                break;
+            }
+            if self.config.effective_order {
+                effective_order = n;
+            }
+
+            if correct[n - 1] == 0.0 {
+                match self.config.smoothing {
+                    BleuSmoothing::Exp => {
+                        smooth_mteval *= 2.0;
+                        precisions[n - 1] = 100.0 / (smooth_mteval * total[n - 1]);
+                    }
+                    BleuSmoothing::Floor(value) => {
+                        precisions[n - 1] = 100.0 * value / total[n - 1];
+                    }
+                    BleuSmoothing::None | BleuSmoothing::AddK(_) => {}
+                }
+            } else {
+                precisions[n - 1] = 100.0 * correct[n - 1] / total[n - 1];
+            }
+        }
+
+        let brevity_penalty = if hyp_len < ref_len {
+            if hyp_len > 0.0 { (1.0 - ref_len / hyp_len).exp() } else { 0.0 }
+        } else {
+            1.0
+        };
+
+        let log_sum: f64 = precisions[..effective_order]
+            .iter()
+            .map(|&p| if p == 0.0 { -9_999_999_999.0 } else { p.ln() })
+            .sum();
+
+        BleuScore {
+            score: brevity_penalty * (log_sum / effective_order as f64).exp(),
+            precisions,
+            brevity_penalty,
+            ratio: if ref_len > 0.0 { hyp_len / ref_len } else { 0.0 },
+            hyp_len: hyp_len as usize,
+            ref_len: ref_len as usize,
+        }
+    }
+}
+
+fn ngram_counts(tokens: &[String], n: usize) -> HashMap<String, usize> {
+    let mut counts = HashMap::new();
+    for window in tokens.windows(n) {
+        *counts.entry(window.join(" ")).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// אורך הייחוס הקרוב ביותר לתרגום; בתיקו נבחר הקצר
+fn closest_reference_length(hyp_len: usize, ref_lens: &[usize]) -> usize {
+    let mut closest: Option<(usize, usize)> = None;
+    for &ref_len in ref_lens {
+        let diff = hyp_len.abs_diff(ref_len);
+        closest = match closest {
+            None => Some((diff, ref_len)),
+            Some((best_diff, _)) if diff < best_diff => Some((diff, ref_len)),
+            Some((best_diff, best_len)) if diff == best_diff && ref_len < best_len => Some((diff, ref_len)),
+            other => other,
+        };
+    }
+    closest.map_or(0, |(_, len)| len)
+}
+
+impl CorpusMetric for Bleu {
+    fn name(&self) -> &'static str {
+        "BLEU"
+    }
+
+    fn segment_statistics(&self, hypothesis: &str, references: &[&str]) -> Vec<f64> {
+        let order = self.config.max_ngram_order;
+        let hypothesis = self.preprocess(hypothesis);
+        let references: Vec<Vec<String>> = references.iter().map(|r| self.preprocess(r)).collect();
+
+        let ref_lens: Vec<usize> = references.iter().map(Vec::len).collect();
+        let mut statistics = vec![
+            hypothesis.len() as f64,
+            closest_reference_length(hypothesis.len(), &ref_lens) as f64,
+        ];
+
+        let mut correct = Vec::with_capacity(order);
+        let mut total = Vec::with_capacity(order);
+        for n in 1..=order {
+            // ספירת הייחוס המרבית לכל n-gram, לקיטום ההתאמות
+            let mut max_reference: HashMap<String, usize> = HashMap::new();
+            for reference in &references {
+                for (ngram, count) in ngram_counts(reference, n) {
+                    let entry = max_reference.entry(ngram).or_insert(0);
+                    *entry = (*entry).max(count);
+                }
+            }
+
+            let hypothesis_counts = ngram_counts(&hypothesis, n);
+            let matches: usize = hypothesis_counts
+                .iter()
+                .map(|(ngram, &count)| count.min(max_reference.get(ngram).copied().unwrap_or(0)))
+                .sum();
+
+            correct.push(matches as f64);
+            total.push(hypothesis.len().saturating_sub(n - 1) as f64);
+        }
+
+        statistics.extend(correct);
+        statistics.extend(total);
+        statistics
+    }
+
+    fn score_from_statistics(&self, statistics: &[f64]) -> f64 {
+        self.bleu_from_statistics(statistics).score
+    }
+}
+
+/// chrF, ו-chrF++ כאשר word_order גדול מאפס
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChrF {
+    pub char_order: usize,
+    pub word_order: usize,
+    pub beta: f64,
+    pub lowercase: bool,
+}
+
+impl Default for ChrF {
+    /// chrF2 בהגדרות של sacreBLEU
+    fn default() -> Self {
+        Self { char_order: 6, word_order: 0, beta: 2.0, lowercase: false }
+    }
+}
+
+impl ChrF {
+    pub fn chrf_plus_plus() -> Self {
+        Self { word_order: 2, ..Self::default() }
+    }
+
+    fn ngrams(&self, line: &str) -> Vec<HashMap<String, usize>> {
+        let line = if self.lowercase { line.to_lowercase() } else { line.to_string() };
+
+        // n-gram של תווים מחושבים על הטקסט ללא רווחים
+        let chars: Vec<char> = line.chars().filter(|c| !c.is_whitespace()).collect();
+        let mut all = Vec::with_capacity(self.char_order + self.word_order);
+        for n in 1..=self.char_order {
+            let mut counts = HashMap::new();
+            for window in chars.windows(n) {
+                *counts.entry(window.iter().collect::<String>()).or_insert(0) += 1;
+            }
+            all.push(counts);
+        }
+
+        if self.word_order > 0 {
+            let words = separate_punctuation(&line);
+            for n in 1..=self.word_order {
+                all.push(ngram_counts(&words, n));
+            }
+        }
+        all
+    }
+
+    fn f_score(&self, statistics: &[f64]) -> f64 {
+        const EPS: f64 = 1e-16;
+        let factor = self.beta.powi(2);
+        let order = self.char_order + self.word_order;
+
+        let mut effective_order = 0;
+        let (mut average_precision, mut average_recall) = (0.0, 0.0);
+        for i in 0..order.min(statistics.len() / 3) {
+            let (hyp, reference, matches) = (statistics[3 * i], statistics[3 * i + 1], statistics[3 * i + 2]);
+            average_precision += if hyp > 0.0 { matches / hyp } else { EPS };
+            average_recall += if reference > 0.0 { matches / reference } else { EPS };
+            if hyp > 0.0 && reference > 0.0 {
+                effective_order += 1;
+            }
+        }
+
+        if effective_order == 0 {
+            return 0.0;
+        }
+        average_precision /= effective_order as f64;
+        average_recall /= effective_order as f64;
+
+        if average_precision + average_recall == 0.0 {
+            return 0.0;
+        }
+        100.0 * (1.0 + factor) * average_precision * average_recall
+            / (factor * average_precision + average_recall)
+    }
+}
+
+/// הפרדת סימן פיסוק אחד מתחילת המילה או מסופה, כמו ב-chrF++ המקורי
+fn separate_punctuation(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    for word in line.split_whitespace() {
+        let chars: Vec<char> = word.chars().collect();
+        if chars.len() == 1 {
+            words.push(word.to_string());
+        } else if CHRF_PUNCTUATION.contains(chars[chars.len() - 1]) {
+            words.push(chars[..chars.len() - 1].iter().collect());
+            words.push(chars[chars.len() - 1].to_string());
+        } else if CHRF_PUNCTUATION.contains(chars[0]) {
+            words.push(chars[0].to_string());
+            words.push(chars[1..].iter().collect());
+        } else {
+            words.push(word.to_string());
+        }
+    }
+    words
+}
+
+impl CorpusMetric for ChrF {
+    fn name(&self) -> &'static str {
+        if self.word_order > 0 { "chrF++" } else { "chrF" }
+    }
+
+    /// לכל סדר: [n-grams בתרגום, n-grams בייחוס, התאמות]; מכמה תרגומי ייחוס נבחר זה שנותן את ה-F הגבוה
+    fn segment_statistics(&self, hypothesis: &str, references: &[&str]) -> Vec<f64> {
+        let hypothesis = self.ngrams(hypothesis);
+        let mut best: Option<(f64, Vec<f64>)> = None;
+
+        for reference in references {
+            let reference = self.ngrams(reference);
+            let mut statistics = Vec::with_capacity(3 * hypothesis.len());
+            for (hyp, reference) in hypothesis.iter().zip(&reference) {
+                let matches: usize = hyp
+                    .iter()
+                    .map(|(ngram, &count)| count.min(reference.get(ngram).copied().unwrap_or(0)))
+                    .sum();
+                statistics.push(hyp.values().sum::<usize>() as f64);
+                statistics.push(reference.values().sum::<usize>() as f64);
+                statistics.push(matches as f64);
+            }
+
+            let score = self.f_score(&statistics);
+            if best.as_ref().is_none_or(|(best_score, _)| score > *best_score) {
+                best = Some((score, statistics));
+            }
+        }
+
+        best.map(|(_, statistics)| statistics)
+            .unwrap_or_else(|| vec![0.0; 3 * (self.char_order + self.word_order)])
+    }
+
+    fn score_from_statistics(&self, statistics: &[f64]) -> f64 {
+        self.f_score(statistics)
+    }
+}
+
+/// Translation Edit Rate עם הזזות, בשחזור של tercom כפי שממומש ב-sacreBLEU
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Ter {
+    pub case_sensitive: bool,
+}
+
+impl Ter {
+    fn words(&self, line: &str) -> Vec<String> {
+        let line = if self.case_sensitive { line.to_string() } else { line.to_lowercase() };
+        line.split_whitespace().map(str::to_string).collect()
+    }
+}
+
+impl CorpusMetric for Ter {
+    fn name(&self) -> &'static str {
+        "TER"
+    }
+
+    /// [מספר העריכות מול הייחוס הקרוב ביותר, אורך הייחוס הממוצע]
+    fn segment_statistics(&self, hypothesis: &str, references: &[&str]) -> Vec<f64> {
+        let hypothesis = self.words(hypothesis);
+        let mut best_edits = usize::MAX;
+        let mut reference_lengths = 0;
+
+        for reference in references {
+            let reference = self.words(reference);
+            let (edits, length) = translation_edit_rate(&hypothesis, &reference);
+            reference_lengths += length;
+            best_edits = best_edits.min(edits);
+        }
+
+        if references.is_empty() {
+            return vec![hypothesis.len() as f64, 0.0];
+        }
+        vec![best_edits as f64, reference_lengths as f64 / references.len() as f64]
+    }
+
+    fn score_from_statistics(&self, statistics: &[f64]) -> f64 {
+        let (edits, reference_length) = (statistics[0], statistics[1]);
+        if reference_length > 0.0 {
+            100.0 * edits / reference_length
+        } else if edits > 0.0 {
+            100.0
+        } else {
+            0.0
+        }
+    }
+
+    fn higher_is_better(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EditOp {
+    Match,
+    Substitute,
+    /// צריכת מילה מהייחוס
+    Insert,
+    /// צריכת מילה מהתרגום
+    Delete,
+}
+
+/// עלות מצטברת והפעולה האחרונה במסלול
+type EditCell = (usize, Option<EditOp>);
+
+/// (מספר עריכות כולל הזזות, אורך הייחוס)
+fn translation_edit_rate(hypothesis: &[String], reference: &[String]) -> (usize, usize) {
+    if reference.is_empty() {
+        return (hypothesis.len(), 0);
+    }
+
+    let mut words = hypothesis.to_vec();
+    let mut shifts = 0;
+    let mut checked_candidates = 0;
+
+    // מבצעים הזזות כל עוד הן מקטינות את מרחק העריכה
+    loop {
+        let (delta, shifted) = best_shift(&words, reference, &mut checked_candidates);
+        if checked_candidates >= TER_MAX_SHIFT_CANDIDATES || delta <= 0 {
+            break;
+        }
+        shifts += 1;
+        words = shifted;
+    }
+
+    (shifts + edit_distance(&words, reference).0, reference.len())
+}
+
+/// מרחק Levenshtein מוגבל לרצועה סביב האלכסון, עם העדפת פעולות כמו ב-tercom
+fn edit_distance(hypothesis: &[String], reference: &[String]) -> (usize, Vec<EditOp>) {
+    const INFINITY: usize = usize::MAX / 2;
+    let (hyp_len, ref_len) = (hypothesis.len(), reference.len());
+
+    let length_ratio = if hyp_len > 0 { ref_len as f64 / hyp_len as f64 } else { 1.0 };
+    let beam_width = if length_ratio / 2.0 > TER_BEAM_WIDTH as f64 {
+        (length_ratio / 2.0 + TER_BEAM_WIDTH as f64).ceil() as usize
+    } else {
+        TER_BEAM_WIDTH
+    };
+
+    let mut matrix: Vec<Vec<EditCell>> = Vec::with_capacity(hyp_len + 1);
+    matrix.push((0..=ref_len).map(|j| (j, (j > 0).then_some(EditOp::Insert))).collect());
+
+    for i in 1..=hyp_len {
+        let diagonal = (i as f64 * length_ratio).floor() as usize;
+        let min_j = diagonal.saturating_sub(beam_width);
+        let max_j = if i == hyp_len { ref_len + 1 } else { (ref_len + 1).min(diagonal + beam_width) };
+
+        let mut row = vec![(INFINITY, None); ref_len + 1];
+        for j in min_j..max_j {
+            if j == 0 {
+                row[j] = (matrix[i - 1][j].0 + 1, Some(EditOp::Delete));
+                continue;
+            }
+
+            let (substitution_cost, substitution) = if hypothesis[i - 1] == reference[j - 1] {
+                (0, EditOp::Match)
+            } else {
+                (1, EditOp::Substitute)
+            };
+            // ההעדפה: התאמה/החלפה, מחיקה ואז הוספה (ההפך מ-tercom, כי המסלול מתהפך בהמשך)
+            let options = [
+                (matrix[i - 1][j - 1].0 + substitution_cost, substitution),
+                (matrix[i - 1][j].0 + 1, EditOp::Delete),
+                (row[j - 1].0 + 1, EditOp::Insert),
+            ];
+            for (cost, op) in options {
+                if row[j].0 > cost {
+                    row[j] = (cost, Some(op));
+                }
+            }
+        }
+        matrix.push(row);
+    }
+
+    let mut trace = Vec::new();
+    let (mut i, mut j) = (hyp_len, ref_len);
+    while i > 0 || j > 0 {
+        let op = matrix[i][j].1.expect("מסלול עריכה מחוץ לרצועה");
+        trace.push(op);
+        match op {
+            EditOp::Match | EditOp::Substitute => {
+                i -= 1;
+                j -= 1;
+            }
+            EditOp::Insert => j -= 1,
+            EditOp::Delete => i -= 1,
+        }
+    }
+    trace.reverse();
+
+    (matrix[hyp_len][ref_len].0, trace)
+}
+
+/// יישור מהמסלול ההפוך: לכל מילת ייחוס, מיקום מילת התרגום שמולה (או -1), וסימון שגיאות בשני הצדדים
+fn trace_to_alignment(trace: &[EditOp]) -> (Vec<isize>, Vec<bool>, Vec<bool>) {
+    let (mut hyp_pos, mut ref_pos) = (-1isize, -1isize);
+    let (mut alignment, mut ref_errors, mut hyp_errors) = (Vec::new(), Vec::new(), Vec::new());
+
+    for op in trace {
+        // המסלול מחושב מהתרגום לייחוס, ולכן הוספה ומחיקה מתחלפות
+        match op {
+            EditOp::Match | EditOp::Substitute => {
+                hyp_pos += 1;
+                ref_pos += 1;
+                alignment.push(hyp_pos);
+                let error = *op == EditOp::Substitute;
+                hyp_errors.push(error);
+                ref_errors.push(error);
+            }
+            EditOp::Delete => {
+                hyp_pos += 1;
+                hyp_errors.push(true);
+            }
+            EditOp::Insert => {
+                ref_pos += 1;
+                alignment.push(hyp_pos);
+                ref_errors.push(true);
+            }
+        }
+    }
+    debug_assert_eq!(alignment.len() as isize, ref_pos + 1);
+
+    (alignment, ref_errors, hyp_errors)
+}
+
+/// סדר הדירוג כמו ב-tercom: שיפור, אורך, מיקום מוקדם, יעד מוקדם
+type ShiftRank = (isize, usize, isize, isize);
+
+/// ההזזה הטובה ביותר: (השיפור במרחק העריכה, המילים אחרי ההזזה)
+fn best_shift(words: &[String], reference: &[String], checked_candidates: &mut usize) -> (isize, Vec<String>) {
+    let (pre_score, trace) = edit_distance(words, reference);
+    let (alignment, ref_errors, hyp_errors) = trace_to_alignment(&trace);
+
+    let mut best: Option<(ShiftRank, Vec<String>)> = None;
+
+    'pairs: for start_h in 0..words.len() {
+        for start_r in 0..reference.len() {
+            if start_r.abs_diff(start_h) > TER_MAX_SHIFT_DISTANCE {
+                continue;
+            }
+
+            let mut length = 0;
+            while length < TER_MAX_SHIFT_SIZE && words[start_h + length] == reference[start_r + length] {
+                length += 1;
+
+                // מזיזים רק רצף שגוי בתרגום אל מקום שגוי בייחוס, ולא לתוך עצמו
+                let candidate_valid = hyp_errors[start_h..start_h + length].iter().any(|&e| e)
+                    && ref_errors[start_r..start_r + length].iter().any(|&e| e)
+                    && !(start_h as isize <= alignment[start_r] && alignment[start_r] < (start_h + length) as isize);
+
+                if candidate_valid {
+                    let mut previous_target = None;
+                    for offset in -1..length as isize {
+                        let position = start_r as isize + offset;
+                        let target = if position == -1 { 0 } else { (alignment[position as usize] + 1) as usize };
+                        if previous_target == Some(target) {
+                            continue;
+                        }
+                        previous_target = Some(target);
+
+                        let Some(shifted) = perform_shift(words, start_h, length, target) else {
+                            continue;
+                        };
+                        let gain = pre_score as isize - edit_distance(&shifted, reference).0 as isize;
+                        let rank = (gain, length, -(start_h as isize), -(target as isize));
+                        *checked_candidates += 1;
+
+                        if best.as_ref().is_none_or(|(best_rank, _)| rank > *best_rank) {
+                            best = Some((rank, shifted));
+                        }
+                    }
+                }
+
+                if *checked_candidates >= TER_MAX_SHIFT_CANDIDATES {
+                    break 'pairs;
+                }
+                if start_h + length == words.len() || start_r + length == reference.len() {
+                    break;
+                }
+            }
+        }
+    }
+
+    match best {
+        Some(((gain, ..), shifted)) => (gain, shifted),
+        None => (0, words.to_vec()),
+    }
+}
+
+/// הזזת הרצף [start, start+length) כך שיתחיל לפני המיקום target
+fn perform_shift(words: &[String], start: usize, length: usize, target: usize) -> Option<Vec<String>> {
+    let n = words.len();
+    let slice = |from: usize, to: usize| &words[from.min(n)..to.min(n).max(from.min(n))];
+    let span = slice(start, start + length);
+
+    let shifted: Vec<String> = if target < start {
+        [slice(0, target), span, slice(target, start), slice(start + length, n)].concat()
+    } else if target > start + length {
+        [slice(0, start), slice(start + length, target), span, slice(target, n)].concat()
+    } else {
+        [slice(0, start), slice(start + length, length + target), span, slice(length + target, n)].concat()
+    };
+
+    (shifted.len() == n).then_some(shifted)
+}
+
+/// תוצאת השוואה בין שתי מערכות
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BootstrapResult {
+    pub metric: String,
+    pub baseline: f64,
+    pub system: f64,
+    /// ההסתברות שהמערכת אינה טובה מהבסיס (Koehn, 2004)
+    pub p_value: f64,
+    /// רווח סמך של 95% לציון המערכת
+    pub system_interval: (f64, f64),
+    pub samples: usize,
+}
+
+impl BootstrapResult {
+    pub fn is_significant(&self, alpha: f64) -> bool {
+        self.p_value < alpha
+    }
+}
+
+/// paired bootstrap resampling: דגימה חוזרת של אותם סגמנטים לשתי המערכות והשוואת הציונים
+pub fn paired_bootstrap<M: CorpusMetric + ?Sized>(
+    metric: &M,
+    baseline: &[String],
+    system: &[String],
+    references: &[Vec<String>],
+    samples: usize,
+    seed: u64,
+) -> Result<BootstrapResult, MetricsError> {
+    if baseline.len() != system.len() {
+        return Err(MetricsError::LengthMismatch { hypotheses: system.len(), references: baseline.len() });
+    }
+    let baseline_statistics = metric.corpus_statistics(baseline, references)?;
+    let system_statistics = metric.corpus_statistics(system, references)?;
+    let segments = system.len();
+
+    let baseline_score = metric.score_from_statistics(&sum_statistics(baseline_statistics.iter()));
+    let system_score = metric.score_from_statistics(&sum_statistics(system_statistics.iter()));
+
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut not_better = 0;
+    let mut system_samples = Vec::with_capacity(samples);
+
+    for _ in 0..samples {
+        let indices: Vec<usize> = (0..segments).map(|_| rng.gen_range(0..segments.max(1))).collect();
+        let sampled_baseline = metric.score_from_statistics(&sum_statistics(indices.iter().map(|&i| &baseline_statistics[i])));
+        let sampled_system = metric.score_from_statistics(&sum_statistics(indices.iter().map(|&i| &system_statistics[i])));
+
+        let better = if metric.higher_is_better() {
+            sampled_system > sampled_baseline
+        } else {
+            sampled_system < sampled_baseline
+        };
+        if !better {
+            not_better += 1;
+        }
+        system_samples.push(sampled_system);
+    }
+
+    system_samples.sort_by(|a, b| a.total_cmp(b));
+    let percentile = |q: f64| {
+        system_samples
+            .get(((system_samples.len() as f64 - 1.0) * q).round() as usize)
+            .copied()
+            .unwrap_or(system_score)
+    };
+
+    Ok(BootstrapResult {
+        metric: metric.name().to_string(),
+        baseline: baseline_score,
+        system: system_score,
+        p_value: if samples == 0 { 1.0 } else { not_better as f64 / samples as f64 },
+        system_interval: (percentile(0.025), percentile(0.975)),
+        samples,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    /// הדוגמה מתיעוד sacreBLEU, עם הערכים שהוא מדווח
+    fn sacrebleu_example() -> (Vec<String>, Vec<Vec<String>>) {
+        let hypotheses = strings(&["The dog bit the man.", "It wasn't surprising.", "The man had just bitten him."]);
+        let references = vec![
+            strings(&["The dog bit the man.", "It was not unexpected.", "The man bit him first."]),
+            strings(&["The dog had bit the man.", "No one was surprised.", "The man had bitten the dog."]),
+        ];
+        (hypotheses, references)
+    }
+
+    #[test]
+    fn test_corpus_bleu_matches_sacrebleu() {
+        let (hypotheses, references) = sacrebleu_example();
+        let score = Bleu::default().corpus_bleu(&hypotheses, &references).unwrap();
+
+        assert_eq!(
+            score.to_string(),
+            "BLEU = 48.53 82.4/50.0/45.5/37.5 (BP = 0.943 ratio = 0.944 hyp_len = 17 ref_len = 18)"
+        );
+    }
+
+    #[test]
+    fn test_chrf_matches_sacrebleu() {
+        let (hypotheses, references) = sacrebleu_example();
+        let chrf = ChrF::default().corpus_score(&hypotheses, &references).unwrap();
+
+        assert_eq!(format!("{:.2}", chrf), "59.73");
+    }
+
+    #[test]
+    fn test_ter_matches_snover_example() {
+        // הדוגמה מהמאמר של Snover et al. (2006): הזזה אחת, שתי החלפות ומחיקה אחת מול 12 מילות ייחוס
+        let hypothesis = strings(&["THIS WEEK THE SAUDIS denied information published in the AMERICAN new york times"]);
+        let references = vec![strings(&["SAUDI ARABIA denied THIS WEEK information published in the new york times"])];
+
+        let ter = Ter::default().corpus_score(&hypothesis, &references).unwrap();
+        assert_eq!(format!("{:.2}", ter), "33.33");
+    }
+
+    #[test]
+    fn test_tokenizers() {
+        assert_eq!(BleuTokenizer::Mteval13a.tokenize("Hello, world. 3.5-4"), "Hello , world . 3.5 - 4");
+        // intl מפריד גרש וגרשיים ומשאיר מספרים עשרוניים שלמים
+        assert_eq!(BleuTokenizer::Intl.tokenize("צה\"ל: לחץ 2.5 בר."), "צה \" ל : לחץ 2.5 בר .");
+        assert_eq!(BleuTokenizer::Char.tokenize("לחץ בר"), "ל ח ץ ב ר");
+    }
+
+    #[test]
+    fn test_identical_output_scores_perfectly() {
+        let hypotheses = strings(&["המשאבה פועלת בלחץ של 2.5 בר.", "יש לסגור את המגוף לפני הבדיקה."]);
+        let references = vec![hypotheses.clone()];
+
+        assert!((Bleu::default().corpus_score(&hypotheses, &references).unwrap() - 100.0).abs() < 1e-9);
+        assert!((ChrF::chrf_plus_plus().corpus_score(&hypotheses, &references).unwrap() - 100.0).abs() < 1e-9);
+        assert_eq!(Ter::default().corpus_score(&hypotheses, &references).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn test_ter_counts_block_shift_as_one_edit() {
+        let hypothesis = strings(&["c d a b"]);
+        let references = vec![strings(&["a b c d"])];
+
+        // הזזה אחת של "a b" במקום ארבע החלפות
+        assert_eq!(Ter::default().corpus_score(&hypothesis, &references).unwrap(), 25.0);
+    }
+
+    #[test]
+    fn test_paired_bootstrap() {
+        let references = vec![strings(&[
+            "המשאבה פועלת", "המגוף סגור", "הלחץ תקין", "יש לבדוק את הצינור",
+            "המערכת מוכנה", "הספיקה נמוכה", "הברז פתוח", "המתזים פעילים",
+        ])];
+        let system = references[0].clone();
+        let baseline = strings(&[
+            "משאבה עובדת", "המגוף פתוח", "לחץ בסדר", "לבדוק צינור",
+            "המערכת מוכנה", "ספיקה קטנה", "ברז נפתח", "מתזים עובדים",
+        ]);
+
+        let result = paired_bootstrap(&ChrF::default(), &baseline, &system, &references, 200, 7).unwrap();
+        assert!(result.system > result.baseline);
+        assert!(result.is_significant(0.05));
+        let again = paired_bootstrap(&ChrF::default(), &baseline, &system, &references, 200, 7).unwrap();
+        assert_eq!((again.p_value, again.system_interval), (result.p_value, result.system_interval));
+
+        let same = paired_bootstrap(&Ter::default(), &system, &system, &references, 200, 7).unwrap();
+        assert_eq!(same.p_value, 1.0);
+
+        assert!(matches!(
+            Bleu::default().corpus_score(&system[..2], &references),
+            Err(MetricsError::LengthMismatch { .. })
+        ));
+    }
+}
@@ -1,10 +1,23 @@
+pub mod calibration;
+pub mod metrics;
+pub mod register;
+pub mod regression;
+
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
+use std::path::Path;
+
+use self::metrics::{paired_bootstrap, Bleu, BleuConfig, BleuScore, BleuTokenizer, BootstrapResult, ChrF, CorpusMetric, MetricsError, Ter};
+use self::register::{RegisterIssue, RegisterRule, StyleGuideError};
+use crate::language_detection::{Language, LanguageDetector};
 
+/// מדדים לסגמנט בודד, בסקאלה 0-1; BLEU, chrF ו-TER הם ציוני sacreBLEU חלקי 100
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EvaluationMetrics {
+    /// BLEU ברמת המשפט (effective order)
     pub bleu_score: f64,
     pub meteor_score: f64,
+    /// TER עם הזזות; נמוך יותר הוא טוב יותר
     pub ter_score: f64,
     pub chrf_score: f64,
     pub technical_accuracy: f64,
@@ -83,10 +96,28 @@ pub enum ContextErrorType {
     Other(String),
 }
 
+/// ציוני קורפוס בסקאלה של sacreBLEU (0-100), להשוואה עם תוצאות מפורסמות
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorpusScores {
+    pub bleu: BleuScore,
+    pub chrf: f64,
+    pub chrf_plus_plus: f64,
+    pub ter: f64,
+}
+
 pub struct Evaluator {
     reference_translations: HashMap<String, String>,
     technical_terms: HashMap<String, String>,
     style_guide: StyleGuide,
+    bleu_config: BleuConfig,
+}
+
+/// ברירת המחדל לעברית ולרוסית: טוקנייזר intl, שמפריד פיסוק לפי קטגוריות Unicode
+fn default_bleu_config() -> BleuConfig {
+    BleuConfig {
+        tokenizer: BleuTokenizer::Intl,
+        ..BleuConfig::default()
+    }
 }
 
 impl Evaluator {
@@ -99,8 +130,65 @@ impl Evaluator {
             reference_translations,
             technical_terms,
             style_guide,
+            bleu_config: default_bleu_config(),
         }
     }
+
+    pub fn with_bleu_config(mut self, bleu_config: BleuConfig) -> Self {
+        self.bleu_config = bleu_config;
+        self
+    }
+
+    /// ציוני קורפוס מול זרמי ייחוס; references[k][i] הוא הייחוס ה-k של התרגום i
+    pub fn score_corpus(&self, hypotheses: &[String], references: &[Vec<String>]) -> Result<CorpusScores, MetricsError> {
+        Ok(CorpusScores {
+            bleu: Bleu::new(self.bleu_config.clone()).corpus_bleu(hypotheses, references)?,
+            chrf: ChrF::default().corpus_score(hypotheses, references)?,
+            chrf_plus_plus: ChrF::chrf_plus_plus().corpus_score(hypotheses, references)?,
+            ter: Ter::default().corpus_score(hypotheses, references)?,
+        })
+    }
+
+    /// ציוני קורפוס מול תרגומי הייחוס של המקורות
+    pub fn evaluate_corpus(&self, sources: &[String], hypotheses: &[String]) -> Result<CorpusScores, MetricsError> {
+        let references = self.references_for(sources)?;
+        self.score_corpus(hypotheses, &references)
+    }
+
+    /// השוואת שתי מערכות ב-BLEU, chrF++ ו-TER בשיטת paired bootstrap resampling
+    pub fn compare_systems(
+        &self,
+        sources: &[String],
+        baseline: &[String],
+        system: &[String],
+        samples: usize,
+        seed: u64,
+    ) -> Result<Vec<BootstrapResult>, MetricsError> {
+        let references = self.references_for(sources)?;
+        let metrics: [Box<dyn CorpusMetric>; 3] = [
+            Box::new(Bleu::new(self.bleu_config.clone())),
+            Box::new(ChrF::chrf_plus_plus()),
+            Box::new(Ter::default()),
+        ];
+
+        metrics
+            .iter()
+            .map(|metric| paired_bootstrap(metric.as_ref(), baseline, system, &references, samples, seed))
+            .collect()
+    }
+
+    fn references_for(&self, sources: &[String]) -> Result<Vec<Vec<String>>, MetricsError> {
+        let stream = sources
+            .iter()
+            .map(|source| {
+                self.reference_translations
+                    .get(source)
+                    .cloned()
+                    .ok_or_else(|| MetricsError::MissingReference(source.clone()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(vec![stream])
+    }
     
     pub fn evaluate(&self, source_text: &str, translated_text: &str) -> EvaluationMetrics {
         let reference = self.reference_translations.get(source_text)
@@ -120,36 +208,12 @@ impl Evaluator {
     }
     
     fn calculate_bleu(&self, hypothesis: &str, reference: &str) -> f64 {
-        // חישוב ציון BLEU
-        let hyp_words: Vec<&str> = hypothesis.split_whitespace().collect();
-        let ref_words: Vec<&str> = reference.split_whitespace().collect();
-        
-        let mut matches = 0;
-        let mut total = 0;
-        
-        // חישוב התאמות ברמת המילה
-        for window_size in 1..=4 {
-            for i in 0..=hyp_words.len().saturating_sub(window_size) {
-                let hyp_ngram: Vec<_> = hyp_words[i..i+window_size].to_vec();
-                
-                for j in 0..=ref_words.len().saturating_sub(window_size) {
-                    let ref_ngram: Vec<_> = ref_words[j..j+window_size].to_vec();
-                    
-                    if hyp_ngram == ref_ngram {
-                        matches += 1;
-                        break;
-                    }
-                }
-                
-                total += 1;
-            }
-        }
-        
-        if total == 0 {
-            0.0
-        } else {
-            matches as f64 / total as f64
-        }
+        // BLEU ברמת המשפט מחושב על סדרי ה-n-gram הקיימים בלבד
+        let bleu = Bleu::new(BleuConfig {
+            effective_order: true,
+            ..self.bleu_config.clone()
+        });
+        bleu.sentence_score(hypothesis, &[reference]) / 100.0
     }
     
     fn calculate_meteor(&self, hypothesis: &str, reference: &str) -> f64 {
@@ -190,66 +254,11 @@ impl Evaluator {
     }
     
     fn calculate_ter(&self, hypothesis: &str, reference: &str) -> f64 {
-        // חישוב ציון TER (Translation Edit Rate)
-        let hyp_words: Vec<&str> = hypothesis.split_whitespace().collect();
-        let ref_words: Vec<&str> = reference.split_whitespace().collect();
-        
-        let mut dp = vec![vec![0; ref_words.len() + 1]; hyp_words.len() + 1];
-        
-        // אתחול מטריצת המרחקים
-        for i in 0..=hyp_words.len() {
-            dp[i][0] = i;
-        }
-        for j in 0..=ref_words.len() {
-            dp[0][j] = j;
-        }
-        
-        // חישוב מרחק עריכה מינימלי
-        for i in 1..=hyp_words.len() {
-            for j in 1..=ref_words.len() {
-                let substitution_cost = if hyp_words[i-1] == ref_words[j-1] { 0 } else { 1 };
-                
-                dp[i][j] = (dp[i-1][j] + 1) // מחיקה
-                    .min(dp[i][j-1] + 1) // הוספה
-                    .min(dp[i-1][j-1] + substitution_cost); // החלפה
-            }
-        }
-        
-        let edit_distance = dp[hyp_words.len()][ref_words.len()];
-        edit_distance as f64 / ref_words.len() as f64
+        Ter::default().sentence_score(hypothesis, &[reference]) / 100.0
     }
     
     fn calculate_chrf(&self, hypothesis: &str, reference: &str) -> f64 {
-        // חישוב ציון chrF (Character n-gram F-score)
-        let mut char_matches = 0;
-        let mut total_chars = 0;
-        
-        // חישוב התאמות ברמת התווים
-        for window_size in 1..=6 {
-            let hyp_chars: Vec<char> = hypothesis.chars().collect();
-            let ref_chars: Vec<char> = reference.chars().collect();
-            
-            for i in 0..=hyp_chars.len().saturating_sub(window_size) {
-                let hyp_ngram: Vec<_> = hyp_chars[i..i+window_size].to_vec();
-                
-                for j in 0..=ref_chars.len().saturating_sub(window_size) {
-                    let ref_ngram: Vec<_> = ref_chars[j..j+window_size].to_vec();
-                    
-                    if hyp_ngram == ref_ngram {
-                        char_matches += 1;
-                        break;
-                    }
-                }
-                
-                total_chars += 1;
-            }
-        }
-        
-        if total_chars == 0 {
-            0.0
-        } else {
-            char_matches as f64 / total_chars as f64
-        }
+        ChrF::default().sentence_score(hypothesis, &[reference]) / 100.0
     }
     
     fn evaluate_technical_accuracy(&self, source_text: &str, translated_text: &str) -> f64 {
@@ -342,13 +351,13 @@ impl Evaluator {
     fn check_style(&self, text: &str) -> Vec<StyleError> {
         let mut errors = Vec::new();
         
-        // בדיקת התאמה לסגנון הנדרש
-        if !self.style_guide.check_formality(text) {
+        // בדיקת התאמה למשלב הנדרש
+        for issue in self.style_guide.check_formality(text) {
             errors.push(StyleError {
                 error_type: StyleErrorType::Formality,
-                text_span: text.to_string(),
-                suggestion: "".to_string(),
-                explanation: "רמת הפורמליות אינה מתאימה".to_string(),
+                text_span: issue.matched,
+                suggestion: issue.replacement.unwrap_or_default(),
+                explanation: issue.message,
             });
         }
         
@@ -373,13 +382,22 @@ impl Evaluator {
     }
 }
 
-#[derive(Debug, Clone)]
+/// הגדרות המשלב של הפרויקט; נטען מקובץ TOML או JSON לצד המסמכים
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct StyleGuide {
     formality_level: FormalityLevel,
+    /// לכל תחום, כללים בכתיב "אסור => מועדף"; מילה בלי חץ רק מסומנת
     domain_specific_rules: HashMap<String, Vec<String>>,
 }
 
-#[derive(Debug, Clone)]
+impl Default for StyleGuide {
+    fn default() -> Self {
+        Self::new(FormalityLevel::Formal)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum FormalityLevel {
     Formal,
     SemiFormal,
@@ -394,16 +412,41 @@ impl StyleGuide {
         }
     }
     
+    pub fn load(path: &Path) -> Result<Self, StyleGuideError> {
+        register::read_style_guide(path)
+    }
+    
+    pub fn formality_level(&self) -> FormalityLevel {
+        self.formality_level
+    }
+    
     pub fn add_domain_rule(&mut self, domain: String, rules: Vec<String>) {
         self.domain_specific_rules.insert(domain, rules);
     }
     
-    fn check_formality(&self, text: &str) -> bool {
-        // בדיקת התאמה לרמת הפורמליות
-        match self.formality_level {
-            FormalityLevel::Formal => !text.contains("אתה") && !text.contains("את"),
-            FormalityLevel::SemiFormal => true,
-            FormalityLevel::Informal => true,
-        }
+    /// הפרות המשלב בטקסט בשפה נתונה: כללי התחום קודמים לכללים המובנים של רמת הפורמליות
+    pub fn register_issues(&self, text: &str, language: Language, domain: Option<&str>) -> Vec<RegisterIssue> {
+        let domain_rules: Vec<RegisterRule> = domain
+            .and_then(|domain| self.domain_specific_rules.get(domain))
+            .into_iter()
+            .flatten()
+            .filter_map(|rule| RegisterRule::parse_domain_rule(rule))
+            .collect();
+        
+        register::find_issues(
+            text,
+            language,
+            domain_rules.iter().chain(register::builtin_rules(self.formality_level)),
+        )
+    }
+    
+    /// בחירת הווריאנטים הלקסיקליים לפי המשלב; הפרה בלי חלופה בטוחה נשארת לבדיקת QA
+    pub fn apply_register(&self, text: &str, language: Language, domain: Option<&str>) -> String {
+        register::apply_issues(text, &self.register_issues(text, language, domain))
+    }
+    
+    fn check_formality(&self, text: &str) -> Vec<RegisterIssue> {
+        let language = LanguageDetector::new().detect_language(text);
+        self.register_issues(text, language, None)
     }
 } 
\ No newline at end of file
@@ -0,0 +1,342 @@
+//! בקרת משלב: וריאנטים לקסיקליים לפי רמת הפורמליות שהפרויקט הגדיר ב-StyleGuide.
+//! במסמך פורמלי רוסי הפנייה היא ב"Вы" ובציווי רבים, ובעברית בניסוח "יש ל..." ובאוצר מילים גבוה.
+//! כללי התחום נכתבים "אסור => מועדף", ומילה בלי חץ רק מסומנת
+
+use std::fs;
+use std::path::Path;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+use thiserror::Error;
+
+use super::FormalityLevel;
+use crate::language_detection::Language;
+
+/// אותיות שימוש שנשמרות לפני מילה עברית שמוחלפת (ועכשיו -> וכעת). ש אינה ביניהן: "שלמה" ו"שאיפה"
+/// הן מילים בפני עצמן ולא ש+למה או ש+איפה
+const HEBREW_PREFIXES: &str = "ו";
+
+/// פנייה לקורא בגוף שני יחיד, והחלופה הפורמלית
+const RUSSIAN_ADDRESS: &[(&str, &str)] = &[
+    ("ты", "Вы"), ("тебя", "Вас"), ("тебе", "Вам"), ("тобой", "Вами"),
+    ("твой", "Ваш"), ("твоя", "Ваша"), ("твоё", "Ваше"), ("твое", "Ваше"), ("твои", "Ваши"),
+    ("твою", "Вашу"), ("твоего", "Вашего"), ("твоей", "Вашей"), ("твоему", "Вашему"),
+    ("твоим", "Вашим"), ("твоих", "Ваших"),
+    ("нажми", "нажмите"), ("проверь", "проверьте"), ("открой", "откройте"), ("закрой", "закройте"),
+    ("убедись", "убедитесь"), ("включи", "включите"), ("выключи", "выключите"), ("отключи", "отключите"),
+    ("подключи", "подключите"), ("установи", "установите"), ("сними", "снимите"),
+];
+
+const RUSSIAN_VOCABULARY: &[(&str, &str)] = &[
+    ("щас", "сейчас"), ("комп", "компьютер"), ("инфа", "информация"), ("ладно", "хорошо"),
+];
+
+/// עתיד גוף שני כציווי; במסמך פורמלי "יש ל..." ובלי פנייה ישירה
+const HEBREW_ADDRESS: &[(&str, Option<&str>)] = &[
+    ("תלחץ", Some("יש ללחוץ")), ("תסגור", Some("יש לסגור")), ("תפתח", Some("יש לפתוח")),
+    ("תבדוק", Some("יש לבדוק")), ("תוודא", Some("יש לוודא")), ("תכבה", Some("יש לכבות")),
+    ("תדליק", Some("יש להדליק")), ("תחבר", Some("יש לחבר")), ("תנתק", Some("יש לנתק")),
+    ("תעביר", Some("יש להעביר")), ("תוריד", Some("יש להוריד")),
+    ("אתה", None), ("שלך", None),
+];
+
+/// החלפות שתלויות בהקשר ולכן רק מוצעות בבדיקת QA: "ты" דורש התאמת פועל ("ты должен" -> "Вы должны"),
+/// "בגלל ש" אינו "עקב ש", "בסדר" הוא גם "בסדר הפוך", "למה" הוא גם "ל+מה" ("בהתאם למה שמפורט"),
+/// "אחרי" הוא גם מקום ("מאחורי") ולא רק זמן, ו"אבל" הוא גם אֵבֶל
+const REVIEW_ONLY: &[&str] = &["ты", "בגלל", "בסדר", "למה", "אחרי", "אבל"];
+
+const HEBREW_VOCABULARY: &[(&str, &str)] = &[
+    ("בגלל", "עקב"), ("אבל", "אולם"), ("עכשיו", "כעת"), ("אחרי", "לאחר"),
+    ("ככה", "כך"), ("איפה", "היכן"), ("למה", "מדוע"), ("בסדר", "תקין"),
+];
+
+lazy_static! {
+    static ref BUILTIN_RULES: Vec<RegisterRule> = {
+        let mut rules = Vec::new();
+        for (avoid, prefer) in RUSSIAN_ADDRESS {
+            rules.push(RegisterRule::new(Some(Language::Russian), RegisterKind::Address, avoid, Some(prefer)));
+        }
+        for (avoid, prefer) in RUSSIAN_VOCABULARY {
+            rules.push(RegisterRule::new(Some(Language::Russian), RegisterKind::Vocabulary, avoid, Some(prefer)));
+        }
+        // "תסגור" -> "יש לסגור" שובר משפט כמו "אם תסגור" או "אתה תסגור"
+        for (avoid, prefer) in HEBREW_ADDRESS {
+            rules.push(RegisterRule::new(Some(Language::Hebrew), RegisterKind::Address, avoid, *prefer).review_only());
+        }
+        for (avoid, prefer) in HEBREW_VOCABULARY {
+            rules.push(RegisterRule::new(Some(Language::Hebrew), RegisterKind::Vocabulary, avoid, Some(prefer)));
+        }
+        for rule in rules.iter_mut().filter(|rule| REVIEW_ONLY.contains(&rule.avoid.as_str())) {
+            rule.auto_apply = false;
+        }
+        rules
+    };
+}
+
+#[derive(Error, Debug)]
+pub enum StyleGuideError {
+    #[error("שגיאת קובץ: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("JSON לא תקין: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("TOML לא תקין: {0}")]
+    Toml(#[from] toml::de::Error),
+
+    #[error("סוג קובץ לא נתמך: {0}")]
+    UnsupportedFormat(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RegisterKind {
+    /// פנייה ישירה לקורא: גוף שני, ציווי יחיד
+    Address,
+    /// מילה במשלב נמוך שיש לה חלופה גבוהה
+    Vocabulary,
+    /// כלל שהפרויקט הגדיר לתחום
+    Domain,
+}
+
+impl RegisterKind {
+    pub fn rule_id(self) -> &'static str {
+        match self {
+            RegisterKind::Address => "register-address",
+            RegisterKind::Vocabulary => "register-vocabulary",
+            RegisterKind::Domain => "register-domain",
+        }
+    }
+
+    fn message(self) -> &'static str {
+        match self {
+            RegisterKind::Address => "פנייה לא רשמית לקורא במסמך פורמלי",
+            RegisterKind::Vocabulary => "מילה במשלב נמוך מהנדרש בפרויקט",
+            RegisterKind::Domain => "המילה אינה לפי כללי התחום של הפרויקט",
+        }
+    }
+}
+
+/// וריאנט אסור והחלופה שלו; language ריק חל על כל שפה.
+/// auto_apply מסמן חלופה שתקינה בכל הקשר; אחרת היא מוצעת רק בבדיקת QA
+#[derive(Debug, Clone)]
+pub struct RegisterRule {
+    pub language: Option<Language>,
+    pub kind: RegisterKind,
+    pub avoid: String,
+    pub prefer: Option<String>,
+    pub auto_apply: bool,
+    pattern: Regex,
+}
+
+impl RegisterRule {
+    pub fn new(language: Option<Language>, kind: RegisterKind, avoid: &str, prefer: Option<&str>) -> Self {
+        // בעברית נשמרות אותיות השימוש ו/ש, ברוסית ההתאמה אינה תלויה ברישיות
+        let pattern = match language {
+            Some(Language::Hebrew) => format!(r"\b([{}]?)({})\b", HEBREW_PREFIXES, regex::escape(avoid)),
+            _ => format!(r"(?i)\b()({})\b", regex::escape(avoid)),
+        };
+        Self {
+            language,
+            kind,
+            avoid: avoid.to_string(),
+            prefer: prefer.map(str::to_string),
+            auto_apply: true,
+            pattern: Regex::new(&pattern).expect("ביטוי מוברח תמיד תקין"),
+        }
+    }
+
+    /// החלופה מוצעת לבודק ואינה מוחלת על התרגום
+    pub fn review_only(mut self) -> Self {
+        self.auto_apply = false;
+        self
+    }
+
+    /// כלל תחום בכתיב "אסור => מועדף" או "אסור -> מועדף"; השפה נקבעת לפי הכתב של המילה האסורה
+    pub fn parse_domain_rule(rule: &str) -> Option<Self> {
+        let (avoid, prefer) = match rule.split_once("=>").or_else(|| rule.split_once("->")) {
+            Some((avoid, prefer)) => (avoid.trim(), Some(prefer.trim()).filter(|p| !p.is_empty())),
+            None => (rule.trim(), None),
+        };
+        if avoid.is_empty() {
+            return None;
+        }
+
+        let language = if avoid.chars().any(|c| ('\u{05D0}'..='\u{05EA}').contains(&c)) {
+            Some(Language::Hebrew)
+        } else if avoid.chars().any(|c| ('\u{0400}'..='\u{04FF}').contains(&c)) {
+            Some(Language::Russian)
+        } else {
+            None
+        };
+        Some(Self::new(language, RegisterKind::Domain, avoid, prefer))
+    }
+
+    fn applies_to(&self, language: Language) -> bool {
+        self.language.is_none_or(|l| l == language)
+    }
+}
+
+/// הכללים המובנים שנאכפים ברמת הפורמליות: פורמלי - פנייה ואוצר מילים, חצי פורמלי - פנייה בלבד
+pub fn builtin_rules(level: FormalityLevel) -> Vec<&'static RegisterRule> {
+    BUILTIN_RULES
+        .iter()
+        .filter(|rule| match level {
+            FormalityLevel::Formal => true,
+            FormalityLevel::SemiFormal => rule.kind == RegisterKind::Address,
+            FormalityLevel::Informal => false,
+        })
+        .collect()
+}
+
+/// הפרה של המשלב בטקסט, עם טווח בבתים והחלופה המוצעת
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegisterIssue {
+    pub kind: RegisterKind,
+    pub span: (usize, usize),
+    pub matched: String,
+    pub replacement: Option<String>,
+    pub message: String,
+    #[serde(default)]
+    pub auto_apply: bool,
+}
+
+/// כל ההפרות בטקסט לפי סדר ההופעה; התאמות חופפות נשמרות לכלל הראשון
+pub fn find_issues<'a>(text: &str, language: Language, rules: impl IntoIterator<Item = &'a RegisterRule>) -> Vec<RegisterIssue> {
+    let mut issues: Vec<RegisterIssue> = Vec::new();
+    for rule in rules.into_iter().filter(|rule| rule.applies_to(language)) {
+        for captures in rule.pattern.captures_iter(text) {
+            let word = captures.get(2).unwrap();
+            if issues.iter().any(|issue| issue.span.0 < word.end() && word.start() < issue.span.1) {
+                continue;
+            }
+            issues.push(RegisterIssue {
+                kind: rule.kind,
+                span: (word.start(), word.end()),
+                matched: word.as_str().to_string(),
+                replacement: rule.prefer.as_deref().map(|prefer| match_case(word.as_str(), prefer)),
+                message: format!("{}: \"{}\"", rule.kind.message(), word.as_str()),
+                auto_apply: rule.auto_apply,
+            });
+        }
+    }
+    issues.sort_by_key(|issue| issue.span.0);
+    issues
+}
+
+/// החלפת ההפרות שהחלופה שלהן תקינה בכל הקשר; השאר נשארות בטקסט לבדיקת QA
+pub fn apply_issues(text: &str, issues: &[RegisterIssue]) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut last = 0;
+    for issue in issues.iter().filter(|issue| issue.auto_apply) {
+        let Some(replacement) = &issue.replacement else {
+            continue;
+        };
+        result.push_str(&text[last..issue.span.0]);
+        result.push_str(replacement);
+        last = issue.span.1;
+    }
+    result.push_str(&text[last..]);
+    result
+}
+
+/// מילה שמתחילה באות גדולה ("Нажми") מקבלת חלופה שמתחילה באות גדולה
+fn match_case(matched: &str, replacement: &str) -> String {
+    let mut chars = replacement.chars();
+    match (matched.chars().next(), chars.next()) {
+        (Some(first), Some(replacement_first)) if first.is_uppercase() => {
+            replacement_first.to_uppercase().chain(chars).collect()
+        }
+        _ => replacement.to_string(),
+    }
+}
+
+/// קריאת הגדרות הסגנון של הפרויקט מ-TOML או JSON, לפי הסיומת
+pub(super) fn read_style_guide<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T, StyleGuideError> {
+    let content = fs::read_to_string(path)?;
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("toml") => Ok(toml::from_str(&content)?),
+        Some("json") => Ok(serde_json::from_str(&content)?),
+        _ => Err(StyleGuideError::UnsupportedFormat(path.display().to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fix(text: &str, language: Language, level: FormalityLevel) -> String {
+        apply_issues(text, &find_issues(text, language, builtin_rules(level)))
+    }
+
+    #[test]
+    fn test_formal_russian_address() {
+        assert_eq!(
+            fix("Нажми кнопку и проверь, что твой насос включён.", Language::Russian, FormalityLevel::Formal),
+            "Нажмите кнопку и проверьте, что Ваш насос включён."
+        );
+        assert_eq!(fix("Это твоя задача.", Language::Russian, FormalityLevel::SemiFormal), "Это Ваша задача.");
+        // "ты" מחייב התאמת פועל: מוצע לבודק בלבד
+        let text = "Сейчас ты должен закрыть клапан.";
+        let issues = find_issues(text, Language::Russian, builtin_rules(FormalityLevel::Formal));
+        assert_eq!(issues[0].replacement.as_deref(), Some("Вы"));
+        assert!(!issues[0].auto_apply);
+        assert_eq!(apply_issues(text, &issues), text);
+        // "ты" בתוך מילה אחרת אינו פנייה
+        assert!(find_issues("Результаты испытаний", Language::Russian, builtin_rules(FormalityLevel::Formal)).is_empty());
+    }
+
+    #[test]
+    fn test_hebrew_register_by_level() {
+        let text = "עכשיו תסגור את המגוף, אבל אתה צריך לבדוק ככה";
+        // הפנייה תלויה בהקשר ונשארת לבודק; אוצר המילים החד-משמעי מוחלף
+        assert_eq!(
+            fix(text, Language::Hebrew, FormalityLevel::Formal),
+            "כעת תסגור את המגוף, אבל אתה צריך לבדוק כך"
+        );
+        assert_eq!(fix("אם תסגור בגלל הלחץ", Language::Hebrew, FormalityLevel::Formal), "אם תסגור בגלל הלחץ");
+        // חצי פורמלי: רק הפנייה; "אתה" מסומן בלי חלופה
+        let issues = find_issues(text, Language::Hebrew, builtin_rules(FormalityLevel::SemiFormal));
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].replacement.as_deref(), Some("יש לסגור"));
+        assert_eq!(issues[1].matched, "אתה");
+        assert_eq!(issues[1].replacement, None);
+        assert!(builtin_rules(FormalityLevel::Informal).is_empty());
+
+        // אות השימוש נשמרת
+        assert_eq!(fix("ועכשיו לחץ", Language::Hebrew, FormalityLevel::Formal), "וכעת לחץ");
+    }
+
+    #[test]
+    fn test_hebrew_homographs_are_not_rewritten() {
+        for text in ["בדיקה שלמה של המשאבה", "בהתאם למה שמפורט בתקן", "צינור שאיפה", "המסנן מותקן אחרי המשאבה"] {
+            assert_eq!(fix(text, Language::Hebrew, FormalityLevel::Formal), text);
+        }
+        // "למה" עדיין מוצע לבודק
+        let issues = find_issues("למה הלחץ נמוך", Language::Hebrew, builtin_rules(FormalityLevel::Formal));
+        assert_eq!(issues[0].replacement.as_deref(), Some("מדוע"));
+        assert!(!issues[0].auto_apply);
+    }
+
+    #[test]
+    fn test_domain_rules() {
+        let rules: Vec<RegisterRule> = ["כבאי => לוחם אש", "огнетушитель ОУ -> огнетушитель углекислотный", "ברז"]
+            .iter()
+            .filter_map(|rule| RegisterRule::parse_domain_rule(rule))
+            .collect();
+        assert_eq!(rules[0].language, Some(Language::Hebrew));
+        assert_eq!(rules[2].prefer, None);
+
+        let text = "וכבאי פתח את ברז המים";
+        let issues = find_issues(text, Language::Hebrew, &rules);
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].kind, RegisterKind::Domain);
+        assert_eq!(issues[1].replacement, None);
+        assert_eq!(apply_issues(text, &issues), "ולוחם אש פתח את ברז המים");
+        // "ברז" מותאם רק כמילה שלמה
+        assert!(find_issues("הברזים", Language::Hebrew, &rules).is_empty());
+
+        let russian = find_issues("Взять огнетушитель ОУ-5", Language::Russian, &rules);
+        assert_eq!(russian[0].replacement.as_deref(), Some("огнетушитель углекислотный"));
+    }
+}
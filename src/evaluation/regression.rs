@@ -0,0 +1,514 @@
+//! בדיקות רגרסיה מול סט זהב: מתרגמים סט מגורסה של זוגות רוסית-עברית, מחשבים מדדים דרך Evaluator,
+//! משווים לבסיס השמור של הגרסה הקודמת ונכשלים בירידה שחורגת מהספים, עם השוואה לכל סגמנט
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use serde::{Serialize, Deserialize};
+use thiserror::Error;
+
+use super::metrics::{ChrF, CorpusMetric, MetricsError};
+use super::{CorpusScores, Evaluator};
+use crate::language_detection::Language;
+use crate::translation::{TranslationEngine, TranslationRequest};
+
+/// הבסיס השמור של הגרסה האחרונה לסט הזהב ru-he
+pub const DEFAULT_BASELINE_PATH: &str = "golden/ru-he.baseline.json";
+
+#[derive(Error, Debug)]
+pub enum RegressionError {
+    #[error(transparent)]
+    Metrics(#[from] MetricsError),
+
+    #[error("שגיאת תרגום: {0}")]
+    Translation(String),
+
+    #[error("המנוע החזיר {outputs} תרגומים ל-{segments} סגמנטים")]
+    OutputCount { segments: usize, outputs: usize },
+
+    #[error("מזהה סגמנט כפול בסט הזהב: {0}")]
+    DuplicateId(String),
+
+    /// תרגום שזהה למקור הוא פלט של מנוע ללא מודל; השוואה אליו או ממנו אינה מוכיחה דבר
+    #[error("התרגום זהה למקור בסגמנטים {}; יש להריץ עם מודל", .0.join(", "))]
+    UntranslatedOutput(Vec<String>),
+
+    #[error("שגיאת קובץ: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("קובץ לא תקין: {0}")]
+    Format(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoldenSegment {
+    pub id: String,
+    pub source: String,
+    pub reference: String,
+    #[serde(default)]
+    pub domain: Option<String>,
+}
+
+/// סט זהב מגורסה; שינוי בסגמנטים מחייב העלאת גרסה, כדי שלא ישוו לבסיס של סט אחר
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoldenSet {
+    pub name: String,
+    pub version: String,
+    pub segments: Vec<GoldenSegment>,
+}
+
+impl GoldenSet {
+    pub fn load(path: &Path) -> Result<Self, RegressionError> {
+        let set: GoldenSet = serde_json::from_str(&fs::read_to_string(path)?)?;
+        let mut ids = std::collections::HashSet::new();
+        if let Some(duplicate) = set.segments.iter().find(|s| !ids.insert(s.id.as_str())) {
+            return Err(RegressionError::DuplicateId(duplicate.id.clone()));
+        }
+        Ok(set)
+    }
+}
+
+/// תוצאות שמורות של גרסה, שמשמשות בסיס להשוואה בגרסה הבאה
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegressionBaseline {
+    pub release: String,
+    pub golden_set: String,
+    pub golden_version: String,
+    pub scores: CorpusScores,
+    /// תרגום ו-chrF לכל סגמנט לפי מזהה
+    pub outputs: BTreeMap<String, String>,
+    pub segment_chrf: BTreeMap<String, f64>,
+}
+
+impl RegressionBaseline {
+    pub fn load(path: &Path) -> Result<Self, RegressionError> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), RegressionError> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// הירידות המותרות בנקודות sacreBLEU (0-100) לפני שהבדיקה נכשלת
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RegressionThresholds {
+    pub max_bleu_drop: f64,
+    pub max_chrf_drop: f64,
+    /// TER נמוך יותר הוא טוב יותר, ולכן נבדקת עלייה
+    pub max_ter_increase: f64,
+    /// ירידה ב-chrF של סגמנט בודד שמסמנת אותו כרגרסיה בהשוואה
+    pub segment_chrf_drop: f64,
+}
+
+impl Default for RegressionThresholds {
+    fn default() -> Self {
+        Self {
+            max_bleu_drop: 1.0,
+            max_chrf_drop: 1.0,
+            max_ter_increase: 1.0,
+            segment_chrf_drop: 10.0,
+        }
+    }
+}
+
+/// סגמנט שהתרגום שלו השתנה מאז הבסיס
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SegmentChange {
+    pub id: String,
+    pub source: String,
+    pub reference: String,
+    /// ריק לסגמנט חדש שאין לו תרגום בבסיס
+    pub baseline_output: Option<String>,
+    pub output: String,
+    pub baseline_chrf: Option<f64>,
+    pub chrf: f64,
+    pub regressed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricChange {
+    pub metric: String,
+    pub baseline: f64,
+    pub current: f64,
+    /// השינוי בכיוון "טוב יותר": שלילי הוא ירידה באיכות
+    pub improvement: f64,
+    pub allowed_drop: f64,
+    pub failed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegressionReport {
+    pub release: String,
+    pub baseline_release: Option<String>,
+    pub scores: CorpusScores,
+    pub metric_changes: Vec<MetricChange>,
+    /// הסגמנטים שהשתנו, מהירידה הגדולה ביותר
+    pub changed_segments: Vec<SegmentChange>,
+    /// הבסיס נוצר מגרסה אחרת של סט הזהב, ולכן ההשוואה דולגה; יש לשמור בסיס חדש
+    pub golden_version_changed: bool,
+    /// הבסיס החדש לשמירה אם הגרסה מאושרת
+    pub baseline: RegressionBaseline,
+}
+
+impl RegressionReport {
+    pub fn passed(&self) -> bool {
+        self.metric_changes.iter().all(|change| !change.failed)
+    }
+
+    pub fn summary(&self) -> String {
+        let mut lines = vec![format!(
+            "{}: BLEU {:.2}, chrF2 {:.2}, chrF++ {:.2}, TER {:.2}",
+            self.release, self.scores.bleu.score, self.scores.chrf, self.scores.chrf_plus_plus, self.scores.ter
+        )];
+        for change in &self.metric_changes {
+            lines.push(format!(
+                "{} {}: {:.2} -> {:.2} ({:+.2}, מותר -{:.2})",
+                if change.failed { "FAIL" } else { "ok  " },
+                change.metric,
+                change.baseline,
+                change.current,
+                change.improvement,
+                change.allowed_drop
+            ));
+        }
+        let regressed = self.changed_segments.iter().filter(|s| s.regressed).count();
+        lines.push(format!("{} סגמנטים השתנו, {} מהם בירידה", self.changed_segments.len(), regressed));
+        lines.join("\n")
+    }
+}
+
+pub struct RegressionRunner {
+    evaluator: Evaluator,
+    thresholds: RegressionThresholds,
+}
+
+impl RegressionRunner {
+    pub fn new(evaluator: Evaluator) -> Self {
+        Self {
+            evaluator,
+            thresholds: RegressionThresholds::default(),
+        }
+    }
+
+    pub fn with_thresholds(mut self, thresholds: RegressionThresholds) -> Self {
+        self.thresholds = thresholds;
+        self
+    }
+
+    /// תרגום סט הזהב במנוע ובדיקה מול הבסיס; translate מקבל את כל מקורות הסט ומחזיר תרגום לכל אחד
+    pub fn run<F, E>(
+        &self,
+        golden: &GoldenSet,
+        release: &str,
+        baseline: Option<&RegressionBaseline>,
+        translate: F,
+    ) -> Result<RegressionReport, RegressionError>
+    where
+        F: FnOnce(&[String]) -> Result<Vec<String>, E>,
+        E: std::fmt::Display,
+    {
+        let sources: Vec<String> = golden.segments.iter().map(|s| s.source.clone()).collect();
+        let outputs = translate(&sources).map_err(|e| RegressionError::Translation(e.to_string()))?;
+        self.compare(golden, release, baseline, outputs)
+    }
+
+    /// בדיקה של תרגומים שכבר הופקו, לפי סדר הסגמנטים בסט
+    pub fn compare(
+        &self,
+        golden: &GoldenSet,
+        release: &str,
+        baseline: Option<&RegressionBaseline>,
+        outputs: Vec<String>,
+    ) -> Result<RegressionReport, RegressionError> {
+        if outputs.len() != golden.segments.len() {
+            return Err(RegressionError::OutputCount { segments: golden.segments.len(), outputs: outputs.len() });
+        }
+        let untranslated = untranslated_segments(golden, |index, _| Some(outputs[index].as_str()));
+        if !untranslated.is_empty() {
+            return Err(RegressionError::UntranslatedOutput(untranslated));
+        }
+        if let Some(baseline) = baseline {
+            let untranslated = untranslated_segments(golden, |_, id| baseline.outputs.get(id).map(String::as_str));
+            if !untranslated.is_empty() {
+                return Err(RegressionError::UntranslatedOutput(untranslated));
+            }
+        }
+
+        let references = vec![golden.segments.iter().map(|s| s.reference.clone()).collect::<Vec<_>>()];
+        let scores = self.evaluator.score_corpus(&outputs, &references)?;
+
+        let chrf = ChrF::default();
+        let segment_chrf: BTreeMap<String, f64> = golden
+            .segments
+            .iter()
+            .zip(&outputs)
+            .map(|(segment, output)| (segment.id.clone(), chrf.sentence_score(output, &[&segment.reference])))
+            .collect();
+
+        let new_baseline = RegressionBaseline {
+            release: release.to_string(),
+            golden_set: golden.name.clone(),
+            golden_version: golden.version.clone(),
+            scores: scores.clone(),
+            outputs: golden.segments.iter().map(|s| s.id.clone()).zip(outputs.iter().cloned()).collect(),
+            segment_chrf: segment_chrf.clone(),
+        };
+
+        // ציונים על סט זהב אחר אינם בני השוואה
+        let golden_version_changed = baseline
+            .is_some_and(|baseline| baseline.golden_version != golden.version || baseline.golden_set != golden.name);
+        let baseline = match baseline {
+            Some(baseline) if !golden_version_changed => baseline,
+            _ => {
+                return Ok(RegressionReport {
+                    release: release.to_string(),
+                    baseline_release: baseline.map(|baseline| baseline.release.clone()),
+                    scores,
+                    metric_changes: Vec::new(),
+                    changed_segments: Vec::new(),
+                    golden_version_changed,
+                    baseline: new_baseline,
+                });
+            }
+        };
+
+        let thresholds = &self.thresholds;
+        let metric_changes = vec![
+            metric_change("BLEU", baseline.scores.bleu.score, scores.bleu.score, true, thresholds.max_bleu_drop),
+            metric_change("chrF2", baseline.scores.chrf, scores.chrf, true, thresholds.max_chrf_drop),
+            metric_change("TER", baseline.scores.ter, scores.ter, false, thresholds.max_ter_increase),
+        ];
+
+        let mut changed_segments: Vec<SegmentChange> = golden
+            .segments
+            .iter()
+            .zip(&outputs)
+            .filter(|(segment, output)| baseline.outputs.get(&segment.id) != Some(*output))
+            .map(|(segment, output)| {
+                let chrf = segment_chrf[&segment.id];
+                let baseline_chrf = baseline.segment_chrf.get(&segment.id).copied();
+                SegmentChange {
+                    id: segment.id.clone(),
+                    source: segment.source.clone(),
+                    reference: segment.reference.clone(),
+                    baseline_output: baseline.outputs.get(&segment.id).cloned(),
+                    output: output.clone(),
+                    baseline_chrf,
+                    chrf,
+                    regressed: baseline_chrf.is_some_and(|before| before - chrf >= thresholds.segment_chrf_drop),
+                }
+            })
+            .collect();
+        changed_segments.sort_by(|a, b| {
+            let delta = |s: &SegmentChange| s.chrf - s.baseline_chrf.unwrap_or(s.chrf);
+            delta(a).total_cmp(&delta(b)).then_with(|| a.id.cmp(&b.id))
+        });
+
+        Ok(RegressionReport {
+            release: release.to_string(),
+            baseline_release: Some(baseline.release.clone()),
+            scores,
+            metric_changes,
+            changed_segments,
+            golden_version_changed: false,
+            baseline: new_baseline,
+        })
+    }
+}
+
+/// תרגום סט הזהב במנוע המלא, כמו בממשק: כל סגמנט זהב הוא מסמך רוסי לתרגום לעברית
+pub async fn translate_golden(engine: &TranslationEngine, golden: &GoldenSet) -> Result<Vec<String>, RegressionError> {
+    let mut outputs = Vec::with_capacity(golden.segments.len());
+    for segment in &golden.segments {
+        let request = TranslationRequest {
+            text: segment.source.clone(),
+            source_language: Language::Russian,
+            target_language: Language::Hebrew,
+            overrides: Default::default(),
+        };
+        let result = engine
+            .translate(request)
+            .await
+            .map_err(|e| RegressionError::Translation(format!("{}: {}", segment.id, e)))?;
+        outputs.push(result.translated_text);
+    }
+    Ok(outputs)
+}
+
+/// מזהי הסגמנטים שהתרגום שלהם הוא המקור עצמו; סגמנט בלי אותיות (מספר בלבד) אינו נחשב
+fn untranslated_segments<'a>(golden: &GoldenSet, output: impl Fn(usize, &str) -> Option<&'a str>) -> Vec<String> {
+    golden
+        .segments
+        .iter()
+        .enumerate()
+        .filter(|(index, segment)| {
+            segment.source.chars().any(char::is_alphabetic)
+                && output(*index, &segment.id).is_some_and(|output| output.trim() == segment.source.trim())
+        })
+        .map(|(_, segment)| segment.id.clone())
+        .collect()
+}
+
+fn metric_change(metric: &str, baseline: f64, current: f64, higher_is_better: bool, allowed_drop: f64) -> MetricChange {
+    let improvement = if higher_is_better { current - baseline } else { baseline - current };
+    MetricChange {
+        metric: metric.to_string(),
+        baseline,
+        current,
+        improvement,
+        allowed_drop,
+        failed: -improvement > allowed_drop,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use crate::evaluation::{FormalityLevel, StyleGuide};
+
+    fn runner() -> RegressionRunner {
+        RegressionRunner::new(Evaluator::new(HashMap::new(), HashMap::new(), StyleGuide::new(FormalityLevel::Formal)))
+    }
+
+    fn golden() -> GoldenSet {
+        GoldenSet::load(&PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("golden/ru-he.json")).unwrap()
+    }
+
+    fn references(golden: &GoldenSet) -> Vec<String> {
+        golden.segments.iter().map(|s| s.reference.clone()).collect()
+    }
+
+    #[test]
+    fn test_first_run_creates_baseline() {
+        let golden = golden();
+        let report = runner()
+            .run(&golden, "0.2.0", None, |_: &[String]| Ok::<_, String>(references(&golden)))
+            .unwrap();
+
+        assert!(report.passed());
+        assert!(report.metric_changes.is_empty());
+        assert_eq!(report.baseline.outputs.len(), golden.segments.len());
+        assert_eq!(report.baseline.golden_version, golden.version);
+    }
+
+    #[test]
+    fn test_detects_drop_and_diffs_segments() {
+        let golden = golden();
+        let runner = runner();
+        let baseline = runner.compare(&golden, "0.2.0", None, references(&golden)).unwrap().baseline;
+        assert!((baseline.scores.bleu.score - 100.0).abs() < 1e-6);
+
+        // אותו פלט: אין שינוי
+        let report = runner.compare(&golden, "0.3.0", Some(&baseline), references(&golden)).unwrap();
+        assert!(report.passed());
+        assert!(report.changed_segments.is_empty());
+
+        // סגמנט אחד נפגע מאוד
+        let mut outputs = references(&golden);
+        outputs[1] = "משהו אחר לגמרי".to_string();
+        let report = runner.compare(&golden, "0.3.0", Some(&baseline), outputs).unwrap();
+        assert!(!report.passed());
+        assert_eq!(report.changed_segments.len(), 1);
+        assert_eq!(report.changed_segments[0].id, golden.segments[1].id);
+        assert!(report.changed_segments[0].regressed);
+        assert_eq!(report.changed_segments[0].baseline_output.as_deref(), Some(golden.segments[1].reference.as_str()));
+
+        // ספים רחבים מספיק מאפשרים את השינוי
+        let lenient = RegressionThresholds { max_bleu_drop: 50.0, max_chrf_drop: 50.0, max_ter_increase: 50.0, ..Default::default() };
+        let mut outputs = references(&golden);
+        outputs[1] = "משהו אחר לגמרי".to_string();
+        let report = runner.with_thresholds(lenient).compare(&golden, "0.3.0", Some(&baseline), outputs).unwrap();
+        assert!(report.passed());
+    }
+
+    #[test]
+    fn test_changed_golden_version_skips_comparison() {
+        let golden = golden();
+        let runner = runner();
+        let mut baseline = runner.compare(&golden, "0.2.0", None, references(&golden)).unwrap().baseline;
+        baseline.golden_version = "0".to_string();
+
+        let mut outputs = references(&golden);
+        outputs[1] = "משהו אחר לגמרי".to_string();
+        let report = runner.compare(&golden, "0.3.0", Some(&baseline), outputs).unwrap();
+
+        assert!(report.golden_version_changed);
+        assert!(report.passed());
+        assert!(report.metric_changes.is_empty() && report.changed_segments.is_empty());
+        assert_eq!(report.baseline.golden_version, golden.version);
+    }
+
+    #[test]
+    fn test_untranslated_echo_is_refused() {
+        let golden = golden();
+        let runner = runner();
+
+        let mut outputs = references(&golden);
+        outputs[2] = golden.segments[2].source.clone();
+        match runner.compare(&golden, "0.2.0", None, outputs) {
+            Err(RegressionError::UntranslatedOutput(ids)) => assert_eq!(ids, vec![golden.segments[2].id.clone()]),
+            other => panic!("צפויה שגיאת תרגום זהה למקור, התקבל {:?}", other.map(|r| r.summary())),
+        }
+
+        // גם בסיס שנשמר ממנוע ללא מודל אינו משמש להשוואה
+        let mut baseline = runner.compare(&golden, "0.2.0", None, references(&golden)).unwrap().baseline;
+        baseline.outputs.insert(golden.segments[0].id.clone(), golden.segments[0].source.clone());
+        assert!(matches!(
+            runner.compare(&golden, "0.3.0", Some(&baseline), references(&golden)),
+            Err(RegressionError::UntranslatedOutput(_))
+        ));
+    }
+
+    #[test]
+    fn test_baseline_round_trip_and_errors() {
+        let golden = golden();
+        let runner = runner();
+        let report = runner.compare(&golden, "0.2.0", None, references(&golden)).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("baseline.json");
+        report.baseline.save(&path).unwrap();
+        let loaded = RegressionBaseline::load(&path).unwrap();
+        assert_eq!(loaded.outputs, report.baseline.outputs);
+
+        assert!(matches!(
+            runner.compare(&golden, "0.3.0", Some(&loaded), vec![String::new()]),
+            Err(RegressionError::OutputCount { .. })
+        ));
+        assert!(matches!(
+            runner.run(&golden, "0.3.0", Some(&loaded), |_: &[String]| Err::<Vec<String>, _>("המודל לא נטען")),
+            Err(RegressionError::Translation(_))
+        ));
+    }
+
+    /// בדיקת הרגרסיה המלאה דרך TranslationEngine; רצה רק על פי דרישה. GOLDEN_MODEL_DIR מוסיף מודל ONNX,
+    /// GOLDEN_BASELINE מחליף את הבסיס השמור. בלי מודל המנוע מחזיר את המקור וההרצה נכשלת;
+    /// בלי בסיס ההרצה רק מדווחת את הציונים:
+    /// GOLDEN_MODEL_DIR=models/onnx cargo test --profile golden -- --ignored golden_set
+    #[tokio::test]
+    #[ignore]
+    async fn golden_set_regression() {
+        let mut engine = TranslationEngine::new();
+        if let Ok(model_dir) = std::env::var("GOLDEN_MODEL_DIR") {
+            let translator = crate::neural::onnx::OnnxTranslator::load(&model_dir).unwrap();
+            engine.register_backend(Language::Russian, Language::Hebrew, std::sync::Arc::new(translator));
+        }
+        let baseline_path = std::env::var("GOLDEN_BASELINE")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(DEFAULT_BASELINE_PATH));
+        let baseline = baseline_path.exists().then(|| RegressionBaseline::load(&baseline_path).unwrap());
+
+        let golden = golden();
+        let outputs = translate_golden(&engine, &golden).await.unwrap();
+        let report = runner()
+            .compare(&golden, env!("CARGO_PKG_VERSION"), baseline.as_ref(), outputs)
+            .unwrap();
+        println!("{}", report.summary());
+        assert!(report.passed(), "{}", report.summary());
+    }
+}
@@ -6,6 +6,7 @@ use std::fs::{self, File};
 use std::io::Write;
 use crate::file_processor::{FileType, ProcessedFile};
 use crate::metadata::FileMetadata;
+use crate::quality_control::typography::Typography;
 use crate::security::SecurityManager;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,6 +17,13 @@ pub struct SaveOptions {
     pub create_backup: bool,
     pub include_metadata: bool,
     pub compress: bool,
+    /// נרמול גרשיים, מקפים, מירכאות ורווחים בלתי שבירים לפי שפת הטקסט לפני הכתיבה
+    #[serde(default = "default_normalize_typography")]
+    pub normalize_typography: bool,
+}
+
+fn default_normalize_typography() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -85,8 +93,14 @@ impl FileSaver {
             None
         };
         
+        // שלב אחרון לפני הכתיבה: תיקונים טיפוגרפיים לפי השפה שזוהתה
+        let translated_text = match Typography::for_text(translated_text) {
+            Some(typography) if options.normalize_typography => typography.normalize(translated_text),
+            _ => translated_text.to_string(),
+        };
+        
         // שמירת הקובץ המתורגם
-        self.write_translated_file(&output_path, original_file, translated_text)?;
+        self.write_translated_file(&output_path, original_file, &translated_text)?;
         
         // שמירת מטא-דאטה
         let metadata_path = if options.include_metadata {
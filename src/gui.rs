@@ -1,14 +1,18 @@
 use iced::{
-    button, scrollable, text_input, Alignment, Button, Column, Container, Element,
-    Length, Row, Scrollable, Settings, Text, TextInput, Application, Command,
+    button, scrollable, slider, text_input, Alignment, Button, Column, Container, Element,
+    Length, Row, Scrollable, Settings, Slider, Text, TextInput, Application, Command,
     window, executor, Subscription, Event, keyboard,
 };
 use iced_native::{Event as NativeEvent, event};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::collections::HashMap;
+use crate::evaluation::calibration::{self, CalibrationConfig, ConfidenceCalibrator};
+use crate::evaluation::StyleGuide;
 use crate::file_processor::{FileProcessor, ProcessedFile, FileType};
-use crate::translation::{TranslationEngine, TranslationRequest, TranslationResult};
+use crate::translation::{EngineConfig, TranslationEngine, TranslationRequest, TranslationResult};
 use crate::file_saver::{FileSaver, SaveOptions};
+use crate::learning_manager::{EventMetrics, LearningEvent, LearningEventType};
+use crate::quality_control::spelling::{self, HunspellDictionary, Misspelling, SpellChecker};
 use crate::theme::{self, Theme, ColorPalette};
 use crate::icons::Icon;
 use anyhow::Result;
@@ -27,6 +31,10 @@ pub enum Message {
     TranslationCancelled,
     EditTranslation(PathBuf, String),
     ApplyEdit(PathBuf),
+    SelectMisspelling(PathBuf, usize),
+    ApplySpellingSuggestion(PathBuf, usize, String),
+    ToggleReviewFilter,
+    ReviewThresholdChanged(f64),
     
     // אירועי שמירה
     SaveTranslation,
@@ -71,9 +79,19 @@ pub struct TranslatorGui {
     // מצב עריכה
     editing_file: Option<PathBuf>,
     edit_content: String,
+    spell_checker: Option<SpellChecker>,
+    misspellings: Vec<Misspelling>,
+    /// המילה השגויה שהמשתמש בחר; רק לה מחושבות הצעות תיקון
+    selected_misspelling: Option<usize>,
     undo_stack: Vec<EditState>,
     redo_stack: Vec<EditState>,
     
+    // סינון לבדיקה: רק סגמנטים שהביטחון המכויל שלהם מתחת לסף
+    review_only: bool,
+    review_threshold: f64,
+    /// הטקסט האחרון שנרשם ביומן הכיול לכל סגמנט, כדי שהחלה חוזרת של אותה עריכה לא תירשם פעמיים
+    recorded_edits: HashMap<(PathBuf, usize), String>,
+    
     // מצב שמירה
     save_options: SaveOptions,
     
@@ -82,6 +100,7 @@ pub struct TranslatorGui {
     preview_scroll: scrollable::State,
     help_scroll: scrollable::State,
     settings_scroll: scrollable::State,
+    review_slider: slider::State,
     search_input: text_input::State,
     save_button: button::State,
     clear_button: button::State,
@@ -104,6 +123,129 @@ enum DropZoneState {
     Processing,
 }
 
+impl TranslatorGui {
+    /// מנוע תרגום עם המודלים שהוגדרו ב-engine.toml לכל כיוון, וכיול הביטחון מ-models/calibration.json,
+    /// כשהקבצים קיימים
+    fn translation_engine() -> TranslationEngine {
+        let path = Path::new("engine.toml");
+        let mut engine = if path.exists() {
+            EngineConfig::load(path)
+                .and_then(|config| TranslationEngine::with_config(&config))
+                .unwrap_or_else(|e| {
+                    log::warn!("תצורת המנוע לא נטענה, התרגום ללא מודל: {:#}", e);
+                    TranslationEngine::new()
+                })
+        } else {
+            TranslationEngine::new()
+        };
+        let path = Path::new(calibration::DEFAULT_CALIBRATION_PATH);
+        if path.exists() {
+            match ConfidenceCalibrator::load(path) {
+                Ok(calibrator) => engine = engine.with_calibrator(calibrator),
+                Err(e) => log::warn!("כיול הביטחון לא נטען: {}", e),
+            }
+        }
+        // משלב הפרויקט: רמת פורמליות וכללי תחום
+        let path = Path::new("style_guide.toml");
+        if path.exists() {
+            match StyleGuide::load(path) {
+                Ok(style_guide) => engine = engine.with_style_guide(style_guide),
+                Err(e) => log::warn!("מדריך הסגנון לא נטען: {}", e),
+            }
+        }
+        engine
+    }
+    
+    /// מילוני Hunspell מתיקיית dictionaries (he_IL, ru_RU); שפה שאין לה מילון אינה נבדקת
+    fn load_spell_checker() -> Option<SpellChecker> {
+        let directory = Path::new("dictionaries");
+        let load = |name: &str| {
+            HunspellDictionary::load(
+                &directory.join(format!("{}.aff", name)),
+                &directory.join(format!("{}.dic", name)),
+            )
+            .map_err(|e| log::warn!("מילון האיות {} לא נטען: {:#}", name, e))
+            .ok()
+        };
+        
+        let hebrew = load("he_IL");
+        let russian = load("ru_RU");
+        if hebrew.is_none() && russian.is_none() {
+            return None;
+        }
+        
+        let mut checker = SpellChecker::new();
+        if let Some(dictionary) = hebrew {
+            checker = checker.with_hebrew(dictionary);
+        }
+        if let Some(dictionary) = russian {
+            checker = checker.with_russian(dictionary);
+        }
+        Some(checker)
+    }
+    
+    /// סימון המילים השגויות בכל שינוי; הצעות התיקון, שחישובן יקר, רק כשנבחרת מילה
+    fn refresh_spelling(&mut self) {
+        self.selected_misspelling = None;
+        self.misspellings = self
+            .spell_checker
+            .as_ref()
+            .map(|checker| checker.find_misspellings(&self.edit_content))
+            .unwrap_or_default();
+    }
+}
+
+/// רישום תוצאת העריכה של כל סגמנט שתורגם במכונה, עם המנוע והביטחון הגולמי שלו, ביומן הכיול.
+/// `cargo run --bin fit_calibration` מאמן מהיומן את models/calibration.json.
+/// סגמנט שהטקסט שלו זהה למה שכבר נרשם עבורו ב-`recorded` לא נרשם שוב
+fn record_post_edits(
+    path: &Path,
+    translation: &TranslationResult,
+    edited_text: &str,
+    recorded: &mut HashMap<(PathBuf, usize), String>,
+) {
+    let events: Vec<LearningEvent> = translation
+        .post_edits(edited_text)
+        .into_iter()
+        .enumerate()
+        .filter(|(_, (segment, _))| !segment.has_manual_edit)
+        .filter(|(index, (_, corrected))| {
+            let key = (path.to_path_buf(), *index);
+            if recorded.get(&key) == Some(corrected) {
+                return false;
+            }
+            recorded.insert(key, corrected.clone());
+            true
+        })
+        .map(|(_, (segment, corrected))| {
+            let event = LearningEvent::new(
+                LearningEventType::Correction {
+                    original: segment.translated.clone(),
+                    corrected,
+                    error_type: "post_edit".to_string(),
+                },
+                segment.original.clone(),
+                segment.translated.clone(),
+                EventMetrics::new().with_confidence_score(segment.raw_confidence as f32),
+                String::new(),
+                String::new(),
+            );
+            match segment.backend {
+                Some(backend) => event.with_backend(backend),
+                None => event,
+            }
+        })
+        .collect();
+    if events.is_empty() {
+        return;
+    }
+
+    let samples = calibration::samples_from_events(&events, CalibrationConfig::default().accept_hter);
+    if let Err(e) = calibration::append_samples(Path::new(calibration::DEFAULT_SAMPLES_PATH), &samples) {
+        log::warn!("תוצאות העריכה לא נרשמו ביומן הכיול: {}", e);
+    }
+}
+
 impl Application for TranslatorGui {
     type Executor = executor::Default;
     type Message = Message;
@@ -113,7 +255,7 @@ impl Application for TranslatorGui {
         (
             Self {
                 file_processor: FileProcessor::new(),
-                translation_engine: TranslationEngine::new(),
+                translation_engine: Self::translation_engine(),
                 file_saver: FileSaver::new(),
                 processed_files: HashMap::new(),
                 translation_results: HashMap::new(),
@@ -125,13 +267,20 @@ impl Application for TranslatorGui {
                 search_query: String::new(),
                 editing_file: None,
                 edit_content: String::new(),
+                spell_checker: Self::load_spell_checker(),
+                misspellings: Vec::new(),
+                selected_misspelling: None,
                 undo_stack: Vec::new(),
                 redo_stack: Vec::new(),
+                review_only: false,
+                review_threshold: 0.6,
+                recorded_edits: HashMap::new(),
                 save_options: SaveOptions::default(),
                 drop_zone_state: DropZoneState::Idle,
                 preview_scroll: scrollable::State::new(),
                 help_scroll: scrollable::State::new(),
                 settings_scroll: scrollable::State::new(),
+                review_slider: slider::State::new(),
                 search_input: text_input::State::new(),
                 save_button: button::State::new(),
                 clear_button: button::State::new(),
@@ -193,6 +342,8 @@ impl Application for TranslatorGui {
                 if self.editing_file == Some(path.clone()) {
                     self.editing_file = None;
                     self.edit_content.clear();
+                    self.misspellings.clear();
+                    self.selected_misspelling = None;
                 }
                 Command::none()
             }
@@ -202,6 +353,8 @@ impl Application for TranslatorGui {
                 self.translation_results.clear();
                 self.editing_file = None;
                 self.edit_content.clear();
+                self.misspellings.clear();
+                self.selected_misspelling = None;
                 self.undo_stack.clear();
                 self.redo_stack.clear();
                 Command::none()
@@ -243,15 +396,53 @@ impl Application for TranslatorGui {
                 }
                 self.editing_file = Some(path);
                 self.edit_content = content;
+                self.refresh_spelling();
                 Command::none()
             }
             
             Message::ApplyEdit(path) => {
+                if let Some(translation) = self.translation_results.get(&path) {
+                    record_post_edits(&path, translation, &self.edit_content, &mut self.recorded_edits);
+                }
                 if let Some(translation) = self.translation_results.get_mut(&path) {
                     translation.apply_edit(self.edit_content.clone());
                 }
                 self.editing_file = None;
                 self.edit_content.clear();
+                self.misspellings.clear();
+                self.selected_misspelling = None;
+                Command::none()
+            }
+            
+            Message::SelectMisspelling(path, index) => {
+                if self.editing_file.as_ref() == Some(&path) {
+                    if let (Some(checker), Some(misspelling)) = (self.spell_checker.as_ref(), self.misspellings.get_mut(index)) {
+                        if misspelling.suggestions.is_empty() {
+                            misspelling.suggestions = checker.suggest(&misspelling.word);
+                        }
+                        self.selected_misspelling = Some(index);
+                    }
+                }
+                Command::none()
+            }
+            
+            Message::ApplySpellingSuggestion(path, index, replacement) => {
+                match self.misspellings.get(index) {
+                    Some(misspelling) if self.editing_file.as_ref() == Some(&path) => {
+                        let content = spelling::apply_suggestion(&self.edit_content, misspelling, &replacement);
+                        self.update(Message::EditTranslation(path, content))
+                    }
+                    _ => Command::none(),
+                }
+            }
+            
+            Message::ToggleReviewFilter => {
+                self.review_only = !self.review_only;
+                Command::none()
+            }
+            
+            Message::ReviewThresholdChanged(threshold) => {
+                self.review_threshold = threshold;
                 Command::none()
             }
             
@@ -361,6 +552,7 @@ impl Application for TranslatorGui {
                     });
                     self.editing_file = Some(state.file);
                     self.edit_content = state.content;
+                    self.refresh_spelling();
                 }
                 Command::none()
             }
@@ -374,6 +566,7 @@ impl Application for TranslatorGui {
                     });
                     self.editing_file = Some(state.file);
                     self.edit_content = state.content;
+                    self.refresh_spelling();
                 }
                 Command::none()
             }
@@ -471,6 +664,14 @@ impl Application for TranslatorGui {
                 .spacing(10);
 
             for (path, processed_file) in &self.processed_files {
+                // בסינון לבדיקה מוצגים רק קבצים שיש בהם סגמנטים מתחת לסף
+                let needs_review = self.translation_results
+                    .get(path)
+                    .is_none_or(|t| t.segments_for_review(self.review_threshold).next().is_some());
+                if self.review_only && !needs_review {
+                    continue;
+                }
+                
                 let mut file_row = Row::new().spacing(10);
                 
                 // סוג הקובץ
@@ -546,12 +747,61 @@ impl Application for TranslatorGui {
                         .style(theme::TextInput::ReadOnly)
                     };
 
-                    files_preview = files_preview.push(
-                        Column::new()
-                            .push(Text::new("תרגום:").size(14))
-                            .push(translation_content)
-                            .padding(10),
-                    );
+                    let mut translation_column = Column::new()
+                        .push(Text::new("תרגום:").size(14))
+                        .push(translation_content)
+                        .padding(10);
+                    
+                    // קו גלי מתחת למילים שגויות, והצעות תיקון לכל אחת מהן
+                    if Some(path) == self.editing_file.as_ref() && !self.misspellings.is_empty() {
+                        let mut underlined = Row::new();
+                        for segment in spelling::underline_segments(&self.edit_content, &self.misspellings) {
+                            underlined = match segment.misspelling {
+                                Some(_) => underlined.push(
+                                    Container::new(Text::new(segment.text).size(16).color(self.theme.colors().error))
+                                        .style(theme::Container::Error),
+                                ),
+                                None => underlined.push(Text::new(segment.text).size(16)),
+                            };
+                        }
+                        translation_column = translation_column.push(underlined);
+                        
+                        for (index, misspelling) in self.misspellings.iter().enumerate() {
+                            let mut row = Row::new().spacing(5).push(
+                                Button::new(&mut button::State::new(), Text::new(format!("{}:", misspelling.word)).size(14))
+                                    .on_press(Message::SelectMisspelling(path.clone(), index)),
+                            );
+                            if self.selected_misspelling == Some(index) {
+                                for suggestion in misspelling.suggestions.iter().take(3) {
+                                    row = row.push(
+                                        Button::new(&mut button::State::new(), Text::new(suggestion).size(14))
+                                            .on_press(Message::ApplySpellingSuggestion(path.clone(), index, suggestion.clone())),
+                                    );
+                                }
+                            }
+                            translation_column = translation_column.push(row);
+                        }
+                    }
+                    
+                    if self.review_only {
+                        for (index, segment) in translation.segments_for_review(self.review_threshold) {
+                            translation_column = translation_column.push(
+                                Container::new(
+                                    Text::new(format!(
+                                        "{}. [{:.0}%] {} → {}",
+                                        index + 1,
+                                        segment.confidence * 100.0,
+                                        segment.original,
+                                        segment.translated
+                                    ))
+                                    .size(14),
+                                )
+                                .style(theme::Container::Error),
+                            );
+                        }
+                    }
+                    
+                    files_preview = files_preview.push(translation_column);
                 }
             }
 
@@ -637,6 +887,31 @@ impl Application for TranslatorGui {
                                             )),
                                         ),
                                 )
+                                .push(
+                                    Row::new()
+                                        .spacing(10)
+                                        .push(
+                                            Button::new(
+                                                &mut button::State::new(),
+                                                Text::new(if self.review_only {
+                                                    "הצג את כל הסגמנטים"
+                                                } else {
+                                                    "הצג רק סגמנטים לבדיקה"
+                                                }),
+                                            )
+                                            .on_press(Message::ToggleReviewFilter),
+                                        )
+                                        .push(Text::new(format!("סף ביטחון: {:.0}%", self.review_threshold * 100.0)))
+                                        .push(
+                                            Slider::new(
+                                                &mut self.review_slider,
+                                                0.0..=1.0,
+                                                self.review_threshold,
+                                                Message::ReviewThresholdChanged,
+                                            )
+                                            .step(0.05),
+                                        ),
+                                )
                                 .push(
                                     Row::new()
                                         .spacing(10)
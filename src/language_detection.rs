@@ -1,8 +1,9 @@
 use whatlang::{Lang, Script, detect_lang, detect_script};
 use unicode_segmentation::UnicodeSegmentation;
 use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Language {
     Hebrew,
     Russian,
@@ -34,8 +35,6 @@ impl LanguageDetector {
         // חלוקה לקטעים לפי סקריפט
         let mut hebrew_text = String::new();
         let mut russian_text = String::new();
-        let mut current_script = None;
-        let mut current_text = String::new();
 
         for grapheme in text.graphemes(true) {
             if let Some(script) = detect_script(grapheme) {
@@ -12,6 +12,7 @@ use crate::neural::model::EnhancedTransformer;
 use crate::neural::training::{Trainer, TrainingConfig, TrainingMetrics};
 use crate::evaluation::{Evaluator, EvaluationMetrics};
 use crate::technical_dictionary::TechnicalDictionary;
+use crate::system_combination::CandidateSource;
 
 #[derive(Debug, Clone)]
 pub enum LearningEventType {
@@ -109,6 +110,8 @@ pub struct LearningEvent {
     pub metrics: EventMetrics,
     pub domain: String,
     pub context: String,
+    /// המנוע שהפיק את התרגום; לכיול הביטחון בנפרד לכל מנוע
+    pub backend: Option<CandidateSource>,
 }
 
 impl LearningEvent {
@@ -128,6 +131,7 @@ impl LearningEvent {
             metrics,
             domain,
             context,
+            backend: None,
         }
     }
 
@@ -160,6 +164,11 @@ impl LearningEvent {
         self.context = context;
         self
     }
+
+    pub fn with_backend(mut self, backend: CandidateSource) -> Self {
+        self.backend = Some(backend);
+        self
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -470,6 +479,16 @@ impl AdvancedLearningManager {
         Ok(metrics)
     }
 
+    /// האירועים שנרשמו אחרי נקודת זמן נתונה, לעבודות למידה מתוזמנות
+    pub async fn events_since(&self, since: SystemTime) -> Vec<LearningEvent> {
+        let events = self.events.lock().await;
+        events
+            .iter()
+            .filter(|event| event.timestamp > since)
+            .cloned()
+            .collect()
+    }
+
     pub async fn get_learning_statistics(&self) -> Result<EnhancedLearningStatistics> {
         let events = self.events.lock().await;
         let analyzer = self.feedback_analyzer.lock().await;
@@ -1,11 +1,10 @@
 pub mod morphology;
 pub mod neural;
-pub mod translation_engine;
 pub mod quality_control;
 pub mod gui;
 pub mod learning_manager;
+pub mod language_detection;
 pub mod technical_terms;
-pub mod vocabulary;
 pub mod translation_models;
 pub mod technical_dictionary;
 pub mod resources;
@@ -13,6 +12,8 @@ pub mod error;
 pub mod evaluation;
 pub mod text_analyzer;
 pub mod tokenizer;
+pub mod translation;
+pub mod system_combination;
 pub mod file_saver;
 pub mod image_processor;
 
@@ -27,11 +28,11 @@ pub use neural::{
     attention::{MultiHeadAttention, AttentionConfig},
 };
 
-pub use translation_engine::TranslationEngine;
+pub use translation::TranslationEngine;
 pub use quality_control::{QualityControl, IssueSeverity};
 pub use learning_manager::{LearningManager, LearningEvent, LearningEventType, UserFeedback};
 pub use technical_terms::TechnicalTermsManager;
-pub use vocabulary::{Vocabulary, VocabularyError};
+pub use neural::vocabulary::{Vocabulary, VocabularyError};
 pub use technical_dictionary::TechnicalDictionary;
 pub use resources::HebrewResources;
 pub use error::Error;
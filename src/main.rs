@@ -9,12 +9,11 @@ use image_processor::ImageProcessor;
 use error::{Result, ErrorExt};
 use log::{info, warn, error};
 use std::env;
+use rustohebru::{language_detection, translation};
 
 mod gui;
 mod file_processor;
 mod security;
-mod translation;
-mod language_detection;
 mod fonts;
 mod metadata;
 mod technical_terms;
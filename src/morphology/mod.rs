@@ -142,6 +142,7 @@ pub mod cache;
 pub mod patterns;
 pub mod semantic;
 pub mod statistics;
+pub mod utils;
 
 pub use hebrew::HebrewAnalyzer;
 pub use russian::RussianAnalyzer;
@@ -87,6 +87,81 @@ pub fn remove_hebrew_suffixes(word: &str) -> String {
     result
 }
 
+/// תחיליות מותרות לפני מונח (כולל צירופים נפוצים כמו "וה" ו"שב")
+const HEBREW_PREFIX_CHAINS: &[&str] = &[
+    "", "ה", "ב", "כ", "ל", "מ", "ו", "ש",
+    "וה", "וב", "וכ", "ול", "ומ", "שה", "שב", "של", "מה", "כש", "וש",
+];
+
+/// אות סופית חוזרת לצורתה הרגילה לפני סיומת ("שסתום" -> "שסתומ")
+fn without_final_letter(word: &str) -> String {
+    let mut chars: Vec<char> = word.chars().collect();
+    if let Some(last) = chars.last_mut() {
+        *last = match *last {
+            'ם' => 'מ',
+            'ן' => 'נ',
+            'ץ' => 'צ',
+            'ף' => 'פ',
+            'ך' => 'כ',
+            other => other,
+        };
+    }
+    chars.into_iter().collect()
+}
+
+/// מייצר את צורות המשטח המותרות של מונח עברי: תחיליות על המילה הראשונה,
+/// וצורת רבים למונח של מילה אחת (בצירופי סמיכות הנטייה אינה צפויה)
+pub fn hebrew_surface_variants(term: &str) -> Vec<String> {
+    let words: Vec<&str> = term.split_whitespace().collect();
+    if words.is_empty() {
+        return Vec::new();
+    }
+
+    let mut bases = vec![words.join(" ")];
+    if words.len() == 1 {
+        let word = words[0];
+        if let Some(stem) = word.strip_suffix('ה') {
+            bases.push(format!("{}ות", stem));
+        } else if !word.ends_with("ים") && !word.ends_with("ות") {
+            bases.push(format!("{}ים", without_final_letter(word)));
+        }
+    }
+
+    let mut variants = Vec::new();
+    for base in &bases {
+        for prefix in HEBREW_PREFIX_CHAINS {
+            let variant = format!("{}{}", prefix, base);
+            if !variants.contains(&variant) {
+                variants.push(variant);
+            }
+        }
+    }
+
+    variants
+}
+
+/// סיומות נטייה רוסיות, מהארוכה לקצרה
+const RUSSIAN_ENDINGS: &[&str] = &[
+    "иями", "ями", "ами", "ого", "его", "ому", "ему", "ыми", "ими",
+    "ой", "ей", "ом", "ем", "ам", "ям", "ах", "ях", "ов", "ев", "ую", "юю",
+    "ая", "яя", "ое", "ее", "ые", "ие", "ый", "ий", "ых", "их", "ию", "ия", "ье", "ья", "ью",
+    "а", "я", "о", "е", "ы", "и", "у", "ю", "ь", "й",
+];
+
+/// גזע משוער של מילה רוסית להשוואת צורות נטויות ("задвижку", "задвижкой" -> "задвижк");
+/// משאיר לפחות שלוש אותיות כדי לא למזג מילים קצרות
+pub fn russian_stem(word: &str) -> String {
+    let word = word.to_lowercase().replace('ё', "е");
+    for ending in RUSSIAN_ENDINGS {
+        if let Some(stem) = word.strip_suffix(ending) {
+            if stem.chars().count() >= 3 {
+                return stem.to_string();
+            }
+        }
+    }
+    word
+}
+
 /// מנקה מילה מניקוד וסימנים מיוחדים
 pub fn clean_word(word: &str) -> String {
     word.chars()
@@ -136,6 +211,28 @@ mod tests {
         assert_eq!(remove_hebrew_suffixes("שולחנות"), "שולחן");
     }
 
+    #[test]
+    fn test_hebrew_surface_variants() {
+        let variants = hebrew_surface_variants("מתז");
+        assert_eq!(variants[0], "מתז");
+        assert!(variants.contains(&"המתז".to_string()));
+        assert!(variants.contains(&"והמתזים".to_string()));
+        assert!(hebrew_surface_variants("מגוף").contains(&"המגופים".to_string()));
+
+        let variants = hebrew_surface_variants("צנרת כיבוי אש");
+        assert!(variants.contains(&"לצנרת כיבוי אש".to_string()));
+        assert!(hebrew_surface_variants("").is_empty());
+    }
+
+    #[test]
+    fn test_russian_stem() {
+        assert_eq!(russian_stem("задвижка"), "задвижк");
+        assert_eq!(russian_stem("Задвижкой"), "задвижк");
+        assert_eq!(russian_stem("пожарного"), russian_stem("пожарный"));
+        assert_eq!(russian_stem("клапан"), "клапан");
+        assert_eq!(russian_stem("бак"), "бак");
+    }
+
     #[test]
     fn test_tokenize() {
         let text = "שלום עולם! מה נשמע?";
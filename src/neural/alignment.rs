@@ -0,0 +1,323 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+use tch::{Kind, Tensor};
+use crate::translation_models::TranslationError;
+
+/// סימן תחילת מילה בטוקנים של הטוקנייזר
+const WORD_START: char = '▁';
+
+lazy_static! {
+    /// תגיות פורמט בתוך הטקסט: <b>, </b>, <x id="1"/> וכדומה
+    pub(crate) static ref INLINE_TAG: Regex = Regex::new(r"</?[A-Za-z][^<>]*>").unwrap();
+}
+
+/// יישור בין מילת מקור (אינדקס מילה) למילת תרגום
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WordAlignment {
+    pub source: usize,
+    pub target: usize,
+    /// משקל תשומת הלב הממוצע שהוביל ליישור
+    pub weight: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlignmentConfig {
+    /// משקל מינימלי; מילות תרגום שהמשקל המרבי שלהן נמוך ממנו נשארות לא מיושרות
+    pub threshold: f32,
+}
+
+impl Default for AlignmentConfig {
+    fn default() -> Self {
+        Self { threshold: 0.1 }
+    }
+}
+
+/// מיצוע משקולות cross-attention על פני הראשים: [1, heads, tgt, src] או [1, tgt, src] -> מטריצה [tgt][src]
+pub fn attention_matrix(weights: &Tensor) -> Result<Vec<Vec<f32>>, TranslationError> {
+    let weights = match weights.dim() {
+        4 => weights.mean_dim(&[1i64][..], false, Kind::Float),
+        3 => weights.to_kind(Kind::Float),
+        dim => return Err(TranslationError::ModelError(format!("צורת משקולות לא צפויה: {} ממדים", dim))),
+    };
+
+    let weights = weights.get(0);
+    Vec::<Vec<f32>>::try_from(&weights).map_err(|e| TranslationError::ModelError(e.to_string()))
+}
+
+/// מיפוי טוקנים לאינדקס המילה שלהם; טוקנים מיוחדים (<BOS> וכו') מקבלים None
+pub fn word_ids(pieces: &[String]) -> Vec<Option<usize>> {
+    let mut current: Option<usize> = None;
+
+    pieces
+        .iter()
+        .map(|piece| {
+            if piece.starts_with('<') && piece.ends_with('>') && piece.len() > 2 {
+                return None;
+            }
+            if piece.starts_with(WORD_START) || current.is_none() {
+                current = Some(current.map_or(0, |id| id + 1));
+            }
+            current
+        })
+        .collect()
+}
+
+/// הפיכת משקולות תשומת לב ברמת טוקנים ליישור קשיח ברמת מילים.
+/// המשקולות של טוקני-משנה מאותה מילה מתמזגות, ולכל מילת תרגום נבחרת מילת המקור החזקה ביותר
+pub fn hard_alignments(
+    matrix: &[Vec<f32>],
+    source_words: &[Option<usize>],
+    target_words: &[Option<usize>],
+    config: &AlignmentConfig,
+) -> Vec<WordAlignment> {
+    let source_count = source_words.iter().flatten().max().map_or(0, |&id| id + 1);
+    let target_count = target_words.iter().flatten().max().map_or(0, |&id| id + 1);
+    if source_count == 0 || target_count == 0 {
+        return Vec::new();
+    }
+
+    // סכום על טוקני המקור של כל מילה, ממוצע על טוקני התרגום של כל מילה
+    let mut merged = vec![vec![0.0f32; source_count]; target_count];
+    let mut pieces_per_target = vec![0usize; target_count];
+
+    for (row, target_word) in matrix.iter().zip(target_words) {
+        let Some(target_word) = *target_word else { continue };
+        pieces_per_target[target_word] += 1;
+        for (&weight, source_word) in row.iter().zip(source_words) {
+            if let Some(source_word) = *source_word {
+                merged[target_word][source_word] += weight;
+            }
+        }
+    }
+
+    merged
+        .iter()
+        .zip(&pieces_per_target)
+        .enumerate()
+        .filter(|(_, (_, &pieces))| pieces > 0)
+        .filter_map(|(target, (row, &pieces))| {
+            let (source, &weight) = row
+                .iter()
+                .enumerate()
+                .max_by(|a, b| a.1.partial_cmp(b.1).unwrap_or(std::cmp::Ordering::Equal))?;
+            let weight = weight / pieces as f32;
+            (weight >= config.threshold).then_some(WordAlignment { source, target, weight })
+        })
+        .collect()
+}
+
+/// קריאת יישור בפורמט Pharaoh ("0-0 1-2 2-1"), כמו הפלט של fast_align
+pub fn parse_pharaoh(line: &str) -> Vec<WordAlignment> {
+    line.split_whitespace()
+        .filter_map(|pair| {
+            let (source, target) = pair.split_once('-')?;
+            Some(WordAlignment {
+                source: source.parse().ok()?,
+                target: target.parse().ok()?,
+                weight: 1.0,
+            })
+        })
+        .collect()
+}
+
+/// בניית התפלגות יעד ל-guided alignment בצורה [1, tgt_tokens, src_tokens] מיישור חיצוני ברמת מילים
+pub fn reference_attention(
+    alignments: &[WordAlignment],
+    source_words: &[Option<usize>],
+    target_words: &[Option<usize>],
+) -> Tensor {
+    let mut reference = vec![0.0f32; target_words.len() * source_words.len()];
+
+    for (t, target_word) in target_words.iter().enumerate() {
+        let Some(target_word) = *target_word else { continue };
+        let aligned: Vec<usize> = source_words
+            .iter()
+            .enumerate()
+            .filter(|(_, source_word)| {
+                source_word.map_or(false, |s| alignments.iter().any(|a| a.source == s && a.target == target_word))
+            })
+            .map(|(s, _)| s)
+            .collect();
+
+        for &s in &aligned {
+            reference[t * source_words.len() + s] = 1.0 / aligned.len() as f32;
+        }
+    }
+
+    Tensor::from_slice(&reference).view([1, target_words.len() as i64, source_words.len() as i64])
+}
+
+/// Loss של guided alignment: cross-entropy בין משקולות תשומת הלב (ממוצע ראשים) להתפלגות היעד,
+/// מחושב רק על טוקני תרגום שיש להם יישור
+pub fn guided_alignment_loss(attention: &Tensor, reference: &Tensor) -> Tensor {
+    let attention = if attention.dim() == 4 {
+        attention.mean_dim(&[1i64][..], false, Kind::Float)
+    } else {
+        attention.shallow_clone()
+    };
+    let reference = reference.to_device(attention.device());
+
+    let aligned_rows = reference.sum_dim_intlist(&[-1i64][..], false, Kind::Float).gt(0.0).sum(Kind::Float);
+    let cross_entropy = -(reference * (attention + 1e-9).log()).sum(Kind::Float);
+
+    cross_entropy / aligned_rows.clamp_min(1.0)
+}
+
+/// תגית פורמט שהוסרה מהמקור, עם טווח המילים שהיא עוטפת
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagSpan {
+    pub open: String,
+    pub close: Option<String>,
+    /// מילת המקור הראשונה והאחרונה בטווח (כולל); None לתגית ריקה ללא מילים
+    pub words: Option<(usize, usize)>,
+    /// המילה שלפניה הופיעה התגית (לתגיות בודדות כמו <x/>)
+    pub position: usize,
+}
+
+/// הסרת תגיות הפורמט מהמקור לצורך תרגום, ושמירת המילים שכל תגית עוטפת
+pub fn strip_inline_tags(text: &str) -> (String, Vec<TagSpan>) {
+    let mut plain = String::new();
+    let mut spans: Vec<TagSpan> = Vec::new();
+    let mut open: Vec<usize> = Vec::new();
+    let mut last = 0;
+
+    let word_count = |plain: &str| plain.split_whitespace().count();
+
+    for tag in INLINE_TAG.find_iter(text) {
+        plain.push_str(&text[last..tag.start()]);
+        last = tag.end();
+        let tag_text = tag.as_str();
+        let position = word_count(&plain);
+
+        if tag_text.starts_with("</") {
+            if let Some(index) = open.pop() {
+                let span = &mut spans[index];
+                span.close = Some(tag_text.to_string());
+                span.words = (position > span.position).then(|| (span.position, position - 1));
+            }
+        } else {
+            spans.push(TagSpan {
+                open: tag_text.to_string(),
+                close: None,
+                words: None,
+                position,
+            });
+            if !tag_text.ends_with("/>") {
+                open.push(spans.len() - 1);
+            }
+        }
+    }
+    plain.push_str(&text[last..]);
+
+    let plain = plain.split_whitespace().collect::<Vec<_>>().join(" ");
+    (plain, spans)
+}
+
+/// החזרת תגיות הפורמט לתרגום לפי יישור המילים; בהיעדר יישור המיקום נקבע באופן יחסי
+pub fn project_inline_tags(target: &str, spans: &[TagSpan], source_len: usize, alignments: &[WordAlignment]) -> String {
+    let words: Vec<&str> = target.split_whitespace().collect();
+    if spans.is_empty() || words.is_empty() {
+        return target.to_string();
+    }
+
+    let proportional = |source: usize| -> usize {
+        if source_len == 0 {
+            return 0;
+        }
+        (source * words.len() / source_len).min(words.len() - 1)
+    };
+
+    let mut before: Vec<Vec<&str>> = vec![Vec::new(); words.len() + 1];
+    let mut after: Vec<Vec<&str>> = vec![Vec::new(); words.len()];
+
+    for span in spans {
+        match (span.words, &span.close) {
+            (Some((first, last)), Some(close)) => {
+                let aligned: Vec<usize> = alignments
+                    .iter()
+                    .filter(|a| a.source >= first && a.source <= last && a.target < words.len())
+                    .map(|a| a.target)
+                    .collect();
+                let (start, end) = match (aligned.iter().min(), aligned.iter().max()) {
+                    (Some(&start), Some(&end)) => (start, end),
+                    _ => {
+                        let start = proportional(first);
+                        (start, proportional(last).max(start))
+                    }
+                };
+                before[start].push(&span.open);
+                after[end].insert(0, close);
+            }
+            _ => {
+                // תגית בודדת או תגית ללא מילים: לפני המילה המקבילה
+                let target_position = alignments
+                    .iter()
+                    .filter(|a| a.source == span.position && a.target < words.len())
+                    .map(|a| a.target)
+                    .min()
+                    .unwrap_or_else(|| if span.position >= source_len { words.len() } else { proportional(span.position) });
+                before[target_position].push(&span.open);
+                if let Some(close) = &span.close {
+                    before[target_position].push(close);
+                }
+            }
+        }
+    }
+
+    let mut output = Vec::with_capacity(words.len());
+    for (i, word) in words.iter().enumerate() {
+        let mut token = before[i].concat();
+        token.push_str(word);
+        token.push_str(&after[i].concat());
+        output.push(token);
+    }
+    let mut result = output.join(" ");
+    result.push_str(&before[words.len()].concat());
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subwords_are_merged() {
+        let source_words = word_ids(&["▁давл".to_string(), "ение".to_string(), "▁насоса".to_string()]);
+        let target_words = word_ids(&["▁לחץ".to_string(), "▁המשאבה".to_string()]);
+        assert_eq!(source_words, vec![Some(0), Some(0), Some(1)]);
+
+        let matrix = vec![
+            vec![0.4, 0.4, 0.2],
+            vec![0.1, 0.1, 0.8],
+        ];
+        let alignments = hard_alignments(&matrix, &source_words, &target_words, &AlignmentConfig::default());
+
+        assert_eq!(alignments.len(), 2);
+        assert_eq!((alignments[0].source, alignments[0].target), (0, 0));
+        assert_eq!((alignments[1].source, alignments[1].target), (1, 1));
+    }
+
+    #[test]
+    fn test_special_tokens_are_skipped() {
+        let ids = word_ids(&["<BOS>".to_string(), "▁שלום".to_string(), "<EOS>".to_string()]);
+        assert_eq!(ids, vec![None, Some(0), None]);
+    }
+
+    #[test]
+    fn test_tags_follow_alignment() {
+        let (plain, spans) = strip_inline_tags("Давление <b>насоса</b> высокое");
+        assert_eq!(plain, "Давление насоса высокое");
+
+        let alignments = parse_pharaoh("0-0 1-1 2-2");
+        let target = project_inline_tags("לחץ המשאבה גבוה", &spans, 3, &alignments);
+        assert_eq!(target, "לחץ <b>המשאבה</b> גבוה");
+    }
+
+    #[test]
+    fn test_tags_without_alignment_are_kept() {
+        let (plain, spans) = strip_inline_tags("<i>Клапан</i> закрыт<x id=\"1\"/>");
+        let target = project_inline_tags("השסתום סגור", &spans, plain.split_whitespace().count(), &[]);
+        assert_eq!(target, "<i>השסתום</i> סגור<x id=\"1\"/>");
+    }
+}
@@ -1,4 +1,5 @@
 use tch::{nn, Tensor, Kind};
+use tch::nn::Module;
 use crate::translation_models::TranslationError;
 use std::f64;
 use std::sync::Arc;
@@ -242,6 +243,75 @@ pub enum MorphologyFeature {
     Person(Person),
 }
 
+/// תשומת לב מרובת ראשים בסיסית, שמחזירה גם את משקולות תשומת הלב (למשל ליישור מילים)
+#[derive(Debug)]
+pub struct MultiHeadAttention {
+    num_heads: i64,
+    head_dim: i64,
+    query_net: nn::Linear,
+    key_net: nn::Linear,
+    value_net: nn::Linear,
+    output_net: nn::Linear,
+}
+
+impl MultiHeadAttention {
+    pub fn new(vs: &nn::Path, config: &AttentionConfig) -> Self {
+        let hidden = config.hidden_size;
+        
+        Self {
+            num_heads: config.num_heads,
+            head_dim: hidden / config.num_heads,
+            query_net: nn::linear(vs / "query", hidden, hidden, Default::default()),
+            key_net: nn::linear(vs / "key", hidden, hidden, Default::default()),
+            value_net: nn::linear(vs / "value", hidden, hidden, Default::default()),
+            output_net: nn::linear(vs / "output", hidden, hidden, Default::default()),
+        }
+    }
+
+    pub fn forward(
+        &self,
+        query: &Tensor,
+        key: &Tensor,
+        value: &Tensor,
+        mask: Option<&Tensor>,
+    ) -> Result<Tensor, TranslationError> {
+        self.forward_with_weights(query, key, value, mask).map(|(output, _)| output)
+    }
+
+    /// כמו forward, ומחזיר גם את משקולות תשומת הלב בצורה [batch, heads, query_len, key_len].
+    /// המסכה בצורה [batch, key_len], כאשר true מסמן עמדת ריפוד
+    pub fn forward_with_weights(
+        &self,
+        query: &Tensor,
+        key: &Tensor,
+        value: &Tensor,
+        mask: Option<&Tensor>,
+    ) -> Result<(Tensor, Tensor), TranslationError> {
+        let (batch, query_len, hidden) = query.size3()
+            .map_err(|e| TranslationError::ModelError(format!("צורת query לא חוקית: {}", e)))?;
+        let key_len = key.size()[1];
+        
+        let split = |x: Tensor, len: i64| x.view([batch, len, self.num_heads, self.head_dim]).transpose(1, 2);
+        let q = split(self.query_net.forward(query), query_len);
+        let k = split(self.key_net.forward(key), key_len);
+        let v = split(self.value_net.forward(value), key_len);
+        
+        let mut scores = q.matmul(&k.transpose(-2, -1)) / (self.head_dim as f64).sqrt();
+        if let Some(mask) = mask {
+            scores = scores.masked_fill(&mask.view([batch, 1, 1, key_len]), f64::NEG_INFINITY);
+        }
+        let weights = scores.softmax(-1, Kind::Float);
+        
+        let context = weights
+            .matmul(&v)
+            .transpose(1, 2)
+            .contiguous()
+            .view([batch, query_len, hidden]);
+        
+        Ok((self.output_net.forward(&context), weights))
+    }
+}
+
 pub struct SelfAttention {
     attention: MultiHeadAttention,
     layer_norm: LayerNorm,
@@ -0,0 +1,325 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::Duration;
+use serde::{Serialize, Deserialize};
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::Instant;
+use crate::technical_terms::TermsDatabase;
+use crate::translation_models::{Domain, Formality, Style, TranslationContext, TranslationError};
+use super::{AlignedTranslation, BatchSegment, TranslationBackend};
+
+/// הגדרות מתזמן האצוות
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchingConfig {
+    /// מספר הסגמנטים המרבי באצווה אחת
+    pub max_batch_size: usize,
+    /// זמן ההמתנה המרבי מרגע הגעת הסגמנט הראשון ועד הרצת האצווה
+    pub max_latency_ms: u64,
+    /// גודל התור המרבי לפני שבקשות חדשות נחסמות
+    pub queue_capacity: usize,
+}
+
+impl Default for BatchingConfig {
+    fn default() -> Self {
+        Self {
+            max_batch_size: 32,
+            max_latency_ms: 10,
+            queue_capacity: 1024,
+        }
+    }
+}
+
+/// מדדי המתזמן, מתעדכנים בזמן אמת
+#[derive(Debug, Default)]
+pub struct BatchingMetrics {
+    /// סגמנטים שממתינים בתור ועוד לא נאספו לאצווה
+    queue_depth: AtomicUsize,
+    last_batch_size: AtomicUsize,
+    max_batch_size: AtomicUsize,
+    batches: AtomicU64,
+    segments: AtomicU64,
+    failed_batches: AtomicU64,
+}
+
+/// תמונת מצב של מדדי המתזמן
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatchingSnapshot {
+    pub queue_depth: usize,
+    pub last_batch_size: usize,
+    pub max_batch_size: usize,
+    pub batches: u64,
+    pub segments: u64,
+    pub failed_batches: u64,
+    pub average_batch_size: f64,
+}
+
+impl BatchingMetrics {
+    pub fn queue_depth(&self) -> usize {
+        self.queue_depth.load(Ordering::Relaxed)
+    }
+
+    pub fn snapshot(&self) -> BatchingSnapshot {
+        let batches = self.batches.load(Ordering::Relaxed);
+        let segments = self.segments.load(Ordering::Relaxed);
+
+        BatchingSnapshot {
+            queue_depth: self.queue_depth(),
+            last_batch_size: self.last_batch_size.load(Ordering::Relaxed),
+            max_batch_size: self.max_batch_size.load(Ordering::Relaxed),
+            batches,
+            segments,
+            failed_batches: self.failed_batches.load(Ordering::Relaxed),
+            average_batch_size: if batches == 0 { 0.0 } else { segments as f64 / batches as f64 },
+        }
+    }
+
+    fn record_batch(&self, size: usize, succeeded: bool) {
+        self.last_batch_size.store(size, Ordering::Relaxed);
+        self.max_batch_size.fetch_max(size, Ordering::Relaxed);
+        self.batches.fetch_add(1, Ordering::Relaxed);
+        self.segments.fetch_add(size as u64, Ordering::Relaxed);
+        if !succeeded {
+            self.failed_batches.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// סגמנט שממתין בתור יחד עם הערוץ להחזרת התרגום, היישור והסתברות המודל
+struct PendingSegment {
+    segment: BatchSegment,
+    reply: oneshot::Sender<Result<(AlignedTranslation, Option<f64>), TranslationError>>,
+}
+
+/// מתזמן הסקה שאוסף סגמנטים מבקשות מקבילות לאצוות ומריץ אותן דרך המודל
+#[derive(Clone)]
+pub struct BatchScheduler {
+    sender: mpsc::Sender<PendingSegment>,
+    metrics: Arc<BatchingMetrics>,
+}
+
+impl BatchScheduler {
+    /// הפעלת המתזמן ברקע; יש לקרוא מתוך סביבת tokio
+    pub fn spawn(backend: Arc<dyn TranslationBackend>, config: BatchingConfig) -> Self {
+        let (sender, receiver) = mpsc::channel(config.queue_capacity.max(1));
+        let metrics = Arc::new(BatchingMetrics::default());
+
+        tokio::spawn(Self::run(backend, config, receiver, metrics.clone()));
+
+        Self { sender, metrics }
+    }
+
+    pub fn metrics(&self) -> Arc<BatchingMetrics> {
+        self.metrics.clone()
+    }
+
+    /// תרגום סגמנט בודד; הסגמנט יורץ יחד עם סגמנטים מבקשות אחרות
+    pub async fn translate(&self, text: &str) -> Result<String, TranslationError> {
+        let context = TranslationContext::new(Domain::General, Style::Formal, Formality::Medium);
+        Ok(self.translate_in_context(text, &context).await?.0)
+    }
+
+    /// תרגום סגמנט בהקשר המסמך, כמו TranslationBackend::translate_in_context
+    pub async fn translate_in_context(
+        &self,
+        text: &str,
+        context: &TranslationContext,
+    ) -> Result<(String, Option<f64>), TranslationError> {
+        let (output, probability) = self.translate_aligned(text, context, None).await?;
+        Ok((output.text, probability))
+    }
+
+    /// תרגום סגמנט בהקשר עם יישור מילים, ועם אכיפת המונחים כשנמסר מאגר,
+    /// כמו translate_aligned ו-translate_constrained של המנוע
+    pub async fn translate_aligned(
+        &self,
+        text: &str,
+        context: &TranslationContext,
+        terms: Option<Arc<TermsDatabase>>,
+    ) -> Result<(AlignedTranslation, Option<f64>), TranslationError> {
+        self.submit(BatchSegment {
+            text: text.to_string(),
+            context: context.clone(),
+            terms,
+        })
+        .await
+    }
+
+    async fn submit(&self, segment: BatchSegment) -> Result<(AlignedTranslation, Option<f64>), TranslationError> {
+        let (reply, response) = oneshot::channel();
+
+        self.metrics.queue_depth.fetch_add(1, Ordering::Relaxed);
+        if self.sender.send(PendingSegment { segment, reply }).await.is_err() {
+            self.metrics.queue_depth.fetch_sub(1, Ordering::Relaxed);
+            return Err(TranslationError::ModelError("מתזמן האצוות אינו פעיל".to_string()));
+        }
+
+        response
+            .await
+            .map_err(|_| TranslationError::ModelError("מתזמן האצוות נסגר לפני שהוחזר תרגום".to_string()))?
+    }
+
+    /// תרגום מספר סגמנטים של אותה בקשה
+    pub async fn translate_all(&self, texts: &[String]) -> Result<Vec<String>, TranslationError> {
+        let pending = texts.iter().map(|text| self.translate(text));
+        futures::future::join_all(pending).await.into_iter().collect()
+    }
+
+    async fn run(
+        backend: Arc<dyn TranslationBackend>,
+        config: BatchingConfig,
+        mut receiver: mpsc::Receiver<PendingSegment>,
+        metrics: Arc<BatchingMetrics>,
+    ) {
+        let max_batch_size = config.max_batch_size.max(1);
+        let max_latency = Duration::from_millis(config.max_latency_ms);
+
+        // ממתינים לסגמנט הראשון, ואז אוספים עד שהאצווה מלאה או שחלון ההמתנה נגמר
+        while let Some(first) = receiver.recv().await {
+            let deadline = Instant::now() + max_latency;
+            let mut batch = vec![first];
+
+            while batch.len() < max_batch_size {
+                match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                    Ok(Some(segment)) => batch.push(segment),
+                    Ok(None) | Err(_) => break,
+                }
+            }
+            // מכאן הסגמנטים כבר אינם בתור אלא באצווה שרצה
+            metrics.queue_depth.fetch_sub(batch.len(), Ordering::Relaxed);
+
+            Self::run_batch(&backend, batch, &metrics).await;
+        }
+    }
+
+    async fn run_batch(
+        backend: &Arc<dyn TranslationBackend>,
+        mut batch: Vec<PendingSegment>,
+        metrics: &BatchingMetrics,
+    ) {
+        // מיון לפי אורך מצמצם את הריפוד בתוך האצווה
+        batch.sort_by_key(|pending| pending.segment.text.len());
+        let segments: Vec<BatchSegment> = batch.iter().map(|pending| pending.segment.clone()).collect();
+        let size = batch.len();
+
+        // המודל חוסם, ולכן מורץ מחוץ ל-executor
+        let backend = backend.clone();
+        let result = tokio::task::spawn_blocking(move || backend.translate_batch(&segments))
+            .await
+            .unwrap_or_else(|e| Err(TranslationError::ModelError(format!("הרצת האצווה נכשלה: {}", e))));
+
+        match result {
+            Ok(outputs) if outputs.len() == size => {
+                metrics.record_batch(size, true);
+                for (segment, output) in batch.into_iter().zip(outputs) {
+                    let _ = segment.reply.send(Ok(output));
+                }
+            }
+            Ok(outputs) => {
+                metrics.record_batch(size, false);
+                let message = format!("המודל החזיר {} תרגומים לאצווה של {}", outputs.len(), size);
+                for segment in batch {
+                    let _ = segment.reply.send(Err(TranslationError::ModelError(message.clone())));
+                }
+            }
+            Err(e) => {
+                metrics.record_batch(size, false);
+                let message = e.to_string();
+                for segment in batch {
+                    let _ = segment.reply.send(Err(TranslationError::ModelError(message.clone())));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::testing::RecordingBackend;
+
+    #[tokio::test]
+    async fn test_concurrent_requests_share_a_batch() {
+        let backend = Arc::new(RecordingBackend::echo());
+        let scheduler = BatchScheduler::spawn(backend.clone(), BatchingConfig {
+            max_batch_size: 8,
+            max_latency_ms: 200,
+            ..Default::default()
+        });
+
+        let texts: Vec<String> = (0..8).map(|i| format!("segment {}", i)).collect();
+        let results = scheduler.translate_all(&texts).await.unwrap();
+
+        assert_eq!(results[3], "segment 3");
+        assert_eq!(backend.batch_sizes(), vec![8]);
+
+        let snapshot = scheduler.metrics().snapshot();
+        assert_eq!(snapshot.queue_depth, 0);
+        assert_eq!(snapshot.last_batch_size, 8);
+        assert_eq!(snapshot.segments, 8);
+    }
+
+    #[tokio::test]
+    async fn test_batches_are_capped_by_size() {
+        let backend = Arc::new(RecordingBackend::echo());
+        let scheduler = BatchScheduler::spawn(backend.clone(), BatchingConfig {
+            max_batch_size: 4,
+            max_latency_ms: 200,
+            ..Default::default()
+        });
+
+        let texts: Vec<String> = (0..10).map(|i| i.to_string()).collect();
+        scheduler.translate_all(&texts).await.unwrap();
+
+        let sizes = backend.batch_sizes();
+        assert!(sizes.iter().all(|&size| size <= 4));
+        assert_eq!(sizes.iter().sum::<usize>(), 10);
+    }
+
+    #[tokio::test]
+    async fn test_queue_depth_excludes_running_batch() {
+        let (backend, mut started, release) = RecordingBackend::gated();
+        let scheduler = BatchScheduler::spawn(Arc::new(backend), BatchingConfig {
+            max_batch_size: 2,
+            max_latency_ms: 1,
+            ..Default::default()
+        });
+
+        let running = {
+            let scheduler = scheduler.clone();
+            tokio::spawn(async move { scheduler.translate("ראשון").await })
+        };
+        // האצווה הראשונה רצה ונעצרת עד לאישור
+        assert_eq!(started.recv().await, Some(1));
+        assert_eq!(scheduler.metrics().queue_depth(), 0);
+
+        let waiting = {
+            let scheduler = scheduler.clone();
+            tokio::spawn(async move { scheduler.translate("שני").await })
+        };
+        while scheduler.metrics().queue_depth() == 0 {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(scheduler.metrics().queue_depth(), 1);
+
+        release.send(()).unwrap();
+        release.send(()).unwrap();
+        assert_eq!(running.await.unwrap().unwrap(), "ראשון");
+        assert_eq!(waiting.await.unwrap().unwrap(), "שני");
+        assert_eq!(scheduler.metrics().queue_depth(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_latency_window_flushes_partial_batch() {
+        let backend = Arc::new(RecordingBackend::echo());
+        let scheduler = BatchScheduler::spawn(backend.clone(), BatchingConfig {
+            max_batch_size: 64,
+            max_latency_ms: 5,
+            ..Default::default()
+        });
+
+        let result = scheduler.translate("שלום").await.unwrap();
+
+        assert_eq!(result, "שלום");
+        assert_eq!(backend.batch_sizes(), vec![1]);
+    }
+}
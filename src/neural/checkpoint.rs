@@ -0,0 +1,539 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use rand::seq::SliceRandom;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Serialize, Deserialize};
+use tch::{nn, Kind, Tensor};
+use thiserror::Error;
+use super::model_storage::{ModelConfig, ModelMetrics, ModelStorage};
+
+const CHECKPOINT_PREFIX: &str = "checkpoint-";
+const STAGING_SUFFIX: &str = ".partial";
+pub const MODEL_FILE: &str = "model.ot";
+const OPTIMIZER_FILE: &str = "optimizer.ot";
+const STATE_FILE: &str = "state.json";
+
+#[derive(Debug, Error)]
+pub enum CheckpointError {
+    #[error("שגיאת קריאה/כתיבה: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("שגיאת טנסור: {0}")]
+    Torch(#[from] tch::TchError),
+    #[error("שגיאת סריאליזציה: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("המשתנה {0} חסר בנקודת הביקורת")]
+    MissingVariable(String),
+    #[error("אין נקודות ביקורת בתיקייה {0}")]
+    NoCheckpoints(PathBuf),
+    #[error("נדרשו {requested} נקודות ביקורת למיצוע, אך נמצאו רק {available}")]
+    NotEnoughCheckpoints { requested: usize, available: usize },
+    #[error("שגיאה ברישום המודל: {0}")]
+    Storage(String),
+}
+
+/// הגדרות Adam (עם weight decay מנותק, כמו AdamW)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdamConfig {
+    pub beta1: f64,
+    pub beta2: f64,
+    pub eps: f64,
+    pub weight_decay: f64,
+}
+
+impl Default for AdamConfig {
+    fn default() -> Self {
+        Self {
+            beta1: 0.9,
+            beta2: 0.999,
+            eps: 1e-8,
+            weight_decay: 0.0,
+        }
+    }
+}
+
+/// אופטימייזר Adam שמחזיק את המומנטים בעצמו, כדי שאפשר יהיה לשמור ולשחזר אותם במדויק
+pub struct StatefulAdam {
+    variables: BTreeMap<String, Tensor>,
+    exp_avg: BTreeMap<String, Tensor>,
+    exp_avg_sq: BTreeMap<String, Tensor>,
+    config: AdamConfig,
+    lr: f64,
+    step: i64,
+}
+
+impl StatefulAdam {
+    pub fn new(vs: &nn::VarStore, lr: f64, config: AdamConfig) -> Self {
+        let variables: BTreeMap<String, Tensor> = vs
+            .variables()
+            .into_iter()
+            .filter(|(_, tensor)| tensor.requires_grad())
+            .collect();
+        let exp_avg = variables.iter().map(|(name, t)| (name.clone(), t.zeros_like())).collect();
+        let exp_avg_sq = variables.iter().map(|(name, t)| (name.clone(), t.zeros_like())).collect();
+
+        Self {
+            variables,
+            exp_avg,
+            exp_avg_sq,
+            config,
+            lr,
+            step: 0,
+        }
+    }
+
+    pub fn lr(&self) -> f64 {
+        self.lr
+    }
+
+    pub fn set_lr(&mut self, lr: f64) {
+        self.lr = lr;
+    }
+
+    /// מספר צעדי העדכון שבוצעו (משמש לתיקון ההטיה של המומנטים)
+    pub fn step_count(&self) -> i64 {
+        self.step
+    }
+
+    pub fn zero_grad(&mut self) {
+        for variable in self.variables.values_mut() {
+            variable.zero_grad();
+        }
+    }
+
+    pub fn step(&mut self) {
+        self.step += 1;
+        let AdamConfig { beta1, beta2, eps, weight_decay } = self.config;
+        let bias_correction1 = 1.0 - beta1.powi(self.step as i32);
+        let bias_correction2 = 1.0 - beta2.powi(self.step as i32);
+        let lr = self.lr;
+
+        tch::no_grad(|| {
+            for (name, variable) in self.variables.iter_mut() {
+                let grad = variable.grad();
+                if !grad.defined() {
+                    continue;
+                }
+
+                let exp_avg = self.exp_avg.get_mut(name).unwrap();
+                let exp_avg_sq = self.exp_avg_sq.get_mut(name).unwrap();
+
+                if weight_decay > 0.0 {
+                    let _ = variable.g_mul_scalar_(1.0 - lr * weight_decay);
+                }
+                let _ = exp_avg.g_mul_scalar_(beta1);
+                let _ = exp_avg.g_add_(&(&grad * (1.0 - beta1)));
+                let _ = exp_avg_sq.g_mul_scalar_(beta2);
+                let _ = exp_avg_sq.g_add_(&(&grad * &grad * (1.0 - beta2)));
+
+                let denom = (&*exp_avg_sq / bias_correction2).sqrt() + eps;
+                let update = (&*exp_avg / bias_correction1) / denom * lr;
+                let _ = variable.g_sub_(&update);
+            }
+        });
+    }
+
+    /// שמירת המומנטים, מונה הצעדים וקצב הלמידה
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), CheckpointError> {
+        let mut named: Vec<(String, Tensor)> = vec![
+            ("step".to_string(), Tensor::from(self.step)),
+            ("lr".to_string(), Tensor::from(self.lr)),
+        ];
+        for (name, tensor) in &self.exp_avg {
+            named.push((format!("exp_avg/{}", name), tensor.shallow_clone()));
+        }
+        for (name, tensor) in &self.exp_avg_sq {
+            named.push((format!("exp_avg_sq/{}", name), tensor.shallow_clone()));
+        }
+
+        Tensor::save_multi(&named, path)?;
+        Ok(())
+    }
+
+    pub fn load<P: AsRef<Path>>(&mut self, path: P) -> Result<(), CheckpointError> {
+        let loaded: BTreeMap<String, Tensor> = Tensor::load_multi(path)?.into_iter().collect();
+
+        let scalar = |name: &str| loaded
+            .get(name)
+            .ok_or_else(|| CheckpointError::MissingVariable(name.to_string()));
+        self.step = scalar("step")?.int64_value(&[]);
+        self.lr = scalar("lr")?.double_value(&[]);
+
+        for (prefix, moments) in [("exp_avg", &mut self.exp_avg), ("exp_avg_sq", &mut self.exp_avg_sq)] {
+            for (name, tensor) in moments.iter_mut() {
+                let key = format!("{}/{}", prefix, name);
+                let source = loaded.get(&key).ok_or(CheckpointError::MissingVariable(key))?;
+                tch::no_grad(|| tensor.copy_(source));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// מחולל אקראיות של האימון; המצב שלו נשמר בנקודת הביקורת כדי שהמשך האימון יהיה זהה
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrainingRng(ChaCha8Rng);
+
+impl TrainingRng {
+    pub fn new(seed: u64) -> Self {
+        Self(ChaCha8Rng::seed_from_u64(seed))
+    }
+
+    /// זריעת המחולל של torch (dropout) לפני כל צעד
+    pub fn seed_torch(&mut self) {
+        tch::manual_seed(self.0.next_u64() as i64);
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+}
+
+/// סדר הדוגמאות באפוק; נגזר מהזרע ומהאפוק בלבד, כך שאפשר להמשיך באמצע אפוק
+pub fn epoch_order(len: usize, seed: u64, epoch: usize) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..len).collect();
+    let mut rng = ChaCha8Rng::seed_from_u64(seed.wrapping_add(epoch as u64));
+    order.shuffle(&mut rng);
+    order
+}
+
+/// מצב האימון שאינו טנסורים: מיקום, לוח זמני קצב הלמידה, עצירה מוקדמת ומצב האקראיות
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointState {
+    pub step: i64,
+    pub epoch: usize,
+    /// מספר האצוות שהושלמו באפוק הנוכחי
+    pub batch: usize,
+    pub learning_rate: f64,
+    pub best_loss: Option<f64>,
+    pub patience_counter: i64,
+    pub seed: u64,
+    pub rng: TrainingRng,
+}
+
+impl CheckpointState {
+    pub fn new(seed: u64, learning_rate: f64) -> Self {
+        Self {
+            step: 0,
+            epoch: 0,
+            batch: 0,
+            learning_rate,
+            best_loss: None,
+            patience_counter: 0,
+            seed,
+            rng: TrainingRng::new(seed),
+        }
+    }
+}
+
+/// פרטי הרישום של מודל ממוצע ב-ModelStorage
+#[derive(Debug, Clone)]
+pub struct ReleaseInfo {
+    pub name: String,
+    pub description: String,
+    pub version: String,
+    pub metrics: ModelMetrics,
+    pub config: ModelConfig,
+}
+
+/// ניהול תיקיית נקודות ביקורת: שמירה אטומית, רוטציה, המשך והפקת מודל ממוצע
+pub struct CheckpointManager {
+    dir: PathBuf,
+    keep_last: usize,
+}
+
+impl CheckpointManager {
+    pub fn new<P: AsRef<Path>>(dir: P, keep_last: usize) -> Result<Self, CheckpointError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir, keep_last: keep_last.max(1) })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn save(&self, vs: &nn::VarStore, optimizer: &StatefulAdam, state: &CheckpointState) -> Result<PathBuf, CheckpointError> {
+        self.save_with(vs, optimizer, state, |_| Ok(()))
+    }
+
+    /// שמירה עם קבצים נוספים (למשל הטוקנייזר); התיקייה נחשפת רק אחרי שכל הקבצים נכתבו
+    pub fn save_with<F>(
+        &self,
+        vs: &nn::VarStore,
+        optimizer: &StatefulAdam,
+        state: &CheckpointState,
+        extra: F,
+    ) -> Result<PathBuf, CheckpointError>
+    where
+        F: FnOnce(&Path) -> Result<(), CheckpointError>,
+    {
+        let name = format!("{}{:010}", CHECKPOINT_PREFIX, state.step);
+        let staging = self.dir.join(format!("{}{}", name, STAGING_SUFFIX));
+        let target = self.dir.join(name);
+
+        if staging.exists() {
+            fs::remove_dir_all(&staging)?;
+        }
+        Self::write(&staging, vs, optimizer, state)?;
+        extra(&staging)?;
+
+        if target.exists() {
+            fs::remove_dir_all(&target)?;
+        }
+        fs::rename(&staging, &target)?;
+
+        self.rotate()?;
+        Ok(target)
+    }
+
+    /// כתיבת נקודת ביקורת מלאה לתיקייה נתונה, ללא רוטציה
+    pub fn write(dir: &Path, vs: &nn::VarStore, optimizer: &StatefulAdam, state: &CheckpointState) -> Result<(), CheckpointError> {
+        fs::create_dir_all(dir)?;
+        vs.save(dir.join(MODEL_FILE))?;
+        optimizer.save(dir.join(OPTIMIZER_FILE))?;
+        fs::write(dir.join(STATE_FILE), serde_json::to_string_pretty(state)?)?;
+        Ok(())
+    }
+
+    /// שחזור המשקולות, המומנטים והמצב מנקודת ביקורת
+    pub fn restore(dir: &Path, vs: &nn::VarStore, optimizer: &mut StatefulAdam) -> Result<CheckpointState, CheckpointError> {
+        load_weights(vs, &dir.join(MODEL_FILE))?;
+        optimizer.load(dir.join(OPTIMIZER_FILE))?;
+        Self::read_state(dir)
+    }
+
+    pub fn read_state(dir: &Path) -> Result<CheckpointState, CheckpointError> {
+        let json = fs::read_to_string(dir.join(STATE_FILE))?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    pub fn restore_latest(&self, vs: &nn::VarStore, optimizer: &mut StatefulAdam) -> Result<Option<CheckpointState>, CheckpointError> {
+        match self.latest()? {
+            Some(latest) => Ok(Some(Self::restore(&latest, vs, optimizer)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// נקודות הביקורת השלמות, מהישנה לחדשה
+    pub fn list(&self) -> Result<Vec<PathBuf>, CheckpointError> {
+        let mut checkpoints = Vec::new();
+
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            let step = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix(CHECKPOINT_PREFIX))
+                .and_then(|step| step.parse::<i64>().ok());
+
+            if let Some(step) = step {
+                if path.join(STATE_FILE).exists() {
+                    checkpoints.push((step, path));
+                }
+            }
+        }
+
+        checkpoints.sort_by_key(|(step, _)| *step);
+        Ok(checkpoints.into_iter().map(|(_, path)| path).collect())
+    }
+
+    pub fn latest(&self) -> Result<Option<PathBuf>, CheckpointError> {
+        Ok(self.list()?.pop())
+    }
+
+    fn rotate(&self) -> Result<(), CheckpointError> {
+        let checkpoints = self.list()?;
+        if checkpoints.len() > self.keep_last {
+            for old in &checkpoints[..checkpoints.len() - self.keep_last] {
+                fs::remove_dir_all(old)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// מיצוע המשקולות של K נקודות הביקורת האחרונות לתוך vs; מחזיר כמה נקודות מוצעו.
+    /// vs צריך להיות VarStore חדש באותה תצורה ולא זה של האימון: המומנטים של Adam אינם ממוצעים,
+    /// ואימון שממשיך ממשקולות ממוצעות ישמור בנקודת הביקורת הבאה מצב לא עקבי.
+    /// פחות מ-K נקודות הן שגיאה, כדי שמודל השחרור לא יהיה בשקט ממוצע של פחות נקודות מהמוגדר
+    pub fn average_last(&self, k: usize, vs: &nn::VarStore) -> Result<usize, CheckpointError> {
+        let checkpoints = self.list()?;
+        let k = k.max(1);
+        if checkpoints.is_empty() {
+            return Err(CheckpointError::NoCheckpoints(self.dir.clone()));
+        }
+        if checkpoints.len() < k {
+            return Err(CheckpointError::NotEnoughCheckpoints { requested: k, available: checkpoints.len() });
+        }
+        let selected = &checkpoints[checkpoints.len() - k..];
+
+        let variables = vs.variables();
+        let mut sums: BTreeMap<String, Tensor> = variables
+            .iter()
+            .map(|(name, tensor)| (name.clone(), tensor.zeros_like().to_kind(Kind::Double)))
+            .collect();
+
+        for checkpoint in selected {
+            let loaded: BTreeMap<String, Tensor> = Tensor::load_multi(checkpoint.join(MODEL_FILE))?
+                .into_iter()
+                .collect();
+            for (name, sum) in sums.iter_mut() {
+                let tensor = loaded.get(name).ok_or_else(|| CheckpointError::MissingVariable(name.clone()))?;
+                let _ = sum.g_add_(&tensor.to_kind(Kind::Double).to_device(sum.device()));
+            }
+        }
+
+        tch::no_grad(|| {
+            for (name, sum) in &sums {
+                let mut variable = variables[name].shallow_clone();
+                variable.copy_(&(sum / selected.len() as f64).to_kind(variable.kind()));
+            }
+        });
+
+        Ok(selected.len())
+    }
+
+    /// מיצוע K נקודות הביקורת האחרונות לתוך vs (VarStore חדש, כמו ב-average_last) ורישום התוצאה
+    /// כמודל שחרור ב-ModelStorage
+    pub fn release(&self, k: usize, vs: &nn::VarStore, storage: &ModelStorage, info: &ReleaseInfo) -> Result<usize, CheckpointError> {
+        let averaged = self.average_last(k, vs)?;
+
+        let averaged_path = self.dir.join(format!("average-{}.ot", info.version));
+        vs.save(&averaged_path)?;
+
+        let mut metrics = info.metrics.clone();
+        metrics.training_steps = Self::read_state(&self.latest()?.ok_or_else(|| CheckpointError::NoCheckpoints(self.dir.clone()))?)?.step;
+
+        let result = storage.save_model(
+            &info.name,
+            &info.description,
+            &info.version,
+            &averaged_path,
+            metrics,
+            info.config.clone(),
+        );
+        fs::remove_file(&averaged_path)?;
+        result.map_err(|e| CheckpointError::Storage(e.to_string()))?;
+
+        Ok(averaged)
+    }
+}
+
+/// טעינת משקולות לתוך משתנים קיימים (גם דרך VarStore משותף, ללא &mut)
+fn load_weights(vs: &nn::VarStore, path: &Path) -> Result<(), CheckpointError> {
+    let loaded: BTreeMap<String, Tensor> = Tensor::load_multi(path)?.into_iter().collect();
+
+    tch::no_grad(|| {
+        for (name, mut variable) in vs.variables() {
+            let source = loaded.get(&name).ok_or(CheckpointError::MissingVariable(name))?;
+            variable.copy_(source);
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tch::Device;
+    use tempfile::TempDir;
+
+    fn create_var_store(value: f64) -> nn::VarStore {
+        let vs = nn::VarStore::new(Device::Cpu);
+        let _ = vs.root().var("weight", &[2, 2], nn::Init::Const(value));
+        vs
+    }
+
+    fn train_step(vs: &nn::VarStore, optimizer: &mut StatefulAdam) {
+        optimizer.zero_grad();
+        let weight = vs.variables()["weight"].shallow_clone();
+        (&weight * &weight).sum(Kind::Float).backward();
+        optimizer.step();
+    }
+
+    #[test]
+    fn test_resume_matches_uninterrupted_run() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = CheckpointManager::new(temp_dir.path(), 3).unwrap();
+
+        let vs = create_var_store(1.0);
+        let mut optimizer = StatefulAdam::new(&vs, 0.1, AdamConfig::default());
+        for _ in 0..3 {
+            train_step(&vs, &mut optimizer);
+        }
+        let mut state = CheckpointState::new(7, 0.1);
+        state.step = optimizer.step_count();
+        manager.save(&vs, &optimizer, &state).unwrap();
+        for _ in 0..3 {
+            train_step(&vs, &mut optimizer);
+        }
+
+        let resumed_vs = create_var_store(0.0);
+        let mut resumed = StatefulAdam::new(&resumed_vs, 0.0, AdamConfig::default());
+        let restored = manager.restore_latest(&resumed_vs, &mut resumed).unwrap().unwrap();
+        for _ in 0..3 {
+            train_step(&resumed_vs, &mut resumed);
+        }
+
+        assert_eq!(restored.step, 3);
+        assert_eq!(resumed.step_count(), 6);
+        let expected = vs.variables()["weight"].double_value(&[0, 0]);
+        let actual = resumed_vs.variables()["weight"].double_value(&[0, 0]);
+        assert!((expected - actual).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_rotation_keeps_last_n() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = CheckpointManager::new(temp_dir.path(), 2).unwrap();
+        let vs = create_var_store(1.0);
+        let optimizer = StatefulAdam::new(&vs, 0.1, AdamConfig::default());
+
+        let mut state = CheckpointState::new(7, 0.1);
+        for step in 1..=4 {
+            state.step = step;
+            manager.save(&vs, &optimizer, &state).unwrap();
+        }
+
+        let remaining = manager.list().unwrap();
+        assert_eq!(remaining.len(), 2);
+        assert!(remaining[1].ends_with("checkpoint-0000000004"));
+    }
+
+    #[test]
+    fn test_average_last_k() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = CheckpointManager::new(temp_dir.path(), 5).unwrap();
+
+        let mut state = CheckpointState::new(7, 0.1);
+        for (step, value) in [(1, 1.0), (2, 2.0), (3, 4.0)] {
+            let vs = create_var_store(value);
+            let optimizer = StatefulAdam::new(&vs, 0.1, AdamConfig::default());
+            state.step = step;
+            manager.save(&vs, &optimizer, &state).unwrap();
+        }
+
+        let vs = create_var_store(0.0);
+        assert_eq!(manager.average_last(2, &vs).unwrap(), 2);
+        assert!((vs.variables()["weight"].double_value(&[1, 1]) - 3.0).abs() < 1e-6);
+
+        assert!(matches!(
+            manager.average_last(4, &vs),
+            Err(CheckpointError::NotEnoughCheckpoints { requested: 4, available: 3 })
+        ));
+    }
+
+    #[test]
+    fn test_rng_state_roundtrip() {
+        let mut rng = TrainingRng::new(42);
+        rng.next_u64();
+
+        let json = serde_json::to_string(&rng).unwrap();
+        let mut restored: TrainingRng = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(rng.next_u64(), restored.next_u64());
+        assert_eq!(epoch_order(10, 42, 3), epoch_order(10, 42, 3));
+    }
+}
@@ -0,0 +1,521 @@
+use std::collections::HashSet;
+use serde::{Serialize, Deserialize};
+use crate::translation_models::TranslationError;
+use crate::technical_terms::TermsDatabase;
+use crate::morphology::utils::hebrew_surface_variants;
+use super::vocabulary::Vocabulary;
+
+/// אילוץ לקסיקלי: מונח יעד מאושר שחייב להופיע בתרגום
+#[derive(Debug, Clone)]
+pub struct LexicalConstraint {
+    /// המונח כפי שנמצא בטקסט המקור
+    pub source_term: String,
+    /// המונח המאושר בעברית
+    pub target_term: String,
+    /// צורות המשטח המותרות, כל אחת כרצף אינדקסים באוצר המילים של היעד
+    pub variants: Vec<Vec<i64>>,
+}
+
+/// סיבה לאי-קיום אילוץ
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum UnmetReason {
+    /// אף צורה של המונח אינה ניתנת לייצוג באוצר המילים
+    OutOfVocabulary(Vec<String>),
+    /// המפענח לא הצליח לשלב את המונח בתרגום
+    NotGenerated,
+}
+
+/// אילוץ שלא קוים בתרגום
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnmetConstraint {
+    pub source_term: String,
+    pub target_term: String,
+    pub reason: UnmetReason,
+}
+
+/// אוסף האילוצים של סגמנט אחד
+#[derive(Debug, Clone, Default)]
+pub struct ConstraintSet {
+    constraints: Vec<LexicalConstraint>,
+    rejected: Vec<UnmetConstraint>,
+}
+
+impl ConstraintSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// בניית אילוצים מהמונחים המאושרים שנמצאו בסגמנט המקור
+    pub fn from_terms(terms: &TermsDatabase, source: &str, target_vocab: &Vocabulary) -> Self {
+        Self::from_terms_with(terms, source, |surface| vocabulary_encoding(target_vocab, surface))
+    }
+
+    /// כמו from_terms, כשהקידוד של כל צורת משטח נקבע על ידי הקורא (למשל טוקנייזר BPE)
+    pub fn from_terms_with(terms: &TermsDatabase, source: &str, encode: impl Fn(&str) -> Vec<Vec<i64>>) -> Self {
+        let mut set = Self::new();
+        let mut seen = HashSet::new();
+        // גם צורות נטויות של המונח במקור מחייבות את המונח המאושר
+        for (term, form) in terms.find_russian_term_forms(source) {
+            if seen.insert(term.term_ru.to_lowercase()) {
+                set.add_term_with(&form, &term.term_he, &encode);
+            }
+        }
+        set
+    }
+
+    /// הוספת מונח יעד יחד עם הצורות המורפולוגיות שהמחולל העברי מתיר
+    pub fn add_term(&mut self, source_term: &str, target_term: &str, target_vocab: &Vocabulary) {
+        self.add_term_with(source_term, target_term, |surface| vocabulary_encoding(target_vocab, surface));
+    }
+
+    /// הוספת מונח יעד כשכל צורת משטח עשויה להתקודד בכמה רצפי מזהים; רשימה ריקה היא צורה מחוץ לאוצר המילים
+    pub fn add_term_with(&mut self, source_term: &str, target_term: &str, encode: impl Fn(&str) -> Vec<Vec<i64>>) {
+        let mut variants = Vec::new();
+        let mut missing = Vec::new();
+
+        for surface in hebrew_surface_variants(target_term) {
+            let encodings = encode(&surface);
+            if encodings.is_empty() {
+                missing.push(surface);
+            }
+            for ids in encodings {
+                if !variants.contains(&ids) {
+                    variants.push(ids);
+                }
+            }
+        }
+
+        if variants.is_empty() {
+            self.rejected.push(UnmetConstraint {
+                source_term: source_term.to_string(),
+                target_term: target_term.to_string(),
+                reason: UnmetReason::OutOfVocabulary(missing),
+            });
+        } else {
+            self.constraints.push(LexicalConstraint {
+                source_term: source_term.to_string(),
+                target_term: target_term.to_string(),
+                variants,
+            });
+        }
+    }
+
+    pub fn constraints(&self) -> &[LexicalConstraint] {
+        &self.constraints
+    }
+
+    pub fn len(&self) -> usize {
+        self.constraints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.constraints.is_empty()
+    }
+}
+
+/// קידוד מילה-מילה באוצר מילים ברמת מילים; צורה עם מילה חסרה אינה ניתנת לאכיפה
+fn vocabulary_encoding(vocab: &Vocabulary, surface: &str) -> Vec<Vec<i64>> {
+    surface
+        .split_whitespace()
+        .map(|word| vocab.get_index(word))
+        .collect::<Result<Vec<_>, _>>()
+        .into_iter()
+        .collect()
+}
+
+/// מקור הסתברויות לצעד פענוח בודד
+pub trait StepScorer {
+    /// לוג-הסתברויות לכל טוקן באוצר היעד בהינתן הקידומת שפוענחה עד כה
+    fn next_log_probs(&self, prefix: &[i64]) -> Result<Vec<f32>, TranslationError>;
+}
+
+/// הגדרות חיפוש האלומה המאולץ
+#[derive(Debug, Clone)]
+pub struct BeamConfig {
+    pub beam_size: usize,
+    pub max_length: usize,
+    /// מספר הטוקנים המובילים שנשקלים מכל השערה, בנוסף לטוקני האילוצים
+    pub top_k: usize,
+}
+
+impl Default for BeamConfig {
+    fn default() -> Self {
+        Self {
+            beam_size: 10,
+            max_length: 100,
+            top_k: 5,
+        }
+    }
+}
+
+/// תוצאת פענוח מאולץ
+#[derive(Debug, Clone)]
+pub struct ConstrainedOutput {
+    /// טוקני התרגום, ללא BOS ו-EOS
+    pub tokens: Vec<i64>,
+    /// ציון לוג-הסתברות מנורמל לאורך
+    pub score: f64,
+    /// אילוצים שלא קוימו
+    pub unmet: Vec<UnmetConstraint>,
+}
+
+/// השערה בתוך האלומה
+#[derive(Debug, Clone)]
+struct Hypothesis {
+    tokens: Vec<i64>,
+    score: f64,
+    met: Vec<bool>,
+    /// צורות (אילוץ, צורה) שהקידומת שלהן תואמת כרגע את סוף ההשערה
+    in_progress: Vec<(usize, usize)>,
+    matched: usize,
+    finished: bool,
+}
+
+impl Hypothesis {
+    fn met_count(&self) -> usize {
+        self.met.iter().filter(|&&m| m).count()
+    }
+
+    fn normalized_score(&self) -> f64 {
+        self.score / self.tokens.len().max(1) as f64
+    }
+}
+
+/// מפענח אלומה עם הקצאה דינמית (Dynamic Beam Allocation) לפי מספר האילוצים שקוימו
+pub struct ConstrainedDecoder {
+    config: BeamConfig,
+}
+
+impl ConstrainedDecoder {
+    pub fn new(config: BeamConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn decode<S: StepScorer>(
+        &self,
+        scorer: &S,
+        constraints: &ConstraintSet,
+        bos: i64,
+        eos: i64,
+    ) -> Result<ConstrainedOutput, TranslationError> {
+        let num_constraints = constraints.len();
+        let mut beam = vec![Hypothesis {
+            tokens: vec![bos],
+            score: 0.0,
+            met: vec![false; num_constraints],
+            in_progress: Vec::new(),
+            matched: 0,
+            finished: false,
+        }];
+        let mut finished: Vec<Hypothesis> = Vec::new();
+
+        for _ in 0..self.config.max_length {
+            let mut candidates = Vec::new();
+
+            for hyp in &beam {
+                let log_probs = scorer.next_log_probs(&hyp.tokens)?;
+                for token in self.candidate_tokens(hyp, &log_probs, constraints) {
+                    let log_prob = log_probs
+                        .get(token as usize)
+                        .copied()
+                        .ok_or_else(|| TranslationError::ModelError(format!("טוקן מחוץ לטווח: {}", token)))?;
+                    candidates.push(Self::advance(hyp, token, log_prob as f64, constraints, eos));
+                }
+            }
+
+            let (done, live): (Vec<_>, Vec<_>) = self.allocate(candidates, num_constraints)
+                .into_iter()
+                .partition(|h| h.finished);
+            finished.extend(done);
+            beam = live;
+
+            let complete = finished.iter().filter(|h| h.met_count() == num_constraints).count();
+            if beam.is_empty() || complete >= self.config.beam_size {
+                break;
+            }
+        }
+
+        // השערות שלא הסתיימו נשקלות רק אם אין אף השערה גמורה
+        let pool = if finished.is_empty() { beam } else { finished };
+        let best = pool
+            .into_iter()
+            .max_by(|a, b| {
+                a.met_count()
+                    .cmp(&b.met_count())
+                    .then(a.normalized_score().partial_cmp(&b.normalized_score()).unwrap_or(std::cmp::Ordering::Equal))
+            })
+            .ok_or_else(|| TranslationError::ModelError("לא נוצרה אף השערה".to_string()))?;
+
+        let mut unmet = constraints.rejected.clone();
+        for (constraint, &met) in constraints.constraints.iter().zip(&best.met) {
+            if !met {
+                unmet.push(UnmetConstraint {
+                    source_term: constraint.source_term.clone(),
+                    target_term: constraint.target_term.clone(),
+                    reason: UnmetReason::NotGenerated,
+                });
+            }
+        }
+
+        let score = best.normalized_score();
+        let tokens = best
+            .tokens
+            .into_iter()
+            .skip(1)
+            .take_while(|&t| t != eos)
+            .collect();
+
+        Ok(ConstrainedOutput { tokens, score, unmet })
+    }
+
+    /// הטוקנים המובילים, המשך האילוץ הפתוח ופתיחת כל אילוץ שטרם קוים
+    fn candidate_tokens(&self, hyp: &Hypothesis, log_probs: &[f32], constraints: &ConstraintSet) -> Vec<i64> {
+        let mut ranked: Vec<usize> = (0..log_probs.len()).collect();
+        // בחירה חלקית: רק k המובילים נדרשים, בלי מיון כל אוצר המילים
+        let top_k = self.config.top_k.min(ranked.len());
+        if top_k > 0 && top_k < ranked.len() {
+            ranked.select_nth_unstable_by(top_k - 1, |&a, &b| {
+                log_probs[b].partial_cmp(&log_probs[a]).unwrap_or(std::cmp::Ordering::Equal)
+            });
+        }
+
+        let mut tokens: Vec<i64> = ranked.into_iter().take(top_k).map(|t| t as i64).collect();
+        let mut seen: HashSet<i64> = tokens.iter().copied().collect();
+
+        for &(c, v) in &hyp.in_progress {
+            let next = constraints.constraints[c].variants[v][hyp.matched];
+            if seen.insert(next) {
+                tokens.push(next);
+            }
+        }
+
+        for (c, constraint) in constraints.constraints.iter().enumerate() {
+            if hyp.met[c] {
+                continue;
+            }
+            for variant in &constraint.variants {
+                if seen.insert(variant[0]) {
+                    tokens.push(variant[0]);
+                }
+            }
+        }
+
+        tokens
+    }
+
+    fn advance(hyp: &Hypothesis, token: i64, log_prob: f64, constraints: &ConstraintSet, eos: i64) -> Hypothesis {
+        let mut next = hyp.clone();
+        next.tokens.push(token);
+        next.score += log_prob;
+
+        if token == eos {
+            next.finished = true;
+            next.in_progress.clear();
+            return next;
+        }
+
+        // המשך צורות שכבר בתהליך
+        let matched = hyp.matched;
+        next.in_progress.retain(|&(c, v)| constraints.constraints[c].variants[v][matched] == token);
+        next.matched += 1;
+        if let Some(&(c, _)) = next.in_progress.iter()
+            .find(|&&(c, v)| constraints.constraints[c].variants[v].len() == next.matched)
+        {
+            next.met[c] = true;
+            next.in_progress.clear();
+        }
+
+        // פתיחת אילוץ חדש כשאין אילוץ פתוח
+        if next.in_progress.is_empty() {
+            next.matched = 0;
+            let mut completed = None;
+            for (c, constraint) in constraints.constraints.iter().enumerate() {
+                if next.met[c] {
+                    continue;
+                }
+                for (v, variant) in constraint.variants.iter().enumerate() {
+                    if variant[0] == token {
+                        if variant.len() == 1 {
+                            completed.get_or_insert(c);
+                        } else {
+                            next.in_progress.push((c, v));
+                        }
+                    }
+                }
+            }
+            if let Some(c) = completed {
+                next.met[c] = true;
+                next.in_progress.clear();
+            } else if !next.in_progress.is_empty() {
+                next.matched = 1;
+            }
+        }
+
+        next
+    }
+
+    /// חלוקת האלומה לבנקים לפי מספר האילוצים שקוימו; מקומות פנויים עוברים לבנקים הגבוהים
+    fn allocate(&self, candidates: Vec<Hypothesis>, num_constraints: usize) -> Vec<Hypothesis> {
+        let num_banks = num_constraints + 1;
+        let mut banks: Vec<Vec<Hypothesis>> = vec![Vec::new(); num_banks];
+        let mut seen = HashSet::new();
+
+        for candidate in candidates {
+            if seen.insert(candidate.tokens.clone()) {
+                banks[candidate.met_count()].push(candidate);
+            }
+        }
+        for bank in &mut banks {
+            bank.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        }
+
+        let beam_size = self.config.beam_size;
+        let mut quota = vec![beam_size / num_banks; num_banks];
+        for i in (0..num_banks).rev().take(beam_size % num_banks) {
+            quota[i] += 1;
+        }
+
+        let mut spare = 0;
+        for (q, bank) in quota.iter_mut().zip(&banks) {
+            spare += q.saturating_sub(bank.len());
+            *q = (*q).min(bank.len());
+        }
+        for i in (0..num_banks).rev() {
+            let extra = spare.min(banks[i].len() - quota[i]);
+            quota[i] += extra;
+            spare -= extra;
+        }
+
+        banks
+            .into_iter()
+            .zip(quota)
+            .flat_map(|(bank, q)| bank.into_iter().take(q))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// מודל דמה שמעדיף תמיד את אותו טוקן ומסיים אחרי מספר צעדים קבוע
+    struct FixedScorer {
+        vocab_size: usize,
+        preferred: i64,
+        eos: i64,
+        length: usize,
+    }
+
+    impl StepScorer for FixedScorer {
+        fn next_log_probs(&self, prefix: &[i64]) -> Result<Vec<f32>, TranslationError> {
+            let mut probs = vec![-5.0f32; self.vocab_size];
+            if prefix.len() > self.length {
+                probs[self.eos as usize] = -0.1;
+            } else {
+                probs[self.preferred as usize] = -0.1;
+            }
+            Ok(probs)
+        }
+    }
+
+    fn create_test_vocab() -> Vocabulary {
+        let mut vocab = Vocabulary::new();
+        for word in ["התקן", "ספרינקלר", "מתז", "המתז", "במערכת"] {
+            vocab.add_word(word);
+        }
+        vocab
+    }
+
+    fn create_scorer(vocab: &Vocabulary) -> FixedScorer {
+        FixedScorer {
+            vocab_size: vocab.size(),
+            preferred: vocab.get_index("ספרינקלר").unwrap(),
+            eos: vocab.get_eos_index(),
+            length: 3,
+        }
+    }
+
+    #[test]
+    fn test_forces_approved_term() {
+        let vocab = create_test_vocab();
+        let mut constraints = ConstraintSet::new();
+        constraints.add_term("спринклер", "מתז", &vocab);
+
+        let decoder = ConstrainedDecoder::new(BeamConfig::default());
+        let output = decoder
+            .decode(&create_scorer(&vocab), &constraints, vocab.get_bos_index(), vocab.get_eos_index())
+            .unwrap();
+
+        let matz = vocab.get_index("מתז").unwrap();
+        let hamatz = vocab.get_index("המתז").unwrap();
+        assert!(output.tokens.iter().any(|&t| t == matz || t == hamatz));
+        assert!(output.unmet.is_empty());
+    }
+
+    #[test]
+    fn test_unconstrained_follows_model() {
+        let vocab = create_test_vocab();
+        let decoder = ConstrainedDecoder::new(BeamConfig::default());
+        let output = decoder
+            .decode(&create_scorer(&vocab), &ConstraintSet::new(), vocab.get_bos_index(), vocab.get_eos_index())
+            .unwrap();
+
+        let sprinkler = vocab.get_index("ספרינקלר").unwrap();
+        assert!(output.tokens.iter().all(|&t| t == sprinkler));
+    }
+
+    #[test]
+    fn test_reports_out_of_vocabulary_term() {
+        let vocab = create_test_vocab();
+        let mut constraints = ConstraintSet::new();
+        constraints.add_term("клапан", "שסתום", &vocab);
+
+        let decoder = ConstrainedDecoder::new(BeamConfig::default());
+        let output = decoder
+            .decode(&create_scorer(&vocab), &constraints, vocab.get_bos_index(), vocab.get_eos_index())
+            .unwrap();
+
+        assert_eq!(output.unmet.len(), 1);
+        assert!(matches!(output.unmet[0].reason, UnmetReason::OutOfVocabulary(_)));
+    }
+
+    #[test]
+    fn test_constraint_from_inflected_source_term() {
+        let mut terms = TermsDatabase::new();
+        terms.add_term(crate::technical_terms::TechnicalTerm {
+            term_he: "מתז".to_string(),
+            term_ru: "спринклер".to_string(),
+            domain: "fire_protection".to_string(),
+            context: String::new(),
+            examples: Vec::new(),
+            synonyms: Vec::new(),
+            source: String::new(),
+            confidence: 1.0,
+        });
+
+        let vocab = create_test_vocab();
+        let constraints = ConstraintSet::from_terms(&terms, "Установка спринклеров в системе", &vocab);
+
+        assert_eq!(constraints.constraints.len(), 1);
+        assert_eq!(constraints.constraints[0].source_term, "спринклеров");
+        assert_eq!(constraints.constraints[0].target_term, "מתז");
+    }
+
+    #[test]
+    fn test_candidate_tokens_keep_top_k() {
+        let decoder = ConstrainedDecoder::new(BeamConfig { top_k: 2, ..BeamConfig::default() });
+        let hyp = Hypothesis {
+            tokens: vec![0],
+            score: 0.0,
+            met: Vec::new(),
+            in_progress: Vec::new(),
+            matched: 0,
+            finished: false,
+        };
+        let log_probs = [-3.0f32, -0.5, -2.0, -0.1, -4.0];
+
+        let mut tokens = decoder.candidate_tokens(&hyp, &log_probs, &ConstraintSet::new());
+        tokens.sort();
+        assert_eq!(tokens, vec![1, 3]);
+    }
+}
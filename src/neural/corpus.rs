@@ -0,0 +1,550 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Serialize, Deserialize};
+use thiserror::Error;
+use crate::language_detection::{Language, LanguageDetector};
+use crate::quality_control::numbers::{check_numbers, NumberIssue};
+
+const TRAIN_FILE: &str = "train.tsv";
+const DEV_FILE: &str = "dev.tsv";
+const TEST_FILE: &str = "test.tsv";
+const MANIFEST_FILE: &str = "manifest.json";
+
+#[derive(Debug, Error)]
+pub enum CorpusError {
+    #[error("שגיאת קריאה/כתיבה: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("שגיאה בפענוח TMX: {0}")]
+    Tmx(#[from] quick_xml::Error),
+    #[error("שגיאת סריאליזציה: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("קבצי Moses אינם מיושרים: {source_lines} שורות מקור מול {target_lines} שורות יעד")]
+    Misaligned { source_lines: usize, target_lines: usize },
+    #[error("יחסי החלוקה אינם חוקיים: dev {dev_ratio}, test {test_ratio} (כל אחד בין 0 ל-1, וסכומם לכל היותר 1)")]
+    InvalidSplit { dev_ratio: f64, test_ratio: f64 },
+}
+
+/// צמד משפטים מקבילים
+pub type SentencePair = (String, String);
+
+/// מקור קורפוס מקביל
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "format", rename_all = "snake_case")]
+pub enum CorpusSource {
+    /// קובץ אחד, מקור ויעד מופרדים בטאב
+    Tsv { path: PathBuf },
+    /// שני קבצים מיושרים שורה מול שורה (corpus.ru / corpus.he)
+    Moses { source: PathBuf, target: PathBuf },
+    /// זיכרון תרגום בתקן TMX
+    Tmx { path: PathBuf },
+}
+
+/// הגדרות הניקוי והחלוקה של הקורפוס
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorpusConfig {
+    pub source_lang: String,
+    pub target_lang: String,
+    /// יחס האורכים המרבי (בתווים) בין המשפט הארוך לקצר
+    pub max_length_ratio: f64,
+    /// אורך מרבי בתווים לכל צד
+    pub max_length: usize,
+    pub seed: u64,
+    pub dev_ratio: f64,
+    pub test_ratio: f64,
+}
+
+impl CorpusConfig {
+    /// בדיקה שיחסי החלוקה משאירים מקום לכל חלק
+    pub fn validate(&self) -> Result<(), CorpusError> {
+        let valid = |ratio: f64| (0.0..=1.0).contains(&ratio);
+        if valid(self.dev_ratio) && valid(self.test_ratio) && self.dev_ratio + self.test_ratio <= 1.0 {
+            Ok(())
+        } else {
+            Err(CorpusError::InvalidSplit {
+                dev_ratio: self.dev_ratio,
+                test_ratio: self.test_ratio,
+            })
+        }
+    }
+}
+
+impl Default for CorpusConfig {
+    fn default() -> Self {
+        Self {
+            source_lang: "ru".to_string(),
+            target_lang: "he".to_string(),
+            max_length_ratio: 3.0,
+            max_length: 1000,
+            seed: 42,
+            dev_ratio: 0.05,
+            test_ratio: 0.05,
+        }
+    }
+}
+
+/// ספירת הצמדים שנפסלו בכל שלב של הסינון
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FilterStats {
+    /// שורות TSV שאין בהן בדיוק טאב אחד; הן אינן נכנסות לספירת read
+    #[serde(default)]
+    pub malformed: usize,
+    pub read: usize,
+    pub empty: usize,
+    pub duplicates: usize,
+    pub too_long: usize,
+    pub length_ratio: usize,
+    pub wrong_language: usize,
+    pub number_mismatch: usize,
+    pub kept: usize,
+}
+
+/// החלוקה לאימון, פיתוח ובדיקה
+#[derive(Debug, Clone, Default)]
+pub struct CorpusSplits {
+    pub train: Vec<SentencePair>,
+    pub dev: Vec<SentencePair>,
+    pub test: Vec<SentencePair>,
+}
+
+/// תיעוד הרצה של הצינור: מקורות, הגדרות וספירות
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorpusManifest {
+    pub sources: Vec<CorpusSource>,
+    pub config: CorpusConfig,
+    pub filter: FilterStats,
+    pub train: usize,
+    pub dev: usize,
+    pub test: usize,
+}
+
+/// צינור הכנת קורפוס מקביל לאימון
+pub struct CorpusPipeline {
+    config: CorpusConfig,
+    detector: LanguageDetector,
+}
+
+impl CorpusPipeline {
+    pub fn new(config: CorpusConfig) -> Self {
+        Self {
+            config,
+            detector: LanguageDetector::new(),
+        }
+    }
+
+    pub fn config(&self) -> &CorpusConfig {
+        &self.config
+    }
+
+    /// קריאה, סינון וחלוקה של כל המקורות, וכתיבת הקבצים והמניפסט לתיקייה
+    pub fn run<P: AsRef<Path>>(&self, sources: &[CorpusSource], output_dir: P) -> Result<CorpusManifest, CorpusError> {
+        let mut pairs = Vec::new();
+        let mut malformed = 0;
+        for source in sources {
+            let (read, skipped) = self.read(source)?;
+            pairs.extend(read);
+            malformed += skipped;
+        }
+
+        let (kept, mut filter) = self.filter(pairs);
+        filter.malformed = malformed;
+        let splits = self.split(kept)?;
+
+        let output_dir = output_dir.as_ref();
+        std::fs::create_dir_all(output_dir)?;
+        write_tsv(&output_dir.join(TRAIN_FILE), &splits.train)?;
+        write_tsv(&output_dir.join(DEV_FILE), &splits.dev)?;
+        write_tsv(&output_dir.join(TEST_FILE), &splits.test)?;
+
+        let manifest = CorpusManifest {
+            sources: sources.to_vec(),
+            config: self.config.clone(),
+            filter,
+            train: splits.train.len(),
+            dev: splits.dev.len(),
+            test: splits.test.len(),
+        };
+        std::fs::write(output_dir.join(MANIFEST_FILE), serde_json::to_string_pretty(&manifest)?)?;
+
+        Ok(manifest)
+    }
+
+    /// קריאת מקור אחד: הצמדים ומספר השורות הפגומות שדולגו
+    pub fn read(&self, source: &CorpusSource) -> Result<(Vec<SentencePair>, usize), CorpusError> {
+        match source {
+            CorpusSource::Tsv { path } => read_tsv(path),
+            CorpusSource::Moses { source, target } => Ok((read_moses(source, target)?, 0)),
+            CorpusSource::Tmx { path } => {
+                Ok((read_tmx(path, &self.config.source_lang, &self.config.target_lang)?, 0))
+            }
+        }
+    }
+
+    /// סינון הצמדים; הסדר של הצמדים שנשמרו נשמר כמו בקלט
+    pub fn filter<I>(&self, pairs: I) -> (Vec<SentencePair>, FilterStats)
+    where
+        I: IntoIterator<Item = SentencePair>,
+    {
+        let mut stats = FilterStats::default();
+        let mut seen = HashSet::new();
+        let mut kept = Vec::new();
+
+        for (source, target) in pairs {
+            stats.read += 1;
+            let source = normalize_whitespace(&source);
+            let target = normalize_whitespace(&target);
+
+            if source.is_empty() || target.is_empty() {
+                stats.empty += 1;
+                continue;
+            }
+            if !seen.insert((source.clone(), target.clone())) {
+                stats.duplicates += 1;
+                continue;
+            }
+
+            let source_len = source.chars().count();
+            let target_len = target.chars().count();
+            if source_len > self.config.max_length || target_len > self.config.max_length {
+                stats.too_long += 1;
+                continue;
+            }
+            let ratio = source_len.max(target_len) as f64 / source_len.min(target_len) as f64;
+            if ratio > self.config.max_length_ratio {
+                stats.length_ratio += 1;
+                continue;
+            }
+
+            if !self.is_language(&source, &self.config.source_lang)
+                || !self.is_language(&target, &self.config.target_lang)
+            {
+                stats.wrong_language += 1;
+                continue;
+            }
+            if !numbers_match(&source, &target) {
+                stats.number_mismatch += 1;
+                continue;
+            }
+
+            kept.push((source, target));
+        }
+
+        stats.kept = kept.len();
+        (kept, stats)
+    }
+
+    /// ערבוב לפי הזרע וחלוקה; אותו קלט ואותו זרע תמיד נותנים אותה חלוקה
+    pub fn split(&self, mut pairs: Vec<SentencePair>) -> Result<CorpusSplits, CorpusError> {
+        self.config.validate()?;
+
+        let mut rng = ChaCha8Rng::seed_from_u64(self.config.seed);
+        pairs.shuffle(&mut rng);
+
+        let total = pairs.len();
+        let test_size = (total as f64 * self.config.test_ratio) as usize;
+        let dev_size = (total as f64 * self.config.dev_ratio) as usize;
+
+        let test = pairs.split_off(total - test_size);
+        let dev = pairs.split_off(pairs.len() - dev_size);
+
+        Ok(CorpusSplits { train: pairs, dev, test })
+    }
+
+    fn is_language(&self, text: &str, lang: &str) -> bool {
+        let expected = match lang {
+            "he" => Language::Hebrew,
+            "ru" => Language::Russian,
+            // אין זיהוי לשפות אחרות, ולכן לא מסננים לפיהן
+            _ => return true,
+        };
+        self.detector.detect_language(text) == expected
+    }
+}
+
+impl CorpusSplits {
+    /// טעינת חלוקה שנכתבה על ידי CorpusPipeline::run
+    pub fn load<P: AsRef<Path>>(dir: P) -> Result<Self, CorpusError> {
+        let dir = dir.as_ref();
+        let load = |file: &str| -> Result<Vec<SentencePair>, CorpusError> {
+            let (pairs, malformed) = read_tsv(&dir.join(file))?;
+            if malformed > 0 {
+                log::warn!("{} שורות פגומות דולגו ב-{}", malformed, dir.join(file).display());
+            }
+            Ok(pairs)
+        };
+        Ok(Self {
+            train: load(TRAIN_FILE)?,
+            dev: load(DEV_FILE)?,
+            test: load(TEST_FILE)?,
+        })
+    }
+}
+
+impl CorpusManifest {
+    pub fn load<P: AsRef<Path>>(dir: P) -> Result<Self, CorpusError> {
+        let json = std::fs::read_to_string(dir.as_ref().join(MANIFEST_FILE))?;
+        Ok(serde_json::from_str(&json)?)
+    }
+}
+
+fn normalize_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// אותם ערכים בשני הצדדים, לפי בדיקת המספרים של בקרת האיכות (מפרידי אלפים ועשרוניים, טווחים וקודי תקנים);
+/// הבדלי יחידות לבדם אינם פוסלים צמד
+fn numbers_match(source: &str, target: &str) -> bool {
+    !check_numbers(source, target).iter().any(|validation| {
+        matches!(validation.issue, NumberIssue::Missing | NumberIssue::Extra | NumberIssue::AlteredValue)
+    })
+}
+
+/// הצמדים ומספר השורות הפגומות (בלי טאב או עם יותר מטאב אחד); שורות ריקות מדולגות
+fn read_tsv(path: &Path) -> Result<(Vec<SentencePair>, usize), CorpusError> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut pairs = Vec::new();
+    let mut malformed = 0;
+
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match line.split_once('\t') {
+            Some((source, target)) if !target.contains('\t') => {
+                pairs.push((source.to_string(), target.to_string()));
+            }
+            _ => malformed += 1,
+        }
+    }
+
+    Ok((pairs, malformed))
+}
+
+fn read_moses(source: &Path, target: &Path) -> Result<Vec<SentencePair>, CorpusError> {
+    let source_lines = BufReader::new(File::open(source)?).lines().collect::<Result<Vec<_>, _>>()?;
+    let target_lines = BufReader::new(File::open(target)?).lines().collect::<Result<Vec<_>, _>>()?;
+
+    if source_lines.len() != target_lines.len() {
+        return Err(CorpusError::Misaligned {
+            source_lines: source_lines.len(),
+            target_lines: target_lines.len(),
+        });
+    }
+
+    Ok(source_lines.into_iter().zip(target_lines).collect())
+}
+
+/// רכיבי TMX שתוכנם קוד פורמט מקורי ולא טקסט לתרגום
+const TMX_INLINE_CODE: &[&[u8]] = &[b"bpt", b"ept", b"ph", b"it", b"ut"];
+
+/// קריאת יחידות התרגום (tu) מקובץ TMX; יחידות ללא שני הצדדים מדולגות.
+/// התוכן של תגיות הפורמט (bpt, ept, ph, it, ut) אינו נכנס לטקסט
+fn read_tmx(path: &Path, source_lang: &str, target_lang: &str) -> Result<Vec<SentencePair>, CorpusError> {
+    let mut reader = Reader::from_file(path)?;
+    let mut buffer = Vec::new();
+    let mut pairs = Vec::new();
+
+    let mut source = None;
+    let mut target = None;
+    let mut current_lang: Option<String> = None;
+    let mut segment: Option<String> = None;
+    let mut inline_depth = 0usize;
+
+    loop {
+        match reader.read_event_into(&mut buffer)? {
+            Event::Start(e) => match e.name().as_ref() {
+                b"tu" => {
+                    source = None;
+                    target = None;
+                }
+                b"tuv" => {
+                    current_lang = None;
+                    for attribute in e.attributes().flatten() {
+                        let key = attribute.key.as_ref();
+                        if key == b"xml:lang" || key == b"lang" {
+                            current_lang = Some(attribute.unescape_value()?.to_lowercase());
+                        }
+                    }
+                }
+                b"seg" => {
+                    segment = Some(String::new());
+                    inline_depth = 0;
+                }
+                name if segment.is_some() && TMX_INLINE_CODE.contains(&name) => inline_depth += 1,
+                _ => {}
+            },
+            Event::Text(e) => {
+                if let Some(segment) = segment.as_mut().filter(|_| inline_depth == 0) {
+                    segment.push_str(&e.unescape()?);
+                }
+            }
+            Event::CData(e) => {
+                if let Some(segment) = segment.as_mut().filter(|_| inline_depth == 0) {
+                    segment.push_str(&String::from_utf8_lossy(&e));
+                }
+            }
+            Event::End(e) => match e.name().as_ref() {
+                name if segment.is_some() && TMX_INLINE_CODE.contains(&name) => {
+                    inline_depth = inline_depth.saturating_sub(1);
+                }
+                b"seg" => {
+                    let text = segment.take().unwrap_or_default();
+                    match current_lang.as_deref() {
+                        Some(lang) if lang_matches(lang, source_lang) => source = Some(text),
+                        Some(lang) if lang_matches(lang, target_lang) => target = Some(text),
+                        _ => {}
+                    }
+                }
+                b"tu" => {
+                    if let (Some(source), Some(target)) = (source.take(), target.take()) {
+                        pairs.push((source, target));
+                    }
+                }
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+        buffer.clear();
+    }
+
+    Ok(pairs)
+}
+
+/// "ru-RU" ו-"ru" שניהם תואמים ל-"ru"
+fn lang_matches(lang: &str, expected: &str) -> bool {
+    lang == expected || lang.split(['-', '_']).next() == Some(expected)
+}
+
+fn write_tsv(path: &Path, pairs: &[SentencePair]) -> Result<(), CorpusError> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    for (source, target) in pairs {
+        writeln!(writer, "{}\t{}", source.replace('\t', " "), target.replace('\t', " "))?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn pair(source: &str, target: &str) -> SentencePair {
+        (source.to_string(), target.to_string())
+    }
+
+    #[test]
+    fn test_filter() {
+        let pipeline = CorpusPipeline::new(CorpusConfig::default());
+        let (kept, stats) = pipeline.filter(vec![
+            pair("Давление насоса 10 бар", "לחץ המשאבה 10 בר"),
+            pair("Давление  насоса 10 бар", "לחץ המשאבה 10 בר"),
+            pair("Давление насоса 12 бар", "לחץ המשאבה 10 בר"),
+            pair("Давление насоса", "Pump pressure"),
+            pair("Да", "לחץ המשאבה של המערכת ההידראולית הראשית"),
+            pair("", "לחץ"),
+        ]);
+
+        assert_eq!(kept, vec![pair("Давление насоса 10 бар", "לחץ המשאבה 10 בר")]);
+        assert_eq!(stats.read, 6);
+        assert_eq!(stats.duplicates, 1);
+        assert_eq!(stats.number_mismatch, 1);
+        assert_eq!(stats.wrong_language, 1);
+        assert_eq!(stats.length_ratio, 1);
+        assert_eq!(stats.empty, 1);
+    }
+
+    #[test]
+    fn test_number_formats_match() {
+        assert!(numbers_match("диаметр 2,5 мм", "קוטר 2.5 מ\"מ"));
+        assert!(numbers_match("расход 1 000 л/мин", "ספיקה 1,000 ליטר/דקה"));
+        assert!(numbers_match("расход 1\u{00A0}000 л/мин", "ספיקה 1000 ליטר/דקה"));
+        assert!(!numbers_match("давление 16 бар", "לחץ 10 בר"));
+    }
+
+    #[test]
+    fn test_split_is_reproducible() {
+        let pipeline = CorpusPipeline::new(CorpusConfig {
+            dev_ratio: 0.1,
+            test_ratio: 0.1,
+            ..Default::default()
+        });
+        let pairs: Vec<_> = (0..100).map(|i| pair(&format!("src {}", i), &format!("tgt {}", i))).collect();
+
+        let first = pipeline.split(pairs.clone()).unwrap();
+        let second = pipeline.split(pairs).unwrap();
+
+        assert_eq!(first.train, second.train);
+        assert_eq!(first.test, second.test);
+        assert_eq!((first.train.len(), first.dev.len(), first.test.len()), (80, 10, 10));
+    }
+
+    #[test]
+    fn test_invalid_split_ratios_are_rejected() {
+        let pairs: Vec<_> = (0..10).map(|i| pair(&format!("src {}", i), &format!("tgt {}", i))).collect();
+
+        for (dev_ratio, test_ratio) in [(0.6, 0.6), (-0.1, 0.1), (0.1, 1.5), (f64::NAN, 0.1)] {
+            let pipeline = CorpusPipeline::new(CorpusConfig { dev_ratio, test_ratio, ..Default::default() });
+            assert!(matches!(pipeline.split(pairs.clone()), Err(CorpusError::InvalidSplit { .. })));
+        }
+    }
+
+    #[test]
+    fn test_read_tmx() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("memory.tmx");
+        std::fs::write(&path, r#"<?xml version="1.0" encoding="UTF-8"?>
+<tmx version="1.4"><header srclang="ru-RU"/><body>
+<tu><tuv xml:lang="ru-RU"><seg>Клапан &amp; насос</seg></tuv><tuv xml:lang="he-IL"><seg>שסתום ומשאבה</seg></tuv></tu>
+<tu><tuv xml:lang="ru-RU"><seg>Только источник</seg></tuv></tu>
+<tu><tuv xml:lang="ru-RU"><seg><bpt i="1">&lt;b&gt;</bpt>Насос<ept i="1">&lt;/b&gt;</ept> включен<ph>&lt;br/&gt;</ph></seg></tuv><tuv xml:lang="he-IL"><seg><bpt i="1">&lt;b&gt;</bpt>המשאבה<ept i="1">&lt;/b&gt;</ept> מופעלת</seg></tuv></tu>
+</body></tmx>"#).unwrap();
+
+        let pairs = read_tmx(&path, "ru", "he").unwrap();
+        assert_eq!(pairs, vec![pair("Клапан & насос", "שסתום ומשאבה"), pair("Насос включен", "המשאבה מופעלת")]);
+    }
+
+    #[test]
+    fn test_read_tsv_counts_malformed_lines() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("corpus.tsv");
+        std::fs::write(&path, "Клапан закрыт\tהשסתום סגור\nбез перевода\n\nодин\tдва\tтри\n").unwrap();
+
+        let pipeline = CorpusPipeline::new(CorpusConfig::default());
+        let (pairs, malformed) = pipeline.read(&CorpusSource::Tsv { path: path.clone() }).unwrap();
+        assert_eq!(pairs, vec![pair("Клапан закрыт", "השסתום סגור")]);
+        assert_eq!(malformed, 2);
+
+        let manifest = pipeline.run(&[CorpusSource::Tsv { path }], temp_dir.path().join("out")).unwrap();
+        assert_eq!((manifest.filter.malformed, manifest.filter.read), (2, 1));
+    }
+
+    #[test]
+    fn test_run_writes_splits_and_manifest() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("corpus.ru");
+        let target = temp_dir.path().join("corpus.he");
+        std::fs::write(&source, "Давление насоса 10 бар\nКлапан закрыт\n").unwrap();
+        std::fs::write(&target, "לחץ המשאבה 10 בר\nהשסתום סגור\n").unwrap();
+
+        let pipeline = CorpusPipeline::new(CorpusConfig {
+            dev_ratio: 0.0,
+            test_ratio: 0.0,
+            ..Default::default()
+        });
+        let output = temp_dir.path().join("out");
+        let manifest = pipeline.run(&[CorpusSource::Moses { source, target }], &output).unwrap();
+
+        assert_eq!(manifest.train, 2);
+        assert_eq!(CorpusManifest::load(&output).unwrap().filter, manifest.filter);
+        assert_eq!(CorpusSplits::load(&output).unwrap().train.len(), 2);
+    }
+}
@@ -0,0 +1,445 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Serialize, Deserialize};
+use tokio::task::JoinHandle;
+use crate::evaluation::{Evaluator, FormalityLevel, StyleGuide};
+use crate::learning_manager::{AdvancedLearningManager, LearningEvent, LearningEventType};
+use crate::translation_models::{Domain, Formality, Style, TranslationContext, TranslationError};
+use super::TranslationBackend;
+use super::checkpoint::MODEL_FILE;
+use super::corpus::{CorpusConfig, CorpusPipeline, CorpusSplits, SentencePair};
+use super::learning::{ContinuousLearningManager, TrainingBatch};
+use super::model_storage::{ModelConfig, ModelMetrics, ModelStage, ModelStorage};
+
+/// הגדרות עבודת הכוונון מתיקוני מתרגמים
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FineTuningConfig {
+    /// שם המודל במאגר המודלים
+    pub model_name: String,
+    /// תיקיית עבודה לתמונות המשקולות של הבסיס והמועמד
+    pub work_dir: PathBuf,
+    /// כל כמה שניות להריץ את העבודה
+    pub interval_secs: u64,
+    /// מספר התיקונים המאושרים המינימלי להרצה
+    pub min_post_edits: usize,
+    /// מספר זוגות מהקורפוס הבסיסי לכל זוג מתוקן, למניעת שכחה
+    pub replay_ratio: f64,
+    /// חלק התיקונים שנשמר בצד להערכה
+    pub holdout_ratio: f64,
+    /// מספר זוגות מחלוקת ה-dev של הקורפוס הבסיסי שנבדקים לרגרסיה
+    pub base_eval_size: usize,
+    pub epochs: usize,
+    pub batch_size: usize,
+    /// שיפור BLEU מינימלי על התיקונים שנשמרו בצד כדי לרשום מועמד
+    pub min_improvement: f64,
+    /// ירידת BLEU מרבית המותרת על הקורפוס הבסיסי
+    pub max_base_regression: f64,
+    pub seed: u64,
+}
+
+impl Default for FineTuningConfig {
+    fn default() -> Self {
+        Self {
+            model_name: "ru-he".to_string(),
+            work_dir: PathBuf::from("fine_tuning"),
+            interval_secs: 24 * 60 * 60,
+            min_post_edits: 200,
+            replay_ratio: 3.0,
+            holdout_ratio: 0.1,
+            base_eval_size: 500,
+            epochs: 2,
+            batch_size: 16,
+            min_improvement: 0.005,
+            max_base_regression: 0.01,
+            seed: 42,
+        }
+    }
+}
+
+/// ציוני המודל על הנתונים שנשמרו בצד
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct HeldOutScores {
+    pub post_edit_bleu: f64,
+    pub post_edit_chrf: f64,
+    pub base_bleu: f64,
+    pub base_chrf: f64,
+}
+
+/// סיכום הרצה אחת של העבודה
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FineTuningReport {
+    pub post_edit_pairs: usize,
+    pub replay_pairs: usize,
+    pub held_out_pairs: usize,
+    pub steps: usize,
+    pub final_loss: f64,
+    pub baseline: HeldOutScores,
+    pub candidate: HeldOutScores,
+}
+
+/// תוצאת הרצה של העבודה
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum FineTuningOutcome {
+    /// אין מספיק תיקונים מאושרים
+    Skipped { approved_pairs: usize },
+    /// המודל המכוונן לא השתפר; המשקולות הוחזרו למצב הבסיס
+    Rejected(FineTuningReport),
+    /// המודל המכוונן נרשם כמועמד במאגר
+    Registered { version: String, report: FineTuningReport },
+}
+
+/// איסוף זוגות מקור-תרגום שאושרו על ידי מתרגמים
+pub struct PostEditCollector {
+    pipeline: CorpusPipeline,
+}
+
+impl PostEditCollector {
+    pub fn new(corpus_config: CorpusConfig) -> Self {
+        Self {
+            pipeline: CorpusPipeline::new(corpus_config),
+        }
+    }
+
+    /// רק תיקון מפורש הוא יעד לאימון; דירוג גבוה לתרגום המכונה היה מלמד את המודל את הפלט של עצמו
+    pub fn collect(&self, events: &[LearningEvent]) -> Vec<SentencePair> {
+        let pairs = events.iter().filter_map(|event| match &event.event_type {
+            LearningEventType::Correction { corrected, .. } => {
+                Some((event.source_text.clone(), corrected.clone()))
+            }
+            _ => None,
+        });
+
+        // אותו סינון כמו בקורפוס הבסיסי: ריקים, כפולים, יחס אורכים, שפה ומספרים
+        self.pipeline.filter(pairs).0
+    }
+}
+
+/// חלוקת התיקונים לאימון ולהערכה לפי הזרע
+pub fn split_holdout(mut pairs: Vec<SentencePair>, holdout_ratio: f64, seed: u64) -> (Vec<SentencePair>, Vec<SentencePair>) {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    pairs.shuffle(&mut rng);
+
+    let holdout = ((pairs.len() as f64 * holdout_ratio).round() as usize)
+        .max(1)
+        .min(pairs.len().saturating_sub(1));
+    let held_out = pairs.split_off(pairs.len() - holdout);
+    (pairs, held_out)
+}
+
+/// ערבוב התיקונים עם מדגם חוזר מהקורפוס הבסיסי
+pub fn mix_with_replay(post_edits: &[SentencePair], base: &[SentencePair], replay_ratio: f64, seed: u64) -> Vec<SentencePair> {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let replay = ((post_edits.len() as f64 * replay_ratio).round() as usize).min(base.len());
+
+    let mut mixed: Vec<SentencePair> = post_edits.to_vec();
+    mixed.extend(base.choose_multiple(&mut rng, replay).cloned());
+    mixed.shuffle(&mut rng);
+    mixed
+}
+
+/// המועמד נרשם רק אם השתפר על התיקונים ולא נפגע על הקורפוס הבסיסי
+pub fn improves(baseline: &HeldOutScores, candidate: &HeldOutScores, config: &FineTuningConfig) -> bool {
+    candidate.post_edit_bleu - baseline.post_edit_bleu >= config.min_improvement
+        && baseline.base_bleu - candidate.base_bleu <= config.max_base_regression
+}
+
+/// עבודה מתוזמנת שמכווננת את המודל הנוירוני על תיקוני מתרגמים
+pub struct FineTuningJob {
+    learner: ContinuousLearningManager,
+    /// המנוע שמשתמש באותן משקולות כמו ה-learner, לתרגום קבוצת ההערכה
+    backend: Arc<dyn TranslationBackend>,
+    storage: ModelStorage,
+    base_corpus: CorpusSplits,
+    model_config: ModelConfig,
+    collector: PostEditCollector,
+    config: FineTuningConfig,
+    /// תיקונים שנאספו ועוד לא נכנסו למודל רשום
+    pool: Vec<SentencePair>,
+    last_collected: SystemTime,
+    runs: u64,
+}
+
+impl FineTuningJob {
+    pub fn new(
+        learner: ContinuousLearningManager,
+        backend: Arc<dyn TranslationBackend>,
+        storage: ModelStorage,
+        base_corpus: CorpusSplits,
+        model_config: ModelConfig,
+        corpus_config: CorpusConfig,
+        config: FineTuningConfig,
+    ) -> Self {
+        Self {
+            learner,
+            backend,
+            storage,
+            base_corpus,
+            model_config,
+            collector: PostEditCollector::new(corpus_config),
+            config,
+            pool: Vec::new(),
+            last_collected: UNIX_EPOCH,
+            runs: 0,
+        }
+    }
+
+    /// הפעלת העבודה ברקע; בכל מחזור נאספים האירועים החדשים מאז המחזור הקודם
+    pub fn spawn(mut self, events: Arc<AdvancedLearningManager>) -> JoinHandle<()>
+    where
+        Self: Send + 'static,
+    {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(self.config.interval_secs.max(1)));
+            loop {
+                interval.tick().await;
+
+                let since = self.last_collected;
+                self.last_collected = SystemTime::now();
+                let new_events = events.events_since(since).await;
+
+                // האימון חוסם, ולכן רץ ב-thread של spawn_blocking; העבודה עוברת אליו וחוזרת ממנו
+                let handle = tokio::task::spawn_blocking(move || {
+                    let outcome = self.run_once(&new_events);
+                    (self, outcome)
+                });
+                let outcome = match handle.await {
+                    Ok((job, outcome)) => {
+                        self = job;
+                        outcome
+                    }
+                    Err(e) => {
+                        log::error!("עבודת הכוונון נעצרה: {}", e);
+                        return;
+                    }
+                };
+
+                match outcome {
+                    Ok(FineTuningOutcome::Registered { version, .. }) => {
+                        log::info!("מודל מכוונן נרשם כמועמד: {} {}", self.config.model_name, version);
+                    }
+                    Ok(FineTuningOutcome::Rejected(report)) => {
+                        log::info!("המודל המכוונן לא השתפר: {:?} -> {:?}", report.baseline, report.candidate);
+                    }
+                    Ok(FineTuningOutcome::Skipped { approved_pairs }) => {
+                        log::debug!("אין מספיק תיקונים מאושרים לכוונון: {}", approved_pairs);
+                    }
+                    Err(e) => log::error!("שגיאה בעבודת הכוונון: {}", e),
+                }
+            }
+        })
+    }
+
+    /// מחזור אחד: איסוף, אימון, הערכה ורישום מותנה
+    pub fn run_once(&mut self, events: &[LearningEvent]) -> Result<FineTuningOutcome, TranslationError> {
+        let mut seen: HashSet<SentencePair> = self.pool.iter().cloned().collect();
+        for pair in self.collector.collect(events) {
+            if seen.insert(pair.clone()) {
+                self.pool.push(pair);
+            }
+        }
+
+        if self.pool.len() < self.config.min_post_edits.max(2) {
+            return Ok(FineTuningOutcome::Skipped { approved_pairs: self.pool.len() });
+        }
+
+        self.runs += 1;
+        let seed = self.config.seed.wrapping_add(self.runs);
+        let (train, held_out) = split_holdout(self.pool.clone(), self.config.holdout_ratio, seed);
+        let mixed = mix_with_replay(&train, &self.base_corpus.train, self.config.replay_ratio, seed);
+        let base_eval: Vec<SentencePair> = self.base_corpus.dev
+            .iter()
+            .take(self.config.base_eval_size)
+            .cloned()
+            .collect();
+
+        // תמונת מצב של הבסיס, כדי שמועמד שנדחה לא ישאיר עקבות במשקולות
+        let baseline_dir = self.config.work_dir.join("baseline");
+        self.learner.save_checkpoint(&path_str(&baseline_dir)?)?;
+        let baseline = self.score(&held_out, &base_eval)?;
+
+        let (steps, final_loss) = self.train(&mixed)?;
+        let candidate = self.score(&held_out, &base_eval)?;
+
+        let report = FineTuningReport {
+            post_edit_pairs: train.len(),
+            replay_pairs: mixed.len() - train.len(),
+            held_out_pairs: held_out.len(),
+            steps,
+            final_loss,
+            baseline,
+            candidate,
+        };
+
+        if !improves(&baseline, &candidate, &self.config) {
+            self.learner.load_checkpoint(&path_str(&baseline_dir)?)?;
+            return Ok(FineTuningOutcome::Rejected(report));
+        }
+
+        let version = self.register(&report)?;
+        self.pool.clear();
+        Ok(FineTuningOutcome::Registered { version, report })
+    }
+
+    fn train(&mut self, pairs: &[SentencePair]) -> Result<(usize, f64), TranslationError> {
+        let mut steps = 0;
+        let mut final_loss = f64::NAN;
+
+        for _ in 0..self.config.epochs {
+            for chunk in pairs.chunks(self.config.batch_size.max(1)) {
+                let batch = TrainingBatch {
+                    samples: chunk
+                        .iter()
+                        .map(|(source, target)| (source.clone(), target.clone(), fine_tuning_context()))
+                        .collect(),
+                };
+                final_loss = self.learner.train_batch(&batch)?.loss;
+                steps += 1;
+            }
+        }
+
+        Ok((steps, final_loss))
+    }
+
+    fn score(&self, post_edits: &[SentencePair], base: &[SentencePair]) -> Result<HeldOutScores, TranslationError> {
+        let (post_edit_bleu, post_edit_chrf) = self.evaluate(post_edits)?;
+        let (base_bleu, base_chrf) = self.evaluate(base)?;
+        Ok(HeldOutScores { post_edit_bleu, post_edit_chrf, base_bleu, base_chrf })
+    }
+
+    /// BLEU ו-chrF ברמת הקורפוס של המודל הנוכחי מול התרגומים המאושרים, בסקאלה 0-1
+    fn evaluate(&self, pairs: &[SentencePair]) -> Result<(f64, f64), TranslationError> {
+        if pairs.is_empty() {
+            return Ok((0.0, 0.0));
+        }
+
+        let sources: Vec<String> = pairs.iter().map(|(source, _)| source.clone()).collect();
+        let outputs = self.backend.translate(&sources)?;
+        let references = vec![pairs.iter().map(|(_, target)| target.clone()).collect::<Vec<_>>()];
+        let evaluator = Evaluator::new(HashMap::new(), HashMap::new(), StyleGuide::new(FormalityLevel::Formal));
+
+        let scores = evaluator
+            .score_corpus(&outputs, &references)
+            .map_err(|e| TranslationError::GeneralError(e.to_string()))?;
+        Ok((scores.bleu.score / 100.0, scores.chrf / 100.0))
+    }
+
+    fn register(&self, report: &FineTuningReport) -> Result<String, TranslationError> {
+        let version = format!(
+            "ft-{}",
+            SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+        );
+        let candidate_dir = self.config.work_dir.join("candidate");
+        self.learner.save_checkpoint(&path_str(&candidate_dir)?)?;
+
+        let metrics = ModelMetrics {
+            translation_accuracy: report.candidate.post_edit_chrf,
+            bleu_score: report.candidate.post_edit_bleu,
+            final_loss: report.final_loss,
+            training_steps: self.learner.get_steps(),
+        };
+        let description = format!(
+            "כוונון על {} תיקונים מאושרים ו-{} זוגות חוזרים",
+            report.post_edit_pairs, report.replay_pairs
+        );
+
+        self.storage.save_model(
+            &self.config.model_name,
+            &description,
+            &version,
+            &candidate_dir.join(MODEL_FILE),
+            metrics.clone(),
+            self.model_config.clone(),
+        )?;
+        self.storage.promote(&self.config.model_name, &version, ModelStage::Candidate, metrics)?;
+
+        Ok(version)
+    }
+}
+
+fn fine_tuning_context() -> TranslationContext {
+    TranslationContext::new(Domain::Technical, Style::Formal, Formality::High)
+}
+
+fn path_str(path: &Path) -> Result<String, TranslationError> {
+    path.to_str()
+        .map(str::to_string)
+        .ok_or_else(|| TranslationError::LearningError(format!("נתיב לא תקין: {}", path.display())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::learning_manager::EventMetrics;
+
+    fn pairs(count: usize) -> Vec<SentencePair> {
+        (0..count).map(|i| (format!("source {}", i), format!("target {}", i))).collect()
+    }
+
+    fn event(event_type: LearningEventType, source: &str, target: &str) -> LearningEvent {
+        LearningEvent::new(
+            event_type,
+            source.to_string(),
+            target.to_string(),
+            EventMetrics::default(),
+            "technical".to_string(),
+            String::new(),
+        )
+    }
+
+    #[test]
+    fn test_collect_approved_post_edits() {
+        let collector = PostEditCollector::new(CorpusConfig::default());
+        let events = vec![
+            event(LearningEventType::Correction {
+                original: "מכונה".to_string(),
+                corrected: "המכונה פועלת כראוי".to_string(),
+                error_type: "terminology".to_string(),
+            }, "Станок работает правильно", "מכונה"),
+            event(LearningEventType::UserFeedback { rating: 5, comments: String::new() },
+                "Насос включен", "המשאבה מופעלת"),
+            event(LearningEventType::UserFeedback { rating: 2, comments: String::new() },
+                "Клапан закрыт", "השסתום פתוח"),
+        ];
+
+        let collected = collector.collect(&events);
+
+        // משוב בלי תיקון אינו יעד לאימון, גם בדירוג גבוה
+        assert_eq!(collected, vec![
+            ("Станок работает правильно".to_string(), "המכונה פועלת כראוי".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_replay_mix_is_reproducible() {
+        let post_edits = pairs(10);
+        let base: Vec<SentencePair> = (0..100).map(|i| (format!("base {}", i), format!("בסיס {}", i))).collect();
+
+        let mixed = mix_with_replay(&post_edits, &base, 3.0, 7);
+
+        assert_eq!(mixed.len(), 40);
+        assert!(post_edits.iter().all(|pair| mixed.contains(pair)));
+        assert_eq!(mixed, mix_with_replay(&post_edits, &base, 3.0, 7));
+
+        let (train, held_out) = split_holdout(post_edits, 0.2, 7);
+        assert_eq!((train.len(), held_out.len()), (8, 2));
+    }
+
+    #[test]
+    fn test_candidate_gate() {
+        let config = FineTuningConfig::default();
+        let baseline = HeldOutScores { post_edit_bleu: 0.30, base_bleu: 0.40, ..Default::default() };
+
+        let better = HeldOutScores { post_edit_bleu: 0.35, base_bleu: 0.395, ..Default::default() };
+        let forgetful = HeldOutScores { post_edit_bleu: 0.45, base_bleu: 0.30, ..Default::default() };
+        let flat = HeldOutScores { post_edit_bleu: 0.301, base_bleu: 0.40, ..Default::default() };
+
+        assert!(improves(&baseline, &better, &config));
+        assert!(!improves(&baseline, &forgetful, &config));
+        assert!(!improves(&baseline, &flat, &config));
+    }
+}
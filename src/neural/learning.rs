@@ -1,13 +1,16 @@
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tch::{nn, Tensor, Device, Kind};
 use crate::translation_models::{TranslationError, TranslationContext};
 use super::vocabulary::Vocabulary;
 use super::NeuralTranslator;
+use super::alignment::{self, WordAlignment};
+use super::checkpoint::{AdamConfig, CheckpointError, CheckpointManager, CheckpointState, StatefulAdam, TrainingRng};
 
 /// מנהל הלמידה המתמשכת
 pub struct ContinuousLearningManager {
     translator: Arc<NeuralTranslator>,
-    optimizer: nn::Optimizer,
+    optimizer: StatefulAdam,
     var_store: Arc<nn::VarStore>,
     learning_rate: f64,
     batch_size: i64,
@@ -18,6 +21,11 @@ pub struct ContinuousLearningManager {
     best_loss: f64,
     patience: i64,
     patience_counter: i64,
+    seed: u64,
+    rng: TrainingRng,
+    checkpoints: Option<CheckpointManager>,
+    checkpoint_every: i64,
+    guided_alignment_weight: f64,
 }
 
 impl ContinuousLearningManager {
@@ -25,10 +33,16 @@ impl ContinuousLearningManager {
         translator: Arc<NeuralTranslator>,
         var_store: Arc<nn::VarStore>,
         config: LearningConfig
-    ) -> Self {
-        let optimizer = nn::Adam::default().build(&var_store, config.learning_rate).unwrap();
-        
-        Self {
+    ) -> Result<Self, TranslationError> {
+        let optimizer = StatefulAdam::new(&var_store, config.learning_rate, AdamConfig::default());
+        // תיקייה שהוגדרה ואי אפשר ליצור היא שגיאה, ולא למידה בשקט בלי נקודות ביקורת
+        let checkpoints = config.checkpoint_dir
+            .as_ref()
+            .map(|dir| CheckpointManager::new(dir, config.keep_last_checkpoints))
+            .transpose()
+            .map_err(|e| TranslationError::LearningError(format!("שגיאה ביצירת תיקיית נקודות הביקורת: {}", e)))?;
+        
+        Ok(Self {
             translator,
             optimizer,
             var_store,
@@ -41,12 +55,18 @@ impl ContinuousLearningManager {
             best_loss: f64::INFINITY,
             patience: config.patience,
             patience_counter: 0,
-        }
+            seed: config.seed,
+            rng: TrainingRng::new(config.seed),
+            checkpoints,
+            checkpoint_every: config.checkpoint_every,
+            guided_alignment_weight: config.guided_alignment_weight,
+        })
     }
 
     /// עדכון המודל על סמך דוגמה חדשה
     pub fn update(&mut self, source: &str, target: &str, context: &TranslationContext) -> Result<TrainingMetrics, TranslationError> {
         self.var_store.zero_grad();
+        self.rng.seed_torch();
         
         // חישוב Loss ועדכון משקולות
         let loss = self.compute_loss(source, target)?;
@@ -66,6 +86,7 @@ impl ContinuousLearningManager {
         
         let loss_value = loss.double_value(&[]);
         self.update_early_stopping(loss_value);
+        self.checkpoint_if_due()?;
         
         Ok(TrainingMetrics {
             loss: loss_value,
@@ -73,9 +94,58 @@ impl ContinuousLearningManager {
         })
     }
 
+    /// עדכון עם guided alignment: ה-Loss כולל גם את המרחק בין ה-cross-attention
+    /// ליישור מילים חיצוני (למשל פלט fast_align), במשקל guided_alignment_weight
+    pub fn update_with_alignment(
+        &mut self,
+        source: &str,
+        target: &str,
+        reference: &[WordAlignment],
+        context: &TranslationContext,
+    ) -> Result<TrainingMetrics, TranslationError> {
+        if self.guided_alignment_weight <= 0.0 {
+            return self.update(source, target, context);
+        }
+
+        self.var_store.zero_grad();
+        self.rng.seed_torch();
+
+        let (loss, attention) = self.compute_loss_with_attention(source, target)?;
+
+        // תשומת הלב היא בין תת-מילים: כל עמדה ממופה למילה שלה, כך שיישור של מילה חל על כל תת-המילים שלה.
+        // העמדות זהות לאלה של prepare_input/prepare_target, כולל <BOS>/<EOS> שאינם שייכים לאף מילה
+        let size = attention.size();
+        let fit = |mut words: Vec<Option<usize>>, len: i64| {
+            words.resize(len as usize, None);
+            words
+        };
+        let source_ids = fit(self.translator.source_tokenizer.encode_words(source).1, size[size.len() - 1]);
+        let target_ids = fit(self.translator.target_tokenizer.encode_words(target).1, size[size.len() - 2]);
+
+        let reference = alignment::reference_attention(reference, &source_ids, &target_ids);
+        let guided = alignment::guided_alignment_loss(&attention, &reference);
+        let loss = loss + guided * self.guided_alignment_weight;
+
+        loss.backward();
+        self.var_store.clip_grad_norm(self.max_grad_norm);
+        self.update_learning_rate_with_warmup();
+        self.optimizer.step();
+        self.steps += 1;
+
+        let loss_value = loss.double_value(&[]);
+        self.update_early_stopping(loss_value);
+        self.checkpoint_if_due()?;
+
+        Ok(TrainingMetrics {
+            loss: loss_value,
+            perplexity: f64::exp(loss_value),
+        })
+    }
+
     /// אימון על אצווה של דוגמאות
     pub fn train_batch(&mut self, batch: &TrainingBatch) -> Result<TrainingMetrics, TranslationError> {
         self.var_store.zero_grad();
+        self.rng.seed_torch();
         
         let mut total_loss = 0.0;
         let mut sample_count = 0;
@@ -103,6 +173,7 @@ impl ContinuousLearningManager {
         self.steps += 1;
         
         self.update_early_stopping(avg_loss);
+        self.checkpoint_if_due()?;
         
         Ok(TrainingMetrics {
             loss: avg_loss,
@@ -110,18 +181,73 @@ impl ContinuousLearningManager {
         })
     }
 
-    /// שמירת מצב האימון
+    /// שמירת מצב האימון המלא: משקולות, מומנטי האופטימייזר, שלב ה-Warmup, עצירה מוקדמת ומצב האקראיות
     pub fn save_checkpoint(&self, path: &str) -> Result<(), TranslationError> {
-        self.var_store
-            .save(path)
+        CheckpointManager::write(Path::new(path), &self.var_store, &self.optimizer, &self.checkpoint_state())
             .map_err(|e| TranslationError::LearningError(format!("שגיאה בשמירת נקודת ביקורת: {}", e)))
     }
 
-    /// טעינת מצב אימון
-    pub fn load_checkpoint(&self, path: &str) -> Result<(), TranslationError> {
-        self.var_store
-            .load(path)
-            .map_err(|e| TranslationError::LearningError(format!("שגיאה בטעינת נקודת ביקורת: {}", e)))
+    /// טעינת מצב אימון שנשמר ב-save_checkpoint, כך שההמשך זהה לאימון שלא נקטע
+    pub fn load_checkpoint(&mut self, path: &str) -> Result<(), TranslationError> {
+        let state = CheckpointManager::restore(Path::new(path), &self.var_store, &mut self.optimizer)
+            .map_err(|e| TranslationError::LearningError(format!("שגיאה בטעינת נקודת ביקורת: {}", e)))?;
+        self.apply_checkpoint_state(state);
+        Ok(())
+    }
+
+    /// המשך מנקודת הביקורת האחרונה בתיקייה שהוגדרה ב-LearningConfig, אם יש כזו
+    pub fn resume(&mut self) -> Result<bool, TranslationError> {
+        let Some(manager) = &self.checkpoints else {
+            return Ok(false);
+        };
+
+        let state = manager
+            .restore_latest(&self.var_store, &mut self.optimizer)
+            .map_err(|e| TranslationError::LearningError(format!("שגיאה בטעינת נקודת ביקורת: {}", e)))?;
+
+        match state {
+            Some(state) => {
+                self.apply_checkpoint_state(state);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn checkpoint_if_due(&mut self) -> Result<(), TranslationError> {
+        let Some(manager) = &self.checkpoints else {
+            return Ok(());
+        };
+        if self.checkpoint_every <= 0 || self.steps % self.checkpoint_every != 0 {
+            return Ok(());
+        }
+
+        manager
+            .save(&self.var_store, &self.optimizer, &self.checkpoint_state())
+            .map_err(|e: CheckpointError| TranslationError::LearningError(format!("שגיאה בשמירת נקודת ביקורת: {}", e)))?;
+        Ok(())
+    }
+
+    fn checkpoint_state(&self) -> CheckpointState {
+        CheckpointState {
+            step: self.steps,
+            epoch: 0,
+            batch: 0,
+            learning_rate: self.learning_rate,
+            best_loss: self.best_loss.is_finite().then_some(self.best_loss),
+            patience_counter: self.patience_counter,
+            seed: self.seed,
+            rng: self.rng.clone(),
+        }
+    }
+
+    fn apply_checkpoint_state(&mut self, state: CheckpointState) {
+        self.steps = state.step;
+        self.learning_rate = state.learning_rate;
+        self.best_loss = state.best_loss.unwrap_or(f64::INFINITY);
+        self.patience_counter = state.patience_counter;
+        self.seed = state.seed;
+        self.rng = state.rng;
     }
 
     /// עדכון קצב הלמידה
@@ -146,9 +272,13 @@ impl ContinuousLearningManager {
     }
 
     fn compute_loss(&self, source: &str, target: &str) -> Result<Tensor, TranslationError> {
+        self.compute_loss_with_attention(source, target).map(|(loss, _)| loss)
+    }
+
+    fn compute_loss_with_attention(&self, source: &str, target: &str) -> Result<(Tensor, Tensor), TranslationError> {
         // המרת המשפטים לטנסורים
         let source_tensor = self.translator.prepare_input(&[source.to_string()])?;
-        let target_tensor = self.translator.prepare_input(&[target.to_string()])?;
+        let target_tensor = self.translator.prepare_target(&[target.to_string()])?;
         
         // קידוד המשפט המקורי
         let encoded = self.translator.encoder.forward(&source_tensor)?;
@@ -160,18 +290,18 @@ impl ContinuousLearningManager {
             .to_kind(Kind::Bool);
         
         // פענוח והחלת Cross-Entropy Loss
-        let decoded = self.translator.decoder.forward(&encoded, &target_tensor)?;
+        let (decoded, attention) = self.translator.decoder.forward_with_attention(&encoded, &target_tensor, true)?;
         
         // חישוב Loss
         let vocab_size = decoded.size()[2];
         let loss = decoded.view([-1, vocab_size])
             .cross_entropy_loss(
                 &target_tensor.view([-1]),
-                Some(self.translator.target_vocab.get_pad_index()),
+                Some(self.translator.target_tokenizer.pad_id()),
                 tch::Reduction::Mean,
             );
         
-        Ok(loss)
+        Ok((loss, attention))
     }
 
     fn update_learning_rate_with_warmup(&mut self) {
@@ -202,6 +332,14 @@ pub struct LearningConfig {
     pub max_grad_norm: f64,
     pub warmup_steps: i64,
     pub patience: i64,
+    pub seed: u64,
+    /// תיקיית נקודות ביקורת לשמירה תקופתית ולהמשך אוטומטי
+    pub checkpoint_dir: Option<PathBuf>,
+    /// כל כמה צעדים לשמור נקודת ביקורת (0 - ללא שמירה תקופתית)
+    pub checkpoint_every: i64,
+    pub keep_last_checkpoints: usize,
+    /// משקל ה-Loss של guided alignment ב-update_with_alignment (0 - כבוי)
+    pub guided_alignment_weight: f64,
 }
 
 impl Default for LearningConfig {
@@ -212,6 +350,11 @@ impl Default for LearningConfig {
             max_grad_norm: 1.0,
             warmup_steps: 4000,
             patience: 10,
+            seed: 42,
+            checkpoint_dir: None,
+            checkpoint_every: 0,
+            keep_last_checkpoints: 5,
+            guided_alignment_weight: 0.0,
         }
     }
 }
@@ -228,16 +371,10 @@ pub struct TrainingBatch {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
     use crate::translation_models::{Domain, Style, Formality};
 
     fn create_test_context() -> TranslationContext {
-        TranslationContext {
-            domain: Domain::Technical,
-            style: Style::Formal,
-            formality: Formality::High,
-            metadata: HashMap::new(),
-        }
+        TranslationContext::new(Domain::Technical, Style::Formal, Formality::High)
     }
 
     fn create_test_translator() -> Arc<NeuralTranslator> {
@@ -267,20 +404,32 @@ mod tests {
         let var_store = create_test_var_store();
         let config = LearningConfig::default();
         
-        let manager = ContinuousLearningManager::new(translator, var_store, config);
+        let manager = ContinuousLearningManager::new(translator, var_store, config).unwrap();
         assert_eq!(manager.learning_rate, 0.001);
         assert_eq!(manager.batch_size, 32);
         assert_eq!(manager.steps, 0);
         assert_eq!(manager.warmup_steps, 4000);
     }
 
+    #[test]
+    fn test_unusable_checkpoint_dir_is_an_error() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let config = LearningConfig {
+            checkpoint_dir: Some(file.path().join("checkpoints")),
+            ..LearningConfig::default()
+        };
+
+        let result = ContinuousLearningManager::new(create_test_translator(), create_test_var_store(), config);
+        assert!(matches!(result, Err(TranslationError::LearningError(_))));
+    }
+
     #[test]
     fn test_learning_rate_update() {
         let translator = create_test_translator();
         let var_store = create_test_var_store();
         let config = LearningConfig::default();
         
-        let mut manager = ContinuousLearningManager::new(translator, var_store, config);
+        let mut manager = ContinuousLearningManager::new(translator, var_store, config).unwrap();
         let new_rate = 0.0005;
         manager.update_learning_rate(new_rate);
         assert_eq!(manager.learning_rate, new_rate);
@@ -288,18 +437,22 @@ mod tests {
 
     #[test]
     fn test_checkpoint_save_load() {
-        use tempfile::NamedTempFile;
+        use tempfile::TempDir;
         
         let translator = create_test_translator();
         let var_store = create_test_var_store();
         let config = LearningConfig::default();
-        let manager = ContinuousLearningManager::new(translator, var_store, config);
+        let mut manager = ContinuousLearningManager::new(translator, var_store, config).unwrap();
         
-        let temp_file = NamedTempFile::new().unwrap();
-        let path = temp_file.path().to_str().unwrap();
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().to_str().unwrap();
         
+        manager.steps = 12;
         assert!(manager.save_checkpoint(path).is_ok());
+        
+        manager.steps = 0;
         assert!(manager.load_checkpoint(path).is_ok());
+        assert_eq!(manager.get_steps(), 12);
     }
 
     #[test]
@@ -307,7 +460,7 @@ mod tests {
         let translator = create_test_translator();
         let var_store = create_test_var_store();
         let config = LearningConfig::default();
-        let mut manager = ContinuousLearningManager::new(translator, var_store, config);
+        let mut manager = ContinuousLearningManager::new(translator, var_store, config).unwrap();
         
         let context = create_test_context();
         let result = manager.update("שלום", "привет", &context);
@@ -324,7 +477,7 @@ mod tests {
         let translator = create_test_translator();
         let var_store = create_test_var_store();
         let config = LearningConfig::default();
-        let mut manager = ContinuousLearningManager::new(translator, var_store, config);
+        let mut manager = ContinuousLearningManager::new(translator, var_store, config).unwrap();
         
         let context = create_test_context();
         let batch = TrainingBatch {
@@ -347,7 +500,7 @@ mod tests {
         let translator = create_test_translator();
         let var_store = create_test_var_store();
         let config = LearningConfig::default();
-        let manager = ContinuousLearningManager::new(translator, var_store, config);
+        let manager = ContinuousLearningManager::new(translator, var_store, config).unwrap();
         
         let loss = manager.compute_loss("שלום", "привет");
         assert!(loss.is_ok());
@@ -362,7 +515,7 @@ mod tests {
         let mut config = LearningConfig::default();
         config.patience = 2;
         
-        let mut manager = ContinuousLearningManager::new(translator, var_store, config);
+        let mut manager = ContinuousLearningManager::new(translator, var_store, config).unwrap();
         
         // עדכון ראשון - Loss טוב יותר
         manager.update_early_stopping(1.0);
@@ -391,7 +544,7 @@ mod tests {
         config.warmup_steps = 10;
         config.learning_rate = 0.001;
         
-        let mut manager = ContinuousLearningManager::new(translator, var_store, config);
+        let mut manager = ContinuousLearningManager::new(translator, var_store, config).unwrap();
         
         // בדיקת Warmup
         manager.steps = 5;
@@ -1,12 +1,224 @@
 use std::sync::Arc;
 use tch::{nn, Device, Tensor, Kind, IndexOp};
 use tch::nn::Module;
-use crate::translation_models::TranslationError;
-use super::vocabulary::{Vocabulary, VocabularyError};
+use crate::translation_models::{TranslationContext, TranslationError};
+use self::vocabulary::VocabularyArtifact;
 use super::attention::{MultiHeadAttention, AttentionConfig};
 use super::normalization::{EnhancedLayerNorm, TranslationNorm};
 use super::optimization::{EnhancedOptimizer, OptimizationConfig};
 use serde::{Serialize, Deserialize};
+use crate::technical_terms::TermsDatabase;
+use crate::tokenizer::Tokenizer;
+
+pub mod alignment;
+pub mod batching;
+pub mod checkpoint;
+pub mod constrained;
+pub mod corpus;
+pub mod fine_tuning;
+pub mod learning;
+pub mod model;
+pub mod model_storage;
+pub mod onnx;
+pub mod quantization;
+pub mod safetensors;
+pub mod training;
+pub mod vocabulary;
+#[cfg(test)]
+pub(crate) mod testing;
+
+pub use alignment::{AlignmentConfig, WordAlignment};
+pub use constrained::{
+    BeamConfig, ConstrainedDecoder, ConstrainedOutput, ConstraintSet,
+    LexicalConstraint, StepScorer, UnmetConstraint, UnmetReason,
+};
+
+/// מפריד בין סגמנטי ההקשר לסגמנט המתורגם בקלט של מודל מודע-הקשר
+pub const CONTEXT_SEPARATOR: &str = "<SEP>";
+
+/// מפריד בין המקור של סגמנט הקשר לתרגום שנבחר לו
+pub const CONTEXT_TARGET_SEPARATOR: &str = "<TGT>";
+
+/// ממשק משותף לכל מנועי התרגום הנוירוניים (מקומי, מקוונטט, ONNX)
+pub trait TranslationBackend: Send + Sync {
+    fn translate(&self, input: &[String]) -> Result<Vec<String>, TranslationError>;
+
+    /// תרגום יחד עם הסתברות המודל (0-1) לכל תרגום, למנועים שיודעים לחשב אותה
+    fn translate_scored(&self, input: &[String]) -> Result<Vec<(String, Option<f64>)>, TranslationError> {
+        Ok(self.translate(input)?.into_iter().map(|text| (text, None)).collect())
+    }
+
+    /// הקלט שהמודל מקבל עבור סגמנט בהקשר המסמך; מנוע שלא אומן עם הקשר מקבל את הסגמנט לבדו
+    fn model_input(&self, input: &str, context: &TranslationContext) -> String {
+        let _ = context;
+        input.to_string()
+    }
+
+    /// תרגום סגמנט בהקשר המסמך
+    fn translate_in_context(&self, input: &str, context: &TranslationContext) -> Result<(String, Option<f64>), TranslationError> {
+        self.translate_scored(&[self.model_input(input, context)])?
+            .pop()
+            .ok_or_else(|| TranslationError::ModelError("המנוע לא החזיר תרגום".to_string()))
+    }
+
+    /// תרגום בהקשר יחד עם יישור מילים לסגמנט הנוכחי; מנוע שאינו חושף את משקולות ה-attention מחזיר יישור ריק
+    fn translate_aligned(&self, input: &str, context: &TranslationContext) -> Result<(AlignedTranslation, Option<f64>), TranslationError> {
+        let (text, probability) = self.translate_in_context(input, context)?;
+        Ok((AlignedTranslation { text, alignments: Vec::new() }, probability))
+    }
+
+    /// תרגום בהקשר עם אכיפת המונחים המאושרים; מנוע בלי מפענח מאולץ מתרגם כרגיל
+    fn translate_constrained(
+        &self,
+        input: &str,
+        context: &TranslationContext,
+        terms: &TermsDatabase,
+    ) -> Result<(AlignedTranslation, Option<f64>), TranslationError> {
+        let _ = terms;
+        self.translate_aligned(input, context)
+    }
+
+    /// תרגום אצווה של סגמנטים ממתזמן האצוות. סגמנט עם מאגר מונחים עובר דרך translate_constrained,
+    /// והשאר רצים יחד דרך translate_scored; מנוע שמחזיר יישור מילים מחליף את ברירת המחדל
+    fn translate_batch(&self, segments: &[BatchSegment]) -> Result<Vec<(AlignedTranslation, Option<f64>)>, TranslationError> {
+        let plain: Vec<String> = segments
+            .iter()
+            .filter(|segment| segment.terms.is_none())
+            .map(|segment| self.model_input(&segment.text, &segment.context))
+            .collect();
+        let mut plain = self.translate_scored(&plain)?.into_iter();
+
+        segments
+            .iter()
+            .map(|segment| match &segment.terms {
+                Some(terms) => self.translate_constrained(&segment.text, &segment.context, terms),
+                None => plain
+                    .next()
+                    .map(|(text, probability)| (AlignedTranslation { text, alignments: Vec::new() }, probability))
+                    .ok_or_else(|| TranslationError::ModelError("המנוע לא החזיר תרגום לכל סגמנט באצווה".to_string())),
+            })
+            .collect()
+    }
+}
+
+/// סגמנט באצווה: הטקסט, ההקשר שלו במסמך ומאגר המונחים לאכיפה, אם יש
+#[derive(Clone)]
+pub struct BatchSegment {
+    pub text: String,
+    pub context: TranslationContext,
+    pub terms: Option<Arc<TermsDatabase>>,
+}
+
+/// קלט מודע-הקשר בשיטת השרשור: כל סגמנט קודם כמקור, <TGT> והתרגום שנבחר לו, מופרדים ב-<SEP>,
+/// ואחריהם הסגמנט הנוכחי. המודל מחזיר רק את תרגום הסגמנט האחרון
+pub fn context_input(input: &str, context: &TranslationContext) -> String {
+    context.previous_segments
+        .iter()
+        .map(|segment| format!("{} {} {}", segment.source, CONTEXT_TARGET_SEPARATOR, segment.target))
+        .chain(std::iter::once(input.to_string()))
+        .collect::<Vec<_>>()
+        .join(&format!(" {} ", CONTEXT_SEPARATOR))
+}
+
+/// רק מודל שאומן עם שני המפרידים כטוקנים שמורים יודע להשתמש בהקשר
+fn is_context_aware(tokenizer: &Tokenizer) -> bool {
+    let reserved = &tokenizer.special_tokens().reserved;
+    [CONTEXT_SEPARATOR, CONTEXT_TARGET_SEPARATOR]
+        .iter()
+        .all(|separator| reserved.iter().any(|token| token == separator))
+}
+
+/// הטוקן המוביל בצעד פענוח חמדני ולוג-ההסתברות שלו לפי softmax על ה-logits
+pub(crate) fn greedy_token(logits: &[f32]) -> (usize, f64) {
+    let (best, max) = logits
+        .iter()
+        .enumerate()
+        .fold((0, f32::NEG_INFINITY), |best, (i, &v)| if v >= best.1 { (i, v) } else { best });
+    let log_sum: f64 = logits.iter().map(|&v| ((v - max) as f64).exp()).sum::<f64>().ln();
+    (best, -log_sum)
+}
+
+/// הסתברות הרצף כממוצע גיאומטרי של הסתברויות הטוקנים, כדי שתרגום ארוך לא ייענש על אורכו
+pub(crate) fn sequence_probability(log_prob: f64, tokens: usize) -> Option<f64> {
+    (tokens > 0).then(|| (log_prob / tokens as f64).exp())
+}
+
+impl TranslationBackend for EnhancedNeuralTranslator {
+    fn translate(&self, input: &[String]) -> Result<Vec<String>, TranslationError> {
+        EnhancedNeuralTranslator::translate(self, input)
+    }
+
+    fn model_input(&self, input: &str, context: &TranslationContext) -> String {
+        if is_context_aware(&self.source_tokenizer) {
+            context_input(input, context)
+        } else {
+            input.to_string()
+        }
+    }
+
+    fn translate_aligned(&self, input: &str, context: &TranslationContext) -> Result<(AlignedTranslation, Option<f64>), TranslationError> {
+        if !is_context_aware(&self.source_tokenizer) {
+            return Ok((self.translate_with_alignment(input, &AlignmentConfig::default())?, None));
+        }
+
+        // היישור מחושב על הקלט המשורשר; נשארות רק מילות הסגמנט הנוכחי, באינדקסים שלו
+        let full_input = context_input(input, context);
+        let offset = full_input.split_whitespace().count() - input.split_whitespace().count();
+        let mut aligned = self.translate_with_alignment(&full_input, &AlignmentConfig::default())?;
+        aligned.alignments = aligned.alignments
+            .into_iter()
+            .filter(|alignment| alignment.source >= offset)
+            .map(|alignment| WordAlignment { source: alignment.source - offset, ..alignment })
+            .collect();
+        Ok((aligned, None))
+    }
+
+    fn translate_constrained(
+        &self,
+        input: &str,
+        context: &TranslationContext,
+        terms: &TermsDatabase,
+    ) -> Result<(AlignedTranslation, Option<f64>), TranslationError> {
+        // בלי מונחים בסגמנט אין מה לאכוף, ונשמר היישור של הפענוח הרגיל
+        if terms.find_russian_term_forms(input).is_empty() {
+            return self.translate_aligned(input, context);
+        }
+
+        let output = self.decode_with_terms(&self.model_input(input, context), input, terms, BeamConfig::default())?;
+        for unmet in &output.unmet_constraints {
+            log::warn!("המונח \"{}\" לא נאכף בתרגום: {:?}", unmet.target_term, unmet.reason);
+        }
+        // הציון הוא לוג-הסתברות ממוצעת לטוקן, כלומר ממוצע גיאומטרי של ההסתברויות
+        Ok((AlignedTranslation { text: output.text, alignments: Vec::new() }, Some(output.score.exp())))
+    }
+
+    /// היישור נלקח ממשקולות ה-attention של כל סגמנט בנפרד, ולכן האצווה רצה סגמנט אחר סגמנט
+    fn translate_batch(&self, segments: &[BatchSegment]) -> Result<Vec<(AlignedTranslation, Option<f64>)>, TranslationError> {
+        segments
+            .iter()
+            .map(|segment| match &segment.terms {
+                Some(terms) => self.translate_constrained(&segment.text, &segment.context, terms),
+                None => self.translate_aligned(&segment.text, &segment.context),
+            })
+            .collect()
+    }
+}
+
+impl TranslationBackend for quantization::QuantizedTranslator {
+    fn translate(&self, input: &[String]) -> Result<Vec<String>, TranslationError> {
+        input
+            .iter()
+            .map(|text| self.translate_str(text).map_err(|e| TranslationError::ModelError(e.to_string())))
+            .collect()
+    }
+
+    fn translate_scored(&self, input: &[String]) -> Result<Vec<(String, Option<f64>)>, TranslationError> {
+        input
+            .iter()
+            .map(|text| self.translate_scored_str(text).map(|(text, probability)| (text, Some(probability))))
+            .collect()
+    }
+}
 
 /// מודל נוירוני משופר לתרגום
 pub struct EnhancedNeuralTranslator {
@@ -15,8 +227,8 @@ pub struct EnhancedNeuralTranslator {
     embedding: EnhancedEmbedding,
     optimizer: Option<EnhancedOptimizer>,
     device: Device,
-    source_vocab: Arc<Vocabulary>,
-    target_vocab: Arc<Vocabulary>,
+    source_tokenizer: Arc<Tokenizer>,
+    target_tokenizer: Arc<Tokenizer>,
 }
 
 /// שכבת קידוד משופרת
@@ -49,11 +261,28 @@ struct EnhancedEmbedding {
 }
 
 impl EnhancedNeuralTranslator {
+    /// המודל מקודד ומפוענח דרך הטוקנייזר של קובץ אוצר המילים, עם אותם מזהים ומיזוגים כמו באימון
     pub fn new(
         config: TranslatorConfig,
-        source_vocab: Arc<Vocabulary>,
-        target_vocab: Arc<Vocabulary>,
+        source_vocab: &VocabularyArtifact,
+        target_vocab: &VocabularyArtifact,
     ) -> Result<Self, TranslationError> {
+        let source_tokenizer = source_vocab.to_tokenizer()
+            .map_err(|e| TranslationError::VocabularyError(e.to_string()))?;
+        let target_tokenizer = target_vocab.to_tokenizer()
+            .map_err(|e| TranslationError::VocabularyError(e.to_string()))?;
+        for (side, tokenizer, size) in [
+            ("המקור", &source_tokenizer, config.source_vocab_size),
+            ("היעד", &target_tokenizer, config.target_vocab_size),
+        ] {
+            if tokenizer.vocab_size() as i64 != size {
+                return Err(TranslationError::VocabularyError(format!(
+                    "אוצר המילים של {} מכיל {} טוקנים, אך המודל מוגדר ל-{}",
+                    side, tokenizer.vocab_size(), size
+                )));
+            }
+        }
+
         let device = Device::cuda_if_available();
         let vs = nn::VarStore::new(device);
         
@@ -80,8 +309,8 @@ impl EnhancedNeuralTranslator {
             embedding: Arc::try_unwrap(embedding).unwrap_or_else(|arc| (*arc).clone()),
             optimizer,
             device,
-            source_vocab,
-            target_vocab,
+            source_tokenizer: Arc::new(source_tokenizer),
+            target_tokenizer: Arc::new(target_tokenizer),
         })
     }
 
@@ -107,17 +336,26 @@ impl EnhancedNeuralTranslator {
     }
 
     pub fn translate(&self, input: &[String]) -> Result<Vec<String>, TranslationError> {
-        // המרת הקלט לטנסורים
+        if input.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // המרת הקלט לתת-מילים וקידוד
         let input_tensor = self.prepare_input(input)?;
-        
-        // קידוד
-        let encoded = self.encoder.forward(&input_tensor)?;
-        
-        // חצירת טנסור התחלתי לפענוח
-        let batch_size = input_tensor.size()[0];
+        let encoded = self.encoder.forward(&input_tensor, false)?;
+
+        // פענוח והמרה חזרה לטקסט דרך הטוקנייזר
+        Ok(self.decode_greedy(&encoded, input.len() as i64)?
+            .iter()
+            .map(|ids| self.target_tokenizer.detokenize(ids).trim().to_string())
+            .collect())
+    }
+
+    /// פענוח חמדני של אצווה מקודדת; לכל שורה מזהי תת-המילים שלפני <EOS>
+    fn decode_greedy(&self, encoded: &Tensor, batch_size: i64) -> Result<Vec<Vec<i64>>, TranslationError> {
         let mut decoder_input = Tensor::full(
             &[batch_size, 1],
-            self.target_vocab.get_bos_index() as i64,
+            self.target_tokenizer.bos_id(),
             (Kind::Int64, self.device)
         );
         
@@ -127,82 +365,182 @@ impl EnhancedNeuralTranslator {
         // פענוח אוטורגרסיבי
         for _ in 0..max_length {
             // פענוח צעד אחד
-            let step_output = self.decoder.forward(&encoded, &decoder_input)?;
+            let step_output = self.decoder.forward(encoded, &decoder_input, false)?;
             
-            // בחירת המילה הבאה
-            let next_words = step_output
+            // בחירת תת-המילה הבאה
+            let next_tokens = step_output
                 .i((.., -1, ..))
                 .argmax(-1, false)
                 .to_kind(Kind::Int64);
             
             // הוספה לפלט
-            outputs.push(next_words.copy());
+            outputs.push(next_tokens.copy());
             
             // בדיקה אם הגענו לסוף המשפט
-            let is_eos = next_words.eq(self.target_vocab.get_eos_index());
+            let is_eos = next_tokens.eq(self.target_tokenizer.eos_id());
             if is_eos.all().totype(Kind::Bool).into() {
                 break;
             }
             
             // עדכון הקלט לצעד הבא
-            decoder_input = Tensor::cat(&[decoder_input, next_words.view([-1, 1])], 1);
+            decoder_input = Tensor::cat(&[decoder_input, next_tokens.view([-1, 1])], 1);
         }
         
         // שילוב כל הצעדים לטנסור אחד
         let output_tensor = Tensor::stack(&outputs, 1);
-        
-        // המרה חזרה למילים
-        self.tensor_to_text(&output_tensor)
+        self.tensor_to_ids(&output_tensor)
     }
 
-    fn prepare_input(&self, input: &[String]) -> Result<Tensor, TranslationError> {
-        let batch_size = input.len() as i64;
-        let max_length = input.iter().map(|s| s.len()).max().unwrap() as i64;
-        
-        // יצירת טנסור אפסים
-        let mut tensor = Tensor::zeros(&[batch_size, max_length], (Kind::Int64, self.device));
-        
-        // המרת המילים למספרים
-        for (i, sentence) in input.iter().enumerate() {
-            for (j, word) in sentence.split_whitespace().enumerate() {
-                let word_idx = match self.source_vocab.get_index(word) {
-                    Ok(idx) => idx,
-                    Err(VocabularyError::WordNotFound(_)) => self.source_vocab.get_unk_index(),
-                    Err(e) => return Err(TranslationError::VocabularyError(e.to_string())),
-                };
-                tensor.i((i as i64, j as i64)).copy_(&Tensor::from(word_idx));
+    /// תרגום סגמנט עם אכיפת המונחים המאושרים ממאגר המונחים
+    pub fn translate_with_terms(
+        &self,
+        input: &str,
+        terms: &TermsDatabase,
+        config: BeamConfig,
+    ) -> Result<ConstrainedTranslation, TranslationError> {
+        self.decode_with_terms(input, input, terms, config)
+    }
+
+    /// פענוח מאולץ של קלט המודל (שעשוי לכלול הקשר), עם אילוצים מהסגמנט הנוכחי בלבד
+    fn decode_with_terms(
+        &self,
+        model_input: &str,
+        segment: &str,
+        terms: &TermsDatabase,
+        config: BeamConfig,
+    ) -> Result<ConstrainedTranslation, TranslationError> {
+        let input_tensor = self.prepare_input(&[model_input.to_string()])?;
+        let encoded = self.encoder.forward(&input_tensor, false)?;
+        let constraints = ConstraintSet::from_terms_with(terms, segment, |surface| self.term_encodings(surface));
+
+        let scorer = EncodedSource { translator: self, encoded };
+        let output = ConstrainedDecoder::new(config).decode(
+            &scorer,
+            &constraints,
+            self.target_tokenizer.bos_id(),
+            self.target_tokenizer.eos_id(),
+        )?;
+
+        Ok(ConstrainedTranslation {
+            text: self.target_tokenizer.detokenize(&output.tokens).trim().to_string(),
+            score: output.score,
+            unmet_constraints: output.unmet,
+        })
+    }
+
+    /// רצפי תת-המילים של צורת משטח של מונח: בתחילת התרגום ואחרי רווח. צורה שמתפרקת לטוקן לא-ידוע אינה ניתנת לאכיפה
+    fn term_encodings(&self, surface: &str) -> Vec<Vec<i64>> {
+        let unk = self.target_tokenizer.unk_id();
+        let mut encodings: Vec<Vec<i64>> = Vec::new();
+        for text in [surface.to_string(), format!(" {}", surface)] {
+            let ids = self.target_tokenizer.tokenize(&text);
+            let pieces = ids[1..ids.len() - 1].to_vec();
+            if !pieces.is_empty() && !pieces.contains(&unk) && !encodings.contains(&pieces) {
+                encodings.push(pieces);
             }
         }
-        
-        Ok(tensor)
+        encodings
     }
 
-    fn tensor_to_text(&self, tensor: &Tensor) -> Result<Vec<String>, TranslationError> {
-        let batch_size = tensor.size()[0];
-        let mut result = Vec::with_capacity(batch_size as usize);
-        
-        for i in 0..batch_size {
-            let mut sentence = Vec::new();
-            let sequence = tensor.i(i);
-            
-            for j in 0..sequence.size()[0] {
-                let word_idx = sequence.i(j).int64_value(&[]) as i64;
-                let word = match self.target_vocab.get_word(word_idx) {
-                    Ok(w) => w,
-                    Err(e) => return Err(TranslationError::VocabularyError(e.to_string())),
-                };
-                if word == "<EOS>" {
-                    break;
-                }
-                if word != "<PAD>" && word != "<BOS>" {
-                    sentence.push(word);
-                }
-            }
-            
-            result.push(sentence.join(" "));
+    /// תרגום סגמנט יחד עם יישור מילים מקור-תרגום שמחושב ממשקולות ה-cross-attention
+    pub fn translate_with_alignment(&self, input: &str, config: &AlignmentConfig) -> Result<AlignedTranslation, TranslationError> {
+        let (source_ids, source_words) = self.source_tokenizer.encode_words(input);
+        let input_tensor = self.batch_tensor(vec![source_ids], self.source_tokenizer.pad_id());
+        let encoded = self.encoder.forward(&input_tensor, false)?;
+        let target_ids = self.decode_greedy(&encoded, 1)?.pop().unwrap_or_default();
+        let text = self.target_tokenizer.detokenize(&target_ids).trim().to_string();
+        if source_words.iter().all(Option::is_none) || target_ids.is_empty() {
+            return Ok(AlignedTranslation { text, alignments: Vec::new() });
         }
-        
-        Ok(result)
+
+        // הרצה חוזרת של המפענח על התרגום המלא (teacher forcing) לקבלת המשקולות
+        let mut decoder_ids = vec![self.target_tokenizer.bos_id()];
+        decoder_ids.extend_from_slice(&target_ids);
+        let decoder_input = Tensor::of_slice(&decoder_ids).view([1, -1]).to_device(self.device);
+        let (_, weights) = self.decoder.forward_with_attention(&encoded, &decoder_input, false)?;
+
+        // המשקולות הן בין תת-מילים; תת-המילה בעמדה i נחזתה מהקלט שבעמדה i, ולכן <BOS> מיושר ליעד הראשון.
+        // hard_alignments ממזג את תת-המילים של כל מילה משני הצדדים
+        let matrix = alignment::attention_matrix(&weights)?;
+        let target_words = self.target_tokenizer.word_ids(&target_ids);
+
+        Ok(AlignedTranslation {
+            text,
+            alignments: alignment::hard_alignments(&matrix, &source_words, &target_words, config),
+        })
+    }
+
+    /// קידוד אצוות מקור לתת-מילים דרך הטוקנייזר, מרופדות ב-<PAD>
+    fn prepare_input(&self, input: &[String]) -> Result<Tensor, TranslationError> {
+        let sequences = input.iter().map(|text| self.source_tokenizer.encode_words(text).0).collect();
+        Ok(self.batch_tensor(sequences, self.source_tokenizer.pad_id()))
+    }
+
+    /// קידוד אצוות יעד לתת-מילים, לאימון עם teacher forcing
+    fn prepare_target(&self, target: &[String]) -> Result<Tensor, TranslationError> {
+        let sequences = target.iter().map(|text| self.target_tokenizer.encode_words(text).0).collect();
+        Ok(self.batch_tensor(sequences, self.target_tokenizer.pad_id()))
+    }
+
+    fn batch_tensor(&self, sequences: Vec<Vec<i64>>, pad: i64) -> Tensor {
+        let max_length = sequences.iter().map(Vec::len).max().unwrap_or(0);
+        let flat: Vec<i64> = sequences
+            .iter()
+            .flat_map(|ids| ids.iter().copied().chain(std::iter::repeat(pad).take(max_length - ids.len())))
+            .collect();
+
+        Tensor::of_slice(&flat)
+            .view([sequences.len() as i64, max_length as i64])
+            .to_device(self.device)
+    }
+
+    fn tensor_to_ids(&self, tensor: &Tensor) -> Result<Vec<Vec<i64>>, TranslationError> {
+        let rows = Vec::<Vec<i64>>::try_from(tensor).map_err(|e| TranslationError::ModelError(e.to_string()))?;
+        let skipped = [self.target_tokenizer.pad_id(), self.target_tokenizer.bos_id()];
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .take_while(|&id| id != self.target_tokenizer.eos_id())
+                    .filter(|id| !skipped.contains(id))
+                    .collect()
+            })
+            .collect())
+    }
+}
+
+/// תרגום עם יישור מילים בין המקור לתרגום
+#[derive(Debug, Clone)]
+pub struct AlignedTranslation {
+    pub text: String,
+    pub alignments: Vec<WordAlignment>,
+}
+
+/// תרגום שעבר פענוח מאולץ מונחים
+#[derive(Debug, Clone)]
+pub struct ConstrainedTranslation {
+    pub text: String,
+    pub score: f64,
+    /// מונחים מאושרים שלא שולבו בתרגום
+    pub unmet_constraints: Vec<UnmetConstraint>,
+}
+
+/// קידוד מקור מוכן לפענוח צעד-אחר-צעד
+struct EncodedSource<'a> {
+    translator: &'a EnhancedNeuralTranslator,
+    encoded: Tensor,
+}
+
+impl StepScorer for EncodedSource<'_> {
+    fn next_log_probs(&self, prefix: &[i64]) -> Result<Vec<f32>, TranslationError> {
+        let decoder_input = Tensor::of_slice(prefix)
+            .view([1, -1])
+            .to_device(self.translator.device);
+        let logits = self.translator.decoder.forward(&self.encoded, &decoder_input, false)?;
+        let log_probs = logits.i((0, -1)).log_softmax(-1, Kind::Float);
+
+        Vec::<f32>::try_from(&log_probs).map_err(|e| TranslationError::ModelError(e.to_string()))
     }
 }
 
@@ -272,6 +610,11 @@ impl EnhancedDecoder {
     }
 
     fn forward(&self, encoded: &Tensor, decoder_input: &Tensor, training: bool) -> Result<Tensor, TranslationError> {
+        self.forward_with_attention(encoded, decoder_input, training).map(|(logits, _)| logits)
+    }
+
+    /// פענוח שמחזיר גם את משקולות ה-cross-attention בצורה [batch, heads, tgt_len, src_len]
+    fn forward_with_attention(&self, encoded: &Tensor, decoder_input: &Tensor, training: bool) -> Result<(Tensor, Tensor), TranslationError> {
         // העברת הקלט דרך שכבת ה-Embedding
         let embedded = self.embedding.decoder_embedding.forward(decoder_input);
         
@@ -285,7 +628,7 @@ impl EnhancedDecoder {
         let self_attended = self.self_attention.forward(&output, &output, &output, None)?;
         
         // תשומת לב צולבת עם הקידוד
-        let cross_attended = self.cross_attention.forward(&self_attended, encoded, encoded, None)?;
+        let (cross_attended, cross_weights) = self.cross_attention.forward_with_weights(&self_attended, encoded, encoded, None)?;
         
         // החלת נורמליזציה
         let normalized = self.norm.forward(&cross_attended);
@@ -293,7 +636,7 @@ impl EnhancedDecoder {
         // העברה דרך שכבת הפלט
         let logits = self.output_layer.forward(&normalized);
         
-        Ok(logits)
+        Ok((logits, cross_weights))
     }
 }
 
@@ -415,21 +758,20 @@ impl Module for Attention {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::sync::Arc;
-
-    fn create_test_vocab() -> Arc<Vocabulary> {
-        let mut vocab = Vocabulary::new();
-        vocab.add_word("שלום");
-        vocab.add_word("עולם");
-        vocab.add_word("אני");
-        vocab.add_word("אוהב");
-        vocab.add_word("לתכנת");
-        vocab.add_word("привет");
-        vocab.add_word("мир");
-        vocab.add_word("я");
-        vocab.add_word("люблю");
-        vocab.add_word("программировать");
-        Arc::new(vocab)
+    use crate::tokenizer::{SpecialTokens, TokenizerConfig};
+    use crate::translation_models::{Domain, Formality, Style};
+
+    fn create_test_vocab() -> VocabularyArtifact {
+        let config = TokenizerConfig { vocab_size: 400, ..TokenizerConfig::default() };
+        let mut tokenizer = Tokenizer::with_config(config, SpecialTokens::default());
+        tokenizer.train(
+            vec![
+                "שלום עולם אני אוהב לתכנת".to_string(),
+                "привет мир я люблю программировать".to_string(),
+            ].repeat(10),
+            2,
+        );
+        VocabularyArtifact::from_tokenizer(&tokenizer, "test").unwrap()
     }
 
     fn create_test_config(vocab_size: i64) -> TranslatorConfig {
@@ -448,14 +790,13 @@ mod tests {
 
     #[test]
     fn test_neural_translator_creation() {
-        let source_vocab = create_test_vocab();
-        let target_vocab = create_test_vocab();
-        let config = create_test_config(source_vocab.size() as i64);
+        let vocab = create_test_vocab();
+        let config = create_test_config(vocab.tokens.len() as i64);
 
         let translator = EnhancedNeuralTranslator::new(
             config,
-            source_vocab.clone(),
-            target_vocab.clone(),
+            &vocab,
+            &vocab,
         );
 
         assert!(translator.is_ok());
@@ -463,14 +804,13 @@ mod tests {
 
     #[test]
     fn test_translation() {
-        let source_vocab = create_test_vocab();
-        let target_vocab = create_test_vocab();
-        let config = create_test_config(source_vocab.size() as i64);
+        let vocab = create_test_vocab();
+        let config = create_test_config(vocab.tokens.len() as i64);
 
         let translator = EnhancedNeuralTranslator::new(
             config,
-            source_vocab.clone(),
-            target_vocab.clone(),
+            &vocab,
+            &vocab,
         ).unwrap();
 
         let input = vec!["שלום עולם".to_string()];
@@ -480,14 +820,13 @@ mod tests {
 
     #[test]
     fn test_unknown_word_handling() {
-        let source_vocab = create_test_vocab();
-        let target_vocab = create_test_vocab();
-        let config = create_test_config(source_vocab.size() as i64);
+        let vocab = create_test_vocab();
+        let config = create_test_config(vocab.tokens.len() as i64);
 
         let translator = EnhancedNeuralTranslator::new(
             config,
-            source_vocab.clone(),
-            target_vocab.clone(),
+            &vocab,
+            &vocab,
         ).unwrap();
 
         let input = vec!["מילה_לא_קיימת".to_string()];
@@ -497,14 +836,13 @@ mod tests {
 
     #[test]
     fn test_batch_translation() {
-        let source_vocab = create_test_vocab();
-        let target_vocab = create_test_vocab();
-        let config = create_test_config(source_vocab.size() as i64);
+        let vocab = create_test_vocab();
+        let config = create_test_config(vocab.tokens.len() as i64);
 
         let translator = EnhancedNeuralTranslator::new(
             config,
-            source_vocab.clone(),
-            target_vocab.clone(),
+            &vocab,
+            &vocab,
         ).unwrap();
 
         let input = vec![
@@ -518,14 +856,13 @@ mod tests {
 
     #[test]
     fn test_long_sequence_translation() {
-        let source_vocab = create_test_vocab();
-        let target_vocab = create_test_vocab();
-        let config = create_test_config(source_vocab.size() as i64);
+        let vocab = create_test_vocab();
+        let config = create_test_config(vocab.tokens.len() as i64);
 
         let translator = EnhancedNeuralTranslator::new(
             config,
-            source_vocab.clone(),
-            target_vocab.clone(),
+            &vocab,
+            &vocab,
         ).unwrap();
 
         let input = vec!["אני אוהב לתכנת בשפת ראסט".to_string()];
@@ -535,14 +872,13 @@ mod tests {
 
     #[test]
     fn test_empty_input() {
-        let source_vocab = create_test_vocab();
-        let target_vocab = create_test_vocab();
-        let config = create_test_config(source_vocab.size() as i64);
+        let vocab = create_test_vocab();
+        let config = create_test_config(vocab.tokens.len() as i64);
 
         let translator = EnhancedNeuralTranslator::new(
             config,
-            source_vocab.clone(),
-            target_vocab.clone(),
+            &vocab,
+            &vocab,
         ).unwrap();
 
         let input = vec!["".to_string()];
@@ -552,14 +888,13 @@ mod tests {
 
     #[test]
     fn test_special_tokens_handling() {
-        let source_vocab = create_test_vocab();
-        let target_vocab = create_test_vocab();
-        let config = create_test_config(source_vocab.size() as i64);
+        let vocab = create_test_vocab();
+        let config = create_test_config(vocab.tokens.len() as i64);
 
         let translator = EnhancedNeuralTranslator::new(
             config,
-            source_vocab.clone(),
-            target_vocab.clone(),
+            &vocab,
+            &vocab,
         ).unwrap();
 
         let input = vec!["<PAD> שלום <UNK>".to_string()];
@@ -569,14 +904,13 @@ mod tests {
 
     #[test]
     fn test_encoder_output_shape() {
-        let source_vocab = create_test_vocab();
-        let target_vocab = create_test_vocab();
-        let config = create_test_config(source_vocab.size() as i64);
+        let vocab = create_test_vocab();
+        let config = create_test_config(vocab.tokens.len() as i64);
 
         let translator = EnhancedNeuralTranslator::new(
             config,
-            source_vocab.clone(),
-            target_vocab.clone(),
+            &vocab,
+            &vocab,
         ).unwrap();
 
         let input = vec!["שלום עולם".to_string()];
@@ -589,14 +923,13 @@ mod tests {
 
     #[test]
     fn test_decoder_output_shape() {
-        let source_vocab = create_test_vocab();
-        let target_vocab = create_test_vocab();
-        let config = create_test_config(source_vocab.size() as i64);
+        let vocab = create_test_vocab();
+        let config = create_test_config(vocab.tokens.len() as i64);
 
         let translator = EnhancedNeuralTranslator::new(
             config,
-            source_vocab.clone(),
-            target_vocab.clone(),
+            &vocab,
+            &vocab,
         ).unwrap();
 
         let batch_size = 1;
@@ -611,4 +944,39 @@ mod tests {
         assert_eq!(output.size()[0], batch_size);
         assert_eq!(output.size()[2] as i64, config.target_vocab_size);
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_input_is_encoded_with_the_vocabulary_tokenizer() {
+        let vocab = create_test_vocab();
+        let config = create_test_config(vocab.tokens.len() as i64);
+        let translator = EnhancedNeuralTranslator::new(config, &vocab, &vocab).unwrap();
+
+        let input_tensor = translator.prepare_input(&["שלום עולם".to_string()]).unwrap();
+        let ids = Vec::<Vec<i64>>::try_from(&input_tensor).unwrap();
+
+        assert_eq!(ids[0], vocab.to_tokenizer().unwrap().tokenize("שלום עולם"));
+    }
+
+    #[test]
+    fn test_vocabulary_size_must_match_config() {
+        let vocab = create_test_vocab();
+        let config = create_test_config(vocab.tokens.len() as i64 + 1);
+
+        assert!(matches!(
+            EnhancedNeuralTranslator::new(config, &vocab, &vocab),
+            Err(TranslationError::VocabularyError(_))
+        ));
+    }
+
+    #[test]
+    fn test_context_input_includes_previous_targets() {
+        let mut context = TranslationContext::new(Domain::Technical, Style::Technical, Formality::High);
+        assert_eq!(context_input("Открыть насос.", &context), "Открыть насос.");
+
+        context.push_segment("Закрыть клапан.", "לסגור את המגוף.", 2);
+        assert_eq!(
+            context_input("Открыть насос.", &context),
+            "Закрыть клапан. <TGT> לסגור את המגוף. <SEP> Открыть насос."
+        );
+    }
+}
@@ -1,6 +1,8 @@
-use tch::{nn, Device, Tensor};
+use tch::{nn, Device, Tensor, Kind};
+use tch::nn::Module;
 use serde::{Serialize, Deserialize};
 use crate::tokenizer::Tokenizer;
+use super::quantization::{QuantizedTransformer, QuantizedTranslator};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TranslationInput {
@@ -38,7 +40,11 @@ pub struct TranslationMetadata {
     pub processing_time: f32,
 }
 
+/// מספר השכבות בכל אחד מהמקודד והמפענח
+pub const NUM_LAYERS: usize = 3;
+
 pub struct EnhancedTransformer {
+    var_store: nn::VarStore,
     encoder: nn::Sequential,
     decoder: nn::Sequential,
     embedding: nn::Embedding,
@@ -54,21 +60,10 @@ impl EnhancedTransformer {
         let vocab_size = 50000;
         let d_model = 512;
         let vs = nn::VarStore::new(device);
-        let root = vs.root();
-        
-        let embedding = nn::embedding(&root, vocab_size, d_model, Default::default());
-        
-        let encoder = nn::seq()
-            .add(Self::encoder_layer(&root, d_model))
-            .add(Self::encoder_layer(&root, d_model))
-            .add(Self::encoder_layer(&root, d_model));
-            
-        let decoder = nn::seq()
-            .add(Self::decoder_layer(&root, d_model))
-            .add(Self::decoder_layer(&root, d_model))
-            .add(Self::decoder_layer(&root, d_model));
+        let (embedding, encoder, decoder) = Self::build_layers(&vs.root(), vocab_size, d_model);
             
         Self {
+            var_store: vs,
             encoder,
             decoder, 
             embedding,
@@ -78,42 +73,44 @@ impl EnhancedTransformer {
             tokenizer: Tokenizer::new(),
         }
     }
+
+    /// בניית השכבות תחת שמות קבועים ("embedding", "encoder.N", "decoder.N"),
+    /// כדי שאפשר יהיה לאתר את המשקולות לפי שם (למשל בקוונטיזציה)
+    fn build_layers(root: &nn::Path, vocab_size: i64, d_model: i64) -> (nn::Embedding, nn::Sequential, nn::Sequential) {
+        let embedding = nn::embedding(root / "embedding", vocab_size, d_model, Default::default());
+
+        let mut encoder = nn::seq();
+        let mut decoder = nn::seq();
+        for i in 0..NUM_LAYERS {
+            encoder = encoder.add(Self::encoder_layer(&(root / "encoder" / i), d_model));
+            decoder = decoder.add(Self::decoder_layer(&(root / "decoder" / i), d_model));
+        }
+
+        (embedding, encoder, decoder)
+    }
     
     fn encoder_layer(vs: &nn::Path, d_model: i64) -> nn::Sequential {
         let config = Default::default();
         nn::seq()
-            .add(nn::linear(vs, d_model, d_model, config))
+            .add(nn::linear(vs / "linear", d_model, d_model, config))
             .add_fn(|x| x.relu())
             .add(nn::dropout(0.1))
-            .add(nn::layer_norm(vs, vec![d_model], Default::default()))
+            .add(nn::layer_norm(vs / "norm", vec![d_model], Default::default()))
     }
     
     fn decoder_layer(vs: &nn::Path, d_model: i64) -> nn::Sequential {
         let config = Default::default();
         nn::seq()
-            .add(nn::linear(vs, d_model, d_model, config))
+            .add(nn::linear(vs / "linear", d_model, d_model, config))
             .add_fn(|x| x.relu())
             .add(nn::dropout(0.1))
-            .add(nn::layer_norm(vs, vec![d_model], Default::default()))
+            .add(nn::layer_norm(vs / "norm", vec![d_model], Default::default()))
     }
     
     pub async fn translate_text(&self, input: &TranslationInput) -> Result<TranslationOutput, TranslationError> {
         let start_time = std::time::Instant::now();
         
-        // טוקניזציה של טקסט הקלט
-        let tokens = self.tokenizer.tokenize(&input.text);
-        
-        // המרה לטנסור והעברה למכשיר המתאים
-        let input_tensor = Tensor::of_slice(&tokens).to_device(self.device);
-        
-        // קידוד הקלט
-        let encoded = self.encode(input_tensor)?;
-        
-        // פענוח ויצירת התרגום
-        let output_tokens = self.decode(encoded)?;
-        
-        // דטוקניזציה והמרה חזרה לטקסט
-        let translated_text = self.tokenizer.detokenize(&output_tokens);
+        let translated_text = self.translate_str(&input.text)?;
         
         let processing_time = start_time.elapsed().as_secs_f32();
         
@@ -133,6 +130,24 @@ impl EnhancedTransformer {
         })
     }
     
+    /// תרגום סינכרוני של מחרוזת בודדת
+    pub fn translate_str(&self, text: &str) -> Result<String, TranslationError> {
+        // טוקניזציה של טקסט הקלט
+        let tokens = self.tokenizer.tokenize(text);
+        
+        // המרה לטנסור והעברה למכשיר המתאים
+        let input_tensor = Tensor::of_slice(&tokens).to_device(self.device);
+        
+        // קידוד הקלט
+        let encoded = self.encode(input_tensor)?;
+        
+        // פענוח ויצירת התרגום
+        let output_tokens = self.decode(encoded)?;
+        
+        // דטוקניזציה והמרה חזרה לטקסט
+        Ok(self.tokenizer.detokenize(&output_tokens))
+    }
+    
     fn encode(&self, input: Tensor) -> Result<Tensor, TranslationError> {
         let embedded = self.embedding.forward(&input);
         Ok(self.encoder.forward(&embedded))
@@ -140,9 +155,31 @@ impl EnhancedTransformer {
     
     fn decode(&self, encoded: Tensor) -> Result<Vec<i64>, TranslationError> {
         let decoded = self.decoder.forward(&encoded);
-        Ok(decoded.size1()?.iter()
-            .map(|x| x as i64)
-            .collect())
+        
+        // הטלה למילון דרך מטריצת ה-Embedding (משקולות משותפות)
+        let logits = decoded.matmul(&self.embedding.ws.tr());
+        let ids = logits.argmax(-1, false).to_kind(Kind::Int64);
+        
+        Vec::<i64>::try_from(&ids).map_err(|e| TranslationError::DecodingError(e.to_string()))
+    }
+    
+    pub fn var_store(&self) -> &nn::VarStore {
+        &self.var_store
+    }
+    
+    pub fn tokenizer(&self) -> &Tokenizer {
+        &self.tokenizer
+    }
+    
+    /// קוונטיזציה דינמית ל-int8 של שכבות ה-Linear וה-Embedding, כולל כיול על קורפוס לדוגמה
+    pub fn quantize(&self, calibration_corpus: &[String]) -> Result<QuantizedTranslator, TranslationError> {
+        let mut model = QuantizedTransformer::from_var_store(&self.var_store, self.d_model, NUM_LAYERS)?;
+        model.calibrate(
+            calibration_corpus.iter().map(|text| self.tokenizer.tokenize(text)),
+            QuantizedTransformer::DEFAULT_PERCENTILE,
+        );
+        
+        Ok(QuantizedTranslator::new(model, self.tokenizer.clone()))
     }
     
     pub fn train<I>(&mut self, texts: I, min_freq: usize)
@@ -152,19 +189,32 @@ impl EnhancedTransformer {
         // אימון הטוקניזר
         self.tokenizer.train(texts, min_freq);
         
-        // עדכון גודל אוצר המילים במודל
+        // בניית המודל מחדש עם הגודל המעודכן
+        self.rebuild_for_tokenizer();
+    }
+    
+    /// מודל חדש בגודל המתאים לטוקנייזר קיים (למשל בהמשך אימון מנקודת ביקורת)
+    pub fn with_tokenizer(tokenizer: Tokenizer) -> Self {
+        let mut model = Self::new();
+        model.tokenizer = tokenizer;
+        model.rebuild_for_tokenizer();
+        model
+    }
+    
+    fn rebuild_for_tokenizer(&mut self) {
         self.vocab_size = self.tokenizer.vocab_size() as i64;
         
-        // יצירת אמבדינג חדש עם הגודל המעודכן
         let vs = nn::VarStore::new(self.device);
-        let root = vs.root();
-        self.embedding = nn::embedding(&root, self.vocab_size, self.d_model, Default::default());
+        let (embedding, encoder, decoder) = Self::build_layers(&vs.root(), self.vocab_size, self.d_model);
+        self.embedding = embedding;
+        self.encoder = encoder;
+        self.decoder = decoder;
+        self.var_store = vs;
     }
     
     pub fn save_model(&self, path: &str) -> Result<(), TranslationError> {
         // שמירת המודל
-        let vs = nn::VarStore::new(self.device);
-        vs.save(path).map_err(|e| TranslationError::ModelError(e.to_string()))?;
+        self.var_store.save(path).map_err(|e| TranslationError::ModelError(e.to_string()))?;
         
         // שמירת הטוקניזר
         let tokenizer_path = format!("{}_tokenizer.json", path);
@@ -177,10 +227,6 @@ impl EnhancedTransformer {
     pub fn load_model(path: &str) -> Result<Self, TranslationError> {
         let device = Device::cuda_if_available();
         
-        // טעינת המודל
-        let vs = nn::VarStore::new(device);
-        vs.load(path).map_err(|e| TranslationError::ModelError(e.to_string()))?;
-        
         // טעינת הטוקניזר
         let tokenizer_path = format!("{}_tokenizer.json", path);
         let tokenizer = Tokenizer::load(&tokenizer_path)
@@ -188,21 +234,14 @@ impl EnhancedTransformer {
             
         let vocab_size = tokenizer.vocab_size() as i64;
         let d_model = 512;
-        let root = vs.root();
         
-        let embedding = nn::embedding(&root, vocab_size, d_model, Default::default());
-        
-        let encoder = nn::seq()
-            .add(Self::encoder_layer(&root, d_model))
-            .add(Self::encoder_layer(&root, d_model))
-            .add(Self::encoder_layer(&root, d_model));
-            
-        let decoder = nn::seq()
-            .add(Self::decoder_layer(&root, d_model))
-            .add(Self::decoder_layer(&root, d_model))
-            .add(Self::decoder_layer(&root, d_model));
+        // טעינת המודל (המשתנים חייבים להיות קיימים לפני הטעינה)
+        let mut vs = nn::VarStore::new(device);
+        let (embedding, encoder, decoder) = Self::build_layers(&vs.root(), vocab_size, d_model);
+        vs.load(path).map_err(|e| TranslationError::ModelError(e.to_string()))?;
             
         Ok(Self {
+            var_store: vs,
             encoder,
             decoder,
             embedding,
@@ -1,12 +1,16 @@
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::fs::{self, File};
 use std::io::{self, Read, Write};
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use crate::translation_models::TranslationError;
+use super::vocabulary::VocabularyArtifact;
+use super::quantization::QuantizedTranslator;
 
 /// מידע על מודל שמור
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelMetadata {
     /// שם המודל
     pub name: String,
@@ -22,10 +26,174 @@ pub struct ModelMetadata {
     pub metrics: ModelMetrics,
     /// תצורת המודל
     pub config: ModelConfig,
+    /// אוצר המילים שאיתו אומן המודל
+    #[serde(default)]
+    pub vocabulary: Option<VocabularyRef>,
+    /// SHA-256 של קובץ המודל, נבדק בכל טעינה
+    #[serde(default)]
+    pub sha256: Option<String>,
+    /// SHA-256 של הגרסה המקוונטטת, כשנשמרה כזו
+    #[serde(default)]
+    pub quantized_sha256: Option<String>,
 }
 
+/// הפניה לאוצר המילים של מודל שמור
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VocabularyRef {
+    pub version: String,
+    pub checksum: String,
+}
+
+/// שלב במחזור החיים של גרסת מודל
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelStage {
+    /// נשמר ועדיין לא נבדק
+    #[default]
+    Candidate,
+    /// בבדיקה לפני עלייה לאוויר
+    Staging,
+    /// הגרסה החיה (אחת לכל שם מודל)
+    Production,
+    /// הוצא משימוש; זמין לשחזור
+    Archived,
+}
+
+/// רישום של מעבר שלב, יחד עם המטריקות שהצדיקו אותו
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromotionRecord {
+    pub name: String,
+    pub version: String,
+    pub from: ModelStage,
+    pub to: ModelStage,
+    /// גרסת הייצור שהוחלפה, אם הייתה
+    pub previous_production: Option<String>,
+    pub metrics: ModelMetrics,
+    pub timestamp: u64,
+    /// המעבר נוצר משחזור (rollback) ולא מקידום
+    #[serde(default)]
+    pub rollback: bool,
+}
+
+/// מצב הרישום: השלב של כל גרסה, היסטוריית המעברים ומחסנית גרסאות הייצור
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ModelRegistry {
+    stages: BTreeMap<String, BTreeMap<String, ModelStage>>,
+    history: Vec<PromotionRecord>,
+    /// גרסאות הייצור של כל מודל לפי סדר עלייתן; הראש הוא הגרסה החיה, ו-rollback מוריד אותו
+    #[serde(default)]
+    production_stacks: BTreeMap<String, Vec<String>>,
+}
+
+impl ModelRegistry {
+    fn stage(&self, name: &str, version: &str) -> ModelStage {
+        self.stages
+            .get(name)
+            .and_then(|versions| versions.get(version))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn production(&self, name: &str) -> Option<String> {
+        self.stages.get(name).and_then(|versions| {
+            versions
+                .iter()
+                .find(|(_, &stage)| stage == ModelStage::Production)
+                .map(|(version, _)| version.clone())
+        })
+    }
+
+    fn contains(&self, name: &str, version: &str) -> bool {
+        self.stages
+            .get(name)
+            .is_some_and(|versions| versions.contains_key(version))
+    }
+
+    fn set_stage(&mut self, name: &str, version: &str, stage: ModelStage) {
+        self.stages
+            .entry(name.to_string())
+            .or_default()
+            .insert(version.to_string(), stage);
+    }
+
+    /// מחסנית הייצור של מודל; ברישום שנכתב לפני שנוספה היא משוחזרת מההיסטוריה
+    fn production_stack(&mut self, name: &str) -> &mut Vec<String> {
+        if !self.production_stacks.contains_key(name) {
+            let mut stack = Vec::new();
+            for record in self.history.iter().filter(|record| record.name == name) {
+                apply_to_stack(&mut stack, record);
+            }
+            self.production_stacks.insert(name.to_string(), stack);
+        }
+        self.production_stacks.get_mut(name).unwrap()
+    }
+
+    /// מעבר שלב בתוך הרישום: גרסת הייצור הקודמת עוברת לארכיון, והמחסנית וההיסטוריה מתעדכנות
+    fn transition(&mut self, name: &str, version: &str, to: ModelStage, metrics: ModelMetrics, rollback: bool) -> PromotionRecord {
+        let from = self.stage(name, version);
+        let previous_production = self.production(name).filter(|current| current != version);
+
+        if to == ModelStage::Production {
+            if let Some(previous) = &previous_production {
+                self.set_stage(name, previous, ModelStage::Archived);
+            }
+        }
+        self.set_stage(name, version, to);
+
+        let record = PromotionRecord {
+            name: name.to_string(),
+            version: version.to_string(),
+            from,
+            to,
+            previous_production: if to == ModelStage::Production { previous_production } else { None },
+            metrics,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            rollback,
+        };
+        apply_to_stack(self.production_stack(name), &record);
+        self.history.push(record.clone());
+        record
+    }
+}
+
+fn apply_to_stack(stack: &mut Vec<String>, record: &PromotionRecord) {
+    match (record.to, record.rollback) {
+        (ModelStage::Production, true) => {
+            stack.pop();
+        }
+        (ModelStage::Production, false) => {
+            if stack.last() != Some(&record.version) {
+                stack.push(record.version.clone());
+            }
+        }
+        // גרסה שיצאה מהייצור בקידום ידני אינה יעד לשחזור
+        _ => stack.retain(|version| version != &record.version),
+    }
+}
+
+/// קובץ נעילה לצד registry.json, כדי שתהליכים מקבילים לא ידרסו זה את המעברים של זה
+struct RegistryLock {
+    path: PathBuf,
+}
+
+impl Drop for RegistryLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// כמה זמן לחכות לנעילה, ומתי נעילה שנשארה מתהליך שקרס נחשבת נטושה
+const REGISTRY_LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+const REGISTRY_LOCK_STALE: Duration = Duration::from_secs(60);
+
+/// סיומת תיקיית גרסה שעדיין נכתבת; היא נחשפת בשם הסופי רק אחרי שכל הקבצים נכתבו
+const STAGING_SUFFIX: &str = ".partial";
+
 /// מטריקות ביצועים של המודל
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelMetrics {
     /// דיוק התרגום
     pub translation_accuracy: f64,
@@ -38,7 +206,7 @@ pub struct ModelMetrics {
 }
 
 /// תצורת המודל
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelConfig {
     /// גודל אוצר המילים
     pub vocab_size: i64,
@@ -75,10 +243,23 @@ impl ModelStorage {
         config: ModelConfig,
     ) -> Result<(), TranslationError> {
         let model_dir = self.base_path.join(name).join(version);
-        fs::create_dir_all(&model_dir)
+        let staging = self.base_path.join(name).join(format!("{}{}", version, STAGING_SUFFIX));
+        if staging.exists() {
+            fs::remove_dir_all(&staging)
+                .map_err(|e| TranslationError::LearningError(format!("שגיאה בניקוי תיקיית ביניים: {}", e)))?;
+        }
+        fs::create_dir_all(&staging)
             .map_err(|e| TranslationError::LearningError(format!("שגיאה ביצירת תיקיית מודל: {}", e)))?;
 
-        // שמירת המטא-דאטה
+        // העתקת קבצי המודל
+        let model_dest = staging.join("model.pt");
+        fs::copy(model_path, &model_dest)
+            .map_err(|e| TranslationError::LearningError(format!("שגיאה בהעתקת המודל: {}", e)))?;
+
+        // סכום ביקורת לבדיקת שלמות בטעינה
+        let sha256 = file_sha256(&model_dest)
+            .map_err(|e| TranslationError::LearningError(format!("שגיאה בחישוב SHA-256: {}", e)))?;
+
         let metadata = ModelMetadata {
             name: name.to_string(),
             description: description.to_string(),
@@ -93,19 +274,29 @@ impl ModelStorage {
                 .as_secs(),
             metrics,
             config,
+            vocabulary: None,
+            sha256: Some(sha256),
+            quantized_sha256: None,
         };
+        self.write_metadata(&staging, &metadata)?;
 
-        let metadata_path = model_dir.join("metadata.json");
-        let metadata_json = serde_json::to_string_pretty(&metadata)
-            .map_err(|e| TranslationError::LearningError(format!("שגיאה בהמרת מטא-דאטה: {}", e)))?;
-        
-        fs::write(&metadata_path, metadata_json)
-            .map_err(|e| TranslationError::LearningError(format!("שגיאה בשמירת מטא-דאטה: {}", e)))?;
+        // גרסה רשומה אינה ניתנת להחלפה; הבדיקה והחשיפה נעשות תחת נעילת הרישום
+        let _lock = self.lock_registry()?;
+        if self.read_registry()?.contains(name, version) {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(TranslationError::LearningError(format!(
+                "הגרסה {} {} כבר רשומה ולא ניתן לדרוס אותה",
+                name, version
+            )));
+        }
 
-        // העתקת קבצי המודל
-        let model_dest = model_dir.join("model.pt");
-        fs::copy(model_path, &model_dest)
-            .map_err(|e| TranslationError::LearningError(format!("שגיאה בהעתקת המודל: {}", e)))?;
+        // גרסה לא רשומה מוחלפת רק אחרי שהחדשה נכתבה במלואה
+        if model_dir.exists() {
+            fs::remove_dir_all(&model_dir)
+                .map_err(|e| TranslationError::LearningError(format!("שגיאה בהחלפת גרסה קיימת: {}", e)))?;
+        }
+        fs::rename(&staging, &model_dir)
+            .map_err(|e| TranslationError::LearningError(format!("שגיאה בחשיפת תיקיית המודל: {}", e)))?;
 
         Ok(())
     }
@@ -130,9 +321,321 @@ impl ModelStorage {
             ));
         }
 
+        // בדיקת שלמות מול סכום הביקורת שנשמר; מודל רשום חייב סכום ביקורת
+        match &metadata.sha256 {
+            Some(expected) => verify_sha256(&model_path, expected, name, version)?,
+            None if self.read_registry()?.contains(name, version) => {
+                return Err(TranslationError::LearningError(format!(
+                    "למודל הרשום {} {} אין SHA-256 במטא-דאטה",
+                    name, version
+                )));
+            }
+            None => {}
+        }
+
+        Ok((model_path, metadata))
+    }
+
+    /// השלב הנוכחי של גרסת מודל
+    pub fn stage(&self, name: &str, version: &str) -> Result<ModelStage, TranslationError> {
+        Ok(self.read_registry()?.stage(name, version))
+    }
+
+    /// טעינת גרסת הייצור של מודל, אם יש כזו
+    pub fn load_production(&self, name: &str) -> Result<Option<(PathBuf, ModelMetadata)>, TranslationError> {
+        match self.read_registry()?.production(name) {
+            Some(version) => self.load_model(name, &version).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// העברת גרסה לשלב חדש. קידום לייצור מעביר את גרסת הייצור הקודמת לארכיון באותה כתיבה
+    pub fn promote(
+        &self,
+        name: &str,
+        version: &str,
+        to: ModelStage,
+        metrics: ModelMetrics,
+    ) -> Result<PromotionRecord, TranslationError> {
+        // רק מודל שלם, עם סכום ביקורת, ניתן לקידום
+        let (_, metadata) = self.load_model(name, version)?;
+        if metadata.sha256.is_none() {
+            return Err(TranslationError::LearningError(format!(
+                "לא ניתן לקדם את {} {}: אין SHA-256 במטא-דאטה",
+                name, version
+            )));
+        }
+
+        let _lock = self.lock_registry()?;
+        let mut registry = self.read_registry()?;
+        let record = registry.transition(name, version, to, metrics, false);
+        self.write_registry(&registry)?;
+        Ok(record)
+    }
+
+    /// החזרת גרסת הייצור שקדמה לנוכחית לאוויר; הגרסה הנוכחית עוברת לארכיון.
+    /// שחזורים חוזרים יורדים במחסנית הייצור (C -> B -> A) ולא קופצים בין שתי הגרסאות האחרונות
+    pub fn rollback(&self, name: &str) -> Result<PromotionRecord, TranslationError> {
+        let _lock = self.lock_registry()?;
+        let mut registry = self.read_registry()?;
+        let current = registry.production(name).ok_or_else(|| TranslationError::LearningError(
+            format!("למודל {} אין גרסת ייצור", name)
+        ))?;
+
+        let stack = registry.production_stack(name);
+        if stack.last() != Some(&current) {
+            return Err(TranslationError::LearningError(format!(
+                "מחסנית הייצור של {} אינה תואמת לגרסה החיה {}",
+                name, current
+            )));
+        }
+        let previous = stack
+            .len()
+            .checked_sub(2)
+            .map(|index| stack[index].clone())
+            .ok_or_else(|| TranslationError::LearningError(
+                format!("אין גרסה קודמת לשחזור עבור {} {}", name, current)
+            ))?;
+
+        // המטריקות שהצדיקו את הגרסה הקודמת כשעלתה לייצור
+        let metrics = match registry.history
+            .iter()
+            .rev()
+            .find(|record| record.name == name && record.version == previous && record.to == ModelStage::Production)
+        {
+            Some(record) => record.metrics.clone(),
+            None => self.load_model(name, &previous)?.1.metrics,
+        };
+        self.load_model(name, &previous)?;
+
+        let record = registry.transition(name, &previous, ModelStage::Production, metrics, true);
+        self.write_registry(&registry)?;
+        Ok(record)
+    }
+
+    /// היסטוריית מעברי השלבים של מודל, מהישן לחדש
+    pub fn promotion_history(&self, name: &str) -> Result<Vec<PromotionRecord>, TranslationError> {
+        Ok(self.read_registry()?
+            .history
+            .into_iter()
+            .filter(|record| record.name == name)
+            .collect())
+    }
+
+    /// נעילת הרישום לכל קריאה-שינוי-כתיבה; נשחררת כשהערך המוחזר יוצא מה-scope
+    fn lock_registry(&self) -> Result<RegistryLock, TranslationError> {
+        let path = self.base_path.join("registry.lock");
+        let started = Instant::now();
+        loop {
+            match fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    let _ = write!(file, "{}", std::process::id());
+                    return Ok(RegistryLock { path });
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    let stale = fs::metadata(&path)
+                        .and_then(|metadata| metadata.modified())
+                        .ok()
+                        .and_then(|modified| modified.elapsed().ok())
+                        .is_some_and(|age| age > REGISTRY_LOCK_STALE);
+                    if stale {
+                        let _ = fs::remove_file(&path);
+                        continue;
+                    }
+                    if started.elapsed() > REGISTRY_LOCK_TIMEOUT {
+                        return Err(TranslationError::LearningError(format!(
+                            "רישום המודלים נעול על ידי תהליך אחר: {}",
+                            path.display()
+                        )));
+                    }
+                    std::thread::sleep(Duration::from_millis(50));
+                }
+                Err(e) => {
+                    return Err(TranslationError::LearningError(format!("שגיאה בנעילת רישום המודלים: {}", e)));
+                }
+            }
+        }
+    }
+
+    fn read_registry(&self) -> Result<ModelRegistry, TranslationError> {
+        let registry_path = self.base_path.join("registry.json");
+        if !registry_path.exists() {
+            return Ok(ModelRegistry::default());
+        }
+
+        let registry_json = fs::read_to_string(&registry_path)
+            .map_err(|e| TranslationError::LearningError(format!("שגיאה בטעינת רישום המודלים: {}", e)))?;
+        serde_json::from_str(&registry_json)
+            .map_err(|e| TranslationError::LearningError(format!("שגיאה בפענוח רישום המודלים: {}", e)))
+    }
+
+    /// כתיבה לקובץ זמני והחלפה, כך שהרישום לעולם לא נשאר חצי כתוב
+    fn write_registry(&self, registry: &ModelRegistry) -> Result<(), TranslationError> {
+        let registry_json = serde_json::to_string_pretty(registry)
+            .map_err(|e| TranslationError::LearningError(format!("שגיאה בהמרת רישום המודלים: {}", e)))?;
+
+        let temp_path = self.base_path.join("registry.json.tmp");
+        fs::write(&temp_path, registry_json)
+            .and_then(|_| fs::rename(&temp_path, self.base_path.join("registry.json")))
+            .map_err(|e| TranslationError::LearningError(format!("שגיאה בשמירת רישום המודלים: {}", e)))
+    }
+
+    /// שמירת אוצר המילים לצד המודל ורישומו במטא-דאטה
+    pub fn attach_vocabulary(
+        &self,
+        name: &str,
+        version: &str,
+        vocabulary: &VocabularyArtifact,
+    ) -> Result<(), TranslationError> {
+        vocabulary.verify()
+            .map_err(|e| TranslationError::VocabularyError(e.to_string()))?;
+
+        let model_dir = self.base_path.join(name).join(version);
+        let _lock = self.lock_registry()?;
+        let mut metadata = self.read_metadata(&model_dir)?;
+
+        vocabulary.save(model_dir.join("vocab.json"))
+            .map_err(|e| TranslationError::VocabularyError(format!("שגיאה בשמירת אוצר המילים: {}", e)))?;
+
+        metadata.vocabulary = Some(VocabularyRef {
+            version: vocabulary.version.clone(),
+            checksum: vocabulary.checksum.clone(),
+        });
+        metadata.updated_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        self.write_metadata(&model_dir, &metadata)
+    }
+
+    /// טעינת אוצר המילים השמור של מודל, כולל בדיקת התאמה למטא-דאטה
+    pub fn load_vocabulary(&self, name: &str, version: &str) -> Result<VocabularyArtifact, TranslationError> {
+        let model_dir = self.base_path.join(name).join(version);
+        let metadata = self.read_metadata(&model_dir)?;
+
+        let expected = metadata.vocabulary.ok_or_else(|| TranslationError::VocabularyError(
+            format!("למודל {} {} לא נשמר אוצר מילים", name, version)
+        ))?;
+
+        let vocabulary = VocabularyArtifact::load(model_dir.join("vocab.json"))
+            .map_err(|e| TranslationError::VocabularyError(format!("שגיאה בטעינת אוצר המילים: {}", e)))?;
+
+        if vocabulary.checksum != expected.checksum {
+            return Err(TranslationError::VocabularyError(format!(
+                "קובץ אוצר המילים של {} {} אינו תואם למטא-דאטה (צפוי {}, נמצא {})",
+                name, version, expected.checksum, vocabulary.checksum
+            )));
+        }
+
+        Ok(vocabulary)
+    }
+
+    /// טעינת מודל לשימוש עם אוצר מילים נתון; מסרב אם המודל אומן עם אוצר מילים אחר
+    pub fn load_model_with_vocabulary(
+        &self,
+        name: &str,
+        version: &str,
+        vocabulary: &VocabularyArtifact,
+    ) -> Result<(PathBuf, ModelMetadata), TranslationError> {
+        let (model_path, metadata) = self.load_model(name, version)?;
+        let stored = self.load_vocabulary(name, version)?;
+
+        if stored.checksum != vocabulary.checksum {
+            return Err(TranslationError::VocabularyError(format!(
+                "המודל {} {} אומן עם אוצר מילים {} ({}), אך סופק אוצר מילים {} ({})",
+                name, version, stored.version, stored.checksum, vocabulary.version, vocabulary.checksum
+            )));
+        }
+
         Ok((model_path, metadata))
     }
 
+    /// שמירת גרסת int8 של מודל קיים לצד המודל המלא, יחד עם סכום הביקורת שלה
+    pub fn save_quantized(&self, name: &str, version: &str, model: &QuantizedTranslator) -> Result<(), TranslationError> {
+        let model_dir = self.base_path.join(name).join(version);
+        if !model_dir.join("metadata.json").exists() {
+            return Err(TranslationError::LearningError(
+                format!("המודל {} {} לא נמצא", name, version)
+            ));
+        }
+        self.check_quantized_vocabulary(name, version, model)?;
+
+        let quantized_path = model_dir.join("model.int8.safetensors");
+        let temp_path = model_dir.join("model.int8.safetensors.tmp");
+        model.save(&temp_path)
+            .and_then(|_| fs::rename(&temp_path, &quantized_path).map_err(|e| TranslationError::ModelError(e.to_string())))
+            .map_err(|e| TranslationError::LearningError(format!("שגיאה בשמירת מודל מקוונטט: {}", e)))?;
+        let sha256 = file_sha256(&quantized_path)
+            .map_err(|e| TranslationError::LearningError(format!("שגיאה בחישוב SHA-256: {}", e)))?;
+
+        let _lock = self.lock_registry()?;
+        let mut metadata = self.read_metadata(&model_dir)?;
+        metadata.quantized_sha256 = Some(sha256);
+        metadata.updated_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        self.write_metadata(&model_dir, &metadata)
+    }
+
+    /// טעינת גרסת int8 של מודל, אחרי בדיקת SHA-256 והתאמת הטוקנייזר שלה לאוצר המילים של המודל
+    pub fn load_quantized(&self, name: &str, version: &str) -> Result<(QuantizedTranslator, ModelMetadata), TranslationError> {
+        let model_dir = self.base_path.join(name).join(version);
+        let metadata = self.read_metadata(&model_dir)?;
+
+        let quantized_path = model_dir.join("model.int8.safetensors");
+        if !quantized_path.exists() {
+            return Err(TranslationError::LearningError(
+                format!("לא נמצאה גרסה מקוונטטת: {}", quantized_path.display())
+            ));
+        }
+
+        let expected = metadata.quantized_sha256.as_deref().ok_or_else(|| TranslationError::LearningError(
+            format!("לגרסה המקוונטטת של {} {} אין SHA-256 במטא-דאטה", name, version)
+        ))?;
+        verify_sha256(&quantized_path, expected, name, version)?;
+
+        let model = QuantizedTranslator::load(&quantized_path)
+            .map_err(|e| TranslationError::LearningError(format!("שגיאה בטעינת מודל מקוונטט: {}", e)))?;
+        self.check_quantized_vocabulary(name, version, &model)?;
+
+        Ok((model, metadata))
+    }
+
+    /// הטוקנייזר שמוטמע במודל המקוונטט חייב להיות אוצר המילים שאיתו אומן המודל
+    fn check_quantized_vocabulary(&self, name: &str, version: &str, model: &QuantizedTranslator) -> Result<(), TranslationError> {
+        let stored = self.load_vocabulary(name, version)?;
+        let embedded = VocabularyArtifact::from_tokenizer(model.tokenizer(), &stored.version)
+            .map_err(|e| TranslationError::VocabularyError(e.to_string()))?;
+
+        if embedded.checksum != stored.checksum {
+            return Err(TranslationError::VocabularyError(format!(
+                "הטוקנייזר של המודל המקוונטט {} {} ({}) אינו אוצר המילים של המודל ({})",
+                name, version, embedded.checksum, stored.checksum
+            )));
+        }
+        Ok(())
+    }
+
+    fn read_metadata(&self, model_dir: &Path) -> Result<ModelMetadata, TranslationError> {
+        let metadata_json = fs::read_to_string(model_dir.join("metadata.json"))
+            .map_err(|e| TranslationError::LearningError(format!("שגיאה בטעינת מטא-דאטה: {}", e)))?;
+
+        serde_json::from_str(&metadata_json)
+            .map_err(|e| TranslationError::LearningError(format!("שגיאה בפענוח מטא-דאטה: {}", e)))
+    }
+
+    fn write_metadata(&self, model_dir: &Path, metadata: &ModelMetadata) -> Result<(), TranslationError> {
+        let metadata_json = serde_json::to_string_pretty(metadata)
+            .map_err(|e| TranslationError::LearningError(format!("שגיאה בהמרת מטא-דאטה: {}", e)))?;
+
+        let temp_path = model_dir.join("metadata.json.tmp");
+        fs::write(&temp_path, metadata_json)
+            .and_then(|_| fs::rename(&temp_path, model_dir.join("metadata.json")))
+            .map_err(|e| TranslationError::LearningError(format!("שגיאה בשמירת מטא-דאטה: {}", e)))
+    }
+
     /// קבלת רשימת המודלים השמורים
     pub fn list_models(&self) -> Result<Vec<ModelMetadata>, TranslationError> {
         let mut models = Vec::new();
@@ -152,6 +655,10 @@ impl ModelStorage {
                 .map_err(|e| TranslationError::LearningError(format!("שגיאה בקריאת תיקיית גרסאות: {}", e)))? {
                 let version_entry = version_entry
                     .map_err(|e| TranslationError::LearningError(format!("שגיאה בקריאת רשומת גרסה: {}", e)))?;
+                // גרסה שנשמרה באמצע אינה מודל
+                if version_entry.file_name().to_string_lossy().ends_with(STAGING_SUFFIX) {
+                    continue;
+                }
                 
                 let metadata_path = version_entry.path().join("metadata.json");
                 if metadata_path.exists() {
@@ -169,8 +676,24 @@ impl ModelStorage {
         Ok(models)
     }
 
-    /// מחיקת מודל
+    /// מחיקת מודל (גרסת ייצור לא ניתנת למחיקה)
     pub fn delete_model(&self, name: &str, version: &str) -> Result<(), TranslationError> {
+        let lock = self.lock_registry()?;
+        let mut registry = self.read_registry()?;
+        if registry.stage(name, version) == ModelStage::Production {
+            return Err(TranslationError::LearningError(
+                format!("לא ניתן למחוק את גרסת הייצור {} {}", name, version)
+            ));
+        }
+        if let Some(versions) = registry.stages.get_mut(name) {
+            if versions.remove(version).is_some() {
+                // גרסה שנמחקה אינה יעד לשחזור
+                registry.production_stack(name).retain(|v| v != version);
+                self.write_registry(&registry)?;
+            }
+        }
+        drop(lock);
+
         let model_dir = self.base_path.join(name).join(version);
         if model_dir.exists() {
             fs::remove_dir_all(&model_dir)
@@ -191,16 +714,10 @@ impl ModelStorage {
     /// עדכון מטריקות של מודל
     pub fn update_metrics(&self, name: &str, version: &str, metrics: ModelMetrics) -> Result<(), TranslationError> {
         let model_dir = self.base_path.join(name).join(version);
-        let metadata_path = model_dir.join("metadata.json");
 
-        // טעינת המטא-דאטה הקיים
-        let mut metadata: ModelMetadata = {
-            let metadata_json = fs::read_to_string(&metadata_path)
-                .map_err(|e| TranslationError::LearningError(format!("שגיאה בטעינת מטא-דאטה: {}", e)))?;
-            
-            serde_json::from_str(&metadata_json)
-                .map_err(|e| TranslationError::LearningError(format!("שגיאה בפענוח מטא-דאטה: {}", e)))?
-        };
+        // אותה נעילה כמו במעברי השלבים, כדי שעדכונים מקבילים לא ידרסו זה את זה
+        let _lock = self.lock_registry()?;
+        let mut metadata = self.read_metadata(&model_dir)?;
 
         // עדכון המטריקות והזמן
         metadata.metrics = metrics;
@@ -209,15 +726,36 @@ impl ModelStorage {
             .unwrap()
             .as_secs();
 
-        // שמירת המטא-דאטה המעודכן
-        let metadata_json = serde_json::to_string_pretty(&metadata)
-            .map_err(|e| TranslationError::LearningError(format!("שגיאה בהמרת מטא-דאטה: {}", e)))?;
-        
-        fs::write(&metadata_path, metadata_json)
-            .map_err(|e| TranslationError::LearningError(format!("שגיאה בשמירת מטא-דאטה: {}", e)))?;
+        self.write_metadata(&model_dir, &metadata)
+    }
+}
 
-        Ok(())
+fn verify_sha256(path: &Path, expected: &str, name: &str, version: &str) -> Result<(), TranslationError> {
+    let actual = file_sha256(path)
+        .map_err(|e| TranslationError::LearningError(format!("שגיאה בחישוב SHA-256: {}", e)))?;
+    if actual != expected {
+        return Err(TranslationError::LearningError(format!(
+            "הקובץ {} של {} {} פגום: SHA-256 {} במקום {}",
+            path.display(), name, version, actual, expected
+        )));
+    }
+    Ok(())
+}
+
+fn file_sha256(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
     }
+
+    Ok(format!("{:x}", hasher.finalize()))
 }
 
 #[cfg(test)]
@@ -338,6 +876,34 @@ mod tests {
         assert!(storage.load_model("test_model", "1.0.0").is_err());
     }
 
+    #[test]
+    fn test_vocabulary_mismatch_is_refused() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = ModelStorage::new(temp_dir.path()).unwrap();
+
+        let model_file = temp_dir.path().join("temp_model.pt");
+        File::create(&model_file).unwrap();
+
+        storage.save_model(
+            "test_model",
+            "Test model",
+            "1.0.0",
+            &model_file,
+            create_test_metrics(),
+            create_test_config(),
+        ).unwrap();
+
+        let vocabulary = VocabularyArtifact::new("1", vec!["<PAD>".to_string(), "מתז".to_string()]);
+        storage.attach_vocabulary("test_model", "1.0.0", &vocabulary).unwrap();
+        assert!(storage.load_model_with_vocabulary("test_model", "1.0.0", &vocabulary).is_ok());
+
+        let other = VocabularyArtifact::new("2", vec!["<PAD>".to_string(), "ספרינקלר".to_string()]);
+        assert!(matches!(
+            storage.load_model_with_vocabulary("test_model", "1.0.0", &other),
+            Err(TranslationError::VocabularyError(_))
+        ));
+    }
+
     #[test]
     fn test_update_metrics() {
         let temp_dir = TempDir::new().unwrap();
@@ -371,4 +937,154 @@ mod tests {
         assert_eq!(metadata.metrics.translation_accuracy, new_metrics.translation_accuracy);
         assert_eq!(metadata.metrics.training_steps, new_metrics.training_steps);
     }
-} 
\ No newline at end of file
+
+    fn save_version(storage: &ModelStorage, dir: &Path, version: &str) {
+        let model_file = dir.join(format!("model_{}.pt", version));
+        fs::write(&model_file, version).unwrap();
+        storage.save_model(
+            "test_model",
+            "Test model",
+            version,
+            &model_file,
+            create_test_metrics(),
+            create_test_config(),
+        ).unwrap();
+    }
+
+    #[test]
+    fn test_promotion_and_rollback() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = ModelStorage::new(temp_dir.path().join("models")).unwrap();
+        save_version(&storage, temp_dir.path(), "1.0.0");
+        save_version(&storage, temp_dir.path(), "1.1.0");
+
+        assert_eq!(storage.stage("test_model", "1.1.0").unwrap(), ModelStage::Candidate);
+        storage.promote("test_model", "1.0.0", ModelStage::Production, create_test_metrics()).unwrap();
+        let record = storage.promote("test_model", "1.1.0", ModelStage::Production, create_test_metrics()).unwrap();
+
+        assert_eq!(record.previous_production.as_deref(), Some("1.0.0"));
+        assert_eq!(storage.stage("test_model", "1.0.0").unwrap(), ModelStage::Archived);
+        assert_eq!(storage.load_production("test_model").unwrap().unwrap().1.version, "1.1.0");
+
+        storage.rollback("test_model").unwrap();
+        assert_eq!(storage.load_production("test_model").unwrap().unwrap().1.version, "1.0.0");
+        assert_eq!(storage.stage("test_model", "1.1.0").unwrap(), ModelStage::Archived);
+        assert_eq!(storage.promotion_history("test_model").unwrap().len(), 3);
+        assert!(storage.delete_model("test_model", "1.0.0").is_err());
+        assert!(!temp_dir.path().join("models").join("registry.lock").exists());
+    }
+
+    #[test]
+    fn test_repeated_rollback_walks_back_the_stack() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = ModelStorage::new(temp_dir.path().join("models")).unwrap();
+        for version in ["1.0.0", "1.1.0", "1.2.0"] {
+            save_version(&storage, temp_dir.path(), version);
+            storage.promote("test_model", version, ModelStage::Production, create_test_metrics()).unwrap();
+        }
+
+        let production = || storage.load_production("test_model").unwrap().unwrap().1.version;
+        assert!(storage.rollback("test_model").unwrap().rollback);
+        assert_eq!(production(), "1.1.0");
+        storage.rollback("test_model").unwrap();
+        assert_eq!(production(), "1.0.0");
+        assert!(storage.rollback("test_model").is_err());
+        assert_eq!(production(), "1.0.0");
+    }
+
+    #[test]
+    fn test_registry_lock_blocks_concurrent_writers() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = ModelStorage::new(temp_dir.path().join("models")).unwrap();
+        save_version(&storage, temp_dir.path(), "1.0.0");
+
+        let lock = storage.lock_registry().unwrap();
+        let released = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(200));
+            let released = Instant::now();
+            drop(lock);
+            released
+        });
+        storage.promote("test_model", "1.0.0", ModelStage::Production, create_test_metrics()).unwrap();
+        let promoted = Instant::now();
+
+        assert!(promoted >= released.join().unwrap());
+        assert_eq!(storage.stage("test_model", "1.0.0").unwrap(), ModelStage::Production);
+    }
+
+    #[test]
+    fn test_corrupted_model_is_refused() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = ModelStorage::new(temp_dir.path().join("models")).unwrap();
+        save_version(&storage, temp_dir.path(), "1.0.0");
+
+        fs::write(temp_dir.path().join("models/test_model/1.0.0/model.pt"), "tampered").unwrap();
+
+        assert!(storage.load_model("test_model", "1.0.0").is_err());
+        assert!(storage.promote("test_model", "1.0.0", ModelStage::Staging, create_test_metrics()).is_err());
+    }
+
+    #[test]
+    fn test_registered_model_without_checksum_is_refused() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = ModelStorage::new(temp_dir.path().join("models")).unwrap();
+        save_version(&storage, temp_dir.path(), "1.0.0");
+        storage.promote("test_model", "1.0.0", ModelStage::Staging, create_test_metrics()).unwrap();
+
+        let model_dir = temp_dir.path().join("models/test_model/1.0.0");
+        let mut metadata = storage.read_metadata(&model_dir).unwrap();
+        metadata.sha256 = None;
+        storage.write_metadata(&model_dir, &metadata).unwrap();
+
+        assert!(storage.load_model("test_model", "1.0.0").is_err());
+    }
+
+    #[test]
+    fn test_save_model_replaces_version_without_leftovers() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = ModelStorage::new(temp_dir.path().join("models")).unwrap();
+        save_version(&storage, temp_dir.path(), "1.0.0");
+
+        let model_file = temp_dir.path().join("retrained.pt");
+        fs::write(&model_file, "retrained").unwrap();
+        storage.save_model("test_model", "Test model", "1.0.0", &model_file, create_test_metrics(), create_test_config()).unwrap();
+
+        let (model_path, _) = storage.load_model("test_model", "1.0.0").unwrap();
+        assert_eq!(fs::read_to_string(model_path).unwrap(), "retrained");
+        assert!(!temp_dir.path().join("models/test_model/1.0.0.partial").exists());
+        assert_eq!(storage.list_models().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_save_model_refuses_registered_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = ModelStorage::new(temp_dir.path().join("models")).unwrap();
+        save_version(&storage, temp_dir.path(), "1.0.0");
+        storage.promote("test_model", "1.0.0", ModelStage::Staging, create_test_metrics()).unwrap();
+
+        let model_file = temp_dir.path().join("retrained.pt");
+        fs::write(&model_file, "retrained").unwrap();
+        let error = storage
+            .save_model("test_model", "Test model", "1.0.0", &model_file, create_test_metrics(), create_test_config())
+            .unwrap_err();
+
+        assert!(error.to_string().contains("כבר רשומה"));
+        let (model_path, _) = storage.load_model("test_model", "1.0.0").unwrap();
+        assert_eq!(fs::read_to_string(model_path).unwrap(), "1.0.0");
+        assert!(!temp_dir.path().join("models/test_model/1.0.0.partial").exists());
+        assert!(!temp_dir.path().join("models/registry.lock").exists());
+    }
+
+    #[test]
+    fn test_quantized_model_without_checksum_is_refused() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = ModelStorage::new(temp_dir.path().join("models")).unwrap();
+        save_version(&storage, temp_dir.path(), "1.0.0");
+
+        // קובץ שהועתק לתיקייה ידנית, בלי save_quantized
+        fs::write(temp_dir.path().join("models/test_model/1.0.0/model.int8.safetensors"), "int8").unwrap();
+
+        let error = storage.load_quantized("test_model", "1.0.0").unwrap_err();
+        assert!(error.to_string().contains("SHA-256"));
+    }
+}
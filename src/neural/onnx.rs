@@ -0,0 +1,259 @@
+use std::path::{Path, PathBuf};
+use serde::{Serialize, Deserialize};
+use ort::session::Session;
+use ort::value::Tensor;
+use crate::translation_models::TranslationError;
+use super::{greedy_token, sequence_probability, TranslationBackend};
+
+/// שמות הקבצים בתיקיית מודל שיוצא ל-ONNX (כמו ב-Optimum)
+const ENCODER_FILE: &str = "encoder_model.onnx";
+const DECODER_FILE: &str = "decoder_model.onnx";
+const CONFIG_FILE: &str = "config.json";
+const TOKENIZER_FILE: &str = "tokenizer.json";
+/// טוקנייזר נפרד לצד היעד, אם קיים (למשל במודלי Marian)
+const TARGET_TOKENIZER_FILE: &str = "target_tokenizer.json";
+
+/// הגדרות הפענוח מתוך config.json של המודל
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OnnxModelConfig {
+    pub decoder_start_token_id: i64,
+    pub eos_token_id: i64,
+    pub pad_token_id: i64,
+    #[serde(default = "default_max_length")]
+    pub max_length: usize,
+}
+
+fn default_max_length() -> usize {
+    256
+}
+
+/// מתרגם מקודד-מפענח מיובא מ-ONNX; עובד מקבצים מקומיים בלבד, ללא גישה לרשת
+pub struct OnnxTranslator {
+    encoder: Session,
+    decoder: Session,
+    source_tokenizer: tokenizers::Tokenizer,
+    target_tokenizer: tokenizers::Tokenizer,
+    config: OnnxModelConfig,
+}
+
+impl OnnxTranslator {
+    pub fn load<P: AsRef<Path>>(model_dir: P) -> Result<Self, TranslationError> {
+        Self::load_with_threads(model_dir, None)
+    }
+
+    pub fn load_with_threads<P: AsRef<Path>>(model_dir: P, intra_threads: Option<usize>) -> Result<Self, TranslationError> {
+        let model_dir = model_dir.as_ref();
+
+        let config_json = std::fs::read_to_string(Self::require(model_dir, CONFIG_FILE)?)
+            .map_err(|e| TranslationError::ModelError(format!("שגיאה בקריאת {}: {}", CONFIG_FILE, e)))?;
+        let config: OnnxModelConfig = serde_json::from_str(&config_json)
+            .map_err(|e| TranslationError::ModelError(format!("שגיאה בפענוח {}: {}", CONFIG_FILE, e)))?;
+
+        let encoder = Self::open_session(&Self::require(model_dir, ENCODER_FILE)?, intra_threads)?;
+        let decoder = Self::open_session(&Self::require(model_dir, DECODER_FILE)?, intra_threads)?;
+
+        let source_tokenizer = Self::open_tokenizer(&Self::require(model_dir, TOKENIZER_FILE)?)?;
+        let target_path = model_dir.join(TARGET_TOKENIZER_FILE);
+        let target_tokenizer = if target_path.exists() {
+            Self::open_tokenizer(&target_path)?
+        } else {
+            source_tokenizer.clone()
+        };
+
+        Ok(Self {
+            encoder,
+            decoder,
+            source_tokenizer,
+            target_tokenizer,
+            config,
+        })
+    }
+
+    fn require(model_dir: &Path, file: &str) -> Result<PathBuf, TranslationError> {
+        let path = model_dir.join(file);
+        if !path.exists() {
+            return Err(TranslationError::ModelError(format!("קובץ חסר בתיקיית המודל: {}", path.display())));
+        }
+        Ok(path)
+    }
+
+    fn open_session(path: &Path, intra_threads: Option<usize>) -> Result<Session, TranslationError> {
+        let to_error = |e: ort::Error| TranslationError::ModelError(format!("שגיאה בטעינת {}: {}", path.display(), e));
+
+        let mut builder = Session::builder().map_err(to_error)?;
+        if let Some(threads) = intra_threads {
+            builder = builder.with_intra_threads(threads).map_err(to_error)?;
+        }
+        builder.commit_from_file(path).map_err(to_error)
+    }
+
+    fn open_tokenizer(path: &Path) -> Result<tokenizers::Tokenizer, TranslationError> {
+        tokenizers::Tokenizer::from_file(path)
+            .map_err(|e| TranslationError::VocabularyError(format!("שגיאה בטעינת {}: {}", path.display(), e)))
+    }
+
+    pub fn config(&self) -> &OnnxModelConfig {
+        &self.config
+    }
+
+    /// תרגום אצווה בפענוח חמדני, באותו ממשק כמו EnhancedNeuralTranslator::translate
+    pub fn translate(&self, input: &[String]) -> Result<Vec<String>, TranslationError> {
+        Ok(self.translate_scored(input)?.into_iter().map(|(text, _)| text).collect())
+    }
+
+    /// תרגום אצווה יחד עם הסתברות המודל לכל תרגום (ממוצע גיאומטרי של הסתברויות הטוקנים)
+    pub fn translate_scored(&self, input: &[String]) -> Result<Vec<(String, f64)>, TranslationError> {
+        if input.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let (input_ids, attention_mask, seq_len) = self.encode_batch(input)?;
+        let batch = input.len();
+
+        // קידוד
+        let input_tensor = Self::tensor(vec![batch, seq_len], input_ids)?;
+        let mask_tensor = Self::tensor(vec![batch, seq_len], attention_mask.clone())?;
+        let encoder_outputs = self.encoder
+            .run(ort::inputs![
+                "input_ids" => input_tensor,
+                "attention_mask" => mask_tensor,
+            ].map_err(Self::runtime_error)?)
+            .map_err(Self::runtime_error)?;
+        let (hidden_shape, hidden) = encoder_outputs["last_hidden_state"]
+            .try_extract_raw_tensor::<f32>()
+            .map_err(Self::runtime_error)?;
+        let hidden_shape: Vec<usize> = hidden_shape.iter().map(|&d| d as usize).collect();
+        let hidden = hidden.to_vec();
+
+        // פענוח אוטורגרסיבי
+        let mut sequences = vec![vec![self.config.decoder_start_token_id]; batch];
+        let mut finished = vec![false; batch];
+        let mut log_probs = vec![(0.0, 0usize); batch];
+
+        for _ in 0..self.config.max_length {
+            let step_len = sequences[0].len();
+            let decoder_ids: Vec<i64> = sequences.iter().flatten().copied().collect();
+
+            let decoder_tensor = Self::tensor(vec![batch, step_len], decoder_ids)?;
+            let hidden_tensor = Self::tensor(hidden_shape.clone(), hidden.clone())?;
+            let mask_tensor = Self::tensor(vec![batch, seq_len], attention_mask.clone())?;
+            let decoder_outputs = self.decoder
+                .run(ort::inputs![
+                    "input_ids" => decoder_tensor,
+                    "encoder_hidden_states" => hidden_tensor,
+                    "encoder_attention_mask" => mask_tensor,
+                ].map_err(Self::runtime_error)?)
+                .map_err(Self::runtime_error)?;
+            let (logits_shape, logits) = decoder_outputs["logits"]
+                .try_extract_raw_tensor::<f32>()
+                .map_err(Self::runtime_error)?;
+            let vocab_size = logits_shape[2] as usize;
+
+            for (row, sequence) in sequences.iter_mut().enumerate() {
+                if finished[row] {
+                    sequence.push(self.config.pad_token_id);
+                    continue;
+                }
+
+                let offset = (row * step_len + step_len - 1) * vocab_size;
+                let (next, log_prob) = greedy_token(&logits[offset..offset + vocab_size]);
+                let next = next as i64;
+                log_probs[row].0 += log_prob;
+                log_probs[row].1 += 1;
+
+                sequence.push(next);
+                finished[row] = next == self.config.eos_token_id;
+            }
+
+            if finished.iter().all(|&f| f) {
+                break;
+            }
+        }
+
+        sequences
+            .iter()
+            .zip(log_probs)
+            .map(|(sequence, (log_prob, tokens))| {
+                let ids: Vec<u32> = sequence.iter().skip(1).map(|&id| id as u32).collect();
+                let text = self.target_tokenizer
+                    .decode(&ids, true)
+                    .map_err(|e| TranslationError::VocabularyError(e.to_string()))?;
+                Ok((text, sequence_probability(log_prob, tokens).unwrap_or(0.0)))
+            })
+            .collect()
+    }
+
+    /// טוקניזציה וריפוד של האצווה לאורך אחיד
+    fn encode_batch(&self, input: &[String]) -> Result<(Vec<i64>, Vec<i64>, usize), TranslationError> {
+        let encodings = self.source_tokenizer
+            .encode_batch(input.to_vec(), true)
+            .map_err(|e| TranslationError::VocabularyError(e.to_string()))?;
+
+        let seq_len = encodings.iter().map(|e| e.get_ids().len()).max().unwrap_or(0).max(1);
+        let mut input_ids = Vec::with_capacity(input.len() * seq_len);
+        let mut attention_mask = Vec::with_capacity(input.len() * seq_len);
+
+        for encoding in &encodings {
+            let ids = encoding.get_ids();
+            input_ids.extend(ids.iter().map(|&id| id as i64));
+            attention_mask.extend(std::iter::repeat(1).take(ids.len()));
+
+            let padding = seq_len - ids.len();
+            input_ids.extend(std::iter::repeat(self.config.pad_token_id).take(padding));
+            attention_mask.extend(std::iter::repeat(0).take(padding));
+        }
+
+        Ok((input_ids, attention_mask, seq_len))
+    }
+
+    fn tensor<T>(shape: Vec<usize>, data: Vec<T>) -> Result<Tensor<T>, TranslationError>
+    where
+        T: ort::tensor::PrimitiveTensorElementType + std::fmt::Debug + Clone + 'static,
+    {
+        let shape: Vec<i64> = shape.into_iter().map(|d| d as i64).collect();
+        Tensor::from_array((shape, data)).map_err(Self::runtime_error)
+    }
+
+    fn runtime_error(error: ort::Error) -> TranslationError {
+        TranslationError::ModelError(format!("שגיאת ONNX Runtime: {}", error))
+    }
+}
+
+impl TranslationBackend for OnnxTranslator {
+    fn translate(&self, input: &[String]) -> Result<Vec<String>, TranslationError> {
+        OnnxTranslator::translate(self, input)
+    }
+
+    fn translate_scored(&self, input: &[String]) -> Result<Vec<(String, Option<f64>)>, TranslationError> {
+        Ok(OnnxTranslator::translate_scored(self, input)?
+            .into_iter()
+            .map(|(text, probability)| (text, Some(probability)))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_missing_files_are_reported() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join(CONFIG_FILE),
+            r#"{"decoder_start_token_id": 0, "eos_token_id": 1, "pad_token_id": 0}"#,
+        ).unwrap();
+
+        let error = OnnxTranslator::load(temp_dir.path()).err().unwrap();
+        assert!(error.to_string().contains(ENCODER_FILE));
+    }
+
+    #[test]
+    fn test_config_defaults() {
+        let config: OnnxModelConfig = serde_json::from_str(
+            r#"{"decoder_start_token_id": 61586, "eos_token_id": 0, "pad_token_id": 61586}"#,
+        ).unwrap();
+        assert_eq!(config.max_length, 256);
+    }
+}
@@ -0,0 +1,633 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Instant;
+use serde::{Serialize, Deserialize};
+use tch::nn;
+use crate::evaluation::Evaluator;
+use crate::tokenizer::Tokenizer;
+use super::model::TranslationError;
+use super::safetensors::{Safetensors, SafetensorsError, SafetensorsWriter};
+use super::{greedy_token, sequence_probability};
+
+/// ערך __metadata__ שמזהה קובץ safetensors של QuantizedTranslator
+const SAFETENSORS_FORMAT: &str = "rustohebru-int8";
+
+/// מטריצה מקוונטטת ל-int8 עם קנה מידה סימטרי לכל שורה
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuantizedMatrix {
+    pub rows: usize,
+    pub cols: usize,
+    data: Vec<i8>,
+    scales: Vec<f32>,
+}
+
+impl QuantizedMatrix {
+    pub fn quantize(values: &[f32], rows: usize, cols: usize) -> Result<Self, TranslationError> {
+        if rows == 0 || cols == 0 {
+            return Err(TranslationError::ModelError(format!("לא ניתן לקוונטט מטריצה ריקה ({}x{})", rows, cols)));
+        }
+        if rows.checked_mul(cols) != Some(values.len()) {
+            return Err(TranslationError::ModelError(format!(
+                "גודל המטריצה {}x{} אינו תואם ל-{} ערכים",
+                rows, cols, values.len()
+            )));
+        }
+
+        let mut data = Vec::with_capacity(values.len());
+        let mut scales = Vec::with_capacity(rows);
+
+        for row in values.chunks(cols) {
+            let max_abs = row.iter().fold(0.0f32, |m, v| m.max(v.abs()));
+            let scale = if max_abs > 0.0 { max_abs / 127.0 } else { 1.0 };
+            scales.push(scale);
+            data.extend(row.iter().map(|v| (v / scale).round().clamp(-127.0, 127.0) as i8));
+        }
+
+        Ok(Self { rows, cols, data, scales })
+    }
+
+    /// שורה בודדת בדיוק מלא (לשליפה מטבלת Embedding)
+    pub fn row(&self, index: usize) -> Vec<f32> {
+        let scale = self.scales[index];
+        self.data[index * self.cols..(index + 1) * self.cols]
+            .iter()
+            .map(|&q| q as f32 * scale)
+            .collect()
+    }
+
+    fn write(&self, writer: &mut SafetensorsWriter, name: &str) {
+        writer.add_i8(format!("{}.weight", name), &[self.rows, self.cols], &self.data);
+        writer.add_f32(format!("{}.scales", name), &[self.rows], &self.scales);
+    }
+
+    fn read(file: &Safetensors, name: &str) -> Result<Self, SafetensorsError> {
+        let (shape, data) = file.i8(&format!("{}.weight", name))?;
+        let (_, scales) = file.f32(&format!("{}.scales", name))?;
+        match shape[..] {
+            [rows, cols] if scales.len() == rows => Ok(Self { rows, cols, data, scales }),
+            _ => Err(SafetensorsError::InvalidOffsets(format!("{}.weight", name))),
+        }
+    }
+
+    pub fn dequantize(&self) -> Vec<f32> {
+        (0..self.rows).flat_map(|r| self.row(r)).collect()
+    }
+
+    /// מכפלה של וקטור מקוונטט בכל השורות, עם צבירה ב-i32
+    fn matvec(&self, input: &[i8], input_scale: f32) -> Vec<f32> {
+        self.data
+            .chunks(self.cols)
+            .zip(&self.scales)
+            .map(|(row, &scale)| {
+                let acc: i32 = row.iter().zip(input).map(|(&w, &x)| w as i32 * x as i32).sum();
+                acc as f32 * scale * input_scale
+            })
+            .collect()
+    }
+}
+
+/// שכבת Linear מקוונטטת: משקולות int8, הטיה בדיוק מלא, והפעלות שמקוונטטות בזמן ריצה
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuantizedLinear {
+    weight: QuantizedMatrix,
+    bias: Vec<f32>,
+    /// טווח חיתוך להפעלות שנקבע בכיול; בלעדיו קנה המידה מחושב לכל וקטור
+    activation_clip: Option<f32>,
+}
+
+impl QuantizedLinear {
+    pub fn new(weight: &[f32], bias: Vec<f32>, out_features: usize, in_features: usize) -> Result<Self, TranslationError> {
+        Ok(Self {
+            weight: QuantizedMatrix::quantize(weight, out_features, in_features)?,
+            bias,
+            activation_clip: None,
+        })
+    }
+
+    pub fn forward(&self, input: &[f32]) -> Vec<f32> {
+        let clip = self.activation_clip
+            .unwrap_or_else(|| input.iter().fold(0.0f32, |m, v| m.max(v.abs())));
+        let scale = if clip > 0.0 { clip / 127.0 } else { 1.0 };
+        let quantized: Vec<i8> = input
+            .iter()
+            .map(|v| (v / scale).round().clamp(-127.0, 127.0) as i8)
+            .collect();
+
+        self.weight
+            .matvec(&quantized, scale)
+            .into_iter()
+            .zip(&self.bias)
+            .map(|(v, b)| v + b)
+            .collect()
+    }
+}
+
+/// פרמטרי LayerNorm (נשארים בדיוק מלא)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayerNormParams {
+    weight: Vec<f32>,
+    bias: Vec<f32>,
+    eps: f32,
+}
+
+impl LayerNormParams {
+    fn forward(&self, input: &[f32]) -> Vec<f32> {
+        let n = input.len() as f32;
+        let mean = input.iter().sum::<f32>() / n;
+        let var = input.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / n;
+        let denom = (var + self.eps).sqrt();
+
+        input
+            .iter()
+            .zip(self.weight.iter().zip(&self.bias))
+            .map(|(v, (w, b))| (v - mean) / denom * w + b)
+            .collect()
+    }
+}
+
+/// שכבה אחת במקודד/מפענח: Linear → ReLU → LayerNorm
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuantizedBlock {
+    linear: QuantizedLinear,
+    norm: LayerNormParams,
+}
+
+impl QuantizedBlock {
+    /// שמות הטנסורים כמו ב-VarStore: {prefix}.linear.weight, {prefix}.norm.bias וכו'
+    fn write(&self, writer: &mut SafetensorsWriter, prefix: &str) {
+        self.linear.weight.write(writer, &format!("{}.linear", prefix));
+        writer.add_f32(format!("{}.linear.bias", prefix), &[self.linear.bias.len()], &self.linear.bias);
+        writer.add_f32(format!("{}.norm.weight", prefix), &[self.norm.weight.len()], &self.norm.weight);
+        writer.add_f32(format!("{}.norm.bias", prefix), &[self.norm.bias.len()], &self.norm.bias);
+        writer.add_f32(format!("{}.norm.eps", prefix), &[1], &[self.norm.eps]);
+        if let Some(clip) = self.linear.activation_clip {
+            writer.add_f32(format!("{}.linear.activation_clip", prefix), &[1], &[clip]);
+        }
+    }
+
+    fn read(file: &Safetensors, prefix: &str) -> Result<Self, SafetensorsError> {
+        let clip = format!("{}.linear.activation_clip", prefix);
+        Ok(Self {
+            linear: QuantizedLinear {
+                weight: QuantizedMatrix::read(file, &format!("{}.linear", prefix))?,
+                bias: file.f32(&format!("{}.linear.bias", prefix))?.1,
+                activation_clip: if file.contains(&clip) { file.f32(&clip)?.1.first().copied() } else { None },
+            },
+            norm: LayerNormParams {
+                weight: file.f32(&format!("{}.norm.weight", prefix))?.1,
+                bias: file.f32(&format!("{}.norm.bias", prefix))?.1,
+                eps: file.f32(&format!("{}.norm.eps", prefix))?.1.first().copied().unwrap_or(1e-5),
+            },
+        })
+    }
+
+    fn forward(&self, input: &[f32]) -> Vec<f32> {
+        let hidden: Vec<f32> = self.linear.forward(input).into_iter().map(|v| v.max(0.0)).collect();
+        self.norm.forward(&hidden)
+    }
+}
+
+/// תוצאות הכיול: טווח החיתוך שנבחר לכל שכבה
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CalibrationStats {
+    pub samples: usize,
+    pub percentile: f64,
+    pub clips: HashMap<String, f32>,
+}
+
+/// גרסת int8 של EnhancedTransformer להסקה על CPU
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuantizedTransformer {
+    d_model: usize,
+    embedding: QuantizedMatrix,
+    encoder: Vec<QuantizedBlock>,
+    decoder: Vec<QuantizedBlock>,
+    calibration: Option<CalibrationStats>,
+}
+
+impl QuantizedTransformer {
+    /// אחוזון ברירת המחדל לחיתוך הפעלות חריגות בכיול
+    pub const DEFAULT_PERCENTILE: f64 = 99.9;
+
+    pub fn new(d_model: usize, embedding: QuantizedMatrix, encoder: Vec<QuantizedBlock>, decoder: Vec<QuantizedBlock>) -> Self {
+        Self {
+            d_model,
+            embedding,
+            encoder,
+            decoder,
+            calibration: None,
+        }
+    }
+
+    /// קריאת המשקולות מה-VarStore של EnhancedTransformer לפי שמות השכבות
+    pub fn from_var_store(vs: &nn::VarStore, d_model: i64, num_layers: usize) -> Result<Self, TranslationError> {
+        let vars = vs.variables();
+        let read = |name: &str| -> Result<(Vec<f32>, Vec<i64>), TranslationError> {
+            let tensor = vars
+                .get(name)
+                .ok_or_else(|| TranslationError::ModelError(format!("משתנה חסר במודל: {}", name)))?;
+            let values = Vec::<f32>::try_from(&tensor.flatten(0, -1))
+                .map_err(|e| TranslationError::ModelError(e.to_string()))?;
+            Ok((values, tensor.size()))
+        };
+
+        let block = |prefix: String| -> Result<QuantizedBlock, TranslationError> {
+            let (weight, shape) = read(&format!("{}.linear.weight", prefix))?;
+            let (bias, _) = read(&format!("{}.linear.bias", prefix))?;
+            let (norm_weight, _) = read(&format!("{}.norm.weight", prefix))?;
+            let (norm_bias, _) = read(&format!("{}.norm.bias", prefix))?;
+            Ok(QuantizedBlock {
+                linear: QuantizedLinear::new(&weight, bias, shape[0] as usize, shape[1] as usize)?,
+                norm: LayerNormParams { weight: norm_weight, bias: norm_bias, eps: 1e-5 },
+            })
+        };
+
+        let (embedding, shape) = read("embedding.weight")?;
+        let embedding = QuantizedMatrix::quantize(&embedding, shape[0] as usize, shape[1] as usize)?;
+
+        let encoder = (0..num_layers).map(|i| block(format!("encoder.{}", i))).collect::<Result<_, _>>()?;
+        let decoder = (0..num_layers).map(|i| block(format!("decoder.{}", i))).collect::<Result<_, _>>()?;
+
+        Ok(Self::new(d_model as usize, embedding, encoder, decoder))
+    }
+
+    /// הרצת המודל על רצף טוקנים והחזרת הטוקן המוביל בכל מיקום (כמו EnhancedTransformer::decode)
+    pub fn forward_tokens(&self, tokens: &[i64]) -> Vec<i64> {
+        self.forward_tokens_scored(tokens).0
+    }
+
+    /// כמו forward_tokens, יחד עם הסתברות הרצף (ממוצע גיאומטרי של הסתברויות הטוקנים); לרצף ריק 1.0
+    pub fn forward_tokens_scored(&self, tokens: &[i64]) -> (Vec<i64>, f64) {
+        let mut log_prob = 0.0;
+        let output = tokens
+            .iter()
+            .map(|&token| {
+                let mut hidden = self.embed(token);
+                for block in self.encoder.iter().chain(&self.decoder) {
+                    hidden = block.forward(&hidden);
+                }
+                let (next, token_log_prob) = self.project(&hidden);
+                log_prob += token_log_prob;
+                next
+            })
+            .collect();
+        (output, sequence_probability(log_prob, tokens.len()).unwrap_or(1.0))
+    }
+
+    fn embed(&self, token: i64) -> Vec<f32> {
+        let index = (token.max(0) as usize).min(self.embedding.rows.saturating_sub(1));
+        self.embedding.row(index)
+    }
+
+    /// הטלה למילון דרך מטריצת ה-Embedding המקוונטטת: הטוקן המוביל ולוג-ההסתברות שלו
+    fn project(&self, hidden: &[f32]) -> (i64, f64) {
+        let max_abs = hidden.iter().fold(0.0f32, |m, v| m.max(v.abs()));
+        let scale = if max_abs > 0.0 { max_abs / 127.0 } else { 1.0 };
+        let quantized: Vec<i8> = hidden.iter().map(|v| (v / scale).round().clamp(-127.0, 127.0) as i8).collect();
+
+        let (token, log_prob) = greedy_token(&self.embedding.matvec(&quantized, scale));
+        (token as i64, log_prob)
+    }
+
+    /// כיול: מעבר על קורפוס לדוגמה, איסוף טווחי ההפעלות בכניסה לכל שכבה
+    /// וקביעת טווח חיתוך קבוע לפי אחוזון
+    pub fn calibrate<I>(&mut self, samples: I, percentile: f64) -> CalibrationStats
+    where
+        I: IntoIterator<Item = Vec<i64>>
+    {
+        let num_blocks = self.encoder.len() + self.decoder.len();
+        let mut observed: Vec<Vec<f32>> = vec![Vec::new(); num_blocks];
+        let mut count = 0;
+
+        for tokens in samples {
+            count += 1;
+            for &token in &tokens {
+                let mut hidden = self.embed(token);
+                for (i, block) in self.encoder.iter().chain(&self.decoder).enumerate() {
+                    observed[i].push(hidden.iter().fold(0.0f32, |m, v| m.max(v.abs())));
+                    hidden = block.forward(&hidden);
+                }
+            }
+        }
+
+        let mut stats = CalibrationStats {
+            samples: count,
+            percentile,
+            clips: HashMap::new(),
+        };
+
+        let num_encoder = self.encoder.len();
+        for (i, mut values) in observed.into_iter().enumerate() {
+            if values.is_empty() {
+                continue;
+            }
+            values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+            let rank = ((percentile / 100.0) * (values.len() - 1) as f64).round() as usize;
+            let clip = values[rank.min(values.len() - 1)];
+
+            let (name, block) = if i < num_encoder {
+                (format!("encoder.{}", i), &mut self.encoder[i])
+            } else {
+                (format!("decoder.{}", i - num_encoder), &mut self.decoder[i - num_encoder])
+            };
+            block.linear.activation_clip = Some(clip);
+            stats.clips.insert(name, clip);
+        }
+
+        self.calibration = Some(stats.clone());
+        stats
+    }
+
+    pub fn calibration(&self) -> Option<&CalibrationStats> {
+        self.calibration.as_ref()
+    }
+}
+
+/// מתרגם מקוונטט: מודל int8 יחד עם הטוקנייזר שלו
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuantizedTranslator {
+    model: QuantizedTransformer,
+    tokenizer: Tokenizer,
+}
+
+impl QuantizedTranslator {
+    pub fn new(model: QuantizedTransformer, tokenizer: Tokenizer) -> Self {
+        Self { model, tokenizer }
+    }
+
+    pub fn translate_str(&self, text: &str) -> Result<String, TranslationError> {
+        self.translate_scored_str(text).map(|(text, _)| text)
+    }
+
+    /// תרגום יחד עם הסתברות המודל לתוצאה, לדירוג מול שאר המנועים
+    pub fn translate_scored_str(&self, text: &str) -> Result<(String, f64), TranslationError> {
+        let tokens = self.tokenizer.tokenize(text);
+        let (output, probability) = self.model.forward_tokens_scored(&tokens);
+        Ok((self.tokenizer.detokenize(&output), probability))
+    }
+
+    pub fn model(&self) -> &QuantizedTransformer {
+        &self.model
+    }
+
+    pub fn tokenizer(&self) -> &Tokenizer {
+        &self.tokenizer
+    }
+
+    /// שמירה בפורמט safetensors: המשקולות כטנסורי I8/F32 גולמיים, והטוקנייזר, מבנה השכבות
+    /// ונתוני הכיול כ-JSON ב-__metadata__
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), TranslationError> {
+        std::fs::write(path, self.to_safetensors()?).map_err(|e| TranslationError::ModelError(e.to_string()))
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, TranslationError> {
+        let bytes = std::fs::read(path).map_err(|e| TranslationError::ModelError(e.to_string()))?;
+        Self::from_safetensors(&bytes)
+    }
+
+    pub fn to_safetensors(&self) -> Result<Vec<u8>, TranslationError> {
+        let model = &self.model;
+        let mut writer = SafetensorsWriter::new();
+        writer.add_metadata("format", SAFETENSORS_FORMAT);
+        writer.add_metadata("d_model", model.d_model.to_string());
+        writer.add_metadata("encoder_layers", model.encoder.len().to_string());
+        writer.add_metadata("decoder_layers", model.decoder.len().to_string());
+        writer.add_metadata("tokenizer", serde_json::to_string(&self.tokenizer).map_err(model_error)?);
+        if let Some(calibration) = &model.calibration {
+            writer.add_metadata("calibration", serde_json::to_string(calibration).map_err(model_error)?);
+        }
+
+        model.embedding.write(&mut writer, "embedding");
+        for (i, block) in model.encoder.iter().enumerate() {
+            block.write(&mut writer, &format!("encoder.{}", i));
+        }
+        for (i, block) in model.decoder.iter().enumerate() {
+            block.write(&mut writer, &format!("decoder.{}", i));
+        }
+
+        writer.to_bytes().map_err(model_error)
+    }
+
+    pub fn from_safetensors(bytes: &[u8]) -> Result<Self, TranslationError> {
+        let file = Safetensors::from_bytes(bytes).map_err(model_error)?;
+        let metadata = file.metadata();
+        let field = |key: &str| -> Result<&str, TranslationError> {
+            metadata
+                .get(key)
+                .map(String::as_str)
+                .ok_or_else(|| TranslationError::ModelError(format!("חסר השדה {} ב-__metadata__", key)))
+        };
+        if field("format")? != SAFETENSORS_FORMAT {
+            return Err(TranslationError::ModelError(format!("הקובץ אינו מודל מקוונטט ({})", field("format")?)));
+        }
+        let count = |key: &str| -> Result<usize, TranslationError> { field(key)?.parse().map_err(model_error) };
+
+        let encoder = (0..count("encoder_layers")?)
+            .map(|i| QuantizedBlock::read(&file, &format!("encoder.{}", i)))
+            .collect::<Result<_, _>>()
+            .map_err(model_error)?;
+        let decoder = (0..count("decoder_layers")?)
+            .map(|i| QuantizedBlock::read(&file, &format!("decoder.{}", i)))
+            .collect::<Result<_, _>>()
+            .map_err(model_error)?;
+        let mut model = QuantizedTransformer::new(
+            count("d_model")?,
+            QuantizedMatrix::read(&file, "embedding").map_err(model_error)?,
+            encoder,
+            decoder,
+        );
+        model.calibration = metadata
+            .get("calibration")
+            .map(|json| serde_json::from_str(json))
+            .transpose()
+            .map_err(model_error)?;
+        let tokenizer = serde_json::from_str(field("tokenizer")?).map_err(model_error)?;
+
+        Ok(Self::new(model, tokenizer))
+    }
+}
+
+fn model_error(error: impl std::fmt::Display) -> TranslationError {
+    TranslationError::ModelError(error.to_string())
+}
+
+/// דוח השוואה בין המודל המלא למודל המקוונטט
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuantizationReport {
+    pub segments: usize,
+    pub full_seconds: f64,
+    pub quantized_seconds: f64,
+    pub speedup: f64,
+    /// BLEU קורפוס בסקאלה של sacreBLEU (0-100)
+    pub full_bleu: f64,
+    pub quantized_bleu: f64,
+    /// BLEU מקוונטט פחות BLEU מלא (שלילי = ירידה באיכות)
+    pub bleu_delta: f64,
+}
+
+/// מדידת זמן ואיכות של שני מתרגמים על אותו קורפוס, עם BLEU קורפוס מתוך Evaluator.
+/// מקור בלי תרגום ייחוס הוא שגיאה, ולא ייחוס ריק שמוריד את הציון
+pub fn benchmark<F, Q>(
+    sources: &[String],
+    evaluator: &Evaluator,
+    full: F,
+    quantized: Q,
+) -> Result<QuantizationReport, TranslationError>
+where
+    F: Fn(&str) -> Result<String, TranslationError>,
+    Q: Fn(&str) -> Result<String, TranslationError>,
+{
+    let run = |translate: &dyn Fn(&str) -> Result<String, TranslationError>| -> Result<(f64, f64), TranslationError> {
+        let start = Instant::now();
+        let outputs = sources.iter().map(|s| translate(s)).collect::<Result<Vec<_>, _>>()?;
+        let elapsed = start.elapsed().as_secs_f64();
+
+        let scores = evaluator
+            .evaluate_corpus(sources, &outputs)
+            .map_err(|e| TranslationError::ModelError(format!("שגיאה בחישוב BLEU: {}", e)))?;
+
+        Ok((elapsed, scores.bleu.score))
+    };
+
+    let (full_seconds, full_bleu) = run(&full)?;
+    let (quantized_seconds, quantized_bleu) = run(&quantized)?;
+
+    Ok(QuantizationReport {
+        segments: sources.len(),
+        full_seconds,
+        quantized_seconds,
+        speedup: if quantized_seconds > 0.0 { full_seconds / quantized_seconds } else { 0.0 },
+        full_bleu,
+        quantized_bleu,
+        bleu_delta: quantized_bleu - full_bleu,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluation::{FormalityLevel, StyleGuide};
+
+    fn create_test_model() -> QuantizedTransformer {
+        let d_model = 4;
+        let vocab = 6;
+        let embedding: Vec<f32> = (0..vocab * d_model).map(|i| ((i * 7 % 11) as f32 - 5.0) / 5.0).collect();
+        let identity: Vec<f32> = (0..d_model * d_model)
+            .map(|i| if i % (d_model + 1) == 0 { 1.0 } else { 0.0 })
+            .collect();
+        let block = QuantizedBlock {
+            linear: QuantizedLinear::new(&identity, vec![0.0; d_model], d_model, d_model).unwrap(),
+            norm: LayerNormParams { weight: vec![1.0; d_model], bias: vec![0.0; d_model], eps: 1e-5 },
+        };
+
+        QuantizedTransformer::new(
+            d_model,
+            QuantizedMatrix::quantize(&embedding, vocab, d_model).unwrap(),
+            vec![block.clone()],
+            vec![block],
+        )
+    }
+
+    #[test]
+    fn test_quantization_error_is_bounded() {
+        let values: Vec<f32> = (0..64).map(|i| (i as f32 - 32.0) / 7.0).collect();
+        let matrix = QuantizedMatrix::quantize(&values, 8, 8).unwrap();
+
+        for (original, restored) in values.iter().zip(matrix.dequantize()) {
+            assert!((original - restored).abs() < 0.05);
+        }
+    }
+
+    #[test]
+    fn test_empty_or_mismatched_shapes_are_errors() {
+        assert!(QuantizedMatrix::quantize(&[], 3, 0).is_err());
+        assert!(QuantizedMatrix::quantize(&[], 0, 3).is_err());
+        assert!(QuantizedMatrix::quantize(&[1.0, 2.0, 3.0], 2, 2).is_err());
+    }
+
+    #[test]
+    fn test_linear_matches_full_precision() {
+        let weight = vec![0.5, -1.0, 0.25, 2.0, 0.0, -0.75];
+        let linear = QuantizedLinear::new(&weight, vec![0.1, -0.2], 2, 3).unwrap();
+        let output = linear.forward(&[1.0, 0.5, -2.0]);
+
+        assert!((output[0] - (0.5 - 0.5 - 0.5 + 0.1)).abs() < 0.05);
+        assert!((output[1] - (2.0 + 0.0 + 1.5 - 0.2)).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_calibration_sets_clip_per_layer() {
+        let mut model = create_test_model();
+        let stats = model.calibrate(vec![vec![1, 2, 3], vec![4, 5]], 99.0);
+
+        assert_eq!(stats.samples, 2);
+        assert!(stats.clips.contains_key("encoder.0"));
+        assert!(stats.clips.contains_key("decoder.0"));
+        assert_eq!(model.forward_tokens(&[1, 2, 3]).len(), 3);
+    }
+
+    #[test]
+    fn test_forward_tokens_scored() {
+        let model = create_test_model();
+        let (tokens, probability) = model.forward_tokens_scored(&[1, 2, 3]);
+
+        assert_eq!(tokens, model.forward_tokens(&[1, 2, 3]));
+        assert!(probability > 0.0 && probability <= 1.0);
+        assert_eq!(model.forward_tokens_scored(&[]).1, 1.0);
+
+        // softmax על שני logits שווים: לכל טוקן חצי
+        let (_, log_prob) = greedy_token(&[2.0, 2.0]);
+        assert!((log_prob.exp() - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let translator = QuantizedTranslator::new(create_test_model(), Tokenizer::new());
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("model.int8.safetensors");
+
+        translator.save(&path).unwrap();
+        let loaded = QuantizedTranslator::load(&path).unwrap();
+
+        assert_eq!(
+            loaded.model().forward_tokens(&[1, 2, 3]),
+            translator.model().forward_tokens(&[1, 2, 3])
+        );
+
+        // הקובץ הוא safetensors: אורך כותרת, כותרת JSON ונתונים גולמיים
+        let bytes = std::fs::read(&path).unwrap();
+        let header_len = u64::from_le_bytes(bytes[..8].try_into().unwrap()) as usize;
+        let header: serde_json::Value = serde_json::from_slice(&bytes[8..8 + header_len]).unwrap();
+        assert_eq!(header["embedding.weight"]["dtype"], "I8");
+        assert_eq!(header["embedding.weight"]["shape"], serde_json::json!([6, 4]));
+        assert_eq!(header["__metadata__"]["format"], SAFETENSORS_FORMAT);
+    }
+
+    #[test]
+    fn test_calibration_survives_save() {
+        let mut model = create_test_model();
+        model.calibrate(vec![vec![1, 2, 3]], 99.0);
+        let translator = QuantizedTranslator::new(model, Tokenizer::new());
+
+        let loaded = QuantizedTranslator::from_safetensors(&translator.to_safetensors().unwrap()).unwrap();
+        assert_eq!(
+            loaded.model().encoder[0].linear.activation_clip,
+            translator.model().encoder[0].linear.activation_clip
+        );
+        assert_eq!(loaded.model().forward_tokens_scored(&[4, 5]), translator.model().forward_tokens_scored(&[4, 5]));
+    }
+
+    #[test]
+    fn test_benchmark_uses_corpus_bleu_and_requires_references() {
+        let references = HashMap::from([("закрыть задвижку".to_string(), "יש לסגור את המגוף הראשי מיד".to_string())]);
+        let evaluator = Evaluator::new(references, HashMap::new(), StyleGuide::new(FormalityLevel::Formal));
+        let perfect = |_: &str| Ok("יש לסגור את המגוף הראשי מיד".to_string());
+
+        let report = benchmark(&["закрыть задвижку".to_string()], &evaluator, perfect, perfect).unwrap();
+        assert!((report.full_bleu - 100.0).abs() < 1e-9);
+        assert_eq!(report.bleu_delta, 0.0);
+
+        let unreferenced = ["открыть задвижку".to_string()];
+        assert!(benchmark(&unreferenced, &evaluator, perfect, perfect).is_err());
+    }
+}
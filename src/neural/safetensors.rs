@@ -0,0 +1,228 @@
+//! קריאה וכתיבה של קבצי safetensors: 8 בתים (u64 little-endian) של אורך הכותרת, כותרת JSON
+//! עם סוג, צורה וטווח הבתים של כל טנסור, ואחריה הנתונים הגולמיים ברצף.
+//! נתמכים רק הסוגים שהמודל המקוונטט צריך: I8 ו-F32
+
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+const METADATA_KEY: &str = "__metadata__";
+
+#[derive(Debug, Error)]
+pub enum SafetensorsError {
+    #[error("הקובץ קצר מכדי להכיל את הכותרת")]
+    Truncated,
+    #[error("כותרת לא תקינה: {0}")]
+    Header(#[from] serde_json::Error),
+    #[error("הטנסור {0} חסר")]
+    MissingTensor(String),
+    #[error("הטנסור {name} מסוג {actual:?} ולא {expected:?}")]
+    WrongDtype { name: String, expected: Dtype, actual: Dtype },
+    #[error("טווח הבתים של הטנסור {0} אינו תואם לצורתו או חורג מהנתונים")]
+    InvalidOffsets(String),
+    #[error("גודל הטנסור {0} לפי צורתו חורג מטווח הכתובות")]
+    ShapeOverflow(String),
+}
+
+/// סוג האיברים בטנסור, בשמות של התקן
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Dtype {
+    I8,
+    F32,
+}
+
+impl Dtype {
+    fn size(self) -> usize {
+        match self {
+            Dtype::I8 => 1,
+            Dtype::F32 => 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TensorInfo {
+    dtype: Dtype,
+    shape: Vec<usize>,
+    /// תחילת הטווח וסופו, ביחס לתחילת הנתונים שאחרי הכותרת
+    data_offsets: (usize, usize),
+}
+
+/// בניית קובץ safetensors בזיכרון, טנסור אחר טנסור
+#[derive(Debug, Default)]
+pub struct SafetensorsWriter {
+    tensors: Vec<(String, TensorInfo)>,
+    data: Vec<u8>,
+    metadata: BTreeMap<String, String>,
+}
+
+impl SafetensorsWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_i8(&mut self, name: impl Into<String>, shape: &[usize], values: &[i8]) {
+        let bytes: Vec<u8> = values.iter().map(|&v| v as u8).collect();
+        self.add(name.into(), Dtype::I8, shape, &bytes);
+    }
+
+    pub fn add_f32(&mut self, name: impl Into<String>, shape: &[usize], values: &[f32]) {
+        let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        self.add(name.into(), Dtype::F32, shape, &bytes);
+    }
+
+    /// ערכי __metadata__ של הקובץ (מחרוזות בלבד, לפי התקן)
+    pub fn add_metadata(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.metadata.insert(key.into(), value.into());
+    }
+
+    fn add(&mut self, name: String, dtype: Dtype, shape: &[usize], bytes: &[u8]) {
+        let start = self.data.len();
+        self.data.extend_from_slice(bytes);
+        self.tensors.push((name, TensorInfo { dtype, shape: shape.to_vec(), data_offsets: (start, self.data.len()) }));
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SafetensorsError> {
+        let mut header = Map::new();
+        if !self.metadata.is_empty() {
+            header.insert(METADATA_KEY.to_string(), serde_json::to_value(&self.metadata)?);
+        }
+        for (name, info) in &self.tensors {
+            header.insert(name.clone(), serde_json::to_value(info)?);
+        }
+
+        // הכותרת מרופדת ברווחים לכפולה של 8, כדי שהנתונים יתחילו בכתובת מיושרת
+        let mut header = serde_json::to_vec(&Value::Object(header))?;
+        header.resize(header.len().div_ceil(8) * 8, b' ');
+
+        let mut bytes = Vec::with_capacity(8 + header.len() + self.data.len());
+        bytes.extend_from_slice(&(header.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&header);
+        bytes.extend_from_slice(&self.data);
+        Ok(bytes)
+    }
+}
+
+/// קובץ safetensors שנקרא לזיכרון ונבדק
+#[derive(Debug)]
+pub struct Safetensors {
+    tensors: BTreeMap<String, TensorInfo>,
+    metadata: BTreeMap<String, String>,
+    data: Vec<u8>,
+}
+
+impl Safetensors {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SafetensorsError> {
+        let length: [u8; 8] = bytes.get(..8).ok_or(SafetensorsError::Truncated)?.try_into().unwrap();
+        let header_end = usize::try_from(u64::from_le_bytes(length))
+            .ok()
+            .and_then(|length| length.checked_add(8))
+            .filter(|&end| end <= bytes.len())
+            .ok_or(SafetensorsError::Truncated)?;
+
+        let mut header: Map<String, Value> = serde_json::from_slice(&bytes[8..header_end])?;
+        let metadata = match header.remove(METADATA_KEY) {
+            Some(value) => serde_json::from_value(value)?,
+            None => BTreeMap::new(),
+        };
+        let data = bytes[header_end..].to_vec();
+
+        let mut tensors = BTreeMap::new();
+        for (name, value) in header {
+            let info: TensorInfo = serde_json::from_value(value)?;
+            let (start, end) = info.data_offsets;
+            // צורה מקובץ זדוני או פגום עלולה לגלוש בכפל
+            let expected = info.shape
+                .iter()
+                .try_fold(info.dtype.size(), |size, &dim| size.checked_mul(dim))
+                .ok_or_else(|| SafetensorsError::ShapeOverflow(name.clone()))?;
+            if start > end || end > data.len() || end - start != expected {
+                return Err(SafetensorsError::InvalidOffsets(name));
+            }
+            tensors.insert(name, info);
+        }
+
+        Ok(Self { tensors, metadata, data })
+    }
+
+    pub fn metadata(&self) -> &BTreeMap<String, String> {
+        &self.metadata
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.tensors.contains_key(name)
+    }
+
+    /// טנסור I8 עם צורתו
+    pub fn i8(&self, name: &str) -> Result<(Vec<usize>, Vec<i8>), SafetensorsError> {
+        let (shape, bytes) = self.bytes(name, Dtype::I8)?;
+        Ok((shape, bytes.iter().map(|&b| b as i8).collect()))
+    }
+
+    /// טנסור F32 עם צורתו
+    pub fn f32(&self, name: &str) -> Result<(Vec<usize>, Vec<f32>), SafetensorsError> {
+        let (shape, bytes) = self.bytes(name, Dtype::F32)?;
+        Ok((shape, bytes.chunks_exact(4).map(|b| f32::from_le_bytes(b.try_into().unwrap())).collect()))
+    }
+
+    fn bytes(&self, name: &str, expected: Dtype) -> Result<(Vec<usize>, &[u8]), SafetensorsError> {
+        let info = self.tensors.get(name).ok_or_else(|| SafetensorsError::MissingTensor(name.to_string()))?;
+        if info.dtype != expected {
+            return Err(SafetensorsError::WrongDtype { name: name.to_string(), expected, actual: info.dtype });
+        }
+        let (start, end) = info.data_offsets;
+        Ok((info.shape.clone(), &self.data[start..end]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_and_layout() {
+        let mut writer = SafetensorsWriter::new();
+        writer.add_i8("weight", &[2, 2], &[-127, 0, 5, 127]);
+        writer.add_f32("scales", &[2], &[0.5, -1.25]);
+        writer.add_metadata("format", "int8");
+        let bytes = writer.to_bytes().unwrap();
+
+        let header_len = u64::from_le_bytes(bytes[..8].try_into().unwrap()) as usize;
+        assert_eq!(header_len % 8, 0);
+        assert_eq!(bytes.len(), 8 + header_len + 4 + 8);
+        let header: Value = serde_json::from_slice(&bytes[8..8 + header_len]).unwrap();
+        assert_eq!(header["weight"]["dtype"], "I8");
+        assert_eq!(header["scales"]["data_offsets"], serde_json::json!([4, 12]));
+
+        let file = Safetensors::from_bytes(&bytes).unwrap();
+        assert_eq!(file.i8("weight").unwrap(), (vec![2, 2], vec![-127, 0, 5, 127]));
+        assert_eq!(file.f32("scales").unwrap(), (vec![2], vec![0.5, -1.25]));
+        assert_eq!(file.metadata()["format"], "int8");
+        assert!(matches!(file.f32("weight"), Err(SafetensorsError::WrongDtype { .. })));
+        assert!(matches!(file.i8("bias"), Err(SafetensorsError::MissingTensor(_))));
+    }
+
+    #[test]
+    fn test_rejects_truncated_data() {
+        let mut writer = SafetensorsWriter::new();
+        writer.add_f32("scales", &[2], &[0.5, -1.25]);
+        let bytes = writer.to_bytes().unwrap();
+
+        assert!(matches!(Safetensors::from_bytes(&bytes[..bytes.len() - 1]), Err(SafetensorsError::InvalidOffsets(_))));
+        assert!(matches!(Safetensors::from_bytes(&bytes[..4]), Err(SafetensorsError::Truncated)));
+    }
+
+    #[test]
+    fn test_rejects_overflowing_shape() {
+        let header = format!(
+            r#"{{"huge":{{"dtype":"F32","shape":[{},{}],"data_offsets":[0,0]}}}}"#,
+            usize::MAX / 2,
+            3
+        );
+        let mut bytes = (header.len() as u64).to_le_bytes().to_vec();
+        bytes.extend_from_slice(header.as_bytes());
+
+        assert!(matches!(Safetensors::from_bytes(&bytes), Err(SafetensorsError::ShapeOverflow(name)) if name == "huge"));
+    }
+}
@@ -0,0 +1,74 @@
+use std::sync::Mutex;
+use std::sync::mpsc;
+use std::thread::ThreadId;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use crate::translation_models::TranslationError;
+use super::TranslationBackend;
+
+/// מנוע בדיקה משותף: רושם את גודל כל אצווה ואת ה-thread שבו רצה, ומחזיר פלט קבוע או את הקלט עצמו
+pub(crate) struct RecordingBackend {
+    output: Option<String>,
+    batch_sizes: Mutex<Vec<usize>>,
+    threads: Mutex<Vec<ThreadId>>,
+    /// מודיע על כל אצווה שהתחילה לרוץ
+    started: Option<UnboundedSender<usize>>,
+    /// כל אצווה ממתינה לאישור מהבדיקה לפני שהיא מסתיימת
+    gate: Option<Mutex<mpsc::Receiver<()>>>,
+}
+
+impl RecordingBackend {
+    /// מנוע שמחזיר את הקלט כפי שהוא
+    pub(crate) fn echo() -> Self {
+        Self {
+            output: None,
+            batch_sizes: Mutex::new(Vec::new()),
+            threads: Mutex::new(Vec::new()),
+            started: None,
+            gate: None,
+        }
+    }
+
+    /// מנוע שמחזיר אותו תרגום לכל סגמנט
+    pub(crate) fn fixed(output: &str) -> Self {
+        Self { output: Some(output.to_string()), ..Self::echo() }
+    }
+
+    /// מנוע שמודיע כשאצווה מתחילה ומסיים אותה רק אחרי אישור דרך הערוץ המוחזר
+    pub(crate) fn gated() -> (Self, UnboundedReceiver<usize>, mpsc::Sender<()>) {
+        let (started, started_receiver) = unbounded_channel();
+        let (release, gate) = mpsc::channel();
+        let backend = Self {
+            started: Some(started),
+            gate: Some(Mutex::new(gate)),
+            ..Self::echo()
+        };
+        (backend, started_receiver, release)
+    }
+
+    pub(crate) fn batch_sizes(&self) -> Vec<usize> {
+        self.batch_sizes.lock().unwrap().clone()
+    }
+
+    pub(crate) fn threads(&self) -> Vec<ThreadId> {
+        self.threads.lock().unwrap().clone()
+    }
+}
+
+impl TranslationBackend for RecordingBackend {
+    fn translate(&self, input: &[String]) -> Result<Vec<String>, TranslationError> {
+        self.batch_sizes.lock().unwrap().push(input.len());
+        self.threads.lock().unwrap().push(std::thread::current().id());
+
+        if let Some(started) = &self.started {
+            let _ = started.send(input.len());
+        }
+        if let Some(gate) = &self.gate {
+            gate.lock().unwrap().recv().map_err(|e| TranslationError::ModelError(e.to_string()))?;
+        }
+
+        Ok(input
+            .iter()
+            .map(|text| self.output.clone().unwrap_or_else(|| text.clone()))
+            .collect())
+    }
+}
@@ -1,7 +1,18 @@
-use tch::{nn, Device, Tensor, Kind};
+use tch::{Device, Tensor, Kind};
 use serde::{Serialize, Deserialize};
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 use crate::neural::model::{EnhancedTransformer, TranslationError};
+use crate::neural::corpus::{CorpusConfig, CorpusPipeline, CorpusSplits, SentencePair};
+use crate::neural::checkpoint::{
+    epoch_order, AdamConfig, CheckpointError, CheckpointManager, CheckpointState, ReleaseInfo, StatefulAdam,
+};
+use crate::neural::model_storage::ModelStorage;
+use crate::neural::vocabulary::VocabularyArtifact;
+use crate::tokenizer::Tokenizer;
+
+const TOKENIZER_FILE: &str = "tokenizer.json";
+const TRAINER_CONFIG_FILE: &str = "trainer.json";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TrainingConfig {
@@ -11,6 +22,45 @@ pub struct TrainingConfig {
     pub min_freq: usize,
     pub max_seq_length: usize,
     pub validation_split: f64,
+    /// צמד השפות של המודל, לבדיקת השפה בסינון הקורפוס
+    #[serde(default = "default_source_lang")]
+    pub source_lang: String,
+    #[serde(default = "default_target_lang")]
+    pub target_lang: String,
+    /// זרע לערבוב הנתונים, כדי שחלוקת האימון/אימות תהיה ניתנת לשחזור
+    #[serde(default = "default_seed")]
+    pub seed: u64,
+    /// תיקיית נקודות הביקורת; אם מוגדרת, האימון ממשיך אוטומטית מהנקודה האחרונה
+    #[serde(default)]
+    pub checkpoint_dir: Option<PathBuf>,
+    /// כל כמה צעדים לשמור נקודת ביקורת (0 - רק בסוף כל אפוק)
+    #[serde(default)]
+    pub checkpoint_every: usize,
+    #[serde(default = "default_keep_last_checkpoints")]
+    pub keep_last_checkpoints: usize,
+    /// מספר נקודות הביקורת האחרונות שממוצעות למודל השחרור
+    #[serde(default = "default_average_last")]
+    pub average_last: usize,
+}
+
+fn default_source_lang() -> String {
+    "ru".to_string()
+}
+
+fn default_target_lang() -> String {
+    "he".to_string()
+}
+
+fn default_seed() -> u64 {
+    42
+}
+
+fn default_keep_last_checkpoints() -> usize {
+    5
+}
+
+fn default_average_last() -> usize {
+    3
 }
 
 impl Default for TrainingConfig {
@@ -22,6 +72,13 @@ impl Default for TrainingConfig {
             min_freq: 5,
             max_seq_length: 128,
             validation_split: 0.1,
+            source_lang: default_source_lang(),
+            target_lang: default_target_lang(),
+            seed: default_seed(),
+            checkpoint_dir: None,
+            checkpoint_every: 0,
+            keep_last_checkpoints: default_keep_last_checkpoints(),
+            average_last: default_average_last(),
         }
     }
 }
@@ -38,51 +95,74 @@ pub struct TrainingMetrics {
 
 pub struct Trainer {
     model: EnhancedTransformer,
-    optimizer: nn::Optimizer,
+    optimizer: StatefulAdam,
     config: TrainingConfig,
     device: Device,
+    state: CheckpointState,
 }
 
 impl Trainer {
     pub fn new(model: EnhancedTransformer, config: TrainingConfig) -> Self {
         let device = Device::cuda_if_available();
-        let optimizer = nn::Adam::default().build(&model.trainable_variables(), config.learning_rate)
-            .expect("Failed to create optimizer");
+        let optimizer = StatefulAdam::new(model.var_store(), config.learning_rate, AdamConfig::default());
+        let state = CheckpointState::new(config.seed, config.learning_rate);
             
         Self {
             model,
             optimizer,
             config,
             device,
+            state,
         }
     }
     
+    pub fn model(&self) -> &EnhancedTransformer {
+        &self.model
+    }
+    
+    pub fn state(&self) -> &CheckpointState {
+        &self.state
+    }
+    
     pub fn train<I>(&mut self, train_texts: I) -> Result<Vec<TrainingMetrics>, TranslationError>
     where
         I: IntoIterator<Item = (String, String)>
     {
-        let start_time = Instant::now();
-        let mut metrics = Vec::new();
-        
         // הכנת הנתונים לאימון
         let (train_data, val_data) = self.prepare_data(train_texts)?;
+        self.train_on(&train_data, &val_data)
+    }
+
+    /// אימון על חלוקה שהוכנה מראש על ידי CorpusPipeline::run
+    pub fn train_from_corpus<P: AsRef<Path>>(&mut self, corpus_dir: P) -> Result<Vec<TrainingMetrics>, TranslationError> {
+        let splits = CorpusSplits::load(corpus_dir)
+            .map_err(|e| TranslationError::ModelError(e.to_string()))?;
+        self.train_on(&splits.train, &splits.dev)
+    }
+
+    fn train_on(&mut self, train_data: &[SentencePair], val_data: &[SentencePair]) -> Result<Vec<TrainingMetrics>, TranslationError> {
+        let start_time = Instant::now();
+        let mut metrics = Vec::new();
         
-        // אימון הטוקניזר
-        self.model.train(
-            train_data.iter().map(|(src, _)| src.clone())
-                .chain(train_data.iter().map(|(_, tgt)| tgt.clone())),
-            self.config.min_freq
-        );
+        // המשך מנקודת הביקורת האחרונה; אחרת אימון הטוקניזר ובניית המודל מאפס
+        if !self.resume()? && self.state.step == 0 {
+            self.model.train(
+                train_data.iter().map(|(src, _)| src.clone())
+                    .chain(train_data.iter().map(|(_, tgt)| tgt.clone())),
+                self.config.min_freq
+            );
+            self.optimizer = StatefulAdam::new(self.model.var_store(), self.config.learning_rate, AdamConfig::default());
+        }
         
         // אימון המודל
-        for epoch in 0..self.config.num_epochs {
+        for epoch in self.state.epoch..self.config.num_epochs {
             let epoch_start = Instant::now();
             
-            // אימון על סט האימון
-            let train_metrics = self.train_epoch(&train_data)?;
+            // אימון על סט האימון, מהאצווה שבה נעצרנו
+            let train_metrics = self.train_epoch(train_data, epoch)?;
             
             // בדיקה על סט האימות
-            let val_metrics = self.validate(&val_data)?;
+            let val_metrics = self.validate(val_data)?;
             
             let epoch_time = epoch_start.elapsed().as_secs_f64();
             
@@ -97,7 +177,11 @@ impl Trainer {
             
             metrics.push(epoch_metrics.clone());
             
-            println!(
+            self.state.epoch = epoch + 1;
+            self.state.batch = 0;
+            self.checkpoint()?;
+            
+            log::info!(
                 "Epoch {}/{}: Train Loss = {:.4}, Train Acc = {:.4}, Val Loss = {:.4}, Val Acc = {:.4}, Time = {:.2}s",
                 epoch + 1,
                 self.config.num_epochs,
@@ -110,38 +194,47 @@ impl Trainer {
         }
         
         let total_time = start_time.elapsed().as_secs_f64();
-        println!("Total training time: {:.2}s", total_time);
+        log::info!("Total training time: {:.2}s", total_time);
         
         Ok(metrics)
     }
     
-    fn prepare_data<I>(&self, texts: I) -> Result<(Vec<(String, String)>, Vec<(String, String)>), TranslationError>
+    /// ניקוי וחלוקה של צמדים בזיכרון באותו צינור שמשמש לקורפוסים מקבצים
+    fn prepare_data<I>(&self, texts: I) -> Result<(Vec<SentencePair>, Vec<SentencePair>), TranslationError>
     where
         I: IntoIterator<Item = (String, String)>
     {
-        let mut all_data: Vec<_> = texts.into_iter().collect();
-        
-        // ערבוב הנתונים
-        use rand::seq::SliceRandom;
-        let mut rng = rand::thread_rng();
-        all_data.shuffle(&mut rng);
-        
-        // חלוקה לאימון ואימות
-        let val_size = (all_data.len() as f64 * self.config.validation_split) as usize;
-        let val_data = all_data.split_off(all_data.len() - val_size);
-        
-        Ok((all_data, val_data))
+        let pipeline = CorpusPipeline::new(CorpusConfig {
+            source_lang: self.config.source_lang.clone(),
+            target_lang: self.config.target_lang.clone(),
+            seed: self.config.seed,
+            dev_ratio: self.config.validation_split,
+            test_ratio: 0.0,
+            ..Default::default()
+        });
+
+        let (pairs, stats) = pipeline.filter(texts);
+        log::info!("Corpus: read {}, kept {}", stats.read, stats.kept);
+
+        let splits = pipeline
+            .split(pairs)
+            .map_err(|e| TranslationError::ModelError(format!("שגיאה בחלוקת הקורפוס: {}", e)))?;
+        Ok((splits.train, splits.dev))
     }
     
-    fn train_epoch(&mut self, train_data: &[(String, String)]) -> Result<(f64, f64), TranslationError> {
+    fn train_epoch(&mut self, train_data: &[SentencePair], epoch: usize) -> Result<(f64, f64), TranslationError> {
         let mut total_loss = 0.0;
         let mut total_correct = 0;
         let mut total_tokens = 0;
         
-        // יצירת אצוות
-        for batch in train_data.chunks(self.config.batch_size) {
+        // יצירת אצוות לפי סדר קבוע לאפוק, כדי שאפשר יהיה להמשיך באמצע
+        let order = epoch_order(train_data.len(), self.state.seed, epoch);
+        for batch in order.chunks(self.config.batch_size).skip(self.state.batch) {
+            self.state.rng.seed_torch();
+            
             // הכנת הנתונים
             let (src_tokens, tgt_tokens): (Vec<_>, Vec<_>) = batch.iter()
+                .map(|&i| &train_data[i])
                 .map(|(src, tgt)| {
                     (
                         self.model.tokenizer().tokenize(src),
@@ -176,6 +269,12 @@ impl Trainer {
             loss.backward();
             self.optimizer.step();
             
+            self.state.step += 1;
+            self.state.batch += 1;
+            if self.config.checkpoint_every > 0 && self.state.step as usize % self.config.checkpoint_every == 0 {
+                self.checkpoint()?;
+            }
+            
             // חישוב מדדים
             total_loss += loss.double_value(&[]);
             
@@ -192,7 +291,7 @@ impl Trainer {
         Ok((avg_loss, accuracy))
     }
     
-    fn validate(&self, val_data: &[(String, String)]) -> Result<(f64, f64), TranslationError> {
+    fn validate(&self, val_data: &[SentencePair]) -> Result<(f64, f64), TranslationError> {
         let mut total_loss = 0.0;
         let mut total_correct = 0;
         let mut total_tokens = 0;
@@ -239,50 +338,103 @@ impl Trainer {
         Ok((avg_loss, accuracy))
     }
     
-    pub fn save_checkpoint(&self, path: &str) -> Result<(), TranslationError> {
-        self.model.save_model(path)?;
+    fn checkpoints(&self) -> Result<Option<CheckpointManager>, TranslationError> {
+        self.config.checkpoint_dir
+            .as_ref()
+            .map(|dir| CheckpointManager::new(dir, self.config.keep_last_checkpoints))
+            .transpose()
+            .map_err(checkpoint_error)
+    }
+    
+    /// שמירת נקודת ביקורת בתיקייה המוגדרת, עם רוטציה
+    fn checkpoint(&mut self) -> Result<(), TranslationError> {
+        let Some(manager) = self.checkpoints()? else {
+            return Ok(());
+        };
         
-        let checkpoint = TrainingCheckpoint {
-            config: self.config.clone(),
-            optimizer_state: self.optimizer.serialize(),
+        self.state.learning_rate = self.optimizer.lr();
+        manager
+            .save_with(self.model.var_store(), &self.optimizer, &self.state, |dir| self.write_extra(dir))
+            .map_err(checkpoint_error)?;
+        Ok(())
+    }
+    
+    /// המשך מנקודת הביקורת האחרונה ב-checkpoint_dir, אם יש כזו
+    pub fn resume(&mut self) -> Result<bool, TranslationError> {
+        let Some(manager) = self.checkpoints()? else {
+            return Ok(false);
+        };
+        let Some(latest) = manager.latest().map_err(checkpoint_error)? else {
+            return Ok(false);
         };
         
-        let checkpoint_path = format!("{}_checkpoint.json", path);
-        std::fs::write(
-            &checkpoint_path,
-            serde_json::to_string_pretty(&checkpoint).map_err(|e| TranslationError::ModelError(e.to_string()))?
-        ).map_err(|e| TranslationError::ModelError(e.to_string()))?;
+        self.restore(&latest)?;
+        log::info!("Resumed from {} (step {})", latest.display(), self.state.step);
+        Ok(true)
+    }
+    
+    fn restore(&mut self, dir: &Path) -> Result<(), TranslationError> {
+        let tokenizer = Tokenizer::load(dir.join(TOKENIZER_FILE).to_str().unwrap_or_default())
+            .map_err(|e| TranslationError::ModelError(e.to_string()))?;
         
+        self.model = EnhancedTransformer::with_tokenizer(tokenizer);
+        self.optimizer = StatefulAdam::new(self.model.var_store(), self.config.learning_rate, AdamConfig::default());
+        self.state = CheckpointManager::restore(dir, self.model.var_store(), &mut self.optimizer)
+            .map_err(checkpoint_error)?;
         Ok(())
     }
     
-    pub fn load_checkpoint(path: &str) -> Result<Self, TranslationError> {
-        let model = EnhancedTransformer::load_model(path)?;
+    fn write_extra(&self, dir: &Path) -> Result<(), CheckpointError> {
+        self.model.tokenizer()
+            .save(dir.join(TOKENIZER_FILE).to_str().unwrap_or_default())
+            .map_err(|e| CheckpointError::Io(std::io::Error::other(e.to_string())))?;
+        std::fs::write(dir.join(TRAINER_CONFIG_FILE), serde_json::to_string_pretty(&self.config)?)?;
+        Ok(())
+    }
+    
+    /// שמירת מצב האימון המלא (משקולות, מומנטים, שלב קצב הלמידה, מצב האקראיות) לתיקייה
+    pub fn save_checkpoint(&self, path: &str) -> Result<(), TranslationError> {
+        let dir = Path::new(path);
+        let mut state = self.state.clone();
+        state.learning_rate = self.optimizer.lr();
         
-        let checkpoint_path = format!("{}_checkpoint.json", path);
-        let checkpoint: TrainingCheckpoint = serde_json::from_str(
-            &std::fs::read_to_string(&checkpoint_path)
+        CheckpointManager::write(dir, self.model.var_store(), &self.optimizer, &state)
+            .and_then(|_| self.write_extra(dir))
+            .map_err(checkpoint_error)
+    }
+    
+    pub fn load_checkpoint(path: &str) -> Result<Self, TranslationError> {
+        let dir = Path::new(path);
+        let config: TrainingConfig = serde_json::from_str(
+            &std::fs::read_to_string(dir.join(TRAINER_CONFIG_FILE))
                 .map_err(|e| TranslationError::ModelError(e.to_string()))?
         ).map_err(|e| TranslationError::ModelError(e.to_string()))?;
         
-        let device = Device::cuda_if_available();
-        let mut optimizer = nn::Adam::default()
-            .build(&model.trainable_variables(), checkpoint.config.learning_rate)
-            .expect("Failed to create optimizer");
-            
-        optimizer.deserialize(&checkpoint.optimizer_state);
+        let mut trainer = Self::new(EnhancedTransformer::new(), config);
+        trainer.restore(dir)?;
+        Ok(trainer)
+    }
+    
+    /// מיצוע נקודות הביקורת האחרונות ורישום התוצאה כמודל שחרור ב-ModelStorage, יחד עם אוצר המילים.
+    /// המיצוע נכתב למודל חדש באותה תצורה; המשקולות והמומנטים של האימון נשארים כפי שהיו
+    pub fn release(&self, storage: &ModelStorage, info: &ReleaseInfo) -> Result<(), TranslationError> {
+        let manager = self.checkpoints()?.ok_or_else(|| {
+            TranslationError::ModelError("לא הוגדרה תיקיית נקודות ביקורת".to_string())
+        })?;
         
-        Ok(Self {
-            model,
-            optimizer,
-            config: checkpoint.config,
-            device,
-        })
+        let averaged = EnhancedTransformer::with_tokenizer(self.model.tokenizer().clone());
+        manager
+            .release(self.config.average_last, averaged.var_store(), storage, info)
+            .map_err(checkpoint_error)?;
+        
+        let vocabulary = VocabularyArtifact::from_tokenizer(self.model.tokenizer(), &info.version)
+            .map_err(|e| TranslationError::VocabularyError(e.to_string()))?;
+        storage
+            .attach_vocabulary(&info.name, &info.version, &vocabulary)
+            .map_err(|e| TranslationError::ModelError(e.to_string()))
     }
 }
 
-#[derive(Serialize, Deserialize)]
-struct TrainingCheckpoint {
-    config: TrainingConfig,
-    optimizer_state: Vec<u8>,
-} 
\ No newline at end of file
+fn checkpoint_error(error: CheckpointError) -> TranslationError {
+    TranslationError::ModelError(format!("שגיאה בנקודת ביקורת: {}", error))
+}
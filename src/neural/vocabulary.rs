@@ -2,8 +2,10 @@ use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufRead, BufReader, Write, Result as IoResult};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::path::Path;
 use thiserror::Error;
+use crate::tokenizer::{Normalization, SpecialTokens, Tokenizer, TokenizerConfig};
 
 #[derive(Debug, Error)]
 pub enum VocabularyError {
@@ -13,6 +15,160 @@ pub enum VocabularyError {
     WordNotFound(String),
     #[error("אינדקס לא חוקי: {0}")]
     InvalidIndex(i64),
+    #[error("שגיאת סריאליזציה: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("אוצר המילים פגום: סכום הביקורת {actual} אינו תואם ל-{expected}")]
+    ChecksumMismatch { expected: String, actual: String },
+    #[error("אוצר המילים חסר את הטוקן המיוחד {0}")]
+    MissingSpecialToken(String),
+    #[error("אין טוקן למזהה {0}; המזהים חייבים להיות רציפים")]
+    NonContiguousIds(i64),
+    #[error("הטוקן {0} מופיע יותר מפעם אחת")]
+    DuplicateToken(String),
+}
+
+/// קובץ אוצר מילים יחיד המשותף לטוקנייזר ולמודל, עם גרסה וסכום ביקורת
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VocabularyArtifact {
+    pub version: String,
+    /// SHA-256 על הטוקנים, המיזוגים, הטוקנים המיוחדים והגדרות הפירוק, כמו קבצי המודל
+    pub checksum: String,
+    /// הטוקנים לפי סדר המזהים (המזהה הוא המיקום ברשימה)
+    pub tokens: Vec<String>,
+    /// כללי המיזוג של BPE לפי סדר הלמידה; בלעדיהם טוקנייזר משוחזר מפרק טקסט אחרת
+    #[serde(default)]
+    pub merges: Vec<(String, String)>,
+    #[serde(default)]
+    pub special_tokens: SpecialTokens,
+    /// הנרמול וה-byte fallback של הטוקנייזר; בלעדיהם אותו טקסט מפורק אחרת בשרת
+    #[serde(default)]
+    pub config: TokenizerConfig,
+}
+
+impl VocabularyArtifact {
+    pub fn new(version: &str, tokens: Vec<String>) -> Self {
+        let mut artifact = Self {
+            version: version.to_string(),
+            checksum: String::new(),
+            tokens,
+            merges: Vec::new(),
+            special_tokens: SpecialTokens::default(),
+            config: TokenizerConfig::default(),
+        };
+        artifact.checksum = artifact.checksum_of_contents();
+        artifact
+    }
+
+    pub fn with_merges(mut self, merges: Vec<(String, String)>) -> Self {
+        self.merges = merges;
+        self.checksum = self.checksum_of_contents();
+        self
+    }
+
+    pub fn with_special_tokens(mut self, special_tokens: SpecialTokens) -> Self {
+        self.special_tokens = special_tokens;
+        self.checksum = self.checksum_of_contents();
+        self
+    }
+
+    pub fn with_config(mut self, config: TokenizerConfig) -> Self {
+        self.config = config;
+        self.checksum = self.checksum_of_contents();
+        self
+    }
+
+    /// יצירת הקובץ מהטוקנייזר, כך שהמזהים והפירוק זהים בשני הצדדים
+    pub fn from_tokenizer(tokenizer: &Tokenizer, version: &str) -> Result<Self, VocabularyError> {
+        let tokens = (0..tokenizer.vocab_size() as i64)
+            .map(|id| tokenizer.token(id).map(String::from).ok_or(VocabularyError::NonContiguousIds(id)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::new(version, tokens)
+            .with_merges(tokenizer.merges().to_vec())
+            .with_special_tokens(tokenizer.special_tokens().clone())
+            .with_config(tokenizer.config().clone()))
+    }
+
+    /// שחזור הטוקנייזר שממנו נוצר הקובץ, עם אותם מזהים, מיזוגים ונרמול
+    pub fn to_tokenizer(&self) -> Result<Tokenizer, VocabularyError> {
+        self.verify()?;
+        self.require_tokens(&self.special_tokens.all())?;
+        Ok(Tokenizer::from_parts(&self.tokens, self.merges.clone(), self.special_tokens.clone(), self.config.clone()))
+    }
+
+    /// סכום הביקורת מכסה את הטוקנים, המיזוגים, הטוקנים המיוחדים והגדרות הפירוק
+    pub fn compute_checksum(
+        tokens: &[String],
+        merges: &[(String, String)],
+        special_tokens: &SpecialTokens,
+        config: &TokenizerConfig,
+    ) -> String {
+        let mut hasher = Sha256::new();
+        for token in tokens {
+            hasher.update(token.as_bytes());
+            hasher.update([0u8]);
+        }
+        if !merges.is_empty() {
+            hasher.update([1u8]);
+            for (left, right) in merges {
+                hasher.update(left.as_bytes());
+                hasher.update([0u8]);
+                hasher.update(right.as_bytes());
+                hasher.update([0u8]);
+            }
+        }
+        // תפקיד כל טוקן מיוחד נקבע לפי מיקומו: pad, bos, eos, unk ואחריהם השמורים
+        hasher.update([2u8]);
+        for token in special_tokens.all() {
+            hasher.update(token.as_bytes());
+            hasher.update([0u8]);
+        }
+        hasher.update([3u8]);
+        hasher.update(&[
+            match config.normalization {
+                Normalization::None => 0,
+                Normalization::Nfc => 1,
+                Normalization::Nfkc => 2,
+            },
+            config.byte_fallback as u8,
+        ]);
+        format!("{:x}", hasher.finalize())
+    }
+
+    fn checksum_of_contents(&self) -> String {
+        Self::compute_checksum(&self.tokens, &self.merges, &self.special_tokens, &self.config)
+    }
+
+    fn require_tokens(&self, required: &[&str]) -> Result<(), VocabularyError> {
+        match required.iter().find(|token| !self.tokens.iter().any(|t| t == *token)) {
+            Some(missing) => Err(VocabularyError::MissingSpecialToken(missing.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    /// בדיקה שהטוקנים והמיזוגים לא שונו מאז חישוב סכום הביקורת
+    pub fn verify(&self) -> Result<(), VocabularyError> {
+        let actual = self.checksum_of_contents();
+        if actual != self.checksum {
+            return Err(VocabularyError::ChecksumMismatch {
+                expected: self.checksum.clone(),
+                actual,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), VocabularyError> {
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, VocabularyError> {
+        let file = File::open(path)?;
+        let artifact: Self = serde_json::from_reader(BufReader::new(file))?;
+        artifact.verify()?;
+        Ok(artifact)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -20,6 +176,9 @@ pub struct Vocabulary {
     word_to_index: HashMap<String, i64>,
     index_to_word: HashMap<i64, String>,
     next_index: i64,
+    /// הטוקנים המיוחדים שדרכם נפתרים מזהי UNK/PAD/BOS/EOS
+    #[serde(default)]
+    special_tokens: SpecialTokens,
 }
 
 impl Vocabulary {
@@ -28,17 +187,46 @@ impl Vocabulary {
             word_to_index: HashMap::new(),
             index_to_word: HashMap::new(),
             next_index: 0,
+            special_tokens: SpecialTokens::default(),
         };
 
-        // הוספת טוקנים מיוחדים
-        vocab.add_special_token("<PAD>");  // ריפוד למשפטים קצרים
-        vocab.add_special_token("<UNK>");  // מילים לא מוכרות
-        vocab.add_special_token("<BOS>");  // תחילת משפט
-        vocab.add_special_token("<EOS>");  // סוף משפט
+        // הוספת טוקנים מיוחדים באותו סדר כמו בטוקנייזר
+        for token in SpecialTokens::default().all() {
+            vocab.add_special_token(token);
+        }
         
         vocab
     }
 
+    /// בניית אוצר מילים מקובץ משותף; המזהים נשמרים כפי שהם
+    pub fn from_artifact(artifact: &VocabularyArtifact) -> Result<Self, VocabularyError> {
+        artifact.verify()?;
+        // get_unk_index ודומיו נפתרים דרך הטוקנים המיוחדים של הקובץ, ולכן הם חייבים להופיע בו
+        artifact.require_tokens(&artifact.special_tokens.all())?;
+
+        let mut vocab = Self {
+            word_to_index: HashMap::new(),
+            index_to_word: HashMap::new(),
+            next_index: 0,
+            special_tokens: artifact.special_tokens.clone(),
+        };
+        for (position, token) in artifact.tokens.iter().enumerate() {
+            if vocab.add_word(token) != position as i64 {
+                return Err(VocabularyError::DuplicateToken(token.clone()));
+            }
+        }
+
+        Ok(vocab)
+    }
+
+    /// ייצוא אוצר המילים לקובץ משותף; נכשל אם יש מזהה בלי טוקן
+    pub fn to_artifact(&self, version: &str) -> Result<VocabularyArtifact, VocabularyError> {
+        let tokens = (0..self.next_index)
+            .map(|idx| self.index_to_word.get(&idx).cloned().ok_or(VocabularyError::NonContiguousIds(idx)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(VocabularyArtifact::new(version, tokens).with_special_tokens(self.special_tokens.clone()))
+    }
+
     fn add_special_token(&mut self, token: &str) {
         self.add_word(token);
     }
@@ -103,19 +291,19 @@ impl Vocabulary {
     }
 
     pub fn get_unk_index(&self) -> i64 {
-        self.word_to_index["<UNK>"]
+        self.word_to_index[&self.special_tokens.unk]
     }
 
     pub fn get_pad_index(&self) -> i64 {
-        self.word_to_index["<PAD>"]
+        self.word_to_index[&self.special_tokens.pad]
     }
 
     pub fn get_bos_index(&self) -> i64 {
-        self.word_to_index["<BOS>"]
+        self.word_to_index[&self.special_tokens.bos]
     }
 
     pub fn get_eos_index(&self) -> i64 {
-        self.word_to_index["<EOS>"]
+        self.word_to_index[&self.special_tokens.eos]
     }
 }
 
@@ -185,6 +373,97 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_artifact_matches_tokenizer_ids() {
+        let mut tokenizer = Tokenizer::new();
+        tokenizer.add_token("ספרינקלר");
+        let artifact = VocabularyArtifact::from_tokenizer(&tokenizer, "1.0.0").unwrap();
+        let vocab = Vocabulary::from_artifact(&artifact).unwrap();
+
+        assert_eq!(vocab.size(), tokenizer.vocab_size());
+        assert_eq!(Some(vocab.get_index("ספרינקלר").unwrap()), tokenizer.token_id("ספרינקלר"));
+        assert_eq!(Some(vocab.get_unk_index()), tokenizer.token_id("<UNK>"));
+        assert_eq!(Some(vocab.get_eos_index()), tokenizer.token_id("<EOS>"));
+    }
+
+    #[test]
+    fn test_tampered_artifact_is_rejected() {
+        let mut artifact = Vocabulary::new().to_artifact("1.0.0").unwrap();
+        artifact.tokens.push("מילה".to_string());
+        assert!(matches!(
+            Vocabulary::from_artifact(&artifact),
+            Err(VocabularyError::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_artifact_rebuilds_tokenizer_segmentation() {
+        let config = TokenizerConfig { vocab_size: 300, ..TokenizerConfig::default() };
+        let mut tokenizer = Tokenizer::with_config(config, SpecialTokens::default());
+        tokenizer.train(vec!["מתז מתזים מתז ספרינקלר".to_string(); 20], 2);
+        let artifact = VocabularyArtifact::from_tokenizer(&tokenizer, "1.0.0").unwrap();
+
+        let rebuilt = artifact.to_tokenizer().unwrap();
+        assert_eq!(rebuilt.tokenize("מתזים בספרינקלר"), tokenizer.tokenize("מתזים בספרינקלר"));
+
+        let mut tampered = artifact.clone();
+        tampered.merges.pop();
+        assert!(matches!(tampered.verify(), Err(VocabularyError::ChecksumMismatch { .. })));
+    }
+
+    #[test]
+    fn test_checksum_covers_special_tokens_and_config() {
+        let artifact = VocabularyArtifact::from_tokenizer(&Tokenizer::new(), "1.0.0").unwrap();
+        assert_eq!(artifact.config.normalization, Normalization::Nfc);
+
+        let mut swapped = artifact.clone();
+        std::mem::swap(&mut swapped.special_tokens.bos, &mut swapped.special_tokens.eos);
+        assert!(matches!(swapped.verify(), Err(VocabularyError::ChecksumMismatch { .. })));
+
+        let mut renormalized = artifact.clone();
+        renormalized.config.normalization = Normalization::Nfkc;
+        assert!(matches!(renormalized.verify(), Err(VocabularyError::ChecksumMismatch { .. })));
+
+        let mut without_fallback = artifact;
+        without_fallback.config.byte_fallback = false;
+        assert!(matches!(without_fallback.verify(), Err(VocabularyError::ChecksumMismatch { .. })));
+    }
+
+    #[test]
+    fn test_custom_special_tokens_resolve_ids() {
+        let special_tokens = SpecialTokens {
+            pad: "[PAD]".to_string(),
+            bos: "[CLS]".to_string(),
+            eos: "[SEP]".to_string(),
+            unk: "[UNK]".to_string(),
+            reserved: Vec::new(),
+        };
+        let tokens = ["[PAD]", "[CLS]", "[SEP]", "[UNK]", "מתז"].map(String::from).to_vec();
+        let artifact = VocabularyArtifact::new("1", tokens).with_special_tokens(special_tokens.clone());
+
+        let vocab = Vocabulary::from_artifact(&artifact).unwrap();
+        assert_eq!(vocab.get_unk_index(), 3);
+        assert_eq!(vocab.get_eos_index(), 2);
+        assert_eq!(vocab.to_artifact("1").unwrap().special_tokens, special_tokens);
+    }
+
+    #[test]
+    fn test_artifact_without_special_tokens_is_rejected() {
+        let artifact = VocabularyArtifact::new("1", vec!["<PAD>".to_string(), "מתז".to_string()]);
+        assert!(matches!(
+            Vocabulary::from_artifact(&artifact),
+            Err(VocabularyError::MissingSpecialToken(token)) if token == "<BOS>"
+        ));
+    }
+
+    #[test]
+    fn test_id_gap_is_an_error() {
+        let mut vocab = Vocabulary::new();
+        vocab.add_word("מתז");
+        vocab.index_to_word.remove(&1);
+        assert!(matches!(vocab.to_artifact("1"), Err(VocabularyError::NonContiguousIds(1))));
+    }
+
     #[test]
     fn test_special_tokens() {
         let vocab = Vocabulary::new();
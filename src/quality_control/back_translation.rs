@@ -0,0 +1,295 @@
+//! בדיקת הלוך-חזור לטקסטי בטיחות: התרגום העברי מתורגם בחזרה לרוסית במנוע של הכיוון ההפוך
+//! ומושווה למקור - chrF מול המקור, מספרים ויחידות, ומונחים שנעלמו
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::sync::Arc;
+use anyhow::{anyhow, Result};
+use serde::{Serialize, Deserialize};
+use crate::evaluation::metrics::{ChrF, CorpusMetric};
+use crate::language_detection::Language;
+use crate::neural::TranslationBackend;
+use crate::technical_terms::TermsDatabase;
+use crate::translation::TranslationEngine;
+use super::mqm::escape_html;
+use super::numbers::{check_numbers, NumberValidation};
+use super::Severity;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackTranslationConfig {
+    /// שפת המקור של המסמך; התרגום חוזר אליה
+    pub source_language: Language,
+    pub target_language: Language,
+    /// chrF (0-100) מתחתיו הסגמנט מסומן כחשוד לשינוי משמעות
+    pub min_chrf: f64,
+}
+
+impl Default for BackTranslationConfig {
+    fn default() -> Self {
+        Self {
+            source_language: Language::Russian,
+            target_language: Language::Hebrew,
+            min_chrf: 45.0,
+        }
+    }
+}
+
+/// שלושת הטקסטים של הסגמנט והסטייה שנמדדה ביניהם
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackTranslationResult {
+    pub source: String,
+    pub translation: String,
+    pub back_translation: String,
+    /// chrF של התרגום החוזר מול המקור, 0-100
+    pub chrf: f64,
+    /// מספרים, יחידות וקודים שהשתנו בדרך הלוך-חזור
+    pub number_mismatches: Vec<NumberValidation>,
+    /// מונחי מקור שלא חזרו בתרגום החוזר
+    pub missing_terms: Vec<String>,
+    /// סטייה סמנטית משוערת, 0 (זהה) עד 1
+    pub drift: f64,
+    pub flagged: bool,
+    pub reasons: Vec<String>,
+}
+
+impl BackTranslationResult {
+    /// טבלת HTML של מקור, תרגום ותרגום חוזר, לסקירה של הבודק
+    pub fn side_by_side_html(results: &[BackTranslationResult]) -> String {
+        let rows: String = results
+            .iter()
+            .enumerate()
+            .map(|(index, result)| {
+                format!(
+                    "<tr class=\"{}\"><td>{}</td><td dir=\"ltr\">{}</td><td dir=\"rtl\">{}</td><td dir=\"ltr\">{}</td><td>{:.1}</td><td>{}</td></tr>\n",
+                    if result.flagged { "flagged" } else { "ok" },
+                    index + 1,
+                    escape_html(&result.source),
+                    escape_html(&result.translation),
+                    escape_html(&result.back_translation),
+                    result.chrf,
+                    escape_html(&result.reasons.join("; ")),
+                )
+            })
+            .collect();
+
+        format!(
+            "<table class=\"back-translation\">\n<tr><th>#</th><th>מקור</th><th>תרגום</th><th>תרגום חוזר</th><th>chrF</th><th>הערות</th></tr>\n{}</table>",
+            rows
+        )
+    }
+}
+
+impl fmt::Display for BackTranslationResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} chrF {:.1}", if self.flagged { "[!]" } else { "[ ]" }, self.chrf)?;
+        writeln!(f, "  מקור:         {}", self.source)?;
+        writeln!(f, "  תרגום:        {}", self.translation)?;
+        writeln!(f, "  תרגום חוזר:   {}", self.back_translation)?;
+        for reason in &self.reasons {
+            writeln!(f, "  - {}", reason)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct BackTranslator {
+    /// המנוע של הכיוון ההפוך (יעד -> מקור)
+    reverse: Arc<dyn TranslationBackend>,
+    terms: Option<Arc<TermsDatabase>>,
+    config: BackTranslationConfig,
+}
+
+impl BackTranslator {
+    pub fn new(reverse: Arc<dyn TranslationBackend>) -> Self {
+        Self {
+            reverse,
+            terms: None,
+            config: BackTranslationConfig::default(),
+        }
+    }
+
+    /// המנוע ההפוך מתוך מנוע התרגום; בלי מודל לכיוון ההפוך אין תרגום חוזר שאפשר להשוות, וזו שגיאה
+    pub fn from_engine(engine: &TranslationEngine, config: BackTranslationConfig) -> Result<Self> {
+        let reverse = engine
+            .backend(config.target_language, config.source_language)
+            .ok_or_else(|| {
+                anyhow!(
+                    "לא הוגדר מנוע נוירוני לכיוון {:?} -> {:?}, ולכן אין תרגום חוזר",
+                    config.target_language,
+                    config.source_language
+                )
+            })?;
+        Ok(Self::new(reverse).with_config(config))
+    }
+
+    pub fn with_terms(mut self, terms: Arc<TermsDatabase>) -> Self {
+        self.terms = Some(terms);
+        self
+    }
+
+    pub fn with_config(mut self, config: BackTranslationConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// תרגום חוזר במנוע של הכיוון ההפוך והשוואה למקור
+    pub async fn check(&self, source: &str, translation: &str) -> Result<BackTranslationResult> {
+        let back_translation = self
+            .back_translate(vec![translation.to_string()])
+            .await?
+            .pop()
+            .ok_or_else(|| anyhow!("המנוע ההפוך לא החזיר תרגום"))?;
+        Ok(self.assess(source, translation, &back_translation))
+    }
+
+    /// בדיקת כל סגמנטי המסמך; התרגומים נשלחים למנוע ההפוך כאצווה אחת
+    pub async fn check_document(&self, segments: &[(String, String)]) -> Result<Vec<BackTranslationResult>> {
+        let translations: Vec<String> = segments.iter().map(|(_, translation)| translation.clone()).collect();
+        let back_translations = self.back_translate(translations).await?;
+        Ok(segments
+            .iter()
+            .zip(&back_translations)
+            .map(|((source, translation), back_translation)| self.assess(source, translation, back_translation))
+            .collect())
+    }
+
+    /// ההסקה חוסמת, ולכן רצה מחוץ ל-executor
+    async fn back_translate(&self, translations: Vec<String>) -> Result<Vec<String>> {
+        let count = translations.len();
+        let reverse = self.reverse.clone();
+        let back_translations = tokio::task::spawn_blocking(move || reverse.translate(&translations))
+            .await
+            .map_err(|e| anyhow!("הרצת המנוע ההפוך נכשלה: {}", e))??;
+        if back_translations.len() != count {
+            return Err(anyhow!("המנוע ההפוך החזיר {} תרגומים עבור {} סגמנטים", back_translations.len(), count));
+        }
+        Ok(back_translations)
+    }
+
+    /// השוואה של תרגום חוזר שכבר הופק
+    pub fn assess(&self, source: &str, translation: &str, back_translation: &str) -> BackTranslationResult {
+        let chrf = ChrF { lowercase: true, ..ChrF::default() }.sentence_score(back_translation, &[source]);
+
+        // הכיוון הפוך לבדיקה הרגילה: המקור הוא הייחוס, והתרגום החוזר נבדק מולו
+        let number_mismatches: Vec<NumberValidation> = check_numbers(source, back_translation)
+            .into_iter()
+            .filter(|v| matches!(v.severity, Severity::Critical | Severity::Major))
+            .collect();
+
+        let missing_terms = match &self.terms {
+            Some(terms) => {
+                let returned: BTreeSet<String> = terms
+                    .find_russian_term_forms(back_translation)
+                    .into_iter()
+                    .map(|(term, _)| term.term_ru.to_lowercase())
+                    .collect();
+                terms
+                    .find_russian_term_forms(source)
+                    .into_iter()
+                    .map(|(term, _)| term.term_ru.to_lowercase())
+                    .filter(|term| !returned.contains(term))
+                    .collect::<BTreeSet<_>>()
+                    .into_iter()
+                    .collect()
+            }
+            None => Vec::new(),
+        };
+
+        let mut reasons = Vec::new();
+        if chrf < self.config.min_chrf {
+            reasons.push(format!("התרגום החוזר רחוק מהמקור (chrF {:.1})", chrf));
+        }
+        reasons.extend(number_mismatches.iter().map(|v| v.message.clone()));
+        reasons.extend(missing_terms.iter().map(|term| format!("המונח \"{}\" לא חזר בתרגום החוזר", term)));
+
+        // כל מספר או מונח שאבד מוסיף לסטייה מעבר למרחק התווים
+        let drift = ((1.0 - chrf / 100.0) + 0.2 * (number_mismatches.len() + missing_terms.len()) as f64).min(1.0);
+
+        BackTranslationResult {
+            source: source.to_string(),
+            translation: translation.to_string(),
+            back_translation: back_translation.to_string(),
+            chrf,
+            number_mismatches,
+            missing_terms,
+            drift,
+            flagged: !reasons.is_empty(),
+            reasons,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural::testing::RecordingBackend;
+    use crate::quality_control::terminology::tests::term;
+
+    fn back_translator() -> BackTranslator {
+        let mut terms = TermsDatabase::new();
+        terms.add_term(term("задвижка", "מגוף", &[]));
+        BackTranslator::new(reverse_backend()).with_terms(Arc::new(terms))
+    }
+
+    /// מנוע הפוך לבדיקה שמחזיר תרגום חוזר קבוע
+    fn reverse_backend() -> Arc<RecordingBackend> {
+        Arc::new(RecordingBackend::fixed("Закрыть задвижку."))
+    }
+
+    #[tokio::test]
+    async fn test_check_document_sends_one_batch() {
+        let backend = reverse_backend();
+        let mut engine = TranslationEngine::new();
+        engine.register_backend(Language::Hebrew, Language::Russian, backend.clone());
+        let checker = BackTranslator::from_engine(&engine, BackTranslationConfig::default()).unwrap();
+
+        let segments: Vec<(String, String)> = ["Закрыть задвижку.", "Закрыть задвижку.", "Открыть кран."]
+            .iter()
+            .map(|source| (source.to_string(), "לסגור את המגוף.".to_string()))
+            .collect();
+        let results = checker.check_document(&segments).await.unwrap();
+
+        assert_eq!(backend.batch_sizes(), vec![3]);
+        assert_eq!(results.len(), 3);
+        assert!(!results[0].flagged && !results[1].flagged);
+        assert!(results[2].flagged);
+    }
+
+    #[test]
+    fn test_missing_reverse_backend_is_an_error() {
+        let mut engine = TranslationEngine::new();
+        // מנוע לכיוון הרגיל בלבד אינו מספיק לתרגום חוזר
+        engine.register_backend(Language::Russian, Language::Hebrew, reverse_backend());
+
+        assert!(BackTranslator::from_engine(&engine, BackTranslationConfig::default()).is_err());
+    }
+
+    #[test]
+    fn test_faithful_round_trip_is_not_flagged() {
+        let result = back_translator().assess(
+            "Задвижку закрыть при давлении 1,6 МПа.",
+            "יש לסגור את המגוף בלחץ של 1.6 MPa.",
+            "Закрыть задвижку при давлении 1,6 МПа.",
+        );
+        assert!(!result.flagged, "{}", result);
+        assert!(result.chrf > 60.0);
+        assert!(result.drift < 0.4);
+    }
+
+    #[test]
+    fn test_shifted_meaning_is_flagged() {
+        let result = back_translator().assess(
+            "Задвижку закрыть при давлении 1,6 МПа.",
+            "יש לפתוח את השסתום בלחץ של 16 MPa.",
+            "Открыть клапан при давлении 16 МПа.",
+        );
+        assert!(result.flagged);
+        assert_eq!(result.missing_terms, vec!["задвижка"]);
+        assert!(!result.number_mismatches.is_empty());
+        assert!(result.drift > 0.5);
+
+        let html = BackTranslationResult::side_by_side_html(&[result]);
+        assert!(html.contains("<tr class=\"flagged\">"));
+        assert!(html.contains("Открыть клапан"));
+    }
+}
@@ -0,0 +1,540 @@
+//! הערכת איכות ללא תרגום ייחוס (QE): מאפיינים לכל סגמנט, מודל לוגיסטי שמאומן על מרחק העריכה
+//! של המתרגמים, וכיול איזוטוני לציון 0-1
+
+use std::sync::Arc;
+use anyhow::{bail, Result};
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Serialize, Deserialize};
+use crate::evaluation::calibration::isotonic_fit;
+use crate::evaluation::metrics::{ChrF, CorpusMetric, Ter};
+use crate::language_detection::Language;
+use crate::learning_manager::{LearningEvent, LearningEventType};
+use crate::system_combination::{Candidate, CandidateSource, SystemCombiner};
+use crate::technical_terms::TermsDatabase;
+
+pub const FEATURE_COUNT: usize = 6;
+
+/// ערך נייטרלי למאפיין שאין לו נתונים (ביטחון מודל או תרגום חוזר חסרים)
+const NEUTRAL_FEATURE: f64 = 0.5;
+
+/// סגמנט להערכה: מקור, תרגום, ומה שהמנוע יודע לספר עליו
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QeSegment {
+    pub source: String,
+    pub target: String,
+    pub model_confidence: Option<f64>,
+    pub back_translation: Option<String>,
+}
+
+impl QeSegment {
+    pub fn new(source: impl Into<String>, target: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            target: target.into(),
+            model_confidence: None,
+            back_translation: None,
+        }
+    }
+
+    pub fn with_confidence(mut self, confidence: f64) -> Self {
+        self.model_confidence = Some(confidence);
+        self
+    }
+
+    pub fn with_back_translation(mut self, back_translation: impl Into<String>) -> Self {
+        self.back_translation = Some(back_translation.into());
+        self
+    }
+}
+
+/// מאפייני הסגמנט; כולם בטווח 0-1 פרט ליחס האורכים
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct QeFeatures {
+    /// אורך התרגום חלקי אורך המקור, בתווים
+    pub length_ratio: f64,
+    pub term_coverage: f64,
+    /// התאמת מספרים ויחידות בין המקור לתרגום
+    pub number_match: f64,
+    /// חלק המילים שנותרו <unk> או בכתב של שפת המקור
+    pub unknown_token_rate: f64,
+    pub model_confidence: f64,
+    /// דמיון chrF בין המקור לתרגום החוזר
+    pub back_translation_similarity: f64,
+}
+
+impl QeFeatures {
+    /// וקטור שבו ערך גבוה תמיד טוב; יחס האורכים נמדד כסטייה מהיחס הצפוי
+    fn vector(&self, expected_length_ratio: f64) -> [f64; FEATURE_COUNT] {
+        let length_agreement = if self.length_ratio > 0.0 && expected_length_ratio > 0.0 {
+            (-(self.length_ratio / expected_length_ratio).ln().abs()).exp()
+        } else {
+            0.0
+        };
+
+        [
+            length_agreement,
+            self.term_coverage,
+            self.number_match,
+            1.0 - self.unknown_token_rate,
+            self.model_confidence,
+            self.back_translation_similarity,
+        ]
+    }
+}
+
+/// מודל לוגיסטי ומיפוי כיול איזוטוני; נשמר כ-JSON לצד המודל הנוירוני
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QeModel {
+    pub weights: [f64; FEATURE_COUNT],
+    pub bias: f64,
+    pub expected_length_ratio: f64,
+    /// נקודות (ציון גולמי, איכות נצפית) עולות; ריק כשהמודל לא כויל
+    pub calibration: Vec<(f64, f64)>,
+}
+
+impl Default for QeModel {
+    /// משקלות התחלתיים עד לאימון הראשון: סגמנט נייטרלי מקבל 0.5
+    fn default() -> Self {
+        Self {
+            weights: [1.5; FEATURE_COUNT],
+            bias: -1.5 * FEATURE_COUNT as f64 * NEUTRAL_FEATURE,
+            expected_length_ratio: 1.0,
+            calibration: Vec::new(),
+        }
+    }
+}
+
+impl QeModel {
+    fn raw_score(&self, features: &QeFeatures) -> f64 {
+        let vector = features.vector(self.expected_length_ratio);
+        let z: f64 = self.weights.iter().zip(vector).map(|(w, x)| w * x).sum::<f64>() + self.bias;
+        sigmoid(z)
+    }
+
+    /// אינטרפולציה לינארית בין נקודות הכיול
+    fn calibrate(&self, raw: f64) -> f64 {
+        let points = &self.calibration;
+        let Some(first) = points.first() else {
+            return raw;
+        };
+        let last = points[points.len() - 1];
+        if raw <= first.0 {
+            return first.1;
+        }
+        if raw >= last.0 {
+            return last.1;
+        }
+
+        let upper = points.iter().position(|(x, _)| *x >= raw).unwrap_or(points.len() - 1);
+        let (x0, y0) = points[upper - 1];
+        let (x1, y1) = points[upper];
+        if x1 - x0 <= f64::EPSILON {
+            return y1;
+        }
+        y0 + (y1 - y0) * (raw - x0) / (x1 - x0)
+    }
+}
+
+fn sigmoid(z: f64) -> f64 {
+    1.0 / (1.0 + (-z).exp())
+}
+
+/// דוגמת אימון: תרגום המכונה והתיקון שהמתרגם אישר
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QeExample {
+    pub segment: QeSegment,
+    pub post_edit: String,
+}
+
+impl QeExample {
+    /// האיכות הנצפית: 1 פחות מרחק העריכה (HTER), חסום ל-0
+    pub fn label(&self) -> f64 {
+        let hter = Ter::default().sentence_score(&self.segment.target, &[self.post_edit.as_str()]) / 100.0;
+        (1.0 - hter).clamp(0.0, 1.0)
+    }
+}
+
+/// דוגמאות אימון מהיסטוריית התיקונים של מנהל הלמידה
+pub fn examples_from_events(events: &[LearningEvent]) -> Vec<QeExample> {
+    events
+        .iter()
+        .filter_map(|event| match &event.event_type {
+            LearningEventType::Correction { original, corrected, .. } => {
+                let machine_translation = if original.trim().is_empty() { &event.target_text } else { original };
+                if event.source_text.trim().is_empty() || machine_translation.trim().is_empty() {
+                    return None;
+                }
+
+                let mut segment = QeSegment::new(event.source_text.clone(), machine_translation.clone());
+                if event.metrics.confidence_score > 0.0 {
+                    segment = segment.with_confidence(event.metrics.confidence_score as f64);
+                }
+                Some(QeExample { segment, post_edit: corrected.clone() })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QeTrainingConfig {
+    pub epochs: usize,
+    pub learning_rate: f64,
+    pub l2: f64,
+    /// החלק שנשמר בצד לכיול ולמדידת השגיאה
+    pub calibration_ratio: f64,
+    pub min_examples: usize,
+    pub seed: u64,
+}
+
+impl Default for QeTrainingConfig {
+    fn default() -> Self {
+        Self {
+            epochs: 500,
+            learning_rate: 0.5,
+            l2: 0.001,
+            calibration_ratio: 0.2,
+            min_examples: 20,
+            seed: 42,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QeTrainingReport {
+    pub training_examples: usize,
+    pub calibration_examples: usize,
+    /// שגיאה מוחלטת ממוצעת של הציון המכויל מול האיכות הנצפית בקבוצת הכיול
+    pub mean_absolute_error: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SegmentEstimate {
+    /// ציון מכויל: האיכות הצפויה, 1 פחות מרחק העריכה הצפוי
+    pub score: f64,
+    pub raw_score: f64,
+    pub features: QeFeatures,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentEstimate {
+    /// ממוצע הציונים משוקלל באורך המקור
+    pub score: f64,
+    pub segments: Vec<SegmentEstimate>,
+    /// מיקומי הסגמנטים שמתחת לסף, לבדיקה של מתרגם
+    pub flagged: Vec<usize>,
+}
+
+pub struct QualityEstimator {
+    combiner: SystemCombiner,
+    target_language: Language,
+    model: QeModel,
+    config: QeTrainingConfig,
+    flag_threshold: f64,
+}
+
+impl QualityEstimator {
+    pub fn new(target_language: Language) -> Self {
+        Self {
+            combiner: SystemCombiner::default(),
+            target_language,
+            model: QeModel::default(),
+            config: QeTrainingConfig::default(),
+            flag_threshold: 0.6,
+        }
+    }
+
+    pub fn with_terms(mut self, terms: Arc<TermsDatabase>) -> Self {
+        self.combiner = self.combiner.with_terms(terms);
+        self
+    }
+
+    pub fn with_model(mut self, model: QeModel) -> Self {
+        self.model = model;
+        self
+    }
+
+    pub fn with_training_config(mut self, config: QeTrainingConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn with_flag_threshold(mut self, threshold: f64) -> Self {
+        self.flag_threshold = threshold;
+        self
+    }
+
+    pub fn model(&self) -> &QeModel {
+        &self.model
+    }
+
+    pub fn features(&self, segment: &QeSegment) -> QeFeatures {
+        // כיסוי מונחים והתאמת מספרים באותו חישוב שמדרג מועמדים בצירוף המערכות
+        let candidate = Candidate::new(segment.target.clone(), CandidateSource::Neural);
+        let shared = self.combiner.features(&segment.source, self.target_language, &candidate);
+
+        let source_length = segment.source.chars().filter(|c| !c.is_whitespace()).count();
+        let target_length = segment.target.chars().filter(|c| !c.is_whitespace()).count();
+
+        QeFeatures {
+            length_ratio: if source_length > 0 { target_length as f64 / source_length as f64 } else { 0.0 },
+            term_coverage: shared.term_coverage,
+            number_match: shared.number_consistency,
+            unknown_token_rate: self.unknown_token_rate(&segment.target),
+            model_confidence: segment.model_confidence.map_or(NEUTRAL_FEATURE, |c| c.clamp(0.0, 1.0)),
+            back_translation_similarity: segment.back_translation.as_ref().map_or(NEUTRAL_FEATURE, |back| {
+                ChrF::default().sentence_score(back, &[segment.source.as_str()]) / 100.0
+            }),
+        }
+    }
+
+    pub fn estimate(&self, segment: &QeSegment) -> SegmentEstimate {
+        let features = self.features(segment);
+        let raw_score = self.model.raw_score(&features);
+        SegmentEstimate {
+            score: self.model.calibrate(raw_score).clamp(0.0, 1.0),
+            raw_score,
+            features,
+        }
+    }
+
+    pub fn estimate_document(&self, segments: &[QeSegment]) -> DocumentEstimate {
+        let estimates: Vec<SegmentEstimate> = segments.iter().map(|segment| self.estimate(segment)).collect();
+
+        let weights: Vec<f64> = segments
+            .iter()
+            .map(|segment| segment.source.split_whitespace().count().max(1) as f64)
+            .collect();
+        let total_weight: f64 = weights.iter().sum();
+        let score = if total_weight > 0.0 {
+            estimates.iter().zip(&weights).map(|(e, w)| e.score * w).sum::<f64>() / total_weight
+        } else {
+            0.0
+        };
+
+        let flagged = estimates
+            .iter()
+            .enumerate()
+            .filter(|(_, estimate)| estimate.score < self.flag_threshold)
+            .map(|(index, _)| index)
+            .collect();
+
+        DocumentEstimate { score, segments: estimates, flagged }
+    }
+
+    pub fn train_from_history(&mut self, events: &[LearningEvent]) -> Result<QeTrainingReport> {
+        self.train(&examples_from_events(events))
+    }
+
+    /// אימון המודל הלוגיסטי על האיכות הנצפית וכיולו על קבוצה נפרדת
+    pub fn train(&mut self, examples: &[QeExample]) -> Result<QeTrainingReport> {
+        // דוגמה אחת לפחות לאימון ואחת לכיול
+        let min_examples = self.config.min_examples.max(2);
+        if examples.len() < min_examples {
+            bail!("נדרשות לפחות {} דוגמאות לאימון QE, התקבלו {}", min_examples, examples.len());
+        }
+
+        let mut labeled: Vec<(QeFeatures, f64)> = examples
+            .iter()
+            .map(|example| (self.features(&example.segment), example.label()))
+            .collect();
+        labeled.shuffle(&mut ChaCha8Rng::seed_from_u64(self.config.seed));
+
+        let calibration_size = ((labeled.len() as f64 * self.config.calibration_ratio).round() as usize)
+            .clamp(1, labeled.len() - 1);
+        let calibration_set = labeled.split_off(labeled.len() - calibration_size);
+        let training_set = labeled;
+
+        let mut model = QeModel {
+            expected_length_ratio: median_length_ratio(&training_set),
+            ..QeModel::default()
+        };
+        self.fit(&mut model, &training_set);
+
+        model.calibration = isotonic_fit(
+            calibration_set.iter().map(|(features, label)| (model.raw_score(features), *label)).collect(),
+        );
+
+        let mean_absolute_error = calibration_set
+            .iter()
+            .map(|(features, label)| (model.calibrate(model.raw_score(features)) - label).abs())
+            .sum::<f64>()
+            / calibration_set.len() as f64;
+
+        self.model = model;
+        Ok(QeTrainingReport {
+            training_examples: training_set.len(),
+            calibration_examples: calibration_set.len(),
+            mean_absolute_error,
+        })
+    }
+
+    /// ירידת גרדיאנט על cross-entropy עם תוויות רכות
+    fn fit(&self, model: &mut QeModel, examples: &[(QeFeatures, f64)]) {
+        let vectors: Vec<([f64; FEATURE_COUNT], f64)> = examples
+            .iter()
+            .map(|(features, label)| (features.vector(model.expected_length_ratio), *label))
+            .collect();
+        let count = vectors.len() as f64;
+
+        for _ in 0..self.config.epochs {
+            let mut gradient = [0.0; FEATURE_COUNT];
+            let mut bias_gradient = 0.0;
+
+            for (vector, label) in &vectors {
+                let z: f64 = model.weights.iter().zip(vector).map(|(w, x)| w * x).sum::<f64>() + model.bias;
+                let error = sigmoid(z) - label;
+                for (g, x) in gradient.iter_mut().zip(vector) {
+                    *g += error * x;
+                }
+                bias_gradient += error;
+            }
+
+            for (weight, g) in model.weights.iter_mut().zip(gradient) {
+                *weight -= self.config.learning_rate * (g / count + self.config.l2 * *weight);
+            }
+            model.bias -= self.config.learning_rate * bias_gradient / count;
+        }
+    }
+
+    fn unknown_token_rate(&self, target: &str) -> f64 {
+        let tokens: Vec<&str> = target.split_whitespace().collect();
+        if tokens.is_empty() {
+            return 0.0;
+        }
+
+        let unknown = tokens
+            .iter()
+            .filter(|token| {
+                token.contains("<unk>")
+                    || match self.target_language {
+                        Language::Hebrew => token.chars().any(is_cyrillic),
+                        Language::Russian => token.chars().any(is_hebrew),
+                        Language::Unknown => false,
+                    }
+            })
+            .count();
+
+        unknown as f64 / tokens.len() as f64
+    }
+}
+
+fn is_cyrillic(c: char) -> bool {
+    ('\u{0400}'..='\u{04FF}').contains(&c)
+}
+
+fn is_hebrew(c: char) -> bool {
+    ('\u{0590}'..='\u{05FF}').contains(&c)
+}
+
+fn median_length_ratio(examples: &[(QeFeatures, f64)]) -> f64 {
+    let mut ratios: Vec<f64> = examples
+        .iter()
+        .map(|(features, _)| features.length_ratio)
+        .filter(|ratio| *ratio > 0.0)
+        .collect();
+    if ratios.is_empty() {
+        return 1.0;
+    }
+    ratios.sort_by(|a, b| a.total_cmp(b));
+    ratios[ratios.len() / 2]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::learning_manager::EventMetrics;
+
+    fn correction(source: &str, machine_translation: &str, corrected: &str) -> LearningEvent {
+        LearningEvent::new(
+            LearningEventType::Correction {
+                original: machine_translation.to_string(),
+                corrected: corrected.to_string(),
+                error_type: String::new(),
+            },
+            source.to_string(),
+            machine_translation.to_string(),
+            EventMetrics::default(),
+            "fire_protection".to_string(),
+            String::new(),
+        )
+    }
+
+    fn history() -> Vec<LearningEvent> {
+        let mut events = Vec::new();
+        for i in 0..30 {
+            let source = format!("Давление {} бар в трубе", i);
+            if i % 2 == 0 {
+                // תרגום טוב שאושר כמעט ללא שינוי
+                events.push(correction(&source, &format!("לחץ {} בר בצינור", i), &format!("לחץ של {} בר בצינור", i)));
+            } else {
+                // מספר שגוי ומילה שלא תורגמה
+                events.push(correction(&source, &format!("לחץ {} трубе", i + 1), &format!("לחץ {} בר בצינור", i)));
+            }
+        }
+        events
+    }
+
+    #[test]
+    fn test_features() {
+        let estimator = QualityEstimator::new(Language::Hebrew);
+
+        let good = estimator.features(&QeSegment::new("Давление 2,5 бар", "לחץ 2.5 בר").with_confidence(0.9));
+        assert_eq!(good.number_match, 1.0);
+        assert_eq!(good.unknown_token_rate, 0.0);
+        assert_eq!(good.model_confidence, 0.9);
+        assert_eq!(good.back_translation_similarity, NEUTRAL_FEATURE);
+
+        let bad = estimator.features(
+            &QeSegment::new("Давление 2,5 бар", "לחץ 3 бар").with_back_translation("Температура 3 градуса"),
+        );
+        assert_eq!(bad.number_match, 0.0);
+        assert!((bad.unknown_token_rate - 1.0 / 3.0).abs() < 1e-9);
+        assert!(bad.back_translation_similarity < 0.5);
+    }
+
+    #[test]
+    fn test_training_from_history_separates_segments() {
+        let mut estimator = QualityEstimator::new(Language::Hebrew);
+        let report = estimator.train_from_history(&history()).unwrap();
+        assert_eq!(report.training_examples + report.calibration_examples, 30);
+
+        let good = estimator.estimate(&QeSegment::new("Давление 7 бар в трубе", "לחץ 7 בר בצינור"));
+        let bad = estimator.estimate(&QeSegment::new("Давление 7 бар в трубе", "לחץ 8 трубе"));
+        assert!(good.score > bad.score);
+        assert!((0.0..=1.0).contains(&good.score) && (0.0..=1.0).contains(&bad.score));
+
+        let document = estimator.estimate_document(&[
+            QeSegment::new("Давление 7 бар в трубе", "לחץ 7 בר בצינור"),
+            QeSegment::new("Давление 7 бар в трубе", "לחץ 8 трубе"),
+        ]);
+        assert!(document.score > bad.score && document.score < good.score);
+        assert!(document.flagged.contains(&1));
+        assert!(!document.flagged.contains(&0));
+    }
+
+    #[test]
+    fn test_isotonic_calibration_is_monotone() {
+        let points = isotonic_fit(vec![(0.1, 0.2), (0.2, 0.1), (0.5, 0.6), (0.7, 0.5), (0.9, 0.9)]);
+        assert!(points.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+
+        let model = QeModel { calibration: points, ..QeModel::default() };
+        assert!(model.calibrate(0.0) <= model.calibrate(0.6));
+        assert!(model.calibrate(0.6) <= model.calibrate(1.0));
+    }
+
+    #[test]
+    fn test_too_few_examples() {
+        let mut estimator = QualityEstimator::new(Language::Hebrew);
+        assert!(estimator.train_from_history(&history()[..5]).is_err());
+
+        // גם בלי סף מוגדר נדרשות שתי דוגמאות, אחת לאימון ואחת לכיול
+        let mut estimator = QualityEstimator::new(Language::Hebrew)
+            .with_training_config(QeTrainingConfig { min_examples: 0, ..QeTrainingConfig::default() });
+        assert!(estimator.train_from_history(&[]).is_err());
+        assert!(estimator.train_from_history(&history()[..1]).is_err());
+        assert!(estimator.train_from_history(&history()[..2]).is_ok());
+    }
+}
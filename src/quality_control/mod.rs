@@ -0,0 +1,844 @@
+pub mod back_translation;
+pub mod estimation;
+pub mod mqm;
+pub mod numbers;
+pub mod rules;
+pub mod spelling;
+pub mod terminology;
+pub mod typography;
+
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use anyhow::Result;
+use crate::morphology::{
+    HebrewMorphology, RussianMorphology,
+    HebrewAnalyzer, RussianAnalyzer,
+    Gender, Number, Case,
+};
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use crate::evaluation::{EvaluationMetrics, ErrorAnalysis, StyleGuide};
+use crate::language_detection::LanguageDetector;
+use crate::technical_dictionary::TechnicalDictionary;
+use self::back_translation::{BackTranslationResult, BackTranslator};
+use self::estimation::{DocumentEstimate, QeSegment, QualityEstimator, SegmentEstimate};
+use self::mqm::{MqmAnnotation, PenaltyModel, Scorecard};
+use self::numbers::{check_numbers, NumberValidation};
+use self::rules::{RuleCategory, RuleEngine, RuleFinding};
+use self::spelling::{Misspelling, SpellChecker};
+use self::terminology::{TermConsistencyIssue, TerminologyChecker};
+use self::typography::Typography;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QualityReport {
+    pub source_text: String,
+    pub translated_text: String,
+    pub metrics: EvaluationMetrics,
+    pub validation_results: ValidationResults,
+    pub suggestions: Vec<Suggestion>,
+    pub overall_quality_score: f64,
+    /// ציון QE מכויל (0-1) ללא תרגום ייחוס, כשמוגדר מעריך
+    #[serde(default)]
+    pub estimated_quality: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationResults {
+    pub technical_terms: Vec<TermValidation>,
+    pub grammar: Vec<GrammarValidation>,
+    pub style: Vec<StyleValidation>,
+    pub context: Vec<ContextValidation>,
+    /// מספרים, יחידות וקודי תקנים שחסרים, נוספו או שונו
+    #[serde(default)]
+    pub numbers: Vec<NumberValidation>,
+    /// מילים שגויות בתרגום, כשמוגדר בודק איות
+    #[serde(default)]
+    pub spelling: Vec<Misspelling>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TermValidation {
+    pub term: String,
+    pub translation: String,
+    pub expected: String,
+    pub is_valid: bool,
+    pub context: String,
+    pub domain: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrammarValidation {
+    pub text: String,
+    pub error_type: String,
+    pub suggestion: String,
+    pub severity: Severity,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StyleValidation {
+    pub text: String,
+    pub issue: String,
+    pub expected_style: String,
+    pub suggestion: String,
+    pub severity: Severity,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextValidation {
+    pub text: String,
+    pub context_type: String,
+    pub issue: String,
+    pub suggestion: String,
+    pub severity: Severity,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Suggestion {
+    pub text: String,
+    pub suggestion: String,
+    pub reason: String,
+    pub category: SuggestionCategory,
+    pub priority: Priority,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum Severity {
+    Critical,
+    Major,
+    Minor,
+    Info,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum Priority {
+    High,
+    Medium,
+    Low,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum SuggestionCategory {
+    Technical,
+    Grammar,
+    Style,
+    Context,
+    Spelling,
+    General,
+}
+
+pub struct QualityController {
+    technical_dictionary: TechnicalDictionary,
+    rules: RuleEngine,
+    /// התחום של המסמך, לבחירת הכללים הפעילים
+    domain: Option<String>,
+    quality_thresholds: QualityThresholds,
+    estimator: Option<QualityEstimator>,
+    terminology: Option<TerminologyChecker>,
+    spell_checker: Option<SpellChecker>,
+    penalty_model: PenaltyModel,
+    back_translator: Option<BackTranslator>,
+    /// נרמול טיפוגרפי של התרגום; התיקונים מדווחים כממצאי סגנון
+    typography: Option<Typography>,
+    /// משלב הפרויקט; הפרות מדווחות כממצאי סגנון עם החלופה המועדפת
+    style_guide: Option<StyleGuide>,
+}
+
+impl QualityController {
+    pub fn new(
+        technical_dictionary: TechnicalDictionary,
+        quality_thresholds: Option<QualityThresholds>,
+    ) -> Result<Self> {
+        Ok(Self {
+            technical_dictionary,
+            rules: RuleEngine::with_defaults()?,
+            domain: None,
+            quality_thresholds: quality_thresholds.unwrap_or_default(),
+            estimator: None,
+            terminology: None,
+            spell_checker: None,
+            penalty_model: PenaltyModel::default(),
+            back_translator: None,
+            typography: None,
+            style_guide: None,
+        })
+    }
+    
+    /// החלפת כללי ה-QA, למשל בכללים שנטענו מתיקייה עם RuleEngine::load
+    pub fn with_rules(mut self, rules: RuleEngine) -> Self {
+        self.rules = rules;
+        self
+    }
+    
+    pub fn with_domain(mut self, domain: impl Into<String>) -> Self {
+        self.domain = Some(domain.into());
+        self
+    }
+    
+    pub fn rules(&self) -> &RuleEngine {
+        &self.rules
+    }
+    
+    pub fn with_estimator(mut self, estimator: QualityEstimator) -> Self {
+        self.estimator = Some(estimator);
+        self
+    }
+    
+    pub fn with_spell_checker(mut self, checker: SpellChecker) -> Self {
+        self.spell_checker = Some(checker);
+        self
+    }
+    
+    pub fn spell_checker_mut(&mut self) -> Option<&mut SpellChecker> {
+        self.spell_checker.as_mut()
+    }
+    
+    /// המילים השגויות בטקסט, לסימון בממשק
+    pub fn check_spelling(&self, text: &str) -> Vec<Misspelling> {
+        self.spell_checker
+            .as_ref()
+            .map(|checker| checker.check_text(text))
+            .unwrap_or_default()
+    }
+    
+    pub fn with_back_translator(mut self, back_translator: BackTranslator) -> Self {
+        self.back_translator = Some(back_translator);
+        self
+    }
+    
+    /// תרגום חוזר של המסמך לשפת המקור והשוואה למקור; segments הם זוגות (מקור, תרגום) לפי הסדר
+    pub async fn check_back_translation(&self, segments: &[(String, String)]) -> Result<Vec<BackTranslationResult>> {
+        match &self.back_translator {
+            Some(back_translator) => back_translator.check_document(segments).await,
+            None => Ok(Vec::new()),
+        }
+    }
+    
+    pub fn with_typography(mut self, typography: Typography) -> Self {
+        self.typography = Some(typography);
+        self
+    }
+    
+    /// תיקון אוטומטי של גרשיים, מקפים, מירכאות ורווחים בלתי שבירים בתרגום
+    pub fn autofix_typography(&self, translated_text: &str) -> String {
+        match &self.typography {
+            Some(typography) => typography.normalize(translated_text),
+            None => translated_text.to_string(),
+        }
+    }
+    
+    pub fn with_style_guide(mut self, style_guide: StyleGuide) -> Self {
+        self.style_guide = Some(style_guide);
+        self
+    }
+    
+    /// מודל הקנסות של הלקוח לכרטיסי ציון LQA
+    pub fn with_penalty_model(mut self, model: PenaltyModel) -> Self {
+        self.penalty_model = model;
+        self
+    }
+    
+    /// כרטיס ציון LQA למסמך; annotations הם התיוגים של הבודק, שאפשר להתחיל מ-mqm::annotations_from_report
+    pub fn lqa_scorecard(&self, document: &str, reports: &[QualityReport], annotations: Vec<MqmAnnotation>) -> Scorecard {
+        let word_count = reports
+            .iter()
+            .map(|report| report.source_text.split_whitespace().count())
+            .sum();
+        self.penalty_model.score(document, word_count, annotations)
+    }
+    
+    pub fn with_terminology(mut self, checker: TerminologyChecker) -> Self {
+        self.terminology = Some(checker);
+        self
+    }
+    
+    pub fn terminology_mut(&mut self) -> Option<&mut TerminologyChecker> {
+        self.terminology.as_mut()
+    }
+    
+    /// בדיקת עקביות המינוח במסמך כולו; segments הם זוגות (מקור, תרגום) לפי הסדר
+    pub fn check_document_terminology(&self, segments: &[(String, String)]) -> Vec<TermConsistencyIssue> {
+        self.terminology
+            .as_ref()
+            .map(|checker| checker.check_document(segments))
+            .unwrap_or_default()
+    }
+    
+    pub fn estimator_mut(&mut self) -> Option<&mut QualityEstimator> {
+        self.estimator.as_mut()
+    }
+    
+    /// הערכת איכות לסגמנט בודד ללא תרגום ייחוס
+    pub fn estimate_segment(&self, segment: &QeSegment) -> Option<SegmentEstimate> {
+        self.estimator.as_ref().map(|estimator| estimator.estimate(segment))
+    }
+    
+    /// הערכת איכות למסמך שלם, עם הסגמנטים שמומלץ להעביר לבדיקה
+    pub fn estimate_document(&self, segments: &[QeSegment]) -> Option<DocumentEstimate> {
+        self.estimator.as_ref().map(|estimator| estimator.estimate_document(segments))
+    }
+    
+    pub fn check_quality(&self, source_text: &str, translated_text: &str) -> QualityReport {
+        self.check_segment_quality(&QeSegment::new(source_text, translated_text))
+    }
+    
+    /// כמו check_quality, כשידועים גם ביטחון המודל והתרגום החוזר של הסגמנט; שניהם נכנסים להערכת האיכות
+    pub fn check_segment_quality(&self, segment: &QeSegment) -> QualityReport {
+        self.reload_rules();
+        self.check_with_loaded_rules(segment)
+    }
+    
+    /// בדיקת כל הסגמנטים של מסמך; קבצי הכללים נבדקים פעם אחת לפני המסמך, כך שכל הסגמנטים נבדקים באותם כללים
+    pub fn check_document_quality(&self, segments: &[QeSegment]) -> Vec<QualityReport> {
+        self.reload_rules();
+        segments.iter().map(|segment| self.check_with_loaded_rules(segment)).collect()
+    }
+    
+    /// טעינה מחדש של קבצי כללים שהשתנו; בשגיאה ממשיכים עם הכללים הקודמים
+    fn reload_rules(&self) {
+        if let Err(e) = self.rules.reload_if_changed() {
+            log::warn!("טעינת כללי QA מחדש נכשלה, ממשיך עם הכללים הקודמים: {:#}", e);
+        }
+    }
+    
+    fn check_with_loaded_rules(&self, segment: &QeSegment) -> QualityReport {
+        let source_text = segment.source.as_str();
+        let translated_text = segment.target.as_str();
+        
+        // בדיקת מונחים טכניים
+        let term_validations = self.validate_technical_terms(source_text, translated_text);
+        
+        // כללי דקדוק, סגנון והקשר
+        let mut findings = self.rules.check(source_text, translated_text, self.domain.as_deref());
+        if let Some(typography) = &self.typography {
+            findings.extend(typography.findings(translated_text));
+        }
+        if let Some(style_guide) = &self.style_guide {
+            let language = LanguageDetector::new().detect_language(translated_text);
+            findings.extend(
+                style_guide
+                    .register_issues(translated_text, language, self.domain.as_deref())
+                    .into_iter()
+                    .map(|issue| RuleFinding {
+                        rule_id: issue.kind.rule_id().to_string(),
+                        category: RuleCategory::Style,
+                        severity: Severity::Minor,
+                        message: issue.message,
+                        span: Some(issue.span),
+                        matched: issue.matched,
+                        replacement: issue.replacement,
+                    }),
+            );
+        }
+        let grammar_validations = self.validate_grammar(translated_text, &findings);
+        let style_validations = self.validate_style(translated_text, &findings);
+        let context_validations = self.validate_context(translated_text, &findings);
+        
+        // בדיקת מספרים, יחידות וקודי תקנים
+        let number_validations = check_numbers(source_text, translated_text);
+        
+        // בדיקת איות של התרגום
+        let misspellings = self.check_spelling(translated_text);
+        
+        // חישוב מדדי איכות
+        let metrics = self.calculate_metrics(
+            source_text,
+            translated_text,
+            &term_validations,
+            &grammar_validations,
+            &style_validations,
+            &context_validations,
+            &number_validations,
+            &misspellings,
+        );
+        
+        // יצירת הצעות לשיפור
+        let suggestions = self.generate_suggestions(
+            &term_validations,
+            &grammar_validations,
+            &style_validations,
+            &context_validations,
+            &number_validations,
+            &misspellings,
+        );
+        
+        // חישוב ציון איכות כולל
+        let overall_quality_score = self.calculate_overall_score(&metrics, &term_validations);
+        
+        // הערכת איכות ללא ייחוס
+        let estimated_quality = self
+            .estimate_segment(segment)
+            .map(|estimate| estimate.score);
+        
+        QualityReport {
+            source_text: source_text.to_string(),
+            translated_text: translated_text.to_string(),
+            metrics,
+            validation_results: ValidationResults {
+                technical_terms: term_validations,
+                grammar: grammar_validations,
+                style: style_validations,
+                context: context_validations,
+                numbers: number_validations,
+                spelling: misspellings,
+            },
+            suggestions,
+            overall_quality_score,
+            estimated_quality,
+        }
+    }
+    
+    fn validate_technical_terms(&self, source_text: &str, translated_text: &str) -> Vec<TermValidation> {
+        let mut validations = Vec::new();
+        
+        // בדיקת מונחים טכניים
+        for (term, expected) in self.technical_dictionary.get_terms() {
+            if source_text.contains(term) {
+                validations.push(TermValidation {
+                    term: term.to_string(),
+                    translation: self.find_term_translation(term, translated_text),
+                    expected: expected.to_string(),
+                    is_valid: translated_text.contains(&expected),
+                    context: self.technical_dictionary.get_context(term)
+                        .unwrap_or_default(),
+                    domain: self.technical_dictionary.get_domain(term)
+                        .unwrap_or_default(),
+                });
+            }
+        }
+        
+        validations
+    }
+    
+    fn validate_grammar(&self, text: &str, findings: &[RuleFinding]) -> Vec<GrammarValidation> {
+        findings
+            .iter()
+            .filter(|f| f.category == RuleCategory::Grammar)
+            .map(|f| GrammarValidation {
+                text: finding_text(f, text),
+                error_type: f.message.clone(),
+                suggestion: f.replacement.clone().unwrap_or_default(),
+                severity: f.severity,
+            })
+            .collect()
+    }
+    
+    fn validate_style(&self, text: &str, findings: &[RuleFinding]) -> Vec<StyleValidation> {
+        findings
+            .iter()
+            .filter(|f| f.category == RuleCategory::Style)
+            .map(|f| StyleValidation {
+                text: finding_text(f, text),
+                issue: f.message.clone(),
+                expected_style: f.rule_id.clone(),
+                suggestion: f.replacement.clone().unwrap_or_default(),
+                severity: f.severity,
+            })
+            .collect()
+    }
+    
+    fn validate_context(&self, text: &str, findings: &[RuleFinding]) -> Vec<ContextValidation> {
+        findings
+            .iter()
+            .filter(|f| f.category == RuleCategory::Context)
+            .map(|f| ContextValidation {
+                text: finding_text(f, text),
+                context_type: f.rule_id.clone(),
+                issue: f.message.clone(),
+                suggestion: f.replacement.clone().unwrap_or_default(),
+                severity: f.severity,
+            })
+            .collect()
+    }
+    
+    fn calculate_metrics(
+        &self,
+        source_text: &str,
+        translated_text: &str,
+        term_validations: &[TermValidation],
+        grammar_validations: &[GrammarValidation],
+        style_validations: &[StyleValidation],
+        context_validations: &[ContextValidation],
+        number_validations: &[NumberValidation],
+        misspellings: &[Misspelling],
+    ) -> EvaluationMetrics {
+        // חישוב מדדי איכות שונים
+        let technical_accuracy = self.calculate_technical_accuracy(term_validations);
+        let fluency_score = self.calculate_fluency_score(grammar_validations, style_validations, misspellings);
+        let adequacy_score = self.calculate_adequacy_score(context_validations, number_validations);
+        
+        EvaluationMetrics {
+            bleu_score: 0.0, // יש להוסיף חישוב BLEU
+            meteor_score: 0.0, // יש להוסיף חישוב METEOR
+            ter_score: 0.0, // יש להוסיף חישוב TER
+            chrf_score: 0.0, // יש להוסיף חישוב chrF
+            technical_accuracy,
+            fluency_score,
+            adequacy_score,
+            error_analysis: ErrorAnalysis::default(),
+        }
+    }
+    
+    fn generate_suggestions(
+        &self,
+        term_validations: &[TermValidation],
+        grammar_validations: &[GrammarValidation],
+        style_validations: &[StyleValidation],
+        context_validations: &[ContextValidation],
+        number_validations: &[NumberValidation],
+        misspellings: &[Misspelling],
+    ) -> Vec<Suggestion> {
+        let mut suggestions = Vec::new();
+        
+        // הצעות למונחים טכניים
+        for validation in term_validations {
+            if !validation.is_valid {
+                suggestions.push(Suggestion {
+                    text: validation.translation.clone(),
+                    suggestion: validation.expected.clone(),
+                    reason: format!("מונח טכני לא תקין בתחום {}", validation.domain),
+                    category: SuggestionCategory::Technical,
+                    priority: Priority::High,
+                });
+            }
+        }
+        
+        // הצעות לתיקוני דקדוק
+        for validation in grammar_validations {
+            suggestions.push(Suggestion {
+                text: validation.text.clone(),
+                suggestion: validation.suggestion.clone(),
+                reason: validation.error_type.clone(),
+                category: SuggestionCategory::Grammar,
+                priority: match validation.severity {
+                    Severity::Critical => Priority::High,
+                    Severity::Major => Priority::High,
+                    Severity::Minor => Priority::Medium,
+                    Severity::Info => Priority::Low,
+                },
+            });
+        }
+        
+        // הצעות לשיפור סגנון
+        for validation in style_validations {
+            suggestions.push(Suggestion {
+                text: validation.text.clone(),
+                suggestion: validation.suggestion.clone(),
+                reason: validation.issue.clone(),
+                category: SuggestionCategory::Style,
+                priority: match validation.severity {
+                    Severity::Critical => Priority::High,
+                    Severity::Major => Priority::Medium,
+                    Severity::Minor => Priority::Low,
+                    Severity::Info => Priority::Low,
+                },
+            });
+        }
+        
+        // הצעות להקשר
+        for validation in context_validations {
+            suggestions.push(Suggestion {
+                text: validation.text.clone(),
+                suggestion: validation.suggestion.clone(),
+                reason: validation.issue.clone(),
+                category: SuggestionCategory::Context,
+                priority: match validation.severity {
+                    Severity::Critical => Priority::High,
+                    Severity::Major => Priority::Medium,
+                    Severity::Minor => Priority::Low,
+                    Severity::Info => Priority::Low,
+                },
+            });
+        }
+        
+        // הצעות לתיקון מספרים ויחידות
+        for validation in number_validations {
+            suggestions.push(Suggestion {
+                text: validation.target.clone().unwrap_or_default(),
+                suggestion: validation.source.clone().unwrap_or_default(),
+                reason: validation.message.clone(),
+                category: SuggestionCategory::Technical,
+                priority: match validation.severity {
+                    Severity::Critical => Priority::High,
+                    Severity::Major => Priority::High,
+                    Severity::Minor => Priority::Medium,
+                    Severity::Info => Priority::Low,
+                },
+            });
+        }
+        
+        // הצעות לתיקון איות
+        for misspelling in misspellings {
+            suggestions.push(Suggestion {
+                text: misspelling.word.clone(),
+                suggestion: misspelling.suggestions.first().cloned().unwrap_or_default(),
+                reason: format!("שגיאת איות: {}", misspelling.word),
+                category: SuggestionCategory::Spelling,
+                priority: Priority::Medium,
+            });
+        }
+        
+        // מיון ההצעות לפי עדיפות
+        suggestions.sort_by(|a, b| b.priority.cmp(&a.priority));
+        
+        suggestions
+    }
+    
+    fn calculate_overall_score(&self, metrics: &EvaluationMetrics, term_validations: &[TermValidation]) -> f64 {
+        let technical_weight = 0.4;
+        let fluency_weight = 0.3;
+        let adequacy_weight = 0.3;
+        
+        let technical_score = metrics.technical_accuracy;
+        let fluency_score = metrics.fluency_score;
+        let adequacy_score = metrics.adequacy_score;
+        
+        technical_weight * technical_score +
+            fluency_weight * fluency_score +
+            adequacy_weight * adequacy_score
+    }
+    
+    fn calculate_technical_accuracy(&self, validations: &[TermValidation]) -> f64 {
+        if validations.is_empty() {
+            return 1.0;
+        }
+        
+        let valid_terms = validations.iter()
+            .filter(|v| v.is_valid)
+            .count();
+            
+        valid_terms as f64 / validations.len() as f64
+    }
+    
+    fn calculate_fluency_score(
+        &self,
+        grammar_validations: &[GrammarValidation],
+        style_validations: &[StyleValidation],
+        misspellings: &[Misspelling],
+    ) -> f64 {
+        let mut score = 1.0;
+        
+        // הורדת ניקוד עבור שגיאות דקדוק
+        for validation in grammar_validations {
+            score -= match validation.severity {
+                Severity::Critical => 0.2,
+                Severity::Major => 0.1,
+                Severity::Minor => 0.05,
+                Severity::Info => 0.01,
+            };
+        }
+        
+        // הורדת ניקוד עבור בעיות סגנון
+        for validation in style_validations {
+            score -= match validation.severity {
+                Severity::Critical => 0.15,
+                Severity::Major => 0.08,
+                Severity::Minor => 0.03,
+                Severity::Info => 0.01,
+            };
+        }
+        
+        // הורדת ניקוד עבור שגיאות איות
+        for misspelling in misspellings {
+            score -= match misspelling.severity {
+                Severity::Critical => 0.1,
+                Severity::Major => 0.05,
+                Severity::Minor => 0.03,
+                Severity::Info => 0.01,
+            };
+        }
+        
+        score.max(0.0)
+    }
+    
+    fn calculate_adequacy_score(
+        &self,
+        context_validations: &[ContextValidation],
+        number_validations: &[NumberValidation],
+    ) -> f64 {
+        let mut score = 1.0;
+        
+        // הורדת ניקוד עבור בעיות הקשר
+        for validation in context_validations {
+            score -= match validation.severity {
+                Severity::Critical => 0.2,
+                Severity::Major => 0.1,
+                Severity::Minor => 0.05,
+                Severity::Info => 0.01,
+            };
+        }
+        
+        // מספר שגוי במפרט חמור משגיאת הקשר
+        for validation in number_validations {
+            score -= match validation.severity {
+                Severity::Critical => 0.3,
+                Severity::Major => 0.15,
+                Severity::Minor => 0.05,
+                Severity::Info => 0.01,
+            };
+        }
+        
+        score.max(0.0)
+    }
+    
+    fn find_term_translation(&self, term: &str, text: &str) -> String {
+        // TODO: מימוש מתקדם יותר למציאת התרגום בפועל
+        text.to_string()
+    }
+}
+
+/// הטקסט שהממצא מתייחס אליו; ממצא על הסגמנט כולו מחזיר את התרגום כולו
+fn finding_text(finding: &RuleFinding, text: &str) -> String {
+    if finding.span.is_some() {
+        finding.matched.clone()
+    } else {
+        text.to_string()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct QualityThresholds {
+    pub min_technical_accuracy: f64,
+    pub min_fluency_score: f64,
+    pub min_adequacy_score: f64,
+    pub min_overall_score: f64,
+}
+
+impl Default for QualityThresholds {
+    fn default() -> Self {
+        Self {
+            min_technical_accuracy: 0.95,
+            min_fluency_score: 0.8,
+            min_adequacy_score: 0.8,
+            min_overall_score: 0.85,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language_detection::Language;
+    
+    #[test]
+    fn test_technical_terms_validation() {
+        let mut dictionary = TechnicalDictionary::new();
+        dictionary.add_term("sprinkler", "ספרינקלר", "fire_protection", "");
+        
+        let controller = QualityController::new(dictionary, None).unwrap();
+        
+        let report = controller.check_quality(
+            "Install a sprinkler system",
+            "התקן מערכת ספרינקלר",
+        );
+        
+        assert!(report.validation_results.technical_terms[0].is_valid);
+        assert!(report.overall_quality_score > 0.9);
+    }
+    
+    #[test]
+    fn test_invalid_translation() {
+        let mut dictionary = TechnicalDictionary::new();
+        dictionary.add_term("sprinkler", "ספרינקלר", "fire_protection", "");
+        
+        let controller = QualityController::new(dictionary, None).unwrap();
+        
+        let report = controller.check_quality(
+            "Install a sprinkler system",
+            "התקן מערכת כיבוי",
+        );
+        
+        assert!(!report.validation_results.technical_terms[0].is_valid);
+        assert!(report.overall_quality_score < 0.9);
+    }
+    
+    #[test]
+    fn test_rule_findings_in_report() {
+        let controller = QualityController::new(TechnicalDictionary::new(), None).unwrap();
+        
+        let report = controller.check_quality(
+            "Задвижка должна быть закрыта.",
+            "המגוף סגורה .",
+        );
+        
+        assert_eq!(report.validation_results.grammar.len(), 1);
+        assert_eq!(report.validation_results.grammar[0].text, "המגוף סגורה");
+        assert_eq!(report.validation_results.style[0].suggestion, ".");
+        assert_eq!(report.validation_results.context[0].context_type, "obligation-lost");
+        assert!(report.metrics.fluency_score < 1.0);
+    }
+    
+    #[test]
+    fn test_typography_autofix() {
+        let controller = QualityController::new(TechnicalDictionary::new(), None)
+            .unwrap()
+            .with_typography(Typography::new(typography::Locale::Hebrew));
+        
+        let report = controller.check_quality(
+            "Давление 10 бар по ГОСТ 12.2.",
+            "לחץ של 10 בר לפי ת\"י 12.2.",
+        );
+        
+        assert!(report.validation_results.style.iter().any(|s| s.expected_style == "typography-gershayim"));
+        assert_eq!(
+            controller.autofix_typography(&report.translated_text),
+            "לחץ של 10\u{00A0}בר לפי ת״י 12.2."
+        );
+    }
+    
+    #[test]
+    fn test_segment_quality_uses_model_confidence() {
+        let controller = QualityController::new(TechnicalDictionary::new(), None)
+            .unwrap()
+            .with_estimator(QualityEstimator::new(Language::Hebrew));
+        let segment = QeSegment::new("Давление 7 бар", "לחץ 7 בר");
+        
+        let confident = controller.check_segment_quality(&segment.clone().with_confidence(0.95));
+        let unsure = controller.check_segment_quality(&segment.with_confidence(0.05));
+        
+        assert!(confident.estimated_quality.unwrap() > unsure.estimated_quality.unwrap());
+    }
+    
+    #[test]
+    fn test_document_is_checked_with_one_rule_set() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("custom.toml");
+        let rule = |id: &str| format!("[[rules]]\nid = \"{}\"\ncategory = \"style\"\npattern = 'xx'\nseverity = \"Minor\"\nmessage = \"m\"\n", id);
+        std::fs::write(&path, rule("first")).unwrap();
+        let rules = RuleEngine::load(vec![dir.path().to_path_buf()], false)
+            .unwrap()
+            .with_reload_interval(std::time::Duration::ZERO);
+        let controller = QualityController::new(TechnicalDictionary::new(), None)
+            .unwrap()
+            .with_rules(rules);
+        
+        std::fs::write(&path, rule("second")).unwrap();
+        let reports = controller.check_document_quality(&[
+            QeSegment::new("a", "xx"),
+            QeSegment::new("b", "yy xx"),
+        ]);
+        
+        assert_eq!(reports.len(), 2);
+        for report in &reports {
+            assert_eq!(report.validation_results.style[0].expected_style, "second");
+        }
+    }
+    
+    #[test]
+    fn test_register_findings() {
+        let mut style_guide = StyleGuide::new(crate::evaluation::FormalityLevel::Formal);
+        style_guide.add_domain_rule("fire_protection".to_string(), vec!["כבאי => לוחם אש".to_string()]);
+        let controller = QualityController::new(TechnicalDictionary::new(), None)
+            .unwrap()
+            .with_domain("fire_protection")
+            .with_style_guide(style_guide);
+        
+        let report = controller.check_quality(
+            "Сейчас пожарный должен закрыть задвижку.",
+            "עכשיו על כבאי לסגור את המגוף.",
+        );
+        
+        let style = &report.validation_results.style;
+        assert!(style.iter().any(|s| s.expected_style == "register-vocabulary" && s.suggestion == "כעת"));
+        assert!(style.iter().any(|s| s.expected_style == "register-domain" && s.suggestion == "לוחם אש"));
+    }
+}
@@ -0,0 +1,425 @@
+//! תיוג שגיאות לפי טיפולוגיית MQM וכרטיסי ציון LQA ללקוחות: ניקוד לפי מודל קנסות הניתן להגדרה,
+//! סף מעבר, וייצוא ל-JSON ול-HTML
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use anyhow::{Context, Result};
+use serde::{Serialize, Deserialize};
+use super::{QualityReport, Severity, Suggestion, SuggestionCategory};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MqmCategory {
+    Accuracy,
+    Fluency,
+    Terminology,
+    Style,
+    Locale,
+}
+
+impl MqmCategory {
+    pub fn name(&self) -> &'static str {
+        match self {
+            MqmCategory::Accuracy => "Accuracy",
+            MqmCategory::Fluency => "Fluency",
+            MqmCategory::Terminology => "Terminology",
+            MqmCategory::Style => "Style",
+            MqmCategory::Locale => "Locale conventions",
+        }
+    }
+}
+
+impl From<&SuggestionCategory> for MqmCategory {
+    fn from(category: &SuggestionCategory) -> Self {
+        match category {
+            SuggestionCategory::Technical => MqmCategory::Terminology,
+            SuggestionCategory::Grammar | SuggestionCategory::Spelling | SuggestionCategory::General => MqmCategory::Fluency,
+            SuggestionCategory::Style => MqmCategory::Style,
+            SuggestionCategory::Context => MqmCategory::Accuracy,
+        }
+    }
+}
+
+/// שגיאה מתויגת בסגמנט; Severity::Info משמש כחומרה ניטרלית (הערה ללא קנס)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MqmAnnotation {
+    pub segment: usize,
+    pub category: MqmCategory,
+    /// תת-קטגוריה לפי MQM, למשל "mistranslation" או "spelling"
+    #[serde(default)]
+    pub subcategory: Option<String>,
+    pub severity: Severity,
+    /// הטקסט בתרגום שהשגיאה מתייחסת אליו
+    pub text: String,
+    #[serde(default)]
+    pub comment: String,
+    /// הבודק שתייג; ריק לתיוג אוטומטי
+    #[serde(default)]
+    pub reviewer: Option<String>,
+}
+
+impl MqmAnnotation {
+    pub fn new(segment: usize, category: MqmCategory, severity: Severity, text: impl Into<String>) -> Self {
+        Self {
+            segment,
+            category,
+            subcategory: None,
+            severity,
+            text: text.into(),
+            comment: String::new(),
+            reviewer: None,
+        }
+    }
+
+    pub fn with_subcategory(mut self, subcategory: impl Into<String>) -> Self {
+        self.subcategory = Some(subcategory.into());
+        self
+    }
+
+    pub fn with_comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = comment.into();
+        self
+    }
+
+    pub fn with_reviewer(mut self, reviewer: impl Into<String>) -> Self {
+        self.reviewer = Some(reviewer.into());
+        self
+    }
+
+    /// תיוג של הצעה קיימת; הבודק קובע את החומרה
+    pub fn from_suggestion(segment: usize, suggestion: &Suggestion, severity: Severity) -> Self {
+        let annotation = Self::new(segment, MqmCategory::from(&suggestion.category), severity, suggestion.text.clone())
+            .with_comment(suggestion.reason.clone());
+        match suggestion.category {
+            SuggestionCategory::Spelling => annotation.with_subcategory("spelling"),
+            SuggestionCategory::Grammar => annotation.with_subcategory("grammar"),
+            _ => annotation,
+        }
+    }
+}
+
+/// תיוג אוטומטי של הממצאים בדוח בדיקת איכות, כנקודת פתיחה לבודק
+pub fn annotations_from_report(segment: usize, report: &QualityReport) -> Vec<MqmAnnotation> {
+    let results = &report.validation_results;
+    let mut annotations = Vec::new();
+
+    for term in results.technical_terms.iter().filter(|t| !t.is_valid) {
+        annotations.push(
+            MqmAnnotation::new(segment, MqmCategory::Terminology, Severity::Major, term.translation.clone())
+                .with_subcategory("wrong term")
+                .with_comment(format!("{} -> {}", term.term, term.expected)),
+        );
+    }
+    for grammar in &results.grammar {
+        annotations.push(
+            MqmAnnotation::new(segment, MqmCategory::Fluency, grammar.severity, grammar.text.clone())
+                .with_subcategory("grammar")
+                .with_comment(grammar.error_type.clone()),
+        );
+    }
+    for style in &results.style {
+        annotations.push(
+            MqmAnnotation::new(segment, MqmCategory::Style, style.severity, style.text.clone())
+                .with_comment(style.issue.clone()),
+        );
+    }
+    for context in &results.context {
+        annotations.push(
+            MqmAnnotation::new(segment, MqmCategory::Accuracy, context.severity, context.text.clone())
+                .with_subcategory("mistranslation")
+                .with_comment(context.issue.clone()),
+        );
+    }
+    for number in &results.numbers {
+        annotations.push(
+            MqmAnnotation::new(segment, MqmCategory::Accuracy, number.severity, number.target.clone().unwrap_or_default())
+                .with_subcategory("number")
+                .with_comment(number.message.clone()),
+        );
+    }
+    for misspelling in &results.spelling {
+        annotations.push(
+            MqmAnnotation::new(segment, MqmCategory::Fluency, misspelling.severity, misspelling.word.clone())
+                .with_subcategory("spelling"),
+        );
+    }
+    annotations
+}
+
+/// מודל קנסות: נקודות לכל חומרה, מכפיל לכל קטגוריה וסף מעבר; ניתן לטעינה מ-JSON של הלקוח
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PenaltyModel {
+    pub neutral: f64,
+    pub minor: f64,
+    pub major: f64,
+    pub critical: f64,
+    pub category_weights: HashMap<MqmCategory, f64>,
+    /// הציון (0-100) הנדרש למעבר
+    pub pass_threshold: f64,
+    /// שגיאה קריטית אחת נכשלת בלי קשר לציון
+    pub critical_fails: bool,
+}
+
+impl Default for PenaltyModel {
+    fn default() -> Self {
+        Self {
+            neutral: 0.0,
+            minor: 1.0,
+            major: 5.0,
+            critical: 25.0,
+            category_weights: HashMap::new(),
+            pass_threshold: 99.0,
+            critical_fails: false,
+        }
+    }
+}
+
+impl PenaltyModel {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).with_context(|| format!("קריאת {}", path.display()))?;
+        serde_json::from_str(&content).with_context(|| format!("מודל קנסות לא תקין: {}", path.display()))
+    }
+
+    pub fn with_category_weight(mut self, category: MqmCategory, weight: f64) -> Self {
+        self.category_weights.insert(category, weight);
+        self
+    }
+
+    pub fn with_pass_threshold(mut self, threshold: f64) -> Self {
+        self.pass_threshold = threshold;
+        self
+    }
+
+    pub fn penalty(&self, annotation: &MqmAnnotation) -> f64 {
+        let points = match annotation.severity {
+            Severity::Critical => self.critical,
+            Severity::Major => self.major,
+            Severity::Minor => self.minor,
+            Severity::Info => self.neutral,
+        };
+        points * self.category_weights.get(&annotation.category).copied().unwrap_or(1.0)
+    }
+
+    /// ציון MQM: 100 פחות הקנס הכולל לכל מילה במקור, כאחוז
+    pub fn score(&self, document: &str, word_count: usize, annotations: Vec<MqmAnnotation>) -> Scorecard {
+        let mut categories: BTreeMap<MqmCategory, CategoryTotals> = BTreeMap::new();
+        for annotation in &annotations {
+            let totals = categories.entry(annotation.category).or_default();
+            match annotation.severity {
+                Severity::Critical => totals.critical += 1,
+                Severity::Major => totals.major += 1,
+                Severity::Minor => totals.minor += 1,
+                Severity::Info => totals.neutral += 1,
+            }
+            totals.penalty += self.penalty(annotation);
+        }
+
+        let total_penalty: f64 = categories.values().map(|t| t.penalty).sum();
+        let score = if word_count == 0 {
+            if total_penalty > 0.0 { 0.0 } else { 100.0 }
+        } else {
+            (100.0 * (1.0 - total_penalty / word_count as f64)).max(0.0)
+        };
+        let has_critical = categories.values().any(|t| t.critical > 0);
+        let passed = score >= self.pass_threshold && !(self.critical_fails && has_critical);
+
+        Scorecard {
+            document: document.to_string(),
+            word_count,
+            annotations,
+            categories,
+            total_penalty,
+            score,
+            pass_threshold: self.pass_threshold,
+            passed,
+            model: self.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CategoryTotals {
+    pub neutral: usize,
+    pub minor: usize,
+    pub major: usize,
+    pub critical: usize,
+    pub penalty: f64,
+}
+
+/// כרטיס ציון LQA למסמך
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scorecard {
+    pub document: String,
+    pub word_count: usize,
+    pub annotations: Vec<MqmAnnotation>,
+    pub categories: BTreeMap<MqmCategory, CategoryTotals>,
+    pub total_penalty: f64,
+    pub score: f64,
+    pub pass_threshold: f64,
+    pub passed: bool,
+    pub model: PenaltyModel,
+}
+
+impl Scorecard {
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn to_html(&self) -> String {
+        let category_rows: String = self
+            .categories
+            .iter()
+            .map(|(category, totals)| {
+                format!(
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{:.1}</td></tr>\n",
+                    category.name(), totals.neutral, totals.minor, totals.major, totals.critical, totals.penalty
+                )
+            })
+            .collect();
+        let annotation_rows: String = self
+            .annotations
+            .iter()
+            .map(|a| {
+                format!(
+                    "<tr><td>{}</td><td>{}{}</td><td>{:?}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                    a.segment + 1,
+                    a.category.name(),
+                    a.subcategory.as_deref().map(|s| format!(" / {}", escape_html(s))).unwrap_or_default(),
+                    a.severity,
+                    escape_html(&a.text),
+                    escape_html(&a.comment),
+                    escape_html(a.reviewer.as_deref().unwrap_or("")),
+                )
+            })
+            .collect();
+
+        format!(
+            r#"<!DOCTYPE html>
+<html dir="rtl">
+<head>
+    <meta charset="UTF-8">
+    <title>LQA - {document}</title>
+    <style>
+        body {{
+            font-family: Arial, sans-serif;
+            margin: 2em;
+            line-height: 1.6;
+        }}
+        table {{
+            border-collapse: collapse;
+            margin-bottom: 2em;
+        }}
+        td, th {{
+            border: 1px solid #ccc;
+            padding: 0.3em 0.8em;
+        }}
+        .pass {{ color: #2e7d32; }}
+        .fail {{ color: #c62828; }}
+    </style>
+</head>
+<body>
+    <h1>כרטיס ציון LQA: {document}</h1>
+    <p>מילים: {words} | קנס כולל: {penalty:.1} | ציון: {score:.2} (סף {threshold:.2})</p>
+    <p class="{class}"><strong>{verdict}</strong></p>
+    <table>
+        <tr><th>קטגוריה</th><th>ניטרלי</th><th>קל</th><th>משמעותי</th><th>קריטי</th><th>קנס</th></tr>
+        {category_rows}
+    </table>
+    <table>
+        <tr><th>סגמנט</th><th>קטגוריה</th><th>חומרה</th><th>טקסט</th><th>הערה</th><th>בודק</th></tr>
+        {annotation_rows}
+    </table>
+</body>
+</html>"#,
+            document = escape_html(&self.document),
+            words = self.word_count,
+            penalty = self.total_penalty,
+            score = self.score,
+            threshold = self.pass_threshold,
+            class = if self.passed { "pass" } else { "fail" },
+            verdict = if self.passed { "עבר" } else { "נכשל" },
+            category_rows = category_rows,
+            annotation_rows = annotation_rows,
+        )
+    }
+
+    /// שמירה לפי הסיומת: .html או .json
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = match path.extension().and_then(|e| e.to_str()) {
+            Some("html") | Some("htm") => self.to_html(),
+            _ => self.to_json()?,
+        };
+        fs::write(path, content).with_context(|| format!("כתיבת {}", path.display()))
+    }
+}
+
+pub(super) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn annotations() -> Vec<MqmAnnotation> {
+        vec![
+            MqmAnnotation::new(0, MqmCategory::Accuracy, Severity::Major, "1,6 בר").with_subcategory("number"),
+            MqmAnnotation::new(1, MqmCategory::Fluency, Severity::Minor, "המגוב").with_subcategory("spelling"),
+            MqmAnnotation::new(1, MqmCategory::Terminology, Severity::Minor, "שסתום").with_reviewer("reviewer"),
+            MqmAnnotation::new(2, MqmCategory::Style, Severity::Info, "<רווח>"),
+        ]
+    }
+
+    #[test]
+    fn test_default_penalty_model() {
+        let scorecard = PenaltyModel::default().score("spec.docx", 1000, annotations());
+
+        assert_eq!(scorecard.total_penalty, 7.0);
+        assert!((scorecard.score - 99.3).abs() < 1e-9);
+        assert!(scorecard.passed);
+        assert_eq!(scorecard.categories[&MqmCategory::Fluency].minor, 1);
+        assert_eq!(scorecard.categories[&MqmCategory::Style].neutral, 1);
+    }
+
+    #[test]
+    fn test_configured_model_and_critical_errors() {
+        let model = PenaltyModel::default()
+            .with_category_weight(MqmCategory::Accuracy, 2.0)
+            .with_pass_threshold(99.5);
+        let scorecard = model.score("spec.docx", 1000, annotations());
+        assert_eq!(scorecard.total_penalty, 12.0);
+        assert!(!scorecard.passed);
+
+        let model: PenaltyModel = serde_json::from_str(r#"{"critical": 10, "critical_fails": true, "pass_threshold": 90}"#).unwrap();
+        assert_eq!(model.minor, 1.0);
+        let mut critical = annotations();
+        critical.push(MqmAnnotation::new(3, MqmCategory::Accuracy, Severity::Critical, "מותר"));
+        let scorecard = model.score("spec.docx", 1000, critical);
+        assert!(scorecard.score > 90.0);
+        assert!(!scorecard.passed);
+    }
+
+    #[test]
+    fn test_scorecard_export() {
+        let scorecard = PenaltyModel::default().score("spec <v2>.docx", 1000, annotations());
+
+        let html = scorecard.to_html();
+        assert!(html.contains("spec &lt;v2&gt;.docx"));
+        assert!(html.contains("&lt;רווח&gt;"));
+        assert!(html.contains("Fluency / spelling"));
+
+        let json: serde_json::Value = serde_json::from_str(&scorecard.to_json().unwrap()).unwrap();
+        assert_eq!(json["categories"]["accuracy"]["major"], 1);
+        assert_eq!(json["passed"], true);
+
+        let dir = tempfile::tempdir().unwrap();
+        scorecard.save(&dir.path().join("lqa.html")).unwrap();
+        let saved = fs::read_to_string(dir.path().join("lqa.html")).unwrap();
+        assert!(saved.starts_with("<!DOCTYPE html>"));
+    }
+}
@@ -0,0 +1,496 @@
+//! בדיקת עקביות של מספרים, טווחים, יחידות וקודי תקנים בין המקור לתרגום
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+use super::Severity;
+
+lazy_static! {
+    /// קידומת התקן מתחילה מילה ("THIS 12" אינו תקן IS); בעברית מותרות לפניה אותיות שימוש ("לת"י")
+    static ref STANDARD_CODE: Regex = Regex::new(
+        r#"\b[ובלמשה]?(ГОСТ\s*Р|ГОСТ|GOST\s*R|GOST|СНиП|SNiP|SNIP|СП|SP|NFPA|ISO|IS|ת"י|ת״י|גוסט|סניפ|סנ"פ)\s*(\d+(?:\s*[.\-–:]\s*\d+)*)"#
+    ).unwrap();
+
+    /// מספר סעיף רב-רמתי ("п. 5.2.1"); מספר שכל קבוצותיו אחרי הראשונה בנות שלוש ספרות הוא מפריד אלפים
+    static ref CLAUSE_NUMBER: Regex = Regex::new(r"\b\d+(?:\.\d+){2,}\b").unwrap();
+
+    static ref NUMBER: Regex = Regex::new(
+        r"[-−+]?(?:\d{1,3}(?:[ \u{00A0}\u{202F}.,]\d{3})+|\d+)(?:[.,]\d+)?"
+    ).unwrap();
+
+    /// יחידות לפי צורתן בטקסט, מהארוכה לקצרה כדי שההתאמה תהיה חמדנית
+    pub(super) static ref UNITS: Vec<(&'static str, &'static str)> = {
+        let mut units = vec![
+            ("мм", "mm"), ("mm", "mm"), ("מ\"מ", "mm"), ("מ״מ", "mm"), ("מילימטר", "mm"),
+            ("см", "cm"), ("cm", "cm"), ("ס\"מ", "cm"), ("ס״מ", "cm"),
+            ("км", "km"), ("km", "km"), ("ק\"מ", "km"), ("ק״מ", "km"),
+            ("м²", "m2"), ("м2", "m2"), ("m²", "m2"), ("m2", "m2"), ("מ\"ר", "m2"), ("מ״ר", "m2"),
+            ("м³/ч", "m3/h"), ("м3/ч", "m3/h"), ("m³/h", "m3/h"), ("m3/h", "m3/h"), ("מ\"ק/שעה", "m3/h"), ("מ״ק/שעה", "m3/h"),
+            ("м³", "m3"), ("м3", "m3"), ("m³", "m3"), ("m3", "m3"), ("מ\"ק", "m3"), ("מ״ק", "m3"),
+            ("л/мин", "l/min"), ("l/min", "l/min"), ("ליטר/דקה", "l/min"), ("ל/ד", "l/min"),
+            ("л/с", "l/s"), ("l/s", "l/s"), ("ליטר/שנייה", "l/s"), ("ל/ש", "l/s"),
+            ("м", "m"), ("m", "m"), ("מטר", "m"), ("מ'", "m"), ("מ׳", "m"),
+            ("МПа", "MPa"), ("MPa", "MPa"), ("מגפ\"ס", "MPa"), ("מגפ״ס", "MPa"),
+            ("кПа", "kPa"), ("kPa", "kPa"), ("קפ\"ס", "kPa"), ("קפ״ס", "kPa"),
+            ("Па", "Pa"), ("Pa", "Pa"), ("פסקל", "Pa"),
+            ("бар", "bar"), ("bar", "bar"), ("בר", "bar"),
+            ("атм", "atm"), ("atm", "atm"), ("אטמוספרות", "atm"),
+            ("°C", "°C"), ("°С", "°C"), ("℃", "°C"), ("градусов", "°C"), ("מעלות", "°C"),
+            ("кг", "kg"), ("kg", "kg"), ("ק\"ג", "kg"), ("ק״ג", "kg"),
+            ("кВт", "kW"), ("kW", "kW"), ("קו\"ט", "kW"), ("קו״ט", "kW"), ("קוט\"ש", "kWh"), ("קוט״ש", "kWh"),
+            ("Вт", "W"), ("W", "W"), ("וואט", "W"),
+            ("кВ", "kV"), ("kV", "kV"), ("ק\"ו", "kV"), ("ק״ו", "kV"),
+            ("В", "V"), ("V", "V"), ("וולט", "V"),
+            ("мА", "mA"), ("mA", "mA"), ("מיליאמפר", "mA"),
+            ("А", "A"), ("A", "A"), ("אמפר", "A"),
+            ("мин", "min"), ("min", "min"), ("דקות", "min"), ("דקה", "min"),
+            ("ч", "h"), ("h", "h"), ("שעות", "h"), ("שעה", "h"),
+            ("сек", "s"), ("с", "s"), ("s", "s"), ("שניות", "s"), ("שנייה", "s"),
+            ("%", "%"),
+        ];
+        units.sort_by_key(|unit| std::cmp::Reverse(unit.0.chars().count()));
+        units
+    };
+
+    /// יחידות שבאות לפני המספר: קוטר נומינלי ולחץ נומינלי
+    static ref PREFIX_UNITS: Vec<(&'static str, &'static str)> = vec![
+        ("DN", "DN"), ("Ду", "DN"), ("Dy", "DN"), ("קוטר נומינלי", "DN"),
+        ("PN", "PN"), ("Ру", "PN"), ("Py", "PN"),
+    ];
+}
+
+/// יחידות שהן גם מילה רגילה (מילת היחס "с"); נחשבות ליחידה רק כשאין אחריהן מילה ("30 с." ולא "2 с водой")
+const WORD_LIKE_UNITS: &[&str] = &["с"];
+
+/// מחברי טווח בין שני מספרים
+const RANGE_CONNECTORS: &[&str] = &["-", "–", "—", "…", "...", "до", "עד", "-עד", "÷"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum QuantityValue {
+    Number(f64),
+    Range(f64, f64),
+    /// קוד תקן: משפחה מנורמלת ומספר
+    Code { family: String, number: String },
+    /// מספר סעיף רב-רמתי, כמו שהוא בטקסט
+    Clause(String),
+}
+
+/// ערך שחולץ מהטקסט
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Quantity {
+    pub value: QuantityValue,
+    pub unit: Option<String>,
+    /// הטקסט המקורי
+    pub text: String,
+}
+
+impl Quantity {
+    fn same_value(&self, other: &Quantity) -> bool {
+        match (&self.value, &other.value) {
+            (QuantityValue::Number(a), QuantityValue::Number(b)) => approx_eq(*a, *b),
+            (QuantityValue::Range(a1, a2), QuantityValue::Range(b1, b2)) => approx_eq(*a1, *b1) && approx_eq(*a2, *b2),
+            (a, b) => a == b,
+        }
+    }
+
+    fn is_code(&self) -> bool {
+        matches!(self.value, QuantityValue::Code { .. })
+    }
+
+    fn same_kind(&self, other: &Quantity) -> bool {
+        std::mem::discriminant(&self.value) == std::mem::discriminant(&other.value)
+    }
+}
+
+fn approx_eq(a: f64, b: f64) -> bool {
+    (a - b).abs() <= 1e-9 * a.abs().max(b.abs()).max(1.0)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NumberIssue {
+    Missing,
+    Extra,
+    AlteredValue,
+    AlteredUnit,
+    MissingUnit,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NumberValidation {
+    pub issue: NumberIssue,
+    pub source: Option<String>,
+    pub target: Option<String>,
+    pub message: String,
+    pub severity: Severity,
+}
+
+/// חילוץ ערכים מטקסט; קודי תקנים קודמים למספרים כדי שהספרות שלהם לא ייספרו פעמיים
+pub fn extract_quantities(text: &str) -> Vec<Quantity> {
+    let hebrew = text.chars().any(|c| ('\u{0590}'..='\u{05FF}').contains(&c));
+    let mut found: Vec<(usize, Quantity)> = Vec::new();
+    let mut masked = text.to_string();
+
+    for captures in STANDARD_CODE.captures_iter(text) {
+        let whole = captures.get(0).unwrap();
+        // אות השימוש אינה חלק מהקוד
+        let start = captures.get(1).unwrap().start();
+        let number: String = captures[2]
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| if c == '–' { '-' } else { c })
+            .collect();
+        found.push((
+            start,
+            Quantity {
+                value: QuantityValue::Code { family: standard_family(&captures[1]).to_string(), number },
+                unit: None,
+                text: text[start..whole.end()].to_string(),
+            },
+        ));
+        masked.replace_range(whole.range(), &" ".repeat(whole.len()));
+    }
+
+    let unmasked = masked.clone();
+    for clause in CLAUSE_NUMBER.find_iter(&unmasked) {
+        if clause.as_str().split('.').skip(1).all(|group| group.len() == 3) {
+            continue;
+        }
+        found.push((
+            clause.start(),
+            Quantity {
+                value: QuantityValue::Clause(clause.as_str().to_string()),
+                unit: None,
+                text: clause.as_str().to_string(),
+            },
+        ));
+        masked.replace_range(clause.range(), &" ".repeat(clause.len()));
+    }
+
+    // (התחלה, סוף, ערך, יחידה)
+    let mut numbers: Vec<(usize, usize, f64, Option<String>)> = Vec::new();
+    for number in NUMBER.find_iter(&masked) {
+        let (start, raw) = signed_span(&masked, number.start(), number.as_str());
+        if !is_token_boundary(&masked, number.end()) {
+            continue;
+        }
+        let Some(value) = parse_number(raw, hebrew) else {
+            continue;
+        };
+        let unit = prefix_unit(&masked[..start]).or_else(|| suffix_unit(&masked[number.end()..]));
+        numbers.push((start, number.end(), value, unit));
+    }
+
+    let mut i = 0;
+    while i < numbers.len() {
+        let (start, end, value, unit) = numbers[i].clone();
+        if let Some(next) = numbers.get(i + 1) {
+            let gap = masked[end..next.0].trim();
+            let gap = strip_unit(gap, unit.as_deref());
+            if RANGE_CONNECTORS.contains(&gap) {
+                let unit = unit.clone().or_else(|| next.3.clone());
+                found.push((
+                    start,
+                    Quantity {
+                        value: QuantityValue::Range(value, next.2),
+                        unit,
+                        text: text[start..next.1].to_string(),
+                    },
+                ));
+                i += 2;
+                continue;
+            }
+        }
+
+        found.push((start, Quantity { value: QuantityValue::Number(value), unit, text: text[start..end].to_string() }));
+        i += 1;
+    }
+
+    found.sort_by_key(|(start, _)| *start);
+    found.into_iter().map(|(_, quantity)| quantity).collect()
+}
+
+fn standard_family(prefix: &str) -> &'static str {
+    let compact: String = prefix.chars().filter(|c| !c.is_whitespace()).collect();
+    match compact.as_str() {
+        "ГОСТР" | "GOSTR" => "GOST R",
+        "ГОСТ" | "GOST" | "גוסט" => "GOST",
+        "СНиП" | "SNiP" | "SNIP" | "סניפ" | "סנ\"פ" => "SNiP",
+        "СП" | "SP" => "SP",
+        "NFPA" => "NFPA",
+        "ISO" => "ISO",
+        _ => "IS",
+    }
+}
+
+/// סימן מינוס או פלוס שייך למספר רק כשלפניו רווח או תחילת טקסט
+fn signed_span<'a>(text: &'a str, start: usize, raw: &'a str) -> (usize, &'a str) {
+    if raw.starts_with(['-', '−', '+']) {
+        let sign_len = raw.chars().next().unwrap().len_utf8();
+        let before = text[..start].chars().next_back();
+        if before.is_some_and(|c| !c.is_whitespace() && c != '(') {
+            return (start + sign_len, &raw[sign_len..]);
+        }
+    }
+    (start, raw)
+}
+
+/// מספר שממשיך באות (כמו "2-х" או "3D") אינו ערך עצמאי
+fn is_token_boundary(text: &str, end: usize) -> bool {
+    text[end..].chars().next().is_none_or(|c| !c.is_alphabetic() || is_unit_start(&text[end..]))
+}
+
+fn is_unit_start(rest: &str) -> bool {
+    UNITS.iter().any(|(form, _)| rest.starts_with(form))
+}
+
+/// פסיק עשרוני ונקודה עשרונית שקולים. כששניהם מופיעים ("1,500.25", "1.500,25") האחרון עשרוני
+/// והאחר מפריד אלפים; סימן שחוזר כמה פעמים ("1.500.000") הוא מפריד אלפים.
+/// בעברית פסיק יחיד לפני שלוש ספרות בדיוק הוא מפריד אלפים
+fn parse_number(raw: &str, hebrew: bool) -> Option<f64> {
+    let mut normalized: String = raw
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '\u{00A0}' | '\u{202F}'))
+        .map(|c| if c == '−' { '-' } else { c })
+        .collect();
+
+    let commas = normalized.matches(',').count();
+    let dots = normalized.matches('.').count();
+    if commas > 0 && dots > 0 {
+        let decimal = normalized.rfind([',', '.']).unwrap();
+        let thousands = if normalized[decimal..].starts_with(',') { '.' } else { ',' };
+        normalized = normalized[..decimal].replace(thousands, "") + "." + &normalized[decimal + 1..];
+    } else if commas > 1 || dots > 1 {
+        normalized.retain(|c| c != ',' && c != '.');
+    } else if let Some(position) = normalized.find(',') {
+        let fraction = &normalized[position + 1..];
+        if hebrew && fraction.len() == 3 && fraction.chars().all(|c| c.is_ascii_digit()) {
+            normalized.remove(position);
+        } else {
+            normalized.replace_range(position..position + 1, ".");
+        }
+    }
+    normalized.trim_start_matches('+').parse().ok()
+}
+
+fn suffix_unit(rest: &str) -> Option<String> {
+    let rest = rest.trim_start_matches([' ', '\u{00A0}', '\u{202F}']);
+    UNITS
+        .iter()
+        .find(|(form, _)| {
+            let Some(after) = rest.strip_prefix(form) else {
+                return false;
+            };
+            if !after.chars().next().is_none_or(|c| !c.is_alphanumeric()) {
+                return false;
+            }
+            !WORD_LIKE_UNITS.contains(form)
+                || !after.trim_start_matches([' ', '\u{00A0}', '\u{202F}']).starts_with(char::is_alphabetic)
+        })
+        .map(|(_, unit)| unit.to_string())
+}
+
+fn prefix_unit(before: &str) -> Option<String> {
+    let before = before.trim_end();
+    PREFIX_UNITS
+        .iter()
+        .find(|(form, _)| before.ends_with(form))
+        .map(|(_, unit)| unit.to_string())
+}
+
+/// "2 бар - 5 бар": היחידה של הקצה הראשון אינה חלק ממחבר הטווח
+fn strip_unit<'a>(gap: &'a str, unit: Option<&str>) -> &'a str {
+    let Some(unit) = unit else {
+        return gap;
+    };
+    for (form, canonical) in UNITS.iter() {
+        if *canonical == unit {
+            if let Some(rest) = gap.strip_prefix(form) {
+                return rest.trim();
+            }
+        }
+    }
+    gap
+}
+
+/// יישור הערכים בין המקור לתרגום ודיווח על חסרים, עודפים ושינויים
+pub fn check_numbers(source_text: &str, translated_text: &str) -> Vec<NumberValidation> {
+    let source = extract_quantities(source_text);
+    let mut target: Vec<Option<Quantity>> = extract_quantities(translated_text).into_iter().map(Some).collect();
+    let mut unmatched: Vec<Quantity> = Vec::new();
+    let mut validations = Vec::new();
+
+    // מעבר ראשון: אותו ערך ואותה יחידה (או יחידה שהושמטה בתרגום)
+    for quantity in source {
+        let exact = target.iter().position(|t| {
+            t.as_ref().is_some_and(|t| quantity.same_value(t) && t.unit == quantity.unit)
+        });
+        let same_value = exact.or_else(|| {
+            target.iter().position(|t| t.as_ref().is_some_and(|t| quantity.same_value(t)))
+        });
+
+        match same_value {
+            Some(index) => {
+                let matched = target[index].take().unwrap();
+                match (&quantity.unit, &matched.unit) {
+                    (Some(expected), Some(actual)) if expected != actual => validations.push(NumberValidation {
+                        issue: NumberIssue::AlteredUnit,
+                        message: format!("היחידה השתנתה מ-{} ל-{}", expected, actual),
+                        source: Some(quantity.text),
+                        target: Some(matched.text),
+                        severity: Severity::Major,
+                    }),
+                    (Some(expected), None) => validations.push(NumberValidation {
+                        issue: NumberIssue::MissingUnit,
+                        message: format!("היחידה {} חסרה בתרגום", expected),
+                        source: Some(quantity.text),
+                        target: Some(matched.text),
+                        severity: Severity::Minor,
+                    }),
+                    _ => {}
+                }
+            }
+            None => unmatched.push(quantity),
+        }
+    }
+
+    // מעבר שני: ערך ששונה, מזוהה לפי אותה יחידה או אותה משפחת תקן
+    for quantity in unmatched {
+        let counterpart = target.iter().position(|t| {
+            t.as_ref().is_some_and(|t| {
+                quantity.same_kind(t)
+                    && match (&quantity.value, &t.value) {
+                        (QuantityValue::Code { family: a, .. }, QuantityValue::Code { family: b, .. }) => a == b,
+                        (QuantityValue::Clause(_), QuantityValue::Clause(_)) => true,
+                        _ => quantity.unit.is_some() && quantity.unit == t.unit,
+                    }
+            })
+        });
+
+        match counterpart {
+            Some(index) => {
+                let altered = target[index].take().unwrap();
+                validations.push(NumberValidation {
+                    issue: NumberIssue::AlteredValue,
+                    message: format!("הערך {} השתנה בתרגום ל-{}", quantity.text, altered.text),
+                    source: Some(quantity.text),
+                    target: Some(altered.text),
+                    severity: Severity::Critical,
+                });
+            }
+            None => validations.push(NumberValidation {
+                issue: NumberIssue::Missing,
+                message: format!("הערך {} חסר בתרגום", quantity.text),
+                severity: if quantity.is_code() || quantity.unit.is_some() { Severity::Critical } else { Severity::Major },
+                source: Some(quantity.text),
+                target: None,
+            }),
+        }
+    }
+
+    for extra in target.into_iter().flatten() {
+        validations.push(NumberValidation {
+            issue: NumberIssue::Extra,
+            message: format!("הערך {} מופיע בתרגום אך לא במקור", extra.text),
+            severity: if extra.is_code() { Severity::Major } else { Severity::Minor },
+            source: None,
+            target: Some(extra.text),
+        });
+    }
+
+    validations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issues(source: &str, target: &str) -> Vec<NumberIssue> {
+        check_numbers(source, target).into_iter().map(|v| v.issue).collect()
+    }
+
+    #[test]
+    fn test_extraction() {
+        let quantities = extract_quantities("Давление 2,5 МПа, труба Ду 100, температура от -20 до +50 °C по ГОСТ 12.1.004-91");
+
+        assert_eq!(quantities[0].value, QuantityValue::Number(2.5));
+        assert_eq!(quantities[0].unit.as_deref(), Some("MPa"));
+        assert_eq!(quantities[1].value, QuantityValue::Number(100.0));
+        assert_eq!(quantities[1].unit.as_deref(), Some("DN"));
+        assert_eq!(quantities[2].value, QuantityValue::Range(-20.0, 50.0));
+        assert_eq!(quantities[2].unit.as_deref(), Some("°C"));
+        assert_eq!(
+            quantities[3].value,
+            QuantityValue::Code { family: "GOST".to_string(), number: "12.1.004-91".to_string() }
+        );
+        assert_eq!(quantities.len(), 4);
+    }
+
+    #[test]
+    fn test_localized_formats_are_equal() {
+        assert!(issues("Давление 2,5 бар", "לחץ 2.5 בר").is_empty());
+        assert!(issues("Расход 1 500 л/мин", "ספיקה 1,500 ליטר/דקה").is_empty());
+        assert!(issues("по СНиП 2.04.01-85 и NFPA 13", "לפי SNiP 2.04.01-85 ו-NFPA 13").is_empty());
+        assert!(issues("согласно СП 5.13130.2009", "בהתאם ל-SP 5.13130.2009").is_empty());
+        assert_eq!(issues("согласно СП 5.13130.2009", "בהתאם ל-SP 5.13130.2019"), vec![NumberIssue::AlteredValue]);
+    }
+
+    #[test]
+    fn test_clause_numbers_are_single_tokens() {
+        let quantities = extract_quantities("см. п. 5.2.1 и расход 1.500.000 л");
+        assert_eq!(quantities[0].value, QuantityValue::Clause("5.2.1".to_string()));
+        assert_eq!(quantities[1].value, QuantityValue::Number(1_500_000.0));
+        assert_eq!(quantities.len(), 2);
+
+        assert!(issues("согласно п. 5.2.1", "בהתאם לסעיף 5.2.1").is_empty());
+        assert_eq!(issues("согласно п. 5.2.1", "בהתאם לסעיף 5.2.2"), vec![NumberIssue::AlteredValue]);
+    }
+
+    #[test]
+    fn test_reports_altered_missing_and_extra() {
+        assert_eq!(issues("Давление 16 бар", "לחץ 10 בר"), vec![NumberIssue::AlteredValue]);
+        assert_eq!(issues("Труба DN 100", "צינור DN 150"), vec![NumberIssue::AlteredValue]);
+        assert_eq!(issues("по ГОСТ 12.1.004", "לפי GOST 12.1.044"), vec![NumberIssue::AlteredValue]);
+        assert_eq!(issues("Длина 10 м", "אורך 10 מ\"מ"), vec![NumberIssue::AlteredUnit]);
+        assert_eq!(issues("Давление 6 бар, 2 насоса", "לחץ 6 בר"), vec![NumberIssue::Missing]);
+        assert_eq!(issues("Давление 6 бар", "לחץ 6 בר לפי ת\"י 1596"), vec![NumberIssue::Extra]);
+
+        let altered = check_numbers("Давление 16 бар", "לחץ 10 בר");
+        assert_eq!(altered[0].severity, Severity::Critical);
+    }
+
+    #[test]
+    fn test_preposition_is_not_seconds() {
+        let quantities = extract_quantities("Смешать 2 с водой, выдержать 30 с.");
+        assert_eq!(quantities[0].unit, None);
+        assert_eq!(quantities[1].unit.as_deref(), Some("s"));
+
+        assert!(issues("Подключить 2 с насосом", "לחבר 2 למשאבה").is_empty());
+        assert_eq!(extract_quantities("через 5 сек после пуска")[0].unit.as_deref(), Some("s"));
+    }
+
+    #[test]
+    fn test_grouped_decimals() {
+        assert_eq!(parse_number("1,500.25", false), Some(1500.25));
+        assert_eq!(parse_number("1.500,25", false), Some(1500.25));
+        assert_eq!(parse_number("1.500.000", false), Some(1_500_000.0));
+        assert_eq!(parse_number("2,5", false), Some(2.5));
+        assert_eq!(parse_number("1,500", true), Some(1500.0));
+
+        assert!(issues("Расход 1.500,25 л/мин", "ספיקה 1,500.25 ליטר/דקה").is_empty());
+        assert_eq!(issues("Расход 1.500,25 л/мин", "ספיקה 1,500.52 ליטר/דקה"), vec![NumberIssue::AlteredValue]);
+    }
+
+    #[test]
+    fn test_standard_prefix_starts_a_word() {
+        let quantities = extract_quantities("THIS 12 ANALYSIS 3");
+        assert_eq!(quantities.len(), 2);
+        assert!(quantities.iter().all(|q| !q.is_code()));
+
+        let quantities = extract_quantities("בהתאם לת\"י 1596");
+        assert_eq!(
+            quantities[0].value,
+            QuantityValue::Code { family: "IS".to_string(), number: "1596".to_string() }
+        );
+        assert_eq!(quantities[0].text, "ת\"י 1596");
+    }
+}
@@ -0,0 +1,670 @@
+//! מנוע כללי QA שנטענים מקבצי TOML/JSON: ביטויים רגולריים, תנאים מורפולוגיים, הפעלה לפי תחום,
+//! חומרה, תבנית הודעה ותיקון אוטומטי. הקבצים נטענים מחדש כשהם משתנים
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+use anyhow::{anyhow, bail, Context, Result};
+use regex::{Captures, Regex};
+use serde::{Serialize, Deserialize};
+use crate::morphology::{Gender, Number};
+use super::Severity;
+
+/// כללי ברירת המחדל שמגיעים עם המערכת
+pub const DEFAULT_RULES: &str = include_str!("../../rules/default.toml");
+
+/// תחיליות עבריות שמוסרות לפני חיפוש בלקסיקון
+const HEBREW_PREFIXES: &[&str] = &["וה", "שה", "וב", "ול", "ומ", "ה", "ו", "ב", "ל", "מ", "ש", "כ"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleCategory {
+    Grammar,
+    Style,
+    Context,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PartOfSpeech {
+    Noun,
+    Adjective,
+    Verb,
+    Other,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Feature {
+    Gender,
+    Number,
+}
+
+/// תנאי מורפולוגי על קבוצות שם בביטוי של הכלל; הכלל מופעל רק כשכל התנאים מתקיימים
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Predicate {
+    Pos { group: String, is: PartOfSpeech },
+    Agree { first: String, second: String, feature: Feature },
+    Disagree { first: String, second: String, feature: Feature },
+}
+
+impl Predicate {
+    fn groups(&self) -> Vec<&str> {
+        match self {
+            Predicate::Pos { group, .. } => vec![group],
+            Predicate::Agree { first, second, .. } | Predicate::Disagree { first, second, .. } => vec![first, second],
+        }
+    }
+}
+
+/// דוגמה שמצורפת לכלל ומורצת במנגנון הבדיקות לכותבי כללים
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleTest {
+    pub text: String,
+    #[serde(default)]
+    pub source: String,
+    #[serde(default)]
+    pub domain: Option<String>,
+    /// האם הכלל צריך להתריע על הטקסט
+    pub fires: bool,
+    /// הטקסט הצפוי אחרי התיקון האוטומטי
+    #[serde(default)]
+    pub fixed: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleDefinition {
+    pub id: String,
+    pub category: RuleCategory,
+    #[serde(default)]
+    pub description: String,
+    /// ביטוי שמחפשים בתרגום; כל התאמה שעומדת בתנאים היא ממצא
+    #[serde(default)]
+    pub pattern: Option<String>,
+    /// הכלל חל רק כשהמקור תואם לביטוי זה
+    #[serde(default)]
+    pub source_pattern: Option<String>,
+    /// ממצא כשהתרגום אינו תואם לביטוי זה (בכללי הקשר, יחד עם source_pattern)
+    #[serde(default)]
+    pub target_missing: Option<String>,
+    #[serde(default)]
+    pub when: Vec<Predicate>,
+    /// חיפוש התאמות חופפות, לכללים על זוגות מילים סמוכות
+    #[serde(default)]
+    pub overlapping: bool,
+    /// התחומים שבהם הכלל פעיל; ריק - בכל התחומים
+    #[serde(default)]
+    pub domains: Vec<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub severity: Severity,
+    /// תבנית הודעה: {match} לטקסט שנמצא ו-{name} לקבוצה בשם
+    pub message: String,
+    /// תבנית החלפה בתחביר של regex ($1, ${name})
+    #[serde(default)]
+    pub fix: Option<String>,
+    #[serde(default)]
+    pub tests: Vec<RuleTest>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// ערך בלקסיקון המורפולוגי של קובץ הכללים
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LexiconEntry {
+    pub word: String,
+    pub pos: PartOfSpeech,
+    #[serde(default)]
+    pub gender: Option<Gender>,
+    #[serde(default)]
+    pub number: Option<Number>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RuleFile {
+    #[serde(default)]
+    pub rules: Vec<RuleDefinition>,
+    #[serde(default)]
+    pub lexicon: Vec<LexiconEntry>,
+}
+
+impl RuleFile {
+    pub fn from_toml(content: &str) -> Result<Self> {
+        toml::from_str(content).context("קובץ כללים לא תקין (TOML)")
+    }
+
+    pub fn from_json(content: &str) -> Result<Self> {
+        serde_json::from_str(content).context("קובץ כללים לא תקין (JSON)")
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).with_context(|| format!("קריאת {}", path.display()))?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Self::from_toml(&content),
+            Some("json") => Self::from_json(&content),
+            _ => bail!("סוג קובץ כללים לא נתמך: {}", path.display()),
+        }
+        .with_context(|| path.display().to_string())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WordAnalysis {
+    pub pos: PartOfSpeech,
+    pub gender: Option<Gender>,
+    pub number: Option<Number>,
+}
+
+/// ניתוח מורפולוגי לתנאים של הכללים
+pub trait MorphologyProvider: Send + Sync {
+    fn analyze(&self, word: &str) -> Option<WordAnalysis>;
+}
+
+/// לקסיקון מקבצי הכללים; מילה שאינה בלקסיקון מנותחת לפי סיומות עבריות, ללא חלק דיבר
+#[derive(Debug, Clone, Default)]
+pub struct LexiconMorphology {
+    entries: HashMap<String, WordAnalysis>,
+}
+
+impl LexiconMorphology {
+    pub fn new(entries: &[LexiconEntry]) -> Self {
+        Self {
+            entries: entries
+                .iter()
+                .map(|e| (e.word.clone(), WordAnalysis { pos: e.pos, gender: e.gender, number: e.number }))
+                .collect(),
+        }
+    }
+
+    fn lookup(&self, word: &str) -> Option<WordAnalysis> {
+        self.entries.get(word).copied().or_else(|| {
+            HEBREW_PREFIXES
+                .iter()
+                .filter_map(|prefix| word.strip_prefix(prefix))
+                .find_map(|stem| self.entries.get(stem).copied())
+        })
+    }
+}
+
+impl MorphologyProvider for LexiconMorphology {
+    fn analyze(&self, word: &str) -> Option<WordAnalysis> {
+        if let Some(analysis) = self.lookup(word) {
+            return Some(analysis);
+        }
+        if !word.chars().any(|c| ('\u{05D0}'..='\u{05EA}').contains(&c)) {
+            return None;
+        }
+
+        let (gender, number) = if word.ends_with("ים") {
+            (Gender::Masculine, Number::Plural)
+        } else if word.ends_with("ות") {
+            (Gender::Feminine, Number::Plural)
+        } else if word.ends_with('ה') || word.ends_with('ת') {
+            (Gender::Feminine, Number::Singular)
+        } else {
+            (Gender::Masculine, Number::Singular)
+        };
+        Some(WordAnalysis { pos: PartOfSpeech::Other, gender: Some(gender), number: Some(number) })
+    }
+}
+
+/// ממצא של כלל בתרגום
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleFinding {
+    pub rule_id: String,
+    pub category: RuleCategory,
+    pub severity: Severity,
+    pub message: String,
+    /// טווח בתרגום (בתים); ריק לממצא על הסגמנט כולו
+    pub span: Option<(usize, usize)>,
+    pub matched: String,
+    pub replacement: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleTestResult {
+    pub rule_id: String,
+    pub test: usize,
+    pub passed: bool,
+    pub detail: String,
+}
+
+struct CompiledRule {
+    definition: RuleDefinition,
+    pattern: Option<Regex>,
+    source_pattern: Option<Regex>,
+    target_missing: Option<Regex>,
+}
+
+impl CompiledRule {
+    fn compile(definition: RuleDefinition) -> Result<Self> {
+        let compile = |pattern: &Option<String>| -> Result<Option<Regex>> {
+            pattern
+                .as_deref()
+                .map(|p| Regex::new(p).with_context(|| format!("ביטוי לא תקין בכלל {}", definition.id)))
+                .transpose()
+        };
+        let pattern = compile(&definition.pattern)?;
+        let source_pattern = compile(&definition.source_pattern)?;
+        let target_missing = compile(&definition.target_missing)?;
+
+        if pattern.is_none() && target_missing.is_none() {
+            bail!("לכלל {} חסר pattern או target_missing", definition.id);
+        }
+        for group in definition.when.iter().flat_map(Predicate::groups) {
+            let known = pattern.as_ref().is_some_and(|p| p.capture_names().flatten().any(|name| name == group));
+            if !known {
+                bail!("הכלל {} מתייחס לקבוצה לא קיימת: {}", definition.id, group);
+            }
+        }
+
+        Ok(Self { definition, pattern, source_pattern, target_missing })
+    }
+
+    fn applies_to(&self, domain: Option<&str>) -> bool {
+        self.definition.enabled
+            && (self.definition.domains.is_empty()
+                || domain.is_some_and(|d| self.definition.domains.iter().any(|allowed| allowed == d)))
+    }
+
+    fn check(&self, source: &str, target: &str, morphology: &dyn MorphologyProvider) -> Vec<RuleFinding> {
+        if let Some(source_pattern) = &self.source_pattern {
+            if !source_pattern.is_match(source) {
+                return Vec::new();
+            }
+        }
+
+        let mut findings = Vec::new();
+        if let Some(missing) = &self.target_missing {
+            if !missing.is_match(target) {
+                findings.push(self.finding(None, String::new(), self.definition.message.clone(), None));
+            }
+        }
+
+        let Some(pattern) = &self.pattern else {
+            return findings;
+        };
+        let mut position = 0;
+        while let Some(captures) = pattern.captures_at(target, position) {
+            let whole = captures.get(0).unwrap();
+            position = if self.definition.overlapping || whole.is_empty() {
+                whole.start() + target[whole.start()..].chars().next().map_or(1, char::len_utf8)
+            } else {
+                whole.end()
+            };
+
+            if self.definition.when.iter().all(|predicate| holds(predicate, &captures, morphology)) {
+                let replacement = self.definition.fix.as_ref().map(|fix| {
+                    let mut expanded = String::new();
+                    captures.expand(fix, &mut expanded);
+                    expanded
+                });
+                findings.push(self.finding(
+                    Some((whole.start(), whole.end())),
+                    whole.as_str().to_string(),
+                    render_message(&self.definition.message, pattern, &captures),
+                    replacement,
+                ));
+            }
+
+            if position > target.len() {
+                break;
+            }
+        }
+        findings
+    }
+
+    fn finding(&self, span: Option<(usize, usize)>, matched: String, message: String, replacement: Option<String>) -> RuleFinding {
+        RuleFinding {
+            rule_id: self.definition.id.clone(),
+            category: self.definition.category,
+            severity: self.definition.severity,
+            message,
+            span,
+            matched,
+            replacement,
+        }
+    }
+}
+
+fn holds(predicate: &Predicate, captures: &Captures, morphology: &dyn MorphologyProvider) -> bool {
+    let analyze = |group: &str| captures.name(group).and_then(|m| morphology.analyze(m.as_str()));
+    let feature = |analysis: &WordAnalysis, feature: Feature| match feature {
+        Feature::Gender => analysis.gender.map(|g| g as u8),
+        Feature::Number => analysis.number.map(|n| n as u8),
+    };
+
+    match predicate {
+        Predicate::Pos { group, is } => analyze(group).is_some_and(|a| a.pos == *is),
+        Predicate::Agree { first, second, feature: f } | Predicate::Disagree { first, second, feature: f } => {
+            // מילה שלא נותחה אינה מפעילה את הכלל, כדי לא להתריע לשווא
+            let (Some(a), Some(b)) = (analyze(first), analyze(second)) else {
+                return false;
+            };
+            let (Some(a), Some(b)) = (feature(&a, *f), feature(&b, *f)) else {
+                return false;
+            };
+            matches!(predicate, Predicate::Agree { .. }) == (a == b)
+        }
+    }
+}
+
+fn render_message(template: &str, pattern: &Regex, captures: &Captures) -> String {
+    let mut message = template.replace("{match}", captures.get(0).map_or("", |m| m.as_str()));
+    for name in pattern.capture_names().flatten() {
+        if let Some(value) = captures.name(name) {
+            message = message.replace(&format!("{{{}}}", name), value.as_str());
+        }
+    }
+    message
+}
+
+struct LoadedRules {
+    rules: Vec<CompiledRule>,
+    morphology: LexiconMorphology,
+    /// טביעת התוכן של כל קובץ שנטען, לזיהוי שינוי גם כשזמן השינוי של הקובץ לא התעדכן
+    fingerprints: HashMap<PathBuf, u64>,
+}
+
+impl LoadedRules {
+    fn build(files: Vec<RuleFile>, fingerprints: HashMap<PathBuf, u64>) -> Result<Self> {
+        let mut rules = Vec::new();
+        let mut lexicon = Vec::new();
+        for file in files {
+            for definition in file.rules {
+                if rules.iter().any(|r: &CompiledRule| r.definition.id == definition.id) {
+                    bail!("מזהה כלל כפול: {}", definition.id);
+                }
+                rules.push(CompiledRule::compile(definition)?);
+            }
+            lexicon.extend(file.lexicon);
+        }
+        Ok(Self { rules, morphology: LexiconMorphology::new(&lexicon), fingerprints })
+    }
+}
+
+/// מנוע הכללים; הכללים הפעילים מוחלפים בבת אחת בטעינה מחדש, כך שבדיקה רצה תמיד על קבוצה שלמה
+pub struct RuleEngine {
+    builtin: Option<RuleFile>,
+    paths: Vec<PathBuf>,
+    loaded: RwLock<LoadedRules>,
+    last_check: Mutex<Instant>,
+    reload_interval: Duration,
+}
+
+impl RuleEngine {
+    /// מנוע עם כללי ברירת המחדל בלבד
+    pub fn with_defaults() -> Result<Self> {
+        Self::build(Some(RuleFile::from_toml(DEFAULT_RULES)?), Vec::new())
+    }
+
+    pub fn from_rule_file(file: RuleFile) -> Result<Self> {
+        Self::build(Some(file), Vec::new())
+    }
+
+    /// טעינת קבצים או תיקיות של קבצי .toml/.json, בנוסף לכללי ברירת המחדל אם include_defaults
+    pub fn load(paths: Vec<PathBuf>, include_defaults: bool) -> Result<Self> {
+        let builtin = include_defaults.then(|| RuleFile::from_toml(DEFAULT_RULES)).transpose()?;
+        Self::build(builtin, paths)
+    }
+
+    fn build(builtin: Option<RuleFile>, paths: Vec<PathBuf>) -> Result<Self> {
+        let loaded = Self::read(&builtin, &paths)?;
+        Ok(Self {
+            builtin,
+            paths,
+            loaded: RwLock::new(loaded),
+            last_check: Mutex::new(Instant::now()),
+            reload_interval: Duration::from_secs(2),
+        })
+    }
+
+    pub fn with_reload_interval(mut self, interval: Duration) -> Self {
+        self.reload_interval = interval;
+        self
+    }
+
+    fn rule_files(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        for path in paths {
+            if path.is_dir() {
+                let mut entries: Vec<PathBuf> = fs::read_dir(path)?
+                    .filter_map(|entry| entry.ok().map(|e| e.path()))
+                    .filter(|p| matches!(p.extension().and_then(|e| e.to_str()), Some("toml" | "json")))
+                    .collect();
+                entries.sort();
+                files.extend(entries);
+            } else {
+                files.push(path.clone());
+            }
+        }
+        Ok(files)
+    }
+
+    fn read(builtin: &Option<RuleFile>, paths: &[PathBuf]) -> Result<LoadedRules> {
+        let mut files: Vec<RuleFile> = builtin.iter().cloned().collect();
+        let mut fingerprints = HashMap::new();
+        for path in Self::rule_files(paths)? {
+            fingerprints.insert(path.clone(), Self::fingerprint(&path)?);
+            files.push(RuleFile::load(&path)?);
+        }
+        LoadedRules::build(files, fingerprints)
+    }
+
+    fn fingerprint(path: &Path) -> Result<u64> {
+        let content = fs::read(path).with_context(|| format!("קריאת {}", path.display()))?;
+        let mut hasher = DefaultHasher::new();
+        content.hash(&mut hasher);
+        Ok(hasher.finish())
+    }
+
+    /// טעינה מחדש של כל הקבצים; בשגיאה הכללים הקודמים נשארים בתוקף
+    pub fn reload(&self) -> Result<()> {
+        let loaded = Self::read(&self.builtin, &self.paths)?;
+        *self.loaded.write().map_err(|_| anyhow!("מנעול הכללים הורעל"))? = loaded;
+        Ok(())
+    }
+
+    /// טעינה מחדש אם קובץ נוסף, נמחק או שתוכנו השתנה מאז הטעינה האחרונה; נבדק לכל היותר פעם בפרק הזמן שהוגדר
+    pub fn reload_if_changed(&self) -> Result<bool> {
+        {
+            let mut last_check = self.last_check.lock().map_err(|_| anyhow!("מנעול הכללים הורעל"))?;
+            if last_check.elapsed() < self.reload_interval {
+                return Ok(false);
+            }
+            *last_check = Instant::now();
+        }
+
+        let mut current = HashMap::new();
+        for path in Self::rule_files(&self.paths)? {
+            if let Ok(fingerprint) = Self::fingerprint(&path) {
+                current.insert(path, fingerprint);
+            }
+        }
+
+        let changed = {
+            let loaded = self.loaded.read().map_err(|_| anyhow!("מנעול הכללים הורעל"))?;
+            loaded.fingerprints != current
+        };
+        if changed {
+            self.reload()?;
+        }
+        Ok(changed)
+    }
+
+    pub fn rule_ids(&self) -> Vec<String> {
+        self.loaded
+            .read()
+            .map(|loaded| loaded.rules.iter().map(|r| r.definition.id.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// הרצת הכללים הפעילים בתחום על זוג מקור-תרגום
+    pub fn check(&self, source: &str, target: &str, domain: Option<&str>) -> Vec<RuleFinding> {
+        let Ok(loaded) = self.loaded.read() else {
+            return Vec::new();
+        };
+        loaded
+            .rules
+            .iter()
+            .filter(|rule| rule.applies_to(domain))
+            .flat_map(|rule| rule.check(source, target, &loaded.morphology))
+            .collect()
+    }
+
+    /// החלת התיקונים האוטומטיים; ממצאים חופפים נדלגים
+    pub fn apply_fixes(text: &str, findings: &[RuleFinding]) -> String {
+        let mut fixes: Vec<(usize, usize, &str)> = findings
+            .iter()
+            .filter_map(|f| Some((f.span?.0, f.span?.1, f.replacement.as_deref()?)))
+            .collect();
+        fixes.sort_by_key(|(start, _, _)| *start);
+
+        let mut result = String::with_capacity(text.len());
+        let mut last = 0;
+        for (start, end, replacement) in fixes {
+            if start < last {
+                continue;
+            }
+            result.push_str(&text[last..start]);
+            result.push_str(replacement);
+            last = end;
+        }
+        result.push_str(&text[last..]);
+        result
+    }
+
+    /// הרצת הדוגמאות שבקבצי הכללים: האם הכלל התריע כמצופה והאם התיקון נותן את הטקסט הצפוי
+    pub fn run_tests(&self) -> Vec<RuleTestResult> {
+        let Ok(loaded) = self.loaded.read() else {
+            return Vec::new();
+        };
+
+        let mut results = Vec::new();
+        for rule in &loaded.rules {
+            for (index, test) in rule.definition.tests.iter().enumerate() {
+                let findings = rule.check(&test.source, &test.text, &loaded.morphology);
+                let fired = !findings.is_empty();
+
+                let mut problems = Vec::new();
+                if fired != test.fires {
+                    problems.push(if test.fires { "הכלל לא התריע".to_string() } else { "התראה שגויה".to_string() });
+                }
+                if let Some(expected) = &test.fixed {
+                    let fixed = Self::apply_fixes(&test.text, &findings);
+                    if &fixed != expected {
+                        problems.push(format!("תיקון: התקבל \"{}\", צפוי \"{}\"", fixed, expected));
+                    }
+                }
+                if test.domain.as_deref().is_some_and(|d| !rule.applies_to(Some(d))) {
+                    problems.push("הכלל אינו פעיל בתחום של הדוגמה".to_string());
+                }
+
+                results.push(RuleTestResult {
+                    rule_id: rule.definition.id.clone(),
+                    test: index,
+                    passed: problems.is_empty(),
+                    detail: problems.join("; "),
+                });
+            }
+        }
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_rules_pass_their_tests() {
+        let engine = RuleEngine::with_defaults().unwrap();
+        let failures: Vec<_> = engine.run_tests().into_iter().filter(|r| !r.passed).collect();
+        assert!(failures.is_empty(), "{:?}", failures);
+    }
+
+    #[test]
+    fn test_agreement_predicate_and_fix() {
+        let engine = RuleEngine::with_defaults().unwrap();
+
+        let findings = engine.check("", "המגוף פתוחה והמשאבה סגורה.", None);
+        let agreement: Vec<_> = findings.iter().filter(|f| f.rule_id == "he-adjective-gender-agreement").collect();
+        assert_eq!(agreement.len(), 1);
+        assert_eq!(agreement[0].matched, "המגוף פתוחה");
+        assert!(agreement[0].message.contains("פתוחה"));
+
+        let findings = engine.check("", "לחץ  תקין .", None);
+        assert_eq!(RuleEngine::apply_fixes("לחץ  תקין .", &findings), "לחץ תקין.");
+    }
+
+    #[test]
+    fn test_domains_and_context_rules() {
+        let file = RuleFile::from_json(r#"{
+            "rules": [{
+                "id": "fire-prohibition",
+                "category": "context",
+                "source_pattern": "(?i)запрещается",
+                "target_missing": "אסור",
+                "domains": ["fire_protection"],
+                "severity": "Critical",
+                "message": "האיסור שבמקור לא הועבר לתרגום"
+            }]
+        }"#).unwrap();
+        let engine = RuleEngine::from_rule_file(file).unwrap();
+
+        assert!(engine.check("Запрещается курить", "מותר לעשן", None).is_empty());
+        let findings = engine.check("Запрещается курить", "מותר לעשן", Some("fire_protection"));
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].category, RuleCategory::Context);
+        assert!(engine.check("Запрещается курить", "אסור לעשן", Some("fire_protection")).is_empty());
+    }
+
+    #[test]
+    fn test_invalid_rules_are_rejected() {
+        let unknown_group = RuleFile::from_toml(r#"
+            [[rules]]
+            id = "broken"
+            category = "grammar"
+            pattern = '(?P<a>\w+)'
+            when = [{ type = "pos", group = "b", is = "noun" }]
+            severity = "Minor"
+            message = "x"
+        "#).unwrap();
+        assert!(RuleEngine::from_rule_file(unknown_group).is_err());
+    }
+
+    #[test]
+    fn test_hot_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("custom.toml");
+        let rule = |id: &str| format!("[[rules]]\nid = \"{}\"\ncategory = \"style\"\npattern = 'xx'\nseverity = \"Minor\"\nmessage = \"m\"\n", id);
+        fs::write(&path, rule("first")).unwrap();
+
+        let engine = RuleEngine::load(vec![dir.path().to_path_buf()], false)
+            .unwrap()
+            .with_reload_interval(Duration::ZERO);
+        assert_eq!(engine.rule_ids(), vec!["first"]);
+        assert!(!engine.reload_if_changed().unwrap());
+
+        fs::write(dir.path().join("more.json"), r#"{"rules": [{"id": "second", "category": "style", "pattern": "yy", "severity": "Info", "message": "m"}]}"#).unwrap();
+        assert!(engine.reload_if_changed().unwrap());
+        assert_eq!(engine.rule_ids(), vec!["first", "second"]);
+
+        // שינוי מיד אחרי הכתיבה הקודמת, באותו אורך, מזוהה לפי התוכן גם אם זמן השינוי לא התקדם
+        fs::write(&path, rule("third")).unwrap();
+        assert!(engine.reload_if_changed().unwrap());
+        assert_eq!(engine.rule_ids(), vec!["third", "second"]);
+
+        // קובץ שבור לא מחליף את הכללים הקיימים
+        fs::write(&path, "[[rules]]\nid = ").unwrap();
+        assert!(engine.reload_if_changed().is_err());
+        assert_eq!(engine.rule_ids().len(), 2);
+    }
+}
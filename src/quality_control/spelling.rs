@@ -0,0 +1,898 @@
+//! בדיקת איות לפי מילוני Hunspell (.aff/.dic) שנקראים מהדיסק, ללא ספרייה חיצונית.
+//! נתמכים: קידודי SET נפוצים, FLAG, כינויי דגלים (AF), PFX/SFX עם תנאים ודגלי המשך (עד שתי סיומות),
+//! REP, KEY, TRY, FORBIDDENWORD, NOSUGGEST ו-NEEDAFFIX. כינויי מורפולוגיה (AM) נקראים ואין בהם שימוש.
+//! מילים מורכבות (COMPOUND*) אינן נתמכות
+
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use anyhow::{anyhow, bail, Context, Result};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+use crate::morphology::utils::{hebrew_surface_variants, russian_stem};
+use crate::technical_terms::TermsDatabase;
+use super::Severity;
+
+lazy_static! {
+    static ref WORD: Regex = Regex::new(r#"[\p{L}\p{M}\p{N}]+(?:['’"״\-־][\p{L}\p{M}\p{N}]+)*"#).unwrap();
+}
+
+/// תחיליות עבריות שמוסרות כשהמילה אינה במילון כפי שהיא
+const HEBREW_PREFIX_LETTERS: &[char] = &['ו', 'ה', 'ב', 'ל', 'מ', 'ש', 'כ'];
+
+/// KOI8-R, תווים 0xC0-0xFF
+const KOI8R_LETTERS: &str = "юабцдефгхийклмнопярстужвьызшэщчъЮАБЦДЕФГХИЙКЛМНОПЯРСТУЖВЬЫЗШЭЩЧЪ";
+
+type Flag = u32;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FlagMode {
+    Char,
+    Long,
+    Num,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Encoding {
+    Utf8,
+    Iso8859_8,
+    Koi8R,
+    Cp1251,
+    Latin1,
+}
+
+impl Encoding {
+    fn from_name(name: &str) -> Result<Self> {
+        Ok(match name.to_uppercase().replace('_', "-").as_str() {
+            "UTF-8" | "UTF8" => Encoding::Utf8,
+            "ISO8859-8" | "ISO-8859-8" => Encoding::Iso8859_8,
+            "KOI8-R" => Encoding::Koi8R,
+            "CP1251" | "WINDOWS-1251" | "MICROSOFT-CP1251" => Encoding::Cp1251,
+            "ISO8859-1" | "ISO-8859-1" => Encoding::Latin1,
+            other => bail!("קידוד מילון לא נתמך: {}", other),
+        })
+    }
+
+    fn decode(self, bytes: &[u8]) -> String {
+        if self == Encoding::Utf8 {
+            return String::from_utf8_lossy(bytes).into_owned();
+        }
+        bytes
+            .iter()
+            .map(|&b| match (self, b) {
+                (_, 0..=0x7F) => b as char,
+                (Encoding::Latin1, _) => b as char,
+                (Encoding::Iso8859_8, 0xE0..=0xFA) => char::from_u32(0x05D0 + (b - 0xE0) as u32).unwrap(),
+                (Encoding::Koi8R, 0xC0..=0xFF) => KOI8R_LETTERS.chars().nth((b - 0xC0) as usize).unwrap(),
+                (Encoding::Koi8R, 0xA3) => 'ё',
+                (Encoding::Koi8R, 0xB3) => 'Ё',
+                (Encoding::Cp1251, 0xC0..=0xFF) => char::from_u32(0x0410 + (b - 0xC0) as u32).unwrap(),
+                (Encoding::Cp1251, 0xA8) => 'Ё',
+                (Encoding::Cp1251, 0xB8) => 'ё',
+                _ => char::REPLACEMENT_CHARACTER,
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+enum ConditionPart {
+    Char(char),
+    Set { chars: Vec<char>, negated: bool },
+    Any,
+}
+
+impl ConditionPart {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            ConditionPart::Char(expected) => *expected == c,
+            ConditionPart::Set { chars, negated } => chars.contains(&c) != *negated,
+            ConditionPart::Any => true,
+        }
+    }
+}
+
+/// תנאי של חוק תחילית/סיומת בתחביר Hunspell, למשל "[^aeiou]y"
+#[derive(Debug, Clone)]
+struct Condition(Vec<ConditionPart>);
+
+impl Condition {
+    fn parse(text: &str) -> Result<Self> {
+        let mut parts = Vec::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            parts.push(match c {
+                '.' => ConditionPart::Any,
+                '[' => {
+                    let mut set = Vec::new();
+                    let mut negated = false;
+                    loop {
+                        match chars.next() {
+                            Some(']') => break,
+                            Some('^') if set.is_empty() && !negated => negated = true,
+                            Some(c) => set.push(c),
+                            None => bail!("תנאי לא סגור: {}", text),
+                        }
+                    }
+                    ConditionPart::Set { chars: set, negated }
+                }
+                c => ConditionPart::Char(c),
+            });
+        }
+        Ok(Condition(parts))
+    }
+
+    fn matches_start(&self, word: &str) -> bool {
+        let mut chars = word.chars();
+        self.0.iter().all(|part| chars.next().is_some_and(|c| part.matches(c)))
+    }
+
+    fn matches_end(&self, word: &str) -> bool {
+        let mut chars = word.chars().rev();
+        self.0.iter().rev().all(|part| chars.next().is_some_and(|c| part.matches(c)))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct AffixEntry {
+    flag: Flag,
+    strip: String,
+    condition: Condition,
+    cross_product: bool,
+    /// דגלי ההמשך שאחרי "/": הצורה הנטויה יכולה לקבל גם את החוקים האלה
+    continuation: HashSet<Flag>,
+}
+
+/// מילון Hunspell טעון: מילות בסיס עם הדגלים שלהן וחוקי הנטייה מקובץ ה-.aff
+#[derive(Debug, Clone)]
+pub struct HunspellDictionary {
+    flag_mode: FlagMode,
+    /// כינויי AF: במילון ובדגלי ההמשך, מספר n מציין את קבוצת הדגלים ה-n
+    flag_aliases: Vec<Vec<Flag>>,
+    words: HashMap<String, HashSet<Flag>>,
+    /// לפי המחרוזת שהתחילית מוסיפה
+    prefixes: HashMap<String, Vec<AffixEntry>>,
+    /// לפי המחרוזת שהסיומת מוסיפה
+    suffixes: HashMap<String, Vec<AffixEntry>>,
+    replacements: Vec<(String, String)>,
+    keyboard: Vec<Vec<char>>,
+    try_chars: Vec<char>,
+    forbidden: Option<Flag>,
+    no_suggest: Option<Flag>,
+    need_affix: Option<Flag>,
+}
+
+impl HunspellDictionary {
+    /// טעינה מזוג קבצים, למשל he_IL.aff ו-he_IL.dic
+    pub fn load(aff: &Path, dic: &Path) -> Result<Self> {
+        let aff_bytes = fs::read(aff).with_context(|| format!("קריאת {}", aff.display()))?;
+        let dic_bytes = fs::read(dic).with_context(|| format!("קריאת {}", dic.display()))?;
+        Self::from_bytes(&aff_bytes, &dic_bytes)
+    }
+
+    pub fn from_bytes(aff: &[u8], dic: &[u8]) -> Result<Self> {
+        // שורת SET תמיד ב-ASCII, ולכן אפשר לקרוא אותה לפני שהקידוד ידוע
+        let encoding = String::from_utf8_lossy(aff)
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("SET ").map(|name| Encoding::from_name(name.trim())))
+            .transpose()?
+            .unwrap_or(Encoding::Latin1);
+
+        let mut dictionary = Self::parse_aff(&encoding.decode(aff))?;
+        dictionary.parse_dic(&encoding.decode(dic))?;
+        Ok(dictionary)
+    }
+
+    fn parse_aff(aff: &str) -> Result<Self> {
+        let mut dictionary = Self {
+            flag_mode: FlagMode::Char,
+            flag_aliases: Vec::new(),
+            words: HashMap::new(),
+            prefixes: HashMap::new(),
+            suffixes: HashMap::new(),
+            replacements: Vec::new(),
+            keyboard: Vec::new(),
+            try_chars: Vec::new(),
+            forbidden: None,
+            no_suggest: None,
+            need_affix: None,
+        };
+        // דגל -> האם מותר צירוף עם תחילית
+        let mut cross_products: HashMap<(bool, Flag), bool> = HashMap::new();
+        // השורה הראשונה של AF ושל AM היא מספר הכינויים
+        let (mut af_header, mut am_header) = (false, false);
+
+        for (number, line) in aff.lines().enumerate() {
+            let flag_mode = dictionary.flag_mode;
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let Some(&keyword) = fields.first() else {
+                continue;
+            };
+            let field = |index: usize| -> Result<&str> {
+                fields.get(index).copied().ok_or_else(|| anyhow!("שורה {} בקובץ ה-aff חסרה", number + 1))
+            };
+
+            match keyword {
+                "FLAG" => {
+                    dictionary.flag_mode = match field(1)? {
+                        "long" => FlagMode::Long,
+                        "num" => FlagMode::Num,
+                        _ => FlagMode::Char,
+                    }
+                }
+                "AF" if !af_header => af_header = true,
+                "AF" => {
+                    let flags = parse_flags(field(1)?, flag_mode)?;
+                    dictionary.flag_aliases.push(flags);
+                }
+                "AM" if !am_header => am_header = true,
+                "AM" => {}
+                "TRY" => dictionary.try_chars = field(1)?.chars().collect(),
+                "KEY" => dictionary.keyboard = field(1)?.split('|').map(|row| row.chars().collect()).collect(),
+                "FORBIDDENWORD" => dictionary.forbidden = parse_flags(field(1)?, flag_mode)?.first().copied(),
+                "NOSUGGEST" => dictionary.no_suggest = parse_flags(field(1)?, flag_mode)?.first().copied(),
+                "NEEDAFFIX" | "PSEUDOROOT" => dictionary.need_affix = parse_flags(field(1)?, flag_mode)?.first().copied(),
+                "REP" if fields.len() == 3 => {
+                    dictionary.replacements.push((field(1)?.replace('_', " "), field(2)?.replace('_', " ")));
+                }
+                "PFX" | "SFX" => {
+                    let is_prefix = keyword == "PFX";
+                    let flag = *parse_flags(field(1)?, flag_mode)?
+                        .first()
+                        .ok_or_else(|| anyhow!("שורה {}: דגל ריק", number + 1))?;
+
+                    // שורת כותרת: PFX A Y 3
+                    if fields.len() == 4 && !cross_products.contains_key(&(is_prefix, flag)) {
+                        cross_products.insert((is_prefix, flag), field(2)? == "Y");
+                        continue;
+                    }
+
+                    let strip = match field(2)? {
+                        "0" => String::new(),
+                        strip => strip.to_string(),
+                    };
+                    let (add, continuation) = match split_entry(field(3)?) {
+                        (add, Some(flags)) => (add, dictionary.parse_flag_field(flags)?),
+                        (add, None) => (add, Vec::new()),
+                    };
+                    let add = match add {
+                        "0" => String::new(),
+                        add => add.to_string(),
+                    };
+                    let condition = Condition::parse(fields.get(4).copied().unwrap_or("."))?;
+                    let entry = AffixEntry {
+                        flag,
+                        strip,
+                        condition,
+                        cross_product: cross_products.get(&(is_prefix, flag)).copied().unwrap_or(false),
+                        continuation: continuation.into_iter().collect(),
+                    };
+                    let table = if is_prefix { &mut dictionary.prefixes } else { &mut dictionary.suffixes };
+                    table.entry(add).or_default().push(entry);
+                }
+                _ => {}
+            }
+        }
+
+        dictionary.keyboard.retain(|row| !row.is_empty());
+        Ok(dictionary)
+    }
+
+    fn parse_dic(&mut self, dic: &str) -> Result<()> {
+        // השורה הראשונה היא מספר המילים המשוער
+        for line in dic.lines().skip(1) {
+            let entry = line.split(['\t', ' ']).next().unwrap_or_default().trim();
+            if entry.is_empty() {
+                continue;
+            }
+            let (word, flags) = match split_entry(entry) {
+                (word, Some(flags)) => (word, self.parse_flag_field(flags)?),
+                (word, None) => (word, Vec::new()),
+            };
+            self.words.entry(word.replace("\\/", "/")).or_default().extend(flags);
+        }
+        Ok(())
+    }
+
+    /// דגלים במילון או אחרי "/" בחוק: מספר כינוי כשיש AF, אחרת הדגלים עצמם
+    fn parse_flag_field(&self, text: &str) -> Result<Vec<Flag>> {
+        if self.flag_aliases.is_empty() {
+            return parse_flags(text, self.flag_mode);
+        }
+        let index: usize = text.parse().with_context(|| format!("כינוי דגלים לא תקין: {}", text))?;
+        index
+            .checked_sub(1)
+            .and_then(|index| self.flag_aliases.get(index))
+            .cloned()
+            .ok_or_else(|| anyhow!("כינוי דגלים {} אינו מוגדר ב-AF", index))
+    }
+
+    pub fn word_count(&self) -> usize {
+        self.words.len()
+    }
+
+    /// הוספת מילה למילון (מילון אישי או מונחי לקוח)
+    pub fn add_word(&mut self, word: &str) {
+        self.words.entry(word.to_string()).or_default();
+    }
+
+    fn has_flag(&self, word: &str, flag: Flag) -> bool {
+        self.words.get(word).is_some_and(|flags| flags.contains(&flag) && !self.is_forbidden(flags))
+    }
+
+    fn is_forbidden(&self, flags: &HashSet<Flag>) -> bool {
+        self.forbidden.is_some_and(|f| flags.contains(&f))
+    }
+
+    /// האם המילה תקינה, כולל צורות נטויות לפי חוקי התחיליות והסיומות ותיקון אות גדולה
+    pub fn check(&self, word: &str) -> bool {
+        if word.is_empty() {
+            return true;
+        }
+        case_variants(word).iter().any(|variant| self.check_exact(variant))
+    }
+
+    fn check_exact(&self, word: &str) -> bool {
+        if let Some(flags) = self.words.get(word) {
+            if self.is_forbidden(flags) {
+                return false;
+            }
+            if !self.need_affix.is_some_and(|f| flags.contains(&f)) {
+                return true;
+            }
+        }
+        self.check_suffixed(word) || self.check_prefixed(word)
+    }
+
+    fn check_suffixed(&self, word: &str) -> bool {
+        self.check_suffixed_with(word, None)
+    }
+
+    /// `outer` - דגל הסיומת החיצונית, כשמחפשים סיומת פנימית שדגלי ההמשך שלה מתירים אותה
+    fn check_suffixed_with(&self, word: &str, outer: Option<Flag>) -> bool {
+        for (split, _) in word.char_indices().chain(std::iter::once((word.len(), ' '))) {
+            let (head, ending) = word.split_at(split);
+            let Some(entries) = self.suffixes.get(ending) else {
+                continue;
+            };
+            for entry in entries.iter().filter(|e| outer.is_none_or(|flag| e.continuation.contains(&flag))) {
+                let stem = format!("{}{}", head, entry.strip);
+                if stem.is_empty() || !entry.condition.matches_end(&stem) {
+                    continue;
+                }
+                if self.has_flag(&stem, entry.flag) {
+                    return true;
+                }
+                // שתי סיומות: הצורה שלפני הסיומת הזו נוצרה בסיומת שמתירה אותה כהמשך
+                if outer.is_none() && self.check_suffixed_with(&stem, Some(entry.flag)) {
+                    return true;
+                }
+                // צירוף תחילית וסיומת על אותו בסיס
+                if entry.cross_product && self.check_prefixed_with(&stem, |prefix| {
+                    prefix.cross_product
+                }, Some(entry)) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    fn check_prefixed(&self, word: &str) -> bool {
+        self.check_prefixed_with(word, |_| true, None)
+    }
+
+    /// `suffix` - הסיומת שכבר הוסרה מהמילה; הבסיס צריך את שני הדגלים, או שאחד מהם ניתן כדגל המשך של השני
+    fn check_prefixed_with(&self, word: &str, allowed: impl Fn(&AffixEntry) -> bool, suffix: Option<&AffixEntry>) -> bool {
+        for (split, _) in word.char_indices().skip(1).chain(std::iter::once((word.len(), ' '))) {
+            let (beginning, tail) = word.split_at(split);
+            let Some(entries) = self.prefixes.get(beginning) else {
+                continue;
+            };
+            for entry in entries.iter().filter(|e| allowed(e)) {
+                let stem = format!("{}{}", entry.strip, tail);
+                if stem.is_empty() || !entry.condition.matches_start(&stem) {
+                    continue;
+                }
+                let accepted = match suffix {
+                    None => self.has_flag(&stem, entry.flag),
+                    Some(suffix) => {
+                        let (prefix_flag, suffix_flag) = (self.has_flag(&stem, entry.flag), self.has_flag(&stem, suffix.flag));
+                        (prefix_flag && (suffix_flag || entry.continuation.contains(&suffix.flag)))
+                            || (suffix_flag && suffix.continuation.contains(&entry.flag))
+                    }
+                };
+                if accepted {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// הצעות תיקון מדורגות: החלפות REP, שכנות במקלדת, עריכה אחת לפי TRY ופיצול למילים
+    pub fn suggest(&self, word: &str, limit: usize) -> Vec<String> {
+        let lower = word.to_lowercase();
+        let mut candidates: Vec<(u8, String)> = Vec::new();
+
+        for (from, to) in &self.replacements {
+            for (index, _) in lower.match_indices(from.as_str()) {
+                candidates.push((0, format!("{}{}{}", &lower[..index], to, &lower[index + from.len()..])));
+            }
+        }
+
+        let chars: Vec<char> = lower.chars().collect();
+        let rebuild = |chars: &[char]| chars.iter().collect::<String>();
+        for i in 0..chars.len() {
+            for row in &self.keyboard {
+                if let Some(position) = row.iter().position(|&c| c == chars[i]) {
+                    for neighbour in [position.checked_sub(1), Some(position + 1)].into_iter().flatten() {
+                        if let Some(&c) = row.get(neighbour) {
+                            let mut edited = chars.clone();
+                            edited[i] = c;
+                            candidates.push((1, rebuild(&edited)));
+                        }
+                    }
+                }
+            }
+            if i + 1 < chars.len() {
+                let mut swapped = chars.clone();
+                swapped.swap(i, i + 1);
+                candidates.push((1, rebuild(&swapped)));
+            }
+            let mut deleted = chars.clone();
+            deleted.remove(i);
+            candidates.push((2, rebuild(&deleted)));
+            for &c in &self.try_chars {
+                let mut replaced = chars.clone();
+                replaced[i] = c;
+                candidates.push((2, rebuild(&replaced)));
+            }
+        }
+        for i in 0..=chars.len() {
+            for &c in &self.try_chars {
+                let mut inserted = chars.clone();
+                inserted.insert(i, c);
+                candidates.push((2, rebuild(&inserted)));
+            }
+        }
+        for i in 1..chars.len() {
+            let (first, second) = chars.split_at(i);
+            candidates.push((3, format!("{} {}", rebuild(first), rebuild(second))));
+        }
+
+        let mut seen = HashSet::new();
+        let mut suggestions: Vec<(u8, usize, Reverse<usize>, String)> = candidates
+            .into_iter()
+            .filter(|(_, candidate)| *candidate != lower && seen.insert(candidate.clone()))
+            .filter(|(_, candidate)| candidate.split(' ').all(|part| self.check(part) && !self.is_no_suggest(part)))
+            .map(|(class, candidate)| {
+                let distance = edit_distance(&lower, &candidate);
+                let common = lower.chars().zip(candidate.chars()).take_while(|(a, b)| a == b).count();
+                (class, distance, Reverse(common), candidate)
+            })
+            .collect();
+        suggestions.sort();
+
+        suggestions
+            .into_iter()
+            .take(limit)
+            .map(|(_, _, _, candidate)| match_case(word, &candidate))
+            .collect()
+    }
+
+    fn is_no_suggest(&self, word: &str) -> bool {
+        self.no_suggest
+            .is_some_and(|flag| self.words.get(word).is_some_and(|flags| flags.contains(&flag)))
+    }
+}
+
+fn split_entry(entry: &str) -> (&str, Option<&str>) {
+    // "/" בתוך מילה מסומן כ-"\/"
+    let bytes = entry.as_bytes();
+    for (index, &b) in bytes.iter().enumerate() {
+        if b == b'/' && (index == 0 || bytes[index - 1] != b'\\') {
+            return (&entry[..index], Some(&entry[index + 1..]));
+        }
+    }
+    (entry, None)
+}
+
+fn parse_flags(text: &str, mode: FlagMode) -> Result<Vec<Flag>> {
+    Ok(match mode {
+        FlagMode::Char => text.chars().map(|c| c as Flag).collect(),
+        FlagMode::Long => {
+            let chars: Vec<char> = text.chars().collect();
+            chars.chunks(2).map(|pair| pair.iter().fold(0, |acc, &c| (acc << 16) | c as Flag)).collect()
+        }
+        FlagMode::Num => text
+            .split(',')
+            .filter(|f| !f.is_empty())
+            .map(|f| f.trim().parse::<Flag>().with_context(|| format!("דגל מספרי לא תקין: {}", f)))
+            .collect::<Result<_>>()?,
+    })
+}
+
+/// המילה כפי שהיא, באותיות קטנות, ובאות ראשונה גדולה (למילה שכולה אותיות גדולות)
+fn case_variants(word: &str) -> Vec<String> {
+    let mut variants = vec![word.to_string()];
+    let lower = word.to_lowercase();
+    if lower != word {
+        variants.push(lower.clone());
+        let mut chars = lower.chars();
+        if let Some(first) = chars.next() {
+            let capitalized: String = first.to_uppercase().chain(chars).collect();
+            if capitalized != word {
+                variants.push(capitalized);
+            }
+        }
+    }
+    variants
+}
+
+fn match_case(original: &str, suggestion: &str) -> String {
+    let letters: Vec<char> = original.chars().filter(|c| c.is_alphabetic()).collect();
+    if letters.len() > 1 && letters.iter().all(|c| c.is_uppercase()) {
+        return suggestion.to_uppercase();
+    }
+    match original.chars().next() {
+        Some(first) if first.is_uppercase() => {
+            let mut chars = suggestion.chars();
+            chars.next().map(|c| c.to_uppercase().chain(chars).collect()).unwrap_or_default()
+        }
+        _ => suggestion.to_string(),
+    }
+}
+
+/// מרחק עריכה עם החלפת תווים סמוכים (Damerau-Levenshtein מוגבל)
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            rows[i][j] = (rows[i - 1][j] + 1).min(rows[i][j - 1] + 1).min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                rows[i][j] = rows[i][j].min(rows[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// מילה שגויה בטקסט, עם הטווח שלה (בתים) לסימון בממשק
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Misspelling {
+    pub word: String,
+    pub span: (usize, usize),
+    pub suggestions: Vec<String>,
+    pub severity: Severity,
+}
+
+/// חלוקות אפשריות של מילה עברית לשרשרת תחיליות (עד שלוש) ולבסיס של שתי אותיות לפחות
+fn hebrew_prefix_splits(word: &str) -> Vec<(&str, &str)> {
+    if !word.chars().next().is_some_and(is_hebrew) {
+        return Vec::new();
+    }
+    word.char_indices()
+        .take_while(|(_, c)| HEBREW_PREFIX_LETTERS.contains(c))
+        .take(3)
+        .map(|(index, c)| word.split_at(index + c.len_utf8()))
+        .filter(|(_, rest)| rest.chars().count() >= 2)
+        .collect()
+}
+
+fn is_hebrew(c: char) -> bool {
+    ('\u{05D0}'..='\u{05EA}').contains(&c)
+}
+
+fn is_cyrillic(c: char) -> bool {
+    ('\u{0400}'..='\u{04FF}').contains(&c)
+}
+
+/// בודק איות לעברית ולרוסית לפי הכתב של כל מילה, ומדלג על מונחי מילון, קודים ומספרים
+pub struct SpellChecker {
+    hebrew: Option<HunspellDictionary>,
+    russian: Option<HunspellDictionary>,
+    glossary: HashSet<String>,
+    glossary_stems: HashSet<String>,
+    ignored: HashSet<String>,
+    max_suggestions: usize,
+}
+
+impl Default for SpellChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SpellChecker {
+    pub fn new() -> Self {
+        Self {
+            hebrew: None,
+            russian: None,
+            glossary: HashSet::new(),
+            glossary_stems: HashSet::new(),
+            ignored: HashSet::new(),
+            max_suggestions: 5,
+        }
+    }
+
+    pub fn with_hebrew(mut self, dictionary: HunspellDictionary) -> Self {
+        self.hebrew = Some(dictionary);
+        self
+    }
+
+    pub fn with_russian(mut self, dictionary: HunspellDictionary) -> Self {
+        self.russian = Some(dictionary);
+        self
+    }
+
+    pub fn with_max_suggestions(mut self, max_suggestions: usize) -> Self {
+        self.max_suggestions = max_suggestions;
+        self
+    }
+
+    /// מונחי המילון הטכני אינם מסומנים כשגיאה, בכל צורות המשטח (תחיליות ורבים בעברית, נטייה ברוסית)
+    pub fn with_terms(mut self, terms: &TermsDatabase) -> Self {
+        for term in terms.terms() {
+            for phrase in std::iter::once(&term.term_he).chain(&term.synonyms) {
+                for variant in hebrew_surface_variants(phrase) {
+                    self.glossary.extend(variant.split_whitespace().map(str::to_string));
+                }
+            }
+            for word in term.term_ru.split_whitespace() {
+                self.glossary.insert(word.to_lowercase());
+                self.glossary_stems.insert(russian_stem(word));
+            }
+        }
+        self
+    }
+
+    /// מילה שהמשתמש בחר להתעלם ממנה
+    pub fn ignore_word(&mut self, word: &str) {
+        self.ignored.insert(word.to_lowercase());
+    }
+
+    /// האם לבדוק את המילה בכלל: מספרים, קודים, קיצורים ומילים באותיות לטיניות אינם נבדקים
+    fn should_check(&self, word: &str) -> bool {
+        let uppercase = word.chars().filter(|c| c.is_uppercase()).count();
+        !(word.chars().any(|c| c.is_numeric() || c.is_ascii_alphabetic() || c == '"' || c == '״')
+            || uppercase > 1
+            || self.ignored.contains(&word.to_lowercase())
+            || self.is_glossary_word(word))
+    }
+
+    fn is_glossary_word(&self, word: &str) -> bool {
+        let lower = word.to_lowercase();
+        self.glossary.contains(&lower)
+            || (lower.chars().any(is_cyrillic) && self.glossary_stems.contains(&russian_stem(&lower)))
+    }
+
+    fn dictionary_for(&self, word: &str) -> Option<&HunspellDictionary> {
+        let first = word.chars().find(|c| c.is_alphabetic())?;
+        if is_hebrew(first) {
+            self.hebrew.as_ref()
+        } else if is_cyrillic(first) {
+            self.russian.as_ref()
+        } else {
+            None
+        }
+    }
+
+    /// בדיקת מילה בודדת; מילה בשפה שאין לה מילון נחשבת תקינה
+    pub fn check_word(&self, word: &str) -> bool {
+        if !self.should_check(word) {
+            return true;
+        }
+        let Some(dictionary) = self.dictionary_for(word) else {
+            return true;
+        };
+        if dictionary.check(word) {
+            return true;
+        }
+
+        // תחיליות עבריות (ו, ה, ב, ל, מ, ש, כ) שהמילון אינו מכיר כחוקי נטייה
+        if hebrew_prefix_splits(word)
+            .into_iter()
+            .any(|(_, rest)| dictionary.check(rest) || self.is_glossary_word(rest))
+        {
+            return true;
+        }
+
+        // מילה מחוברת במקף נבדקת לפי חלקיה
+        word.contains(['-', '־']) && word.split(['-', '־']).all(|part| self.check_word(part))
+    }
+
+    /// הצעות תיקון מדורגות למילה; במילה עברית גם לבסיס שאחרי התחיליות, עם התחיליות
+    pub fn suggest(&self, word: &str) -> Vec<String> {
+        let Some(dictionary) = self.dictionary_for(word) else {
+            return Vec::new();
+        };
+
+        let mut suggestions = dictionary.suggest(word, self.max_suggestions);
+        for (prefix, rest) in hebrew_prefix_splits(word) {
+            for suggestion in dictionary.suggest(rest, self.max_suggestions) {
+                let full = format!("{}{}", prefix, suggestion);
+                if !suggestions.contains(&full) {
+                    suggestions.push(full);
+                }
+            }
+        }
+        suggestions.truncate(self.max_suggestions);
+        suggestions
+    }
+
+    /// כל המילים השגויות בטקסט, לפי סדר הופעתן, עם הצעות תיקון
+    pub fn check_text(&self, text: &str) -> Vec<Misspelling> {
+        let mut misspellings = self.find_misspellings(text);
+        for misspelling in &mut misspellings {
+            misspelling.suggestions = self.suggest(&misspelling.word);
+        }
+        misspellings
+    }
+
+    /// המילים השגויות בלבד, בלי הצעות; זול מספיק להרצה בכל הקשה בעורך
+    pub fn find_misspellings(&self, text: &str) -> Vec<Misspelling> {
+        WORD.find_iter(text)
+            .filter(|m| !self.check_word(m.as_str()))
+            .map(|m| Misspelling {
+                word: m.as_str().to_string(),
+                span: (m.start(), m.end()),
+                suggestions: Vec::new(),
+                severity: Severity::Minor,
+            })
+            .collect()
+    }
+}
+
+/// קטע טקסט לתצוגה, עם סימון אם הוא מילה שגויה (האינדקס ברשימת השגיאות)
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextSegment {
+    pub text: String,
+    pub misspelling: Option<usize>,
+}
+
+/// חלוקת הטקסט לקטעים רגילים ולקטעים שיש לסמן בקו גלי
+pub fn underline_segments(text: &str, misspellings: &[Misspelling]) -> Vec<TextSegment> {
+    let mut segments = Vec::new();
+    let mut last = 0;
+    for (index, misspelling) in misspellings.iter().enumerate() {
+        let (start, end) = misspelling.span;
+        if start < last || end > text.len() || !text.is_char_boundary(start) || !text.is_char_boundary(end) {
+            continue;
+        }
+        if start > last {
+            segments.push(TextSegment { text: text[last..start].to_string(), misspelling: None });
+        }
+        segments.push(TextSegment { text: text[start..end].to_string(), misspelling: Some(index) });
+        last = end;
+    }
+    if last < text.len() {
+        segments.push(TextSegment { text: text[last..].to_string(), misspelling: None });
+    }
+    segments
+}
+
+/// החלפת מילה שגויה בהצעה שנבחרה
+pub fn apply_suggestion(text: &str, misspelling: &Misspelling, replacement: &str) -> String {
+    let (start, end) = misspelling.span;
+    if text.get(start..end) != Some(misspelling.word.as_str()) {
+        return text.to_string();
+    }
+    format!("{}{}{}", &text[..start], replacement, &text[end..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quality_control::terminology::tests::term;
+
+    const RU_AFF: &str = "SET UTF-8\nTRY оеаинтсрвлкмдпуяызбгчйхжшюцщэфъё\nKEY йцукенгшщзхъ|фывапролджэ|ячсмитьбю\nREP 1\nREP е ё\n\
+        SFX A Y 3\nSFX A а у а\nSFX A а ой а\nSFX A а и [^кгх]а\n\
+        SFX B Y 2\nSFX B 0 а [^аяь]\nSFX B 0 ом [^аяь]\n\
+        PFX C Y 1\nPFX C 0 не .\n";
+    const RU_DIC: &str = "4\nзадвижка/A\nклапан/B\nоткрытый/C\nпожарный\n";
+
+    fn russian() -> HunspellDictionary {
+        HunspellDictionary::from_bytes(RU_AFF.as_bytes(), RU_DIC.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_affix_rules() {
+        let dictionary = russian();
+        assert_eq!(dictionary.word_count(), 4);
+        for word in ["задвижка", "задвижку", "задвижкой", "клапана", "клапаном", "неоткрытый", "Пожарный", "ЗАДВИЖКА"] {
+            assert!(dictionary.check(word), "{}", word);
+        }
+        // תנאי [^кгх]а חוסם את "задвижки" בחוק A
+        for word in ["задвижки", "задвижкам", "клапану", "незадвижка", "задвишка"] {
+            assert!(!dictionary.check(word), "{}", word);
+        }
+    }
+
+    #[test]
+    fn test_flag_aliases_and_continuation_flags() {
+        // AF 1 = AB, AF 2 = B, AF 3 = C; הסיומת -а (A) מתירה המשך ב-х (C)
+        let aff = "SET UTF-8\nAF 3\nAF AB\nAF B\nAF C\nAM 1\nAM po:noun\n\
+            SFX A Y 1\nSFX A 0 а/3 [^аяь]\n\
+            SFX B Y 1\nSFX B 0 ом [^аяь]\n\
+            SFX C N 1\nSFX C 0 х .\n";
+        let dic = "3\nклапан/1\tst:клапан\nкран/2\nпуск\n";
+        let dictionary = HunspellDictionary::from_bytes(aff.as_bytes(), dic.as_bytes()).unwrap();
+
+        for word in ["клапан", "клапана", "клапаном", "клапанах", "краном"] {
+            assert!(dictionary.check(word), "{}", word);
+        }
+        // C אינו דגל של הבסיס, רק המשך של A
+        for word in ["клапанх", "кранах", "крана"] {
+            assert!(!dictionary.check(word), "{}", word);
+        }
+
+        // AF 1 = D, AF 2 = P; -ов מתירה המשך ב-ых (E), והתחילית не- מתירה את הסיומת -ом (B)
+        let aff = "SET UTF-8\nAF 4\nAF D\nAF P\nAF E\nAF B\n\
+            SFX D Y 1\nSFX D 0 ов/3 .\nSFX E Y 1\nSFX E 0 ых .\nSFX B Y 1\nSFX B 0 ом .\n\
+            PFX P Y 1\nPFX P 0 не/4 .\n";
+        let dic = "2\nтруб/1\nпуск/2\n";
+        let dictionary = HunspellDictionary::from_bytes(aff.as_bytes(), dic.as_bytes()).unwrap();
+        assert!(dictionary.check("трубов"));
+        assert!(dictionary.check("трубовых"));
+        assert!(!dictionary.check("трубых"));
+        assert!(dictionary.check("непуском"));
+        assert!(!dictionary.check("пуском"));
+
+        assert!(HunspellDictionary::from_bytes(b"SET UTF-8\nAF 1\nAF A\n", "1\nслово/2\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_ranked_suggestions() {
+        let dictionary = russian();
+        assert_eq!(dictionary.suggest("задвишка", 3)[0], "задвижка");
+        assert_eq!(dictionary.suggest("калпан", 3)[0], "клапан");
+        assert_eq!(dictionary.suggest("Задвижкаа", 3)[0], "Задвижка");
+    }
+
+    #[test]
+    fn test_legacy_encodings() {
+        // ISO8859-8: מ=EE, ג=E2, ו=E5, ף=F3
+        let aff = b"SET ISO8859-8\n";
+        let dic = [b"1\n".as_slice(), &[0xEE, 0xE2, 0xE5, 0xF3, b'\n']].concat();
+        let dictionary = HunspellDictionary::from_bytes(aff, &dic).unwrap();
+        assert!(dictionary.check("מגוף"));
+
+        let aff = b"SET KOI8-R\n";
+        let dic = [b"1\n".as_slice(), &[0xCB, 0xCC, 0xC1, 0xD0, 0xC1, 0xCE, b'\n']].concat();
+        let dictionary = HunspellDictionary::from_bytes(aff, &dic).unwrap();
+        assert!(dictionary.check("клапан"));
+    }
+
+    #[test]
+    fn test_checker_skips_codes_numbers_and_glossary() {
+        let hebrew = HunspellDictionary::from_bytes("SET UTF-8\nTRY יוהאמלנבשתרכדגפסעקצזחטף\n".as_bytes(), "6\nמגוף\nסגור\nאת\nלפי\nבר\nליד\n".as_bytes()).unwrap();
+        let mut terms = TermsDatabase::new();
+        terms.add_term(term("спринклер", "ספרינקלר", &[]));
+        let checker = SpellChecker::new().with_hebrew(hebrew).with_russian(russian()).with_terms(&terms);
+
+        let text = "לסגור את המגוף DN50 לפי ГОСТ 12.2.047, 16 בר, והספרינקלרים ליד спринклером והמגוב.";
+        let misspellings = checker.check_text(text);
+        let words: Vec<&str> = misspellings.iter().map(|m| m.word.as_str()).collect();
+        assert_eq!(words, vec!["והמגוב"]);
+        assert_eq!(misspellings[0].suggestions[0], "והמגוף");
+
+        let segments = underline_segments(text, &misspellings);
+        assert_eq!(segments[1], TextSegment { text: "והמגוב".to_string(), misspelling: Some(0) });
+        assert_eq!(segments.iter().map(|s| s.text.as_str()).collect::<String>(), text);
+
+        let fixed = apply_suggestion(text, &misspellings[0], &misspellings[0].suggestions[0]);
+        assert!(fixed.ends_with("והמגוף."));
+    }
+}
@@ -0,0 +1,324 @@
+//! עקביות מינוח ברמת המסמך: אותו מונח מקור צריך להיות מתורגם באותו מונח מאושר בכל הסגמנטים
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use serde::{Serialize, Deserialize};
+use crate::morphology::utils::hebrew_surface_variants;
+use crate::technical_terms::{TechnicalTerm, TermsDatabase};
+use super::Severity;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TermIssueKind {
+    /// המונח תורגם ביותר מדרך אחת במסמך
+    Inconsistent,
+    /// המונח תורגם בעקביות, אך לא במונח המאושר במילון
+    GlossaryDeviation,
+    /// לא נמצא בתרגום אף תרגום מוכר של המונח
+    Missing,
+}
+
+/// הופעה של מונח מקור בסגמנט והתרגום שנמצא לה
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TermOccurrence {
+    pub segment: usize,
+    pub source_form: String,
+    pub rendering: Option<String>,
+}
+
+/// החלפה אחת בתרגום של סגמנט
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TermFix {
+    pub segment: usize,
+    pub replace: String,
+    pub with: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TermConsistencyIssue {
+    pub kind: TermIssueKind,
+    pub source_term: String,
+    pub preferred: String,
+    /// כל תרגום שנמצא והסגמנטים שבהם הופיע
+    pub renderings: BTreeMap<String, Vec<usize>>,
+    pub occurrences: Vec<TermOccurrence>,
+    pub message: String,
+    pub severity: Severity,
+    /// ההחלפות שמנרמלות את המסמך למונח המאושר
+    pub fixes: Vec<TermFix>,
+}
+
+pub struct TerminologyChecker {
+    terms: Arc<TermsDatabase>,
+    /// תרגומים חלופיים ידועים שאינם במילון, לפי המונח הרוסי
+    known_variants: HashMap<String, Vec<String>>,
+}
+
+impl TerminologyChecker {
+    pub fn new(terms: Arc<TermsDatabase>) -> Self {
+        Self {
+            terms,
+            known_variants: HashMap::new(),
+        }
+    }
+
+    /// רישום תרגומים לא מאושרים שנראו בפועל, כדי לזהות אותם במסמכים הבאים
+    pub fn add_known_variant(&mut self, source_term: &str, variant: impl Into<String>) {
+        let variants = self.known_variants.entry(source_term.to_lowercase()).or_default();
+        let variant = variant.into();
+        if !variants.contains(&variant) {
+            variants.push(variant);
+        }
+    }
+
+    /// בדיקת המסמך כולו; segments הם זוגות (מקור, תרגום) לפי הסדר
+    pub fn check_document(&self, segments: &[(String, String)]) -> Vec<TermConsistencyIssue> {
+        let mut usages: BTreeMap<String, (TechnicalTerm, Vec<TermOccurrence>)> = BTreeMap::new();
+
+        for (index, (source, target)) in segments.iter().enumerate() {
+            for (term, source_form) in self.terms.find_russian_term_forms(source) {
+                let rendering = self
+                    .candidate_renderings(&term)
+                    .into_iter()
+                    .find(|candidate| !find_surface(target, candidate).is_empty());
+
+                usages
+                    .entry(term.term_ru.to_lowercase())
+                    .or_insert_with(|| (term.clone(), Vec::new()))
+                    .1
+                    .push(TermOccurrence { segment: index, source_form, rendering });
+            }
+        }
+
+        usages
+            .into_values()
+            .filter_map(|(term, occurrences)| self.issue_for(&term, occurrences, segments))
+            .collect()
+    }
+
+    /// החלת ההחלפות על התרגומים; מחזיר את מספר ההחלפות שבוצעו
+    pub fn apply_fixes(targets: &mut [String], fixes: &[TermFix]) -> usize {
+        let mut applied = 0;
+        for fix in fixes {
+            if let Some(target) = targets.get_mut(fix.segment) {
+                let (replaced, count) = replace_surface(target, &fix.replace, &fix.with);
+                *target = replaced;
+                applied += count;
+            }
+        }
+        applied
+    }
+
+    /// המונח המאושר תחילה, אחריו מילים נרדפות בעברית וחלופות שנרשמו
+    fn candidate_renderings(&self, term: &TechnicalTerm) -> Vec<String> {
+        let mut candidates = vec![term.term_he.clone()];
+        let synonyms = term.synonyms.iter().filter(|s| s.chars().any(is_hebrew)).cloned();
+        let variants = self.known_variants.get(&term.term_ru.to_lowercase()).into_iter().flatten().cloned();
+
+        for candidate in synonyms.chain(variants) {
+            if !candidate.is_empty() && !candidates.contains(&candidate) {
+                candidates.push(candidate);
+            }
+        }
+        candidates
+    }
+
+    fn issue_for(
+        &self,
+        term: &TechnicalTerm,
+        occurrences: Vec<TermOccurrence>,
+        segments: &[(String, String)],
+    ) -> Option<TermConsistencyIssue> {
+        let mut renderings: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for occurrence in &occurrences {
+            if let Some(rendering) = &occurrence.rendering {
+                let segments = renderings.entry(rendering.clone()).or_default();
+                if !segments.contains(&occurrence.segment) {
+                    segments.push(occurrence.segment);
+                }
+            }
+        }
+        let missing: Vec<usize> = occurrences.iter().filter(|o| o.rendering.is_none()).map(|o| o.segment).collect();
+
+        let (kind, severity, message) = if renderings.len() > 1 {
+            let forms: Vec<&str> = renderings.keys().map(String::as_str).collect();
+            (
+                TermIssueKind::Inconsistent,
+                Severity::Major,
+                format!("המונח \"{}\" תורגם בכמה אופנים: {}", term.term_ru, forms.join(", ")),
+            )
+        } else if renderings.keys().any(|rendering| *rendering != term.term_he) {
+            (
+                TermIssueKind::GlossaryDeviation,
+                Severity::Minor,
+                format!("המונח \"{}\" תורגם שלא לפי המילון; המונח המאושר: {}", term.term_ru, term.term_he),
+            )
+        } else if !missing.is_empty() {
+            (
+                TermIssueKind::Missing,
+                Severity::Info,
+                format!("לא נמצא תרגום מוכר למונח \"{}\" ב-{} סגמנטים", term.term_ru, missing.len()),
+            )
+        } else {
+            return None;
+        };
+
+        let fixes = renderings
+            .iter()
+            .filter(|(rendering, _)| **rendering != term.term_he)
+            .flat_map(|(rendering, rendering_segments)| {
+                rendering_segments
+                    .iter()
+                    .filter(|&&segment| !find_surface(&segments[segment].1, rendering).is_empty())
+                    .map(|&segment| TermFix { segment, replace: rendering.clone(), with: term.term_he.clone() })
+            })
+            .collect();
+
+        Some(TermConsistencyIssue {
+            kind,
+            source_term: term.term_ru.clone(),
+            preferred: term.term_he.clone(),
+            renderings,
+            occurrences,
+            message,
+            severity,
+            fixes,
+        })
+    }
+}
+
+fn is_hebrew(c: char) -> bool {
+    ('\u{05D0}'..='\u{05EA}').contains(&c)
+}
+
+fn is_letter(c: Option<char>) -> bool {
+    c.is_some_and(|c| c.is_alphabetic() || c == '\'' || c == '"' || c == '״')
+}
+
+/// מיקומי המונח בטקסט בכל צורות המשטח שלו (תחיליות ורבים), כמילה שלמה
+pub(crate) fn find_surface(text: &str, term: &str) -> Vec<(usize, usize, usize)> {
+    let mut variants: Vec<(usize, String)> = hebrew_surface_variants(term).into_iter().enumerate().collect();
+    variants.sort_by_key(|(_, variant)| std::cmp::Reverse(variant.len()));
+
+    let mut found = Vec::new();
+    let mut position = 0;
+    while position < text.len() {
+        let before = text[..position].chars().next_back();
+        let hit = (!is_letter(before))
+            .then(|| {
+                variants.iter().find(|(_, variant)| {
+                    text[position..].starts_with(variant.as_str())
+                        && !is_letter(text[position + variant.len()..].chars().next())
+                })
+            })
+            .flatten();
+
+        match hit {
+            Some((index, variant)) => {
+                found.push((position, position + variant.len(), *index));
+                position += variant.len();
+            }
+            None => position += text[position..].chars().next().map_or(1, char::len_utf8),
+        }
+    }
+    found
+}
+
+/// החלפת המונח במונח אחר תוך שמירה על התחילית ועל צורת הרבים
+fn replace_surface(text: &str, from: &str, to: &str) -> (String, usize) {
+    let replacements = hebrew_surface_variants(to);
+    let hits = find_surface(text, from);
+
+    let mut result = String::with_capacity(text.len());
+    let mut last = 0;
+    let mut count = 0;
+    for (start, end, index) in hits {
+        // כשאין צורת רבים מקבילה, נשארים עם אותה תחילית ביחיד
+        let Some(replacement) = replacements.get(index).or_else(|| replacements.get(index % replacements.len().max(1))) else {
+            continue;
+        };
+        result.push_str(&text[last..start]);
+        result.push_str(replacement);
+        last = end;
+        count += 1;
+    }
+    result.push_str(&text[last..]);
+    (result, count)
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+
+    /// מונח לבדיקות של מודולי בקרת האיכות
+    pub(crate) fn term(term_ru: &str, term_he: &str, synonyms: &[&str]) -> TechnicalTerm {
+        TechnicalTerm {
+            term_he: term_he.to_string(),
+            term_ru: term_ru.to_string(),
+            domain: "fire_protection".to_string(),
+            context: String::new(),
+            examples: Vec::new(),
+            synonyms: synonyms.iter().map(|s| s.to_string()).collect(),
+            source: String::new(),
+            confidence: 1.0,
+        }
+    }
+
+    fn checker() -> TerminologyChecker {
+        let mut terms = TermsDatabase::new();
+        terms.add_term(term("задвижка", "מגוף", &["שסתום"]));
+        terms.add_term(term("пожарный кран", "ברז כיבוי", &[]));
+        TerminologyChecker::new(Arc::new(terms))
+    }
+
+    fn document() -> Vec<(String, String)> {
+        vec![
+            ("Закрыть задвижку.".to_string(), "לסגור את המגוף.".to_string()),
+            ("Задвижка открыта.".to_string(), "השסתום פתוח.".to_string()),
+            ("Проверить задвижки и пожарный кран.".to_string(), "לבדוק את השסתומים ואת ברז הכיבוי.".to_string()),
+        ]
+    }
+
+    #[test]
+    fn test_reports_inconsistent_rendering() {
+        let issues = checker().check_document(&document());
+
+        let valve = issues.iter().find(|i| i.source_term == "задвижка").unwrap();
+        assert_eq!(valve.kind, TermIssueKind::Inconsistent);
+        assert_eq!(valve.renderings["מגוף"], vec![0]);
+        assert_eq!(valve.renderings["שסתום"], vec![1, 2]);
+        assert_eq!(valve.occurrences.len(), 3);
+
+        // "ברז הכיבוי" אינו צורת משטח של "ברז כיבוי", ולכן אין לו תרגום מוכר
+        let hydrant = issues.iter().find(|i| i.source_term == "пожарный кран").unwrap();
+        assert_eq!(hydrant.kind, TermIssueKind::Missing);
+    }
+
+    #[test]
+    fn test_glossary_deviation_and_known_variants() {
+        let mut checker = checker();
+        checker.add_known_variant("задвижка", "ברז");
+        let segments = vec![
+            ("Задвижка открыта.".to_string(), "הברז פתוח.".to_string()),
+            ("Закрыть задвижку.".to_string(), "לסגור את הברז.".to_string()),
+        ];
+
+        let issues = checker.check_document(&segments);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind, TermIssueKind::GlossaryDeviation);
+        assert_eq!(issues[0].renderings["ברז"], vec![0, 1]);
+    }
+
+    #[test]
+    fn test_normalization_keeps_prefixes_and_plural() {
+        let segments = document();
+        let issues = checker().check_document(&segments);
+        let valve = issues.iter().find(|i| i.source_term == "задвижка").unwrap();
+
+        let mut targets: Vec<String> = segments.into_iter().map(|(_, target)| target).collect();
+        let applied = TerminologyChecker::apply_fixes(&mut targets, &valve.fixes);
+
+        assert_eq!(applied, 2);
+        assert_eq!(targets[1], "המגוף פתוח.");
+        assert_eq!(targets[2], "לבדוק את המגופים ואת ברז הכיבוי.");
+    }
+}
@@ -0,0 +1,366 @@
+//! נרמול טיפוגרפי לפי שפת היעד: גרש וגרשיים בראשי תיבות, מקף עברי, מירכאות לפי CLDR
+//! (”…” בעברית, «…» ו-„…“ ברוסית) ורווח בלתי שביר בין מספר ליחידה.
+//! ההחלטות תלויות הקשר: " בין אותיות עבריות הוא גרשיים, אבל אחרי אות שימוש (ב"מגוף") הוא מירכאה.
+//! תגיות פורמט (<a href="...">) אינן טקסט ונשארות כמות שהן
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+use crate::neural::alignment::INLINE_TAG;
+use super::numbers::UNITS;
+use super::rules::{RuleCategory, RuleEngine, RuleFinding};
+use super::Severity;
+
+lazy_static! {
+    /// מספר, רווח רגיל ויחידה; התו שאחרי היחידה נבדק בנפרד כדי לא לתפוס תחילת מילה
+    static ref NUMBER_UNIT: Regex = {
+        let units: Vec<String> = UNITS.iter().map(|(form, _)| regex::escape(form)).collect();
+        Regex::new(&format!(r"\d([ \t]+)(?:{})", units.join("|"))).unwrap()
+    };
+
+    static ref SIGN_NUMBER: Regex = Regex::new(r"[№§]([ \t]+)\d").unwrap();
+}
+
+/// אותיות השימוש שיכולות לבוא לפני מירכאה פותחת
+const HEBREW_PREFIX_LETTERS: &[char] = &['ו', 'ה', 'ב', 'ל', 'מ', 'ש', 'כ'];
+
+const NO_BREAK_SPACE: &str = "\u{00A0}";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Locale {
+    Hebrew,
+    Russian,
+}
+
+impl Locale {
+    /// השפה לפי רוב האותיות בטקסט; None כשאין בו אותיות עבריות או קיריליות
+    pub fn detect(text: &str) -> Option<Self> {
+        let hebrew = text.chars().filter(|c| is_hebrew(*c)).count();
+        let cyrillic = text.chars().filter(|c| is_cyrillic(*c)).count();
+        match (hebrew, cyrillic) {
+            (0, 0) => None,
+            (h, c) if h >= c => Some(Locale::Hebrew),
+            _ => Some(Locale::Russian),
+        }
+    }
+
+    fn opening_quote(self, depth: usize) -> char {
+        match (self, depth) {
+            (Locale::Hebrew, 0) => '”',
+            (Locale::Hebrew, _) => '’',
+            (Locale::Russian, 0) => '«',
+            (Locale::Russian, _) => '„',
+        }
+    }
+
+    fn closing_quote(self, depth: usize) -> char {
+        match (self, depth) {
+            (Locale::Hebrew, 0) => '”',
+            (Locale::Hebrew, _) => '’',
+            (Locale::Russian, 0) => '»',
+            (Locale::Russian, _) => '“',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TypographyFixKind {
+    Gershayim,
+    Geresh,
+    Maqaf,
+    Quotation,
+    NoBreakSpace,
+}
+
+impl TypographyFixKind {
+    fn rule_id(self) -> &'static str {
+        match self {
+            TypographyFixKind::Gershayim => "typography-gershayim",
+            TypographyFixKind::Geresh => "typography-geresh",
+            TypographyFixKind::Maqaf => "typography-maqaf",
+            TypographyFixKind::Quotation => "typography-quotation",
+            TypographyFixKind::NoBreakSpace => "typography-no-break-space",
+        }
+    }
+
+    fn message(self) -> &'static str {
+        match self {
+            TypographyFixKind::Gershayim => "בראשי תיבות יש להשתמש בגרשיים (״) ולא במירכאות",
+            TypographyFixKind::Geresh => "בקיצור או בהגייה זרה יש להשתמש בגרש (׳) ולא בגרש עילי",
+            TypographyFixKind::Maqaf => "בין אות שימוש או מילים צמודות יש להשתמש במקף עברי (־)",
+            TypographyFixKind::Quotation => "סגנון המירכאות אינו מתאים לשפה",
+            TypographyFixKind::NoBreakSpace => "בין מספר ליחידה צריך רווח בלתי שביר",
+        }
+    }
+}
+
+/// החלפה אחת בטקסט המקורי, לפי טווח בתים
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TypographyFix {
+    pub kind: TypographyFixKind,
+    pub span: (usize, usize),
+    pub original: String,
+    pub replacement: String,
+}
+
+impl TypographyFix {
+    /// ממצא סגנון, כדי שהתיקון יופיע בדוח ה-QA לצד ממצאי הכללים
+    pub fn to_finding(&self) -> RuleFinding {
+        RuleFinding {
+            rule_id: self.kind.rule_id().to_string(),
+            category: RuleCategory::Style,
+            severity: match self.kind {
+                TypographyFixKind::NoBreakSpace => Severity::Info,
+                _ => Severity::Minor,
+            },
+            message: self.kind.message().to_string(),
+            span: Some(self.span),
+            matched: self.original.clone(),
+            replacement: Some(self.replacement.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Typography {
+    pub locale: Locale,
+    /// רווח בלתי שביר בין מספר ליחידה ואחרי № ו-§
+    pub no_break_spaces: bool,
+}
+
+impl Typography {
+    pub fn new(locale: Locale) -> Self {
+        Self {
+            locale,
+            no_break_spaces: true,
+        }
+    }
+
+    /// נרמול לפי השפה שזוהתה בטקסט; טקסט בלי עברית או רוסית חוזר כמות שהוא
+    pub fn for_text(text: &str) -> Option<Self> {
+        Locale::detect(text).map(Self::new)
+    }
+
+    pub fn with_no_break_spaces(mut self, enabled: bool) -> Self {
+        self.no_break_spaces = enabled;
+        self
+    }
+
+    /// כל ההחלפות הנדרשות, ממוינות לפי מיקום ובלי חפיפות; שום החלפה אינה נוגעת בתגית
+    pub fn fixes(&self, text: &str) -> Vec<TypographyFix> {
+        let tags: Vec<(usize, usize)> = INLINE_TAG.find_iter(text).map(|tag| (tag.start(), tag.end())).collect();
+        let mut fixes = self.character_fixes(text, &tags);
+        if self.no_break_spaces {
+            fixes.extend(
+                no_break_space_fixes(text)
+                    .into_iter()
+                    .filter(|fix| !tags.iter().any(|&(start, end)| fix.span.0 < end && start < fix.span.1)),
+            );
+        }
+        fixes.sort_by_key(|fix| fix.span.0);
+        fixes
+    }
+
+    pub fn findings(&self, text: &str) -> Vec<RuleFinding> {
+        self.fixes(text).iter().map(TypographyFix::to_finding).collect()
+    }
+
+    pub fn normalize(&self, text: &str) -> String {
+        RuleEngine::apply_fixes(text, &self.findings(text))
+    }
+
+    /// התווים שבתוך תגיות מדולגים, כך שההקשר של כל תו הוא הטקסט הגלוי שמסביבו
+    fn character_fixes(&self, text: &str, tags: &[(usize, usize)]) -> Vec<TypographyFix> {
+        let chars: Vec<(usize, char)> = text
+            .char_indices()
+            .filter(|(position, _)| !tags.iter().any(|&(start, end)| (start..end).contains(position)))
+            .collect();
+        let mut fixes = Vec::new();
+        // עומק המירכאות הכפולות הפתוחות, ומירכאה בודדת פתוחה בעברית שסוגרה נראה כמו גרש
+        let mut depth = 0usize;
+        let mut single_open = false;
+
+        for (i, &(position, c)) in chars.iter().enumerate() {
+            let prev = i.checked_sub(1).map(|j| chars[j].1);
+            let next = chars.get(i + 1).map(|&(_, c)| c);
+            let hebrew = self.locale == Locale::Hebrew;
+            let mut replace = |kind, replacement: char| {
+                if replacement != c {
+                    fixes.push(TypographyFix {
+                        kind,
+                        span: (position, position + c.len_utf8()),
+                        original: c.to_string(),
+                        replacement: replacement.to_string(),
+                    });
+                }
+            };
+
+            match c {
+                '"' | '“' | '”' | '„' | '«' | '»' | '״' => {
+                    let between_letters = prev.is_some_and(is_hebrew) && next.is_some_and(is_hebrew);
+                    if hebrew && (c == '״' || (between_letters && !opens_after_prefix(&chars, i))) {
+                        replace(TypographyFixKind::Gershayim, '״');
+                        continue;
+                    }
+                    if c == '״' {
+                        continue;
+                    }
+
+                    let opening = match c {
+                        '«' | '„' => true,
+                        '»' => false,
+                        _ => opens_quotation(prev, next) || (hebrew && between_letters),
+                    };
+                    if opening {
+                        replace(TypographyFixKind::Quotation, self.locale.opening_quote(depth));
+                        depth += 1;
+                    } else {
+                        depth = depth.saturating_sub(1);
+                        replace(TypographyFixKind::Quotation, self.locale.closing_quote(depth));
+                    }
+                }
+                '\'' | '’' | '׳' if hebrew => {
+                    if c == '׳' {
+                        continue;
+                    }
+                    if prev.is_some_and(is_hebrew) {
+                        if single_open && !next.is_some_and(char::is_alphanumeric) {
+                            single_open = false;
+                        } else {
+                            replace(TypographyFixKind::Geresh, '׳');
+                        }
+                    } else if opens_quotation(prev, next) {
+                        single_open = true;
+                    }
+                }
+                '-' if hebrew && prev.is_some_and(is_hebrew) && next.is_some_and(char::is_alphanumeric) => {
+                    replace(TypographyFixKind::Maqaf, '־');
+                }
+                _ => {}
+            }
+        }
+        fixes
+    }
+}
+
+/// " אחרי אות שימוש בתחילת מילה ולפני מילה שלמה (ב"מגוף") הוא מירכאה ולא גרשיים;
+/// בראשי תיבות הגרשיים באים לפני האות האחרונה (צה"ל, מ"מ)
+fn opens_after_prefix(chars: &[(usize, char)], index: usize) -> bool {
+    let letters_after = chars[index + 1..].iter().take_while(|(_, c)| is_hebrew(*c)).count();
+    if letters_after < 2 {
+        return false;
+    }
+    let prefix: Vec<char> = chars[..index]
+        .iter()
+        .rev()
+        .map(|&(_, c)| c)
+        .take_while(|c| is_hebrew(*c))
+        .collect();
+    prefix.len() <= 3 && prefix.iter().all(|c| HEBREW_PREFIX_LETTERS.contains(c))
+}
+
+/// מירכאה פותחת באה בתחילת הטקסט או אחרי רווח או סוגר, וצמודה לטקסט שאחריה
+fn opens_quotation(prev: Option<char>, next: Option<char>) -> bool {
+    let after_boundary = prev.is_none_or(|c| c.is_whitespace() || "([{—–-/".contains(c));
+    after_boundary && next.is_some_and(|c| !c.is_whitespace())
+}
+
+fn no_break_space_fixes(text: &str) -> Vec<TypographyFix> {
+    let number_unit = NUMBER_UNIT.captures_iter(text).filter(|captures| {
+        // היחידה צריכה להסתיים בגבול מילה: "5 метров" אינו "5 м"
+        let end = captures.get(0).unwrap().end();
+        !text[end..].chars().next().is_some_and(char::is_alphanumeric)
+    });
+    number_unit
+        .chain(SIGN_NUMBER.captures_iter(text))
+        .map(|captures| {
+            let space = captures.get(1).unwrap();
+            TypographyFix {
+                kind: TypographyFixKind::NoBreakSpace,
+                span: (space.start(), space.end()),
+                original: space.as_str().to_string(),
+                replacement: NO_BREAK_SPACE.to_string(),
+            }
+        })
+        .collect()
+}
+
+fn is_hebrew(c: char) -> bool {
+    ('\u{05D0}'..='\u{05EA}').contains(&c)
+}
+
+fn is_cyrillic(c: char) -> bool {
+    ('\u{0400}'..='\u{04FF}').contains(&c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hebrew(text: &str) -> String {
+        Typography::new(Locale::Hebrew).normalize(text)
+    }
+
+    fn russian(text: &str) -> String {
+        Typography::new(Locale::Russian).normalize(text)
+    }
+
+    #[test]
+    fn test_hebrew_acronyms_geresh_and_maqaf() {
+        assert_eq!(hebrew("צינור של 100 מ\"מ"), "צינור של 100\u{00A0}מ״מ");
+        assert_eq!(hebrew("בגובה 1.35 מ' מהרצפה"), "בגובה 1.35\u{00A0}מ׳ מהרצפה");
+        assert_eq!(hebrew("מערכת צ'יפס של צה\"ל"), "מערכת צ׳יפס של צה״ל");
+        assert_eq!(hebrew("ב-20 מקרים, תלת-ממדי"), "ב־20 מקרים, תלת־ממדי");
+        // מקף בין מספרים או עם רווחים נשאר
+        assert_eq!(hebrew("טווח 20-30 - לא פחות"), "טווח 20-30 - לא פחות");
+    }
+
+    #[test]
+    fn test_hebrew_quotation_marks() {
+        assert_eq!(hebrew("לחץ על \"אישור\" ואז «סגור»"), "לחץ על ”אישור” ואז ”סגור”");
+        // אות שימוש לפני מירכאה פותחת
+        assert_eq!(hebrew("ב\"מגוף הראשי\" של ה\"ספרינקלר\""), "ב”מגוף הראשי” של ה”ספרינקלר”");
+        // מירכאות בודדות סביב מילה אינן גרש
+        assert_eq!(hebrew("המילה 'מגוף' בתקן"), "המילה 'מגוף' בתקן");
+    }
+
+    #[test]
+    fn test_russian_quotes_and_units() {
+        assert_eq!(
+            russian("Кнопка \"Пуск\" и «режим \"авто\"»"),
+            "Кнопка «Пуск» и «режим „авто“»"
+        );
+        assert_eq!(russian("“Задвижка” на 1,6 МПа"), "«Задвижка» на 1,6\u{00A0}МПа");
+        assert_eq!(russian("пункт № 5, через 5 метров"), "пункт №\u{00A0}5, через 5 метров");
+    }
+
+    #[test]
+    fn test_normalization_is_idempotent_and_reported() {
+        let text = "ת\"י 1596: לחץ של 10 בר ב\"מגוף\"";
+        let typography = Typography::for_text(text).unwrap();
+        assert_eq!(typography.locale, Locale::Hebrew);
+
+        let normalized = typography.normalize(text);
+        assert_eq!(normalized, "ת״י 1596: לחץ של 10\u{00A0}בר ב”מגוף”");
+        assert!(typography.fixes(&normalized).is_empty());
+
+        let findings = typography.findings(text);
+        assert_eq!(findings.len(), 4);
+        assert!(findings.iter().all(|f| f.category == RuleCategory::Style));
+        assert_eq!(findings[0].rule_id, "typography-gershayim");
+    }
+
+    #[test]
+    fn test_inline_tags_are_left_untouched() {
+        assert_eq!(
+            hebrew("ראו <a href=\"https://x.co\">את \"התקן\"</a> של 10 מ\"מ"),
+            "ראו <a href=\"https://x.co\">את ”התקן”</a> של 10\u{00A0}מ״מ"
+        );
+        assert_eq!(
+            russian("<span class=\"x\">\"Пуск\"</span> <x id=\"1\"/>"),
+            "<span class=\"x\">«Пуск»</span> <x id=\"1\"/>"
+        );
+        // מירכאות סביב תגית פורמט נקבעות לפי הטקסט הגלוי
+        assert_eq!(hebrew("לחץ על \"<b>אישור</b>\""), "לחץ על ”<b>אישור</b>”");
+    }
+}
@@ -0,0 +1,459 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use serde::{Serialize, Deserialize};
+use crate::language_detection::Language;
+use crate::neural::alignment::WordAlignment;
+use crate::quality_control::numbers::{check_numbers, extract_quantities, NumberIssue};
+use crate::quality_control::terminology::find_surface;
+use crate::technical_terms::TermsDatabase;
+
+/// המנוע שממנו הגיע תרגום מועמד
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CandidateSource {
+    TranslationMemory,
+    Dictionary,
+    Rules,
+    Neural,
+}
+
+impl CandidateSource {
+    /// ביטחון ברירת מחדל כשהמנוע לא מחזיר הסתברות משלו
+    fn default_confidence(self) -> f64 {
+        match self {
+            CandidateSource::TranslationMemory => 1.0,
+            CandidateSource::Dictionary => 0.9,
+            CandidateSource::Rules => 0.7,
+            CandidateSource::Neural => 0.5,
+        }
+    }
+}
+
+/// תרגום מועמד לסגמנט, לפני הדירוג
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Candidate {
+    pub text: String,
+    pub source: CandidateSource,
+    /// הסתברות או ציון התאמה (0-1) שהמנוע עצמו נתן לתרגום
+    pub model_score: Option<f64>,
+    /// יישור מילים מקור-תרגום, למנועים שמחשבים אותו
+    #[serde(default)]
+    pub alignments: Vec<WordAlignment>,
+}
+
+impl Candidate {
+    pub fn new(text: impl Into<String>, source: CandidateSource) -> Self {
+        Self { text: text.into(), source, model_score: None, alignments: Vec::new() }
+    }
+
+    pub fn with_score(mut self, score: f64) -> Self {
+        self.model_score = Some(score);
+        self
+    }
+
+    pub fn with_alignments(mut self, alignments: Vec<WordAlignment>) -> Self {
+        self.alignments = alignments;
+        self
+    }
+}
+
+/// מאפייני הערכת איכות ללא תרגום ייחוס, כולם בטווח 0-1
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct QualityFeatures {
+    /// חלק המונחים המאושרים מהמקור שהתרגום המאושר שלהם מופיע במועמד
+    pub term_coverage: f64,
+    /// התאמת המספרים בין המקור למועמד
+    pub number_consistency: f64,
+    /// שטף לפי מודל שפה תווי של שפת היעד
+    pub fluency: f64,
+    /// הסתברות המודל או ציון ההתאמה של המנוע
+    pub model_probability: f64,
+    /// חלק האותיות שבכתב של שפת היעד מתוך האותיות העבריות והקיריליות; מקור שהוחזר כמות שהוא מקבל 0
+    #[serde(default)]
+    pub target_script: f64,
+}
+
+/// מועמד אחרי ניקוד, כפי שנשמר בסגמנט
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoredCandidate {
+    pub text: String,
+    pub source: CandidateSource,
+    pub features: QualityFeatures,
+    pub score: f64,
+    #[serde(default)]
+    pub alignments: Vec<WordAlignment>,
+}
+
+/// משקלות המאפיינים בציון המשולב
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CombinationWeights {
+    pub term_coverage: f64,
+    pub number_consistency: f64,
+    pub fluency: f64,
+    pub model_probability: f64,
+}
+
+impl Default for CombinationWeights {
+    fn default() -> Self {
+        Self {
+            term_coverage: 0.35,
+            number_consistency: 0.25,
+            fluency: 0.15,
+            model_probability: 0.25,
+        }
+    }
+}
+
+/// מודל שפה תווי (n-gram) עם החלקת add-one, לאומדן שטף של מועמדים
+#[derive(Debug, Clone)]
+pub struct CharNgramModel {
+    order: usize,
+    ngrams: HashMap<String, u32>,
+    contexts: HashMap<String, u32>,
+    alphabet: HashSet<char>,
+}
+
+impl CharNgramModel {
+    pub fn new(order: usize) -> Self {
+        Self {
+            order: order.max(1),
+            ngrams: HashMap::new(),
+            contexts: HashMap::new(),
+            alphabet: HashSet::new(),
+        }
+    }
+
+    pub fn train<I, S>(&mut self, texts: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for text in texts {
+            let chars = self.padded(text.as_ref());
+            for &c in &chars {
+                self.alphabet.insert(c);
+            }
+            for window in chars.windows(self.order) {
+                let context: String = window[..self.order - 1].iter().collect();
+                let ngram: String = window.iter().collect();
+                *self.contexts.entry(context).or_insert(0) += 1;
+                *self.ngrams.entry(ngram).or_insert(0) += 1;
+            }
+        }
+    }
+
+    pub fn is_trained(&self) -> bool {
+        !self.ngrams.is_empty()
+    }
+
+    /// ממוצע log-הסתברות לתו, מנורמל כך ש-0 הוא פילוג אחיד ו-1 הוא טקסט צפוי לחלוטין
+    pub fn fluency(&self, text: &str) -> f64 {
+        if !self.is_trained() || text.trim().is_empty() {
+            return 0.5;
+        }
+
+        // תו אחד נוסף לתווים שלא נראו באימון
+        let vocabulary = (self.alphabet.len() + 1) as f64;
+        let chars = self.padded(text);
+        let windows = chars.windows(self.order);
+        let count = windows.len().max(1) as f64;
+
+        let log_prob: f64 = windows
+            .map(|window| {
+                let context: String = window[..self.order - 1].iter().collect();
+                let ngram: String = window.iter().collect();
+                let numerator = self.ngrams.get(&ngram).copied().unwrap_or(0) as f64 + 1.0;
+                let denominator = self.contexts.get(&context).copied().unwrap_or(0) as f64 + vocabulary;
+                (numerator / denominator).ln()
+            })
+            .sum();
+
+        let uniform = (1.0 / vocabulary).ln();
+        ((log_prob / count - uniform) / -uniform).clamp(0.0, 1.0)
+    }
+
+    fn padded(&self, text: &str) -> Vec<char> {
+        let mut chars = vec!['\u{2}'; self.order - 1];
+        chars.extend(text.to_lowercase().chars());
+        chars.push('\u{3}');
+        chars
+    }
+}
+
+/// בחירת התרגום הטוב ביותר לסגמנט מתוך המועמדים של כל המנועים
+pub struct SystemCombiner {
+    weights: CombinationWeights,
+    terms: Option<Arc<TermsDatabase>>,
+    language_model: CharNgramModel,
+}
+
+impl SystemCombiner {
+    pub fn new(weights: CombinationWeights) -> Self {
+        Self {
+            weights,
+            terms: None,
+            language_model: CharNgramModel::new(3),
+        }
+    }
+
+    pub fn with_terms(mut self, terms: Arc<TermsDatabase>) -> Self {
+        self.terms = Some(terms);
+        self
+    }
+
+    pub fn with_language_model(mut self, language_model: CharNgramModel) -> Self {
+        self.language_model = language_model;
+        self
+    }
+
+    pub fn language_model_mut(&mut self) -> &mut CharNgramModel {
+        &mut self.language_model
+    }
+
+    /// ניקוד ודירוג המועמדים מהטוב לגרוע; מועמדים זהים מתמזגים לטובת המנוע עם הציון הגבוה
+    pub fn rank(&self, source: &str, target_language: Language, candidates: Vec<Candidate>) -> Vec<ScoredCandidate> {
+        let mut ranked: Vec<ScoredCandidate> = Vec::new();
+
+        for candidate in candidates {
+            if candidate.text.trim().is_empty() {
+                continue;
+            }
+
+            let features = self.features(source, target_language, &candidate);
+            let mut scored = ScoredCandidate {
+                score: self.combine(&features),
+                text: candidate.text,
+                source: candidate.source,
+                features,
+                alignments: candidate.alignments,
+            };
+
+            // לטקסט זהה היישור תקף בלי קשר למנוע שניצח
+            match ranked.iter_mut().find(|existing| existing.text == scored.text) {
+                Some(existing) if existing.score < scored.score => {
+                    if scored.alignments.is_empty() {
+                        scored.alignments = std::mem::take(&mut existing.alignments);
+                    }
+                    *existing = scored;
+                }
+                Some(existing) => {
+                    if existing.alignments.is_empty() {
+                        existing.alignments = scored.alignments;
+                    }
+                }
+                None => ranked.push(scored),
+            }
+        }
+
+        ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+        ranked
+    }
+
+    pub fn features(&self, source: &str, target_language: Language, candidate: &Candidate) -> QualityFeatures {
+        QualityFeatures {
+            term_coverage: self.term_coverage(source, target_language, &candidate.text),
+            number_consistency: number_consistency(source, &candidate.text),
+            fluency: self.language_model.fluency(&candidate.text),
+            model_probability: candidate
+                .model_score
+                .unwrap_or_else(|| candidate.source.default_confidence())
+                .clamp(0.0, 1.0),
+            target_script: target_script(target_language, &candidate.text),
+        }
+    }
+
+    /// ממוצע משוקלל של המאפיינים, מוכפל בחלק הטקסט שבכתב היעד: מועמד שלא תורגם אינו יכול לנצח
+    fn combine(&self, features: &QualityFeatures) -> f64 {
+        let weights = &self.weights;
+        let total = weights.term_coverage + weights.number_consistency + weights.fluency + weights.model_probability;
+        if total <= 0.0 {
+            return features.model_probability * features.target_script;
+        }
+
+        (weights.term_coverage * features.term_coverage
+            + weights.number_consistency * features.number_consistency
+            + weights.fluency * features.fluency
+            + weights.model_probability * features.model_probability)
+            / total
+            * features.target_script
+    }
+
+    fn term_coverage(&self, source: &str, target_language: Language, candidate: &str) -> f64 {
+        let Some(terms) = &self.terms else {
+            return 1.0;
+        };
+
+        // במקור הרוסי המונחים מזוהים גם בצורתם הנטויה ("задвижку")
+        let mut source_terms: Vec<_> = match target_language {
+            Language::Hebrew => terms.find_russian_term_forms(source).into_iter().map(|(term, _)| term).collect(),
+            _ => terms.find_terms_in_text(source),
+        };
+        let mut seen = HashSet::new();
+        source_terms.retain(|term| seen.insert((term.term_ru.clone(), term.term_he.clone())));
+        if source_terms.is_empty() {
+            return 1.0;
+        }
+
+        let lowercase = candidate.to_lowercase();
+        let covered = source_terms
+            .iter()
+            .filter(|term| match target_language {
+                // המונח העברי נספר גם עם תחיליות (ו, ה, ב, ל) ובצורת הרבים
+                Language::Hebrew => !find_surface(candidate, &term.term_he).is_empty(),
+                _ => lowercase.contains(&term.term_ru.to_lowercase()),
+            })
+            .count();
+
+        covered as f64 / source_terms.len() as f64
+    }
+}
+
+impl Default for SystemCombiner {
+    fn default() -> Self {
+        Self::new(CombinationWeights::default())
+    }
+}
+
+/// יחס הערכים (מספרים, טווחים, קודי תקנים) שתואמים בין המקור למועמד, לפי בדיקת המספרים של בקרת האיכות.
+/// יחידה שהושמטה נספרת כחצי התאמה
+fn number_consistency(source: &str, candidate: &str) -> f64 {
+    let source_count = extract_quantities(source).len();
+    let total = source_count.max(extract_quantities(candidate).len());
+    if total == 0 {
+        return 1.0;
+    }
+
+    let mismatched: f64 = check_numbers(source, candidate)
+        .iter()
+        .map(|validation| match validation.issue {
+            NumberIssue::Missing | NumberIssue::AlteredValue | NumberIssue::AlteredUnit => 1.0,
+            NumberIssue::MissingUnit => 0.5,
+            NumberIssue::Extra => 0.0,
+        })
+        .sum();
+
+    ((source_count as f64 - mismatched) / total as f64).clamp(0.0, 1.0)
+}
+
+/// חלק האותיות בכתב של שפת היעד; אותיות לטיניות (קודי תקנים, יחידות) אינן נספרות
+fn target_script(target_language: Language, text: &str) -> f64 {
+    let is_hebrew = |c: char| ('\u{0590}'..='\u{05FF}').contains(&c);
+    let is_cyrillic = |c: char| ('\u{0400}'..='\u{04FF}').contains(&c);
+
+    let (target, other) = text.chars().filter(|c| c.is_alphabetic()).fold((0usize, 0usize), |(target, other), c| {
+        match target_language {
+            Language::Hebrew if is_hebrew(c) => (target + 1, other),
+            Language::Hebrew if is_cyrillic(c) => (target, other + 1),
+            Language::Russian if is_cyrillic(c) => (target + 1, other),
+            Language::Russian if is_hebrew(c) => (target, other + 1),
+            _ => (target, other),
+        }
+    });
+
+    if target + other == 0 {
+        1.0
+    } else {
+        target as f64 / (target + other) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::technical_terms::TechnicalTerm;
+
+    fn terms() -> Arc<TermsDatabase> {
+        let mut terms = TermsDatabase::new();
+        terms.add_term(TechnicalTerm {
+            term_he: "משאבה".to_string(),
+            term_ru: "насос".to_string(),
+            domain: "mechanical".to_string(),
+            context: String::new(),
+            examples: Vec::new(),
+            synonyms: Vec::new(),
+            source: "test".to_string(),
+            confidence: 1.0,
+        });
+        Arc::new(terms)
+    }
+
+    #[test]
+    fn test_number_consistency() {
+        assert_eq!(number_consistency("Давление 2,5 бар", "לחץ 2.5 בר"), 1.0);
+        assert_eq!(number_consistency("Клапан 12 и 40", "שסתום 12"), 0.5);
+        assert_eq!(number_consistency("Без чисел", "ללא מספרים"), 1.0);
+        assert_eq!(number_consistency("Давление 16 бар", "לחץ 16 מטר"), 0.0);
+        assert_eq!(number_consistency("по ГОСТ 12.1.004", "לפי GOST 12.1.004"), 1.0);
+    }
+
+    #[test]
+    fn test_inflected_terms_are_covered() {
+        let combiner = SystemCombiner::default().with_terms(terms());
+        let coverage = |text: &str| {
+            let candidate = Candidate::new(text, CandidateSource::Neural);
+            combiner.features("Включить насосы", Language::Hebrew, &candidate).term_coverage
+        };
+
+        assert_eq!(coverage("יש להפעיל את המשאבות"), 1.0);
+        assert_eq!(coverage("להפעיל ולבדוק במשאבה"), 1.0);
+        assert_eq!(coverage("יש להפעיל את המנוע"), 0.0);
+    }
+
+    #[test]
+    fn test_untranslated_echo_never_wins() {
+        let combiner = SystemCombiner::default();
+        let source = "Закрыть клапан при 6 бар";
+
+        for probability in [None, Some(0.6)] {
+            let mut neural = Candidate::new("לסגור את המגוף בלחץ 6 בר", CandidateSource::Neural);
+            neural.model_score = probability;
+            let ranked = combiner.rank(source, Language::Hebrew, vec![
+                Candidate::new(source, CandidateSource::Rules),
+                neural,
+            ]);
+
+            assert_eq!(ranked[0].source, CandidateSource::Neural);
+            assert_eq!(ranked[1].features.target_script, 0.0);
+            assert_eq!(ranked[1].score, 0.0);
+        }
+    }
+
+    #[test]
+    fn test_language_model_prefers_seen_text() {
+        let mut model = CharNgramModel::new(3);
+        model.train(["המשאבה פועלת בלחץ גבוה", "המשאבה כבויה", "הלחץ במערכת גבוה"]);
+
+        assert!(model.fluency("המשאבה פועלת") > model.fluency("zzqx qqxz"));
+    }
+
+    #[test]
+    fn test_rank_prefers_term_and_number_consistent_candidate() {
+        let combiner = SystemCombiner::default().with_terms(terms());
+        let source = "Насос работает при 40 бар";
+
+        let ranked = combiner.rank(source, Language::Hebrew, vec![
+            Candidate::new("המשאבה פועלת ב-40 בר", CandidateSource::Neural).with_score(0.6),
+            Candidate::new("המשאבה פועלת ב-400 בר", CandidateSource::Rules),
+            Candidate::new("המנוע פועל ב-40 בר", CandidateSource::Dictionary),
+        ]);
+
+        assert_eq!(ranked.len(), 3);
+        assert_eq!(ranked[0].source, CandidateSource::Neural);
+        assert_eq!(ranked[0].features.term_coverage, 1.0);
+        assert!(ranked.windows(2).all(|pair| pair[0].score >= pair[1].score));
+    }
+
+    #[test]
+    fn test_identical_candidates_are_merged() {
+        let combiner = SystemCombiner::default();
+
+        let ranked = combiner.rank("Тест", Language::Hebrew, vec![
+            Candidate::new("בדיקה", CandidateSource::Rules),
+            Candidate::new("בדיקה", CandidateSource::TranslationMemory),
+            Candidate::new("  ", CandidateSource::Neural),
+        ]);
+
+        assert_eq!(ranked.len(), 1);
+        assert_eq!(ranked[0].source, CandidateSource::TranslationMemory);
+    }
+}
@@ -2,8 +2,9 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use regex::Regex;
 use lazy_static::lazy_static;
+use crate::morphology::utils::russian_stem;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TechnicalTerm {
     pub term_he: String,
     pub term_ru: String,
@@ -41,6 +42,11 @@ impl TermsDatabase {
         self.terms.get(term)
     }
 
+    /// כל המונחים במאגר, כל מונח פעם אחת (המאגר שומר אותם גם לפי המפתח העברי וגם לפי הרוסי)
+    pub fn terms(&self) -> impl Iterator<Item = &TechnicalTerm> {
+        self.terms.iter().filter(|(key, term)| **key == term.term_he).map(|(_, term)| term)
+    }
+
     pub fn find_terms_in_text(&self, text: &str) -> Vec<TechnicalTerm> {
         let mut found_terms = Vec::new();
         let words: Vec<&str> = text.split_whitespace().collect();
@@ -79,6 +85,65 @@ impl TermsDatabase {
         found_terms
     }
 
+    /// איתור מונחים רוסיים מאושרים בטקסט מקור, מהביטוי הארוך ביותר לקצר, ללא חפיפות
+    pub fn find_russian_terms(&self, text: &str) -> Vec<TechnicalTerm> {
+        let mut found_terms: Vec<TechnicalTerm> = Vec::new();
+        for (term, _) in self.scan_russian_terms(text, |word| word.to_string()) {
+            if !found_terms.iter().any(|t| t.term_ru == term.term_ru) {
+                found_terms.push(term.clone());
+            }
+        }
+        found_terms
+    }
+
+    /// כמו find_russian_terms, אך משווה גזעים כדי לזהות גם צורות נטויות ("задвижку", "задвижкой");
+    /// מחזיר כל הופעה עם הצורה שנמצאה בטקסט
+    pub fn find_russian_term_forms(&self, text: &str) -> Vec<(TechnicalTerm, String)> {
+        self.scan_russian_terms(text, russian_stem)
+            .into_iter()
+            .map(|(term, form)| (term.clone(), form))
+            .collect()
+    }
+
+    /// סריקת חלונות של עד חמש מילים, מהארוך לקצר וללא חפיפות, מול אינדקס של המונחים לפי מפתח ההשוואה
+    /// של כל מילה. כשכמה מונחים חולקים מפתח, נבחר הראשון לפי (term_ru, term_he), כך שהתוצאה אינה תלויה
+    /// בסדר ה-HashMap. ההופעות מוחזרות לפי מיקומן בטקסט
+    fn scan_russian_terms(&self, text: &str, key: impl Fn(&str) -> String) -> Vec<(&TechnicalTerm, String)> {
+        let words: Vec<String> = text
+            .split_whitespace()
+            .map(|w| w.trim_matches(|c: char| c.is_ascii_punctuation() || c == '«' || c == '»').to_lowercase())
+            .collect();
+        let keys: Vec<String> = words.iter().map(|w| key(w.as_str())).collect();
+
+        let mut candidates: Vec<&TechnicalTerm> = self.terms.values().filter(|t| !t.term_ru.trim().is_empty()).collect();
+        candidates.sort_by(|a, b| a.term_ru.cmp(&b.term_ru).then_with(|| a.term_he.cmp(&b.term_he)));
+        let mut index: HashMap<Vec<String>, &TechnicalTerm> = HashMap::new();
+        for term in candidates {
+            let term_key = term.term_ru.to_lowercase().split_whitespace().map(|w| key(w)).collect();
+            index.entry(term_key).or_insert(term);
+        }
+
+        let mut covered = vec![false; words.len()];
+        let mut found = Vec::new();
+        for window_size in (1..=5).rev() {
+            if window_size > words.len() {
+                continue;
+            }
+            for start in 0..=words.len() - window_size {
+                if covered[start..start + window_size].iter().any(|&c| c) {
+                    continue;
+                }
+                if let Some(&term) = index.get(&keys[start..start + window_size]) {
+                    covered[start..start + window_size].iter_mut().for_each(|c| *c = true);
+                    found.push((start, term, words[start..start + window_size].join(" ")));
+                }
+            }
+        }
+
+        found.sort_by_key(|(start, _, _)| *start);
+        found.into_iter().map(|(_, term, form)| (term, form)).collect()
+    }
+
     pub fn suggest_translations(&self, term: &str, context: &str) -> Vec<String> {
         let mut suggestions = Vec::new();
         
@@ -242,4 +307,51 @@ impl TermsDatabase {
             )
         })
     }
-} 
\ No newline at end of file
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn term(term_ru: &str, term_he: &str) -> TechnicalTerm {
+        TechnicalTerm {
+            term_he: term_he.to_string(),
+            term_ru: term_ru.to_string(),
+            domain: "fire_protection".to_string(),
+            context: String::new(),
+            examples: Vec::new(),
+            synonyms: Vec::new(),
+            source: String::new(),
+            confidence: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_shared_russian_key_resolves_deterministically() {
+        for _ in 0..20 {
+            let mut terms = TermsDatabase::new();
+            terms.add_term(term("задвижка", "שיבר"));
+            terms.add_term(term("задвижка", "מגוף"));
+            terms.add_term(term("задвижки", "מגופים"));
+
+            let forms = terms.find_russian_term_forms("Закрыть задвижку и пожарный кран");
+            assert_eq!(forms.len(), 1);
+            assert_eq!((forms[0].0.term_he.as_str(), forms[0].1.as_str()), ("מגוף", "задвижку"));
+
+            let exact = terms.find_russian_terms("Проверить задвижка.");
+            assert_eq!(exact.len(), 1);
+            assert_eq!(exact[0].term_he, "מגוף");
+        }
+    }
+
+    #[test]
+    fn test_longest_phrase_wins_without_overlap() {
+        let mut terms = TermsDatabase::new();
+        terms.add_term(term("кран", "ברז"));
+        terms.add_term(term("пожарный кран", "ברז כיבוי"));
+
+        let forms = terms.find_russian_term_forms("Открыть пожарные краны, затем кран");
+        let found: Vec<_> = forms.iter().map(|(term, form)| (term.term_he.as_str(), form.as_str())).collect();
+        assert_eq!(found, vec![("ברז כיבוי", "пожарные краны"), ("ברז", "кран")]);
+    }
+}
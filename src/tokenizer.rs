@@ -1,20 +1,72 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use serde::{Serialize, Deserialize};
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// סמן רווח בתחילת מילה (כמו ב-SentencePiece), מאפשר שחזור מדויק של הרווחים
+pub const SPACE_MARKER: char = '▁';
+
+type SymbolPair = (String, String);
+
+/// קטע בטקסט אחרי הפירוק הראשוני
+enum Segment {
+    /// מילה עם סמן הרווח שלפניה, לפירוק לתת-מילים
+    Word(String),
+    /// טקסט שמקודד תמיד כבתים: סמן רווח שמופיע בטקסט עצמו, ומחרוזות של טוקנים מיוחדים או טוקני בתים,
+    /// כדי שלא יתפרשו בפענוח כרווח או כטוקן מיוחד
+    Literal(String),
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "TokenizerFile")]
 pub struct Tokenizer {
-    vocab: HashMap<String, i64>,
+    vocab: BTreeMap<String, i64>,
+    #[serde(skip)]
     reverse_vocab: HashMap<i64, String>,
+    /// כללי המיזוג של BPE לפי סדר הלמידה (הסדר הוא הדירוג)
+    merges: Vec<(String, String)>,
+    #[serde(skip)]
+    merge_ranks: HashMap<(String, String), usize>,
     special_tokens: SpecialTokens,
+    config: TokenizerConfig,
     next_id: i64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// הצורה השמורה של הטוקנייזר; האינדקסים ההפוכים נבנים מחדש בטעינה
+#[derive(Deserialize)]
+struct TokenizerFile {
+    vocab: BTreeMap<String, i64>,
+    merges: Vec<(String, String)>,
+    special_tokens: SpecialTokens,
+    config: TokenizerConfig,
+    next_id: i64,
+}
+
+impl From<TokenizerFile> for Tokenizer {
+    fn from(file: TokenizerFile) -> Self {
+        let mut tokenizer = Self {
+            vocab: file.vocab,
+            reverse_vocab: HashMap::new(),
+            merges: file.merges,
+            merge_ranks: HashMap::new(),
+            special_tokens: file.special_tokens,
+            config: file.config,
+            next_id: file.next_id,
+        };
+        tokenizer.rebuild_indexes();
+        tokenizer
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SpecialTokens {
     pub pad: String,
     pub bos: String,
     pub eos: String,
     pub unk: String,
+    /// טוקנים שמורים נוספים (למשל סימון מונחים), מקבלים מזהים קבועים מיד אחרי הבסיסיים
+    #[serde(default)]
+    pub reserved: Vec<String>,
 }
 
 impl Default for SpecialTokens {
@@ -24,28 +76,97 @@ impl Default for SpecialTokens {
             bos: "<BOS>".to_string(),
             eos: "<EOS>".to_string(),
             unk: "<UNK>".to_string(),
+            reserved: Vec::new(),
+        }
+    }
+}
+
+impl SpecialTokens {
+    pub fn all(&self) -> Vec<&str> {
+        let mut tokens = vec![self.pad.as_str(), self.bos.as_str(), self.eos.as_str(), self.unk.as_str()];
+        tokens.extend(self.reserved.iter().map(|t| t.as_str()));
+        tokens
+    }
+}
+
+/// צורת נרמול Unicode שמוחלת על הטקסט לפני הפירוק
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Normalization {
+    None,
+    Nfc,
+    Nfkc,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenizerConfig {
+    /// גודל אוצר המילים הרצוי, כולל טוקנים מיוחדים
+    pub vocab_size: usize,
+    pub normalization: Normalization,
+    /// ייצוג תווים שלא נראו באימון כבתים, כך שאין אובדן מידע
+    pub byte_fallback: bool,
+}
+
+impl Default for TokenizerConfig {
+    fn default() -> Self {
+        Self {
+            vocab_size: 32000,
+            normalization: Normalization::Nfc,
+            byte_fallback: true,
         }
     }
 }
 
 impl Tokenizer {
     pub fn new() -> Self {
+        Self::with_config(TokenizerConfig::default(), SpecialTokens::default())
+    }
+
+    pub fn with_config(config: TokenizerConfig, special_tokens: SpecialTokens) -> Self {
         let mut tokenizer = Self {
-            vocab: HashMap::new(),
+            vocab: BTreeMap::new(),
             reverse_vocab: HashMap::new(),
-            special_tokens: SpecialTokens::default(),
+            merges: Vec::new(),
+            merge_ranks: HashMap::new(),
+            special_tokens,
+            config,
             next_id: 0,
         };
-        
-        // הוספת טוקנים מיוחדים
-        tokenizer.add_token(&tokenizer.special_tokens.pad);
-        tokenizer.add_token(&tokenizer.special_tokens.bos);
-        tokenizer.add_token(&tokenizer.special_tokens.eos);
-        tokenizer.add_token(&tokenizer.special_tokens.unk);
-        
+        tokenizer.reset_vocab();
         tokenizer
     }
-    
+
+    /// טוקנים מיוחדים וטוקני בתים תמיד מקבלים את אותם מזהים
+    fn reset_vocab(&mut self) {
+        self.vocab.clear();
+        self.reverse_vocab.clear();
+        self.merges.clear();
+        self.merge_ranks.clear();
+        self.next_id = 0;
+
+        let specials: Vec<String> = self.special_tokens.all().into_iter().map(String::from).collect();
+        for token in &specials {
+            self.add_token(token);
+        }
+        if self.config.byte_fallback {
+            for byte in 0..=255u8 {
+                self.add_token(&Self::byte_token(byte));
+            }
+            // סמן הרווח לבדו, כדי שמילה שלא נראתה תקודד כבתים בלי לאבד את הרווח שלפניה
+            self.add_token(&SPACE_MARKER.to_string());
+        }
+    }
+
+    fn byte_token(byte: u8) -> String {
+        format!("<0x{:02X}>", byte)
+    }
+
+    fn parse_byte_token(token: &str) -> Option<u8> {
+        token.strip_prefix("<0x")
+            .and_then(|t| t.strip_suffix('>'))
+            .filter(|hex| hex.len() == 2)
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+    }
+
     pub fn add_token(&mut self, token: &str) -> i64 {
         if let Some(&id) = self.vocab.get(token) {
             id
@@ -57,141 +178,394 @@ impl Tokenizer {
             id
         }
     }
-    
+
+    pub fn vocab_size(&self) -> usize {
+        self.vocab.len()
+    }
+
+    pub fn token_id(&self, token: &str) -> Option<i64> {
+        self.vocab.get(token).copied()
+    }
+
+    pub fn token(&self, id: i64) -> Option<&str> {
+        self.reverse_vocab.get(&id).map(|t| t.as_str())
+    }
+
+    pub fn special_tokens(&self) -> &SpecialTokens {
+        &self.special_tokens
+    }
+
+    pub fn config(&self) -> &TokenizerConfig {
+        &self.config
+    }
+
+    /// כללי המיזוג לפי סדר הלמידה
+    pub fn merges(&self) -> &[(String, String)] {
+        &self.merges
+    }
+
+    pub fn bos_id(&self) -> i64 {
+        self.vocab[&self.special_tokens.bos]
+    }
+
+    pub fn eos_id(&self) -> i64 {
+        self.vocab[&self.special_tokens.eos]
+    }
+
+    pub fn pad_id(&self) -> i64 {
+        self.vocab[&self.special_tokens.pad]
+    }
+
+    pub fn unk_id(&self) -> i64 {
+        self.vocab[&self.special_tokens.unk]
+    }
+
+    /// קידוד מילה אחר מילה: המזהים (עם BOS ו-EOS) ולכל מזהה אינדקס המילה שממנה בא.
+    /// טוקן שמור שמופיע כמילה שלמה (למשל מפריד הקשר) מקבל את המזהה שלו במקום פירוק לבתים, ו-None כמו BOS ו-EOS
+    pub fn encode_words(&self, text: &str) -> (Vec<i64>, Vec<Option<usize>>) {
+        let mut ids = vec![self.bos_id()];
+        let mut words = vec![None];
+
+        for (index, word) in text.split_whitespace().enumerate() {
+            if self.special_tokens.reserved.iter().any(|reserved| reserved == word) {
+                ids.push(self.vocab[word]);
+                words.push(None);
+                continue;
+            }
+            let encoded = match index {
+                0 => self.tokenize(word),
+                _ => self.tokenize(&format!(" {}", word)),
+            };
+            let pieces = &encoded[1..encoded.len() - 1];
+            ids.extend_from_slice(pieces);
+            words.extend(std::iter::repeat(Some(index)).take(pieces.len()));
+        }
+
+        ids.push(self.eos_id());
+        words.push(None);
+        (ids, words)
+    }
+
+    /// אינדקס המילה של כל מזהה ברצף מפוענח: טוקן שמתחיל בסמן רווח פותח מילה חדשה, וטוקנים מיוחדים מקבלים None
+    pub fn word_ids(&self, ids: &[i64]) -> Vec<Option<usize>> {
+        let specials = self.special_tokens.all();
+        let mut current: Option<usize> = None;
+
+        ids.iter()
+            .map(|id| {
+                let token = self.reverse_vocab.get(id)?;
+                if specials.contains(&token.as_str()) {
+                    return None;
+                }
+                if token.starts_with(SPACE_MARKER) || current.is_none() {
+                    current = Some(current.map_or(0, |word| word + 1));
+                }
+                current
+            })
+            .collect()
+    }
+
+    /// בנייה מחדש מאוצר מילים שמור, שבו המזהה של כל טוקן הוא מיקומו ברשימה
+    pub fn from_parts(
+        tokens: &[String],
+        merges: Vec<(String, String)>,
+        special_tokens: SpecialTokens,
+        config: TokenizerConfig,
+    ) -> Self {
+        Self::from(TokenizerFile {
+            vocab: tokens.iter().enumerate().map(|(id, token)| (token.clone(), id as i64)).collect(),
+            merges,
+            special_tokens,
+            config,
+            next_id: tokens.len() as i64,
+        })
+    }
+
+    pub fn normalize(&self, text: &str) -> String {
+        match self.config.normalization {
+            Normalization::None => text.to_string(),
+            Normalization::Nfc => text.nfc().collect(),
+            Normalization::Nfkc => text.nfkc().collect(),
+        }
+    }
+
+    /// פירוק לקטעים: כל רווח הופך לסמן שפותח מילה חדשה, סמן רווח ומחרוזות טוקנים מיוחדים
+    /// שמופיעים בטקסט עצמו הופכים לקטעים מילוליים, ושאר התווים נשמרים כמות שהם
+    fn pre_tokenize(&self, text: &str) -> Vec<Segment> {
+        let text = self.normalize(text);
+        let specials = self.special_tokens.all();
+        let mut segments = Vec::new();
+        let mut current = String::new();
+        let flush = |current: &mut String, segments: &mut Vec<Segment>| {
+            if !current.is_empty() {
+                segments.push(Segment::Word(std::mem::take(current)));
+            }
+        };
+
+        let mut rest = text.as_str();
+        while let Some(c) = rest.chars().next() {
+            let literal = specials
+                .iter()
+                .find(|special| !special.is_empty() && rest.starts_with(*special))
+                .map(|special| special.len())
+                .or_else(|| rest.get(..6).filter(|t| Self::parse_byte_token(t).is_some()).map(str::len))
+                .or_else(|| (c == SPACE_MARKER).then(|| c.len_utf8()));
+
+            match literal {
+                Some(len) => {
+                    flush(&mut current, &mut segments);
+                    segments.push(Segment::Literal(rest[..len].to_string()));
+                    rest = &rest[len..];
+                    continue;
+                }
+                None if c == ' ' => {
+                    flush(&mut current, &mut segments);
+                    current.push(SPACE_MARKER);
+                }
+                None => current.push(c),
+            }
+            rest = &rest[c.len_utf8()..];
+        }
+        flush(&mut current, &mut segments);
+
+        segments
+    }
+
+    /// פירוק מילה ליחידות בסיס: אשכולות גרפמות, כך שניקוד עברי נשאר צמוד לאות
+    fn graphemes(word: &str) -> Vec<String> {
+        word.graphemes(true).map(String::from).collect()
+    }
+
     pub fn tokenize(&self, text: &str) -> Vec<i64> {
         let mut tokens = Vec::new();
-        
+
         // הוספת טוקן התחלה
         tokens.push(self.vocab[&self.special_tokens.bos]);
-        
-        // פיצול הטקסט למילים
-        for word in text.split_whitespace() {
-            // טיפול במילים מורכבות
-            let subwords = self.split_to_subwords(word);
-            
-            for subword in subwords {
-                let token_id = self.vocab
-                    .get(&subword)
-                    .copied()
-                    .unwrap_or_else(|| self.vocab[&self.special_tokens.unk]);
-                    
-                tokens.push(token_id);
-            }
-        }
-        
+
+        for segment in self.pre_tokenize(text) {
+            match segment {
+                Segment::Word(word) => {
+                    for piece in self.split_to_subwords(&word) {
+                        self.push_piece(&mut tokens, &piece);
+                    }
+                }
+                Segment::Literal(literal) => self.push_bytes(&mut tokens, &literal),
+            }
+        }
+
         // הוספת טוקן סיום
         tokens.push(self.vocab[&self.special_tokens.eos]);
-        
+
         tokens
     }
-    
+
+    fn push_piece(&self, tokens: &mut Vec<i64>, piece: &str) {
+        if let Some(&id) = self.vocab.get(piece) {
+            tokens.push(id);
+            return;
+        }
+        match piece.strip_prefix(SPACE_MARKER) {
+            Some(rest) if self.config.byte_fallback => {
+                tokens.push(self.vocab[&SPACE_MARKER.to_string()]);
+                self.push_bytes(tokens, rest);
+            }
+            _ => self.push_bytes(tokens, piece),
+        }
+    }
+
+    /// קידוד כבתים, או טוקן לא-ידוע כשאין byte fallback
+    fn push_bytes(&self, tokens: &mut Vec<i64>, text: &str) {
+        if self.config.byte_fallback {
+            tokens.extend(text.bytes().map(|b| self.vocab[&Self::byte_token(b)]));
+        } else if !text.is_empty() {
+            tokens.push(self.vocab[&self.special_tokens.unk]);
+        }
+    }
+
+    /// פירוק הטקסט לתת-מילים (מחרוזות) לפי כללי המיזוג שנלמדו; קטעים מילוליים מוחזרים כמות שהם
+    pub fn encode_pieces(&self, text: &str) -> Vec<String> {
+        self.pre_tokenize(text)
+            .into_iter()
+            .flat_map(|segment| match segment {
+                Segment::Word(word) => self.split_to_subwords(&word),
+                Segment::Literal(literal) => vec![literal],
+            })
+            .collect()
+    }
+
     pub fn detokenize(&self, tokens: &[i64]) -> String {
-        let mut text = String::new();
-        let mut is_first = true;
-        
+        let mut bytes = Vec::new();
+        let specials = self.special_tokens.all();
+
         for &token_id in tokens {
+            let token = match self.reverse_vocab.get(&token_id) {
+                Some(token) => token,
+                None => &self.special_tokens.unk,
+            };
+
             // דילוג על טוקנים מיוחדים
-            if token_id == self.vocab[&self.special_tokens.bos] ||
-               token_id == self.vocab[&self.special_tokens.eos] ||
-               token_id == self.vocab[&self.special_tokens.pad] {
+            if specials.contains(&token.as_str()) {
                 continue;
             }
-            
-            let token = self.reverse_vocab
-                .get(&token_id)
-                .unwrap_or(&self.special_tokens.unk);
-                
-            // הוספת רווח בין מילים
-            if !is_first && !token.starts_with("##") {
-                text.push(' ');
-            }
-            
-            // הסרת סימון תת-מילה
-            let clean_token = token.trim_start_matches("##");
-            text.push_str(clean_token);
-            
-            is_first = false;
-        }
-        
-        text
-    }
-    
+
+            // סמן בתוך טוקן רגיל הוא רווח; סמן שקודד כבתים הוא תו מהטקסט עצמו
+            match Self::parse_byte_token(token) {
+                Some(byte) => bytes.push(byte),
+                None => bytes.extend_from_slice(token.replace(SPACE_MARKER, " ").as_bytes()),
+            }
+        }
+
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+
     fn split_to_subwords(&self, word: &str) -> Vec<String> {
-        let mut subwords = Vec::new();
-        let mut start = 0;
-        
-        while start < word.len() {
-            let mut end = word.len();
-            let mut found = false;
-            
-            // חיפוש תת-מילה הארוכה ביותר שקיימת באוצר המילים
-            while end > start && !found {
-                let subword = if start == 0 {
-                    word[start..end].to_string()
-                } else {
-                    format!("##{}", &word[start..end])
-                };
-                
-                if self.vocab.contains_key(&subword) {
-                    subwords.push(subword);
-                    found = true;
-                } else {
-                    end -= 1;
+        let mut symbols = Self::graphemes(word);
+
+        // מיזוג חוזר של הזוג בעל הדירוג הנמוך ביותר
+        loop {
+            let best = symbols.windows(2)
+                .enumerate()
+                .filter_map(|(i, pair)| {
+                    self.merge_ranks
+                        .get(&(pair[0].clone(), pair[1].clone()))
+                        .map(|&rank| (rank, i))
+                })
+                .min();
+
+            match best {
+                Some((_, i)) => {
+                    let merged = format!("{}{}", symbols[i], symbols[i + 1]);
+                    symbols.splice(i..i + 2, std::iter::once(merged));
                 }
+                None => break,
             }
-            
-            // אם לא נמצאה תת-מילה, נוסיף תו בודד
-            if !found {
-                let subword = if start == 0 {
-                    word[start..start+1].to_string()
-                } else {
-                    format!("##{}", &word[start..start+1])
-                };
-                subwords.push(subword);
-                end = start + 1;
-            }
-            
-            start = end;
         }
-        
-        subwords
+
+        symbols
     }
-    
+
+    /// אימון מודל BPE: אלפבית של גרפמות ולאחר מכן מיזוגים עד לגודל אוצר המילים הרצוי
     pub fn train<I>(&mut self, texts: I, min_freq: usize)
     where
         I: IntoIterator<Item = String>
     {
-        let mut token_counts = HashMap::new();
-        
-        // ספירת תדירויות של תת-מילים
+        self.reset_vocab();
+
+        let mut word_counts: HashMap<String, usize> = HashMap::new();
         for text in texts {
-            for word in text.split_whitespace() {
-                let subwords = self.split_to_subwords(word);
-                for subword in subwords {
-                    *token_counts.entry(subword).or_insert(0) += 1;
+            for segment in self.pre_tokenize(&text) {
+                if let Segment::Word(word) = segment {
+                    *word_counts.entry(word).or_insert(0) += 1;
                 }
             }
         }
-        
-        // הוספת תת-מילים שמופיעות מספיק פעמים
-        for (token, count) in token_counts {
-            if count >= min_freq {
-                self.add_token(&token);
+
+        // סדר קבוע של המילים מבטיח אימון דטרמיניסטי
+        let mut words: Vec<(Vec<String>, usize)> = word_counts
+            .into_iter()
+            .map(|(word, count)| (Self::graphemes(&word), count))
+            .collect();
+        words.sort();
+
+        // אלפבית הבסיס
+        let mut symbol_counts: BTreeMap<String, usize> = BTreeMap::new();
+        for (symbols, count) in &words {
+            for symbol in symbols {
+                *symbol_counts.entry(symbol.clone()).or_insert(0) += count;
             }
         }
+        let mut alphabet: Vec<(String, usize)> = symbol_counts
+            .into_iter()
+            .filter(|(_, count)| *count >= min_freq)
+            .collect();
+        alphabet.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        for (symbol, _) in alphabet {
+            if self.vocab.len() >= self.config.vocab_size {
+                break;
+            }
+            self.add_token(&symbol);
+        }
+
+        // ספירת הזוגות פעם אחת; אחרי כל מיזוג מתעדכנות רק המילים שמכילות את הזוג
+        let mut pair_counts: HashMap<SymbolPair, usize> = HashMap::new();
+        let mut pair_words: HashMap<SymbolPair, BTreeSet<usize>> = HashMap::new();
+        for (index, (symbols, count)) in words.iter().enumerate() {
+            Self::count_pairs(&mut pair_counts, &mut pair_words, symbols, *count as isize, index);
+        }
+
+        // למידת מיזוגים
+        while self.vocab.len() < self.config.vocab_size {
+            let best = pair_counts
+                .iter()
+                .filter(|(_, &count)| count >= min_freq.max(1))
+                .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+                .map(|(pair, _)| pair.clone());
+
+            let Some(pair) = best else {
+                break;
+            };
+
+            let merged = format!("{}{}", pair.0, pair.1);
+            for index in pair_words.remove(&pair).unwrap_or_default() {
+                let (symbols, count) = &mut words[index];
+                Self::count_pairs(&mut pair_counts, &mut pair_words, symbols, -(*count as isize), index);
+                let mut i = 0;
+                while i + 1 < symbols.len() {
+                    if symbols[i] == pair.0 && symbols[i + 1] == pair.1 {
+                        symbols.splice(i..i + 2, std::iter::once(merged.clone()));
+                    }
+                    i += 1;
+                }
+                Self::count_pairs(&mut pair_counts, &mut pair_words, symbols, *count as isize, index);
+            }
+
+            self.merge_ranks.insert(pair.clone(), self.merges.len());
+            self.merges.push(pair);
+            self.add_token(&merged);
+        }
     }
-    
+
+    /// הוספת (או הפחתה, כש-delta שלילי) של הזוגות הסמוכים במילה לספירה, ורישום המילה באינדקס הזוגות
+    fn count_pairs(
+        pair_counts: &mut HashMap<SymbolPair, usize>,
+        pair_words: &mut HashMap<SymbolPair, BTreeSet<usize>>,
+        symbols: &[String],
+        delta: isize,
+        index: usize,
+    ) {
+        for pair in symbols.windows(2) {
+            let key = (pair[0].clone(), pair[1].clone());
+            let count = pair_counts.entry(key.clone()).or_insert(0);
+            *count = count.saturating_add_signed(delta);
+            if *count == 0 {
+                pair_counts.remove(&key);
+            } else if delta > 0 {
+                pair_words.entry(key).or_default().insert(index);
+            }
+        }
+    }
+
     pub fn save(&self, path: &str) -> Result<(), TokenizerError> {
         let file = std::fs::File::create(path)?;
         serde_json::to_writer_pretty(file, self)?;
         Ok(())
     }
-    
+
     pub fn load(path: &str) -> Result<Self, TokenizerError> {
         let file = std::fs::File::open(path)?;
         let tokenizer = serde_json::from_reader(file)?;
         Ok(tokenizer)
     }
+
+    /// שחזור האינדקסים שאינם נשמרים לקובץ
+    fn rebuild_indexes(&mut self) {
+        self.reverse_vocab = self.vocab.iter().map(|(token, &id)| (id, token.clone())).collect();
+        self.merge_ranks = self.merges
+            .iter()
+            .enumerate()
+            .map(|(rank, pair)| (pair.clone(), rank))
+            .collect();
+    }
 }
 
 #[derive(Debug)]
@@ -221,4 +595,151 @@ impl std::fmt::Display for TokenizerError {
     }
 }
 
-impl std::error::Error for TokenizerError {} 
\ No newline at end of file
+impl std::error::Error for TokenizerError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn corpus() -> Vec<String> {
+        vec![
+            "מערכת ספרינקלרים לפי ГОСТ 12.2.047".to_string(),
+            "система спринклерного пожаротушения".to_string(),
+            "התקנת מערכת כיבוי אש אוטומטית".to_string(),
+            "система автоматического пожаротушения".to_string(),
+        ]
+    }
+
+    fn trained(vocab_size: usize) -> Tokenizer {
+        let config = TokenizerConfig { vocab_size, ..Default::default() };
+        let mut tokenizer = Tokenizer::with_config(config, SpecialTokens::default());
+        tokenizer.train(corpus(), 1);
+        tokenizer
+    }
+
+    #[test]
+    fn test_encode_words_maps_pieces_to_words() {
+        let special_tokens = SpecialTokens { reserved: vec!["<SEP>".to_string()], ..SpecialTokens::default() };
+        let mut tokenizer = Tokenizer::with_config(TokenizerConfig { vocab_size: 400, ..Default::default() }, special_tokens);
+        tokenizer.train(corpus(), 1);
+
+        let (ids, words) = tokenizer.encode_words("מערכת  ספרינקלרים <SEP> система");
+        assert_eq!(ids, {
+            let mut expected = tokenizer.tokenize("מערכת ספרינקלרים");
+            expected.pop();
+            expected.push(tokenizer.token_id("<SEP>").unwrap());
+            expected.extend(&tokenizer.tokenize(" система")[1..]);
+            expected
+        });
+        assert_eq!(words.first(), Some(&None));
+        assert_eq!(words.last(), Some(&None));
+        assert!(words.contains(&Some(1)) && !words.contains(&Some(2)) && words.contains(&Some(3)));
+
+        // בפלט מפוענח סמן הרווח קובע את גבולות המילים
+        let decoded = &tokenizer.tokenize("מערכת ספרינקלרים")[1..];
+        let decoded_words = tokenizer.word_ids(decoded);
+        assert_eq!(decoded_words.first(), Some(&Some(0)));
+        assert_eq!(decoded_words.iter().flatten().max(), Some(&1));
+        assert_eq!(decoded_words.last(), Some(&None));
+    }
+
+    #[test]
+    fn test_round_trip_mixed_script() {
+        let tokenizer = trained(400);
+        let text = "מערכת  כיבוי\tсистема 12.2 — בְּרֵאשִׁית 🔥 unseen";
+        let tokens = tokenizer.tokenize(text);
+        assert_eq!(tokenizer.detokenize(&tokens), tokenizer.normalize(text));
+    }
+
+    #[test]
+    fn test_untrained_tokenizer_does_not_panic_on_multibyte() {
+        let tokenizer = Tokenizer::new();
+        let text = "пожар בשריפה";
+        assert_eq!(tokenizer.detokenize(&tokenizer.tokenize(text)), text);
+    }
+
+    #[test]
+    fn test_merges_reach_target_size() {
+        let tokenizer = trained(320);
+        assert!(tokenizer.vocab_size() <= 320);
+        assert!(tokenizer.encode_pieces("система").len() < "система".chars().count());
+    }
+
+    #[test]
+    fn test_special_tokens_have_fixed_ids() {
+        let config = TokenizerConfig { vocab_size: 300, ..Default::default() };
+        let special = SpecialTokens { reserved: vec!["<TERM>".to_string()], ..Default::default() };
+        let mut tokenizer = Tokenizer::with_config(config, special);
+        tokenizer.train(corpus(), 1);
+
+        assert_eq!(tokenizer.token_id("<PAD>"), Some(0));
+        assert_eq!(tokenizer.token_id("<UNK>"), Some(3));
+        assert_eq!(tokenizer.token_id("<TERM>"), Some(4));
+    }
+
+    /// הגרסה הישירה של לולאת המיזוגים: ספירה מחדש של כל הזוגות בכל צעד
+    fn merges_by_full_recount(tokenizer: &Tokenizer, vocab_size: usize) -> Vec<(String, String)> {
+        let mut word_counts: BTreeMap<Vec<String>, usize> = BTreeMap::new();
+        for text in corpus() {
+            for segment in tokenizer.pre_tokenize(&text) {
+                if let Segment::Word(word) = segment {
+                    *word_counts.entry(Tokenizer::graphemes(&word)).or_insert(0) += 1;
+                }
+            }
+        }
+        let mut words: Vec<(Vec<String>, usize)> = word_counts.into_iter().collect();
+        let mut size = tokenizer.vocab_size() - tokenizer.merges().len();
+
+        let mut merges = Vec::new();
+        while size < vocab_size {
+            let mut pair_counts: HashMap<(String, String), usize> = HashMap::new();
+            for (symbols, count) in &words {
+                for pair in symbols.windows(2) {
+                    *pair_counts.entry((pair[0].clone(), pair[1].clone())).or_insert(0) += count;
+                }
+            }
+            let Some((pair, _)) = pair_counts.into_iter().max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0))) else {
+                break;
+            };
+            for (symbols, _) in &mut words {
+                let mut i = 0;
+                while i + 1 < symbols.len() {
+                    if symbols[i] == pair.0 && symbols[i + 1] == pair.1 {
+                        symbols.splice(i..i + 2, std::iter::once(format!("{}{}", pair.0, pair.1)));
+                    }
+                    i += 1;
+                }
+            }
+            merges.push(pair);
+            size += 1;
+        }
+        merges
+    }
+
+    #[test]
+    fn test_incremental_counts_match_full_recount() {
+        let tokenizer = trained(420);
+        assert!(!tokenizer.merges().is_empty());
+        assert_eq!(tokenizer.merges(), merges_by_full_recount(&tokenizer, 420).as_slice());
+    }
+
+    #[test]
+    fn test_literal_marker_and_special_strings_round_trip() {
+        let text = "a ▁b <EOS> x<PAD>y <0x41> ▁▁  ▁";
+        for tokenizer in [trained(400), Tokenizer::new()] {
+            let tokens = tokenizer.tokenize(text);
+            assert_eq!(tokenizer.detokenize(&tokens), text);
+
+            // רק טוקן הסיום שהמקודד הוסיף הוא EOS
+            let eos = tokenizer.token_id("<EOS>").unwrap();
+            assert_eq!(tokens.iter().filter(|&&id| id == eos).count(), 1);
+        }
+    }
+
+    #[test]
+    fn test_deterministic_serialization() {
+        let first = serde_json::to_string(&trained(350)).unwrap();
+        let second = serde_json::to_string(&trained(350)).unwrap();
+        assert_eq!(first, second);
+    }
+}
@@ -1,13 +1,115 @@
+use crate::evaluation::calibration::ConfidenceCalibrator;
+use crate::evaluation::{FormalityLevel, StyleGuide};
 use crate::language_detection::Language;
+use crate::neural::TranslationBackend;
+use crate::neural::batching::BatchScheduler;
+use crate::neural::model_storage::ModelStorage;
+use crate::neural::vocabulary::VocabularyArtifact;
+use crate::neural::alignment::{project_inline_tags, strip_inline_tags, WordAlignment};
+use crate::neural::onnx::OnnxTranslator;
+use crate::neural::quantization::QuantizedTranslator;
+use crate::system_combination::{Candidate, CandidateSource, ScoredCandidate, SystemCombiner};
+use crate::technical_terms::TermsDatabase;
+use crate::translation_models::{
+    Domain, DomainModel, Formality, Style, StyleModel, TranslationContext, TranslationError,
+};
 use anyhow::{Result, anyhow};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use serde::{Serialize, Deserialize};
 
+/// המנוע שמשמש כיוון תרגום מסוים
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BackendConfig {
+    /// תרגום מבוסס כללים, זיכרון תרגום ומילון בלבד
+    Rules,
+    /// מודל מקודד-מפענח שיוצא ל-ONNX, מתיקייה מקומית
+    Onnx {
+        model_dir: PathBuf,
+        #[serde(default)]
+        intra_threads: Option<usize>,
+    },
+    /// מודל int8 שנשמר על ידי EnhancedTransformer::quantize
+    Quantized { model_path: PathBuf },
+    /// גרסת הייצור של מודל ב-ModelStorage, בגרסת ה-int8 שלה; נטענת רק אם אומנה עם אוצר המילים הנתון
+    Registry {
+        storage_dir: PathBuf,
+        name: String,
+        vocabulary_path: PathBuf,
+    },
+}
+
+impl Default for BackendConfig {
+    fn default() -> Self {
+        BackendConfig::Rules
+    }
+}
+
+/// תצורת המנוע: בחירת מנוע לכל כיוון תרגום, במפתח כמו "ru-he"
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EngineConfig {
+    #[serde(default)]
+    pub backends: HashMap<String, BackendConfig>,
+}
+
+impl EngineConfig {
+    /// קריאת התצורה מ-TOML או JSON, לפי הסיומת
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => Ok(toml::from_str(&content)?),
+            Some("json") => Ok(serde_json::from_str(&content)?),
+            _ => Err(anyhow!("סוג קובץ לא נתמך: {}", path.display())),
+        }
+    }
+
+    pub fn direction_key(source: Language, target: Language) -> String {
+        format!("{}-{}", language_code(source), language_code(target))
+    }
+
+    pub fn set_backend(&mut self, source: Language, target: Language, backend: BackendConfig) {
+        self.backends.insert(Self::direction_key(source, target), backend);
+    }
+
+    pub fn backend_for(&self, source: Language, target: Language) -> BackendConfig {
+        self.backends
+            .get(&Self::direction_key(source, target))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// טעינת גרסת הייצור דרך בדיקות הרישום: סכום הביקורת של המודל והתאמת אוצר המילים
+fn load_production_model(storage_dir: &Path, name: &str, vocabulary_path: &Path) -> Result<QuantizedTranslator> {
+    let storage = ModelStorage::new(storage_dir)?;
+    let (_, metadata) = storage
+        .load_production(name)?
+        .ok_or_else(|| anyhow!("למודל {} אין גרסת ייצור ב-{}", name, storage_dir.display()))?;
+    let vocabulary = VocabularyArtifact::load(vocabulary_path)?;
+    storage.load_model_with_vocabulary(name, &metadata.version, &vocabulary)?;
+
+    let (model, _) = storage.load_quantized(name, &metadata.version)?;
+    Ok(model)
+}
+
+fn language_code(language: Language) -> &'static str {
+    match language {
+        Language::Hebrew => "he",
+        Language::Russian => "ru",
+        Language::Unknown => "und",
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TranslationRequest {
     pub text: String,
     pub source_language: Language,
     pub target_language: Language,
+    /// תרגומים שהמשתמש קבע לסגמנטים מסוימים, לפי מיקום הסגמנט במסמך
+    #[serde(default)]
+    pub overrides: HashMap<usize, String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,9 +127,64 @@ pub struct TranslationResult {
 pub struct TranslationSegment {
     pub original: String,
     pub translated: String,
+    /// ביטחון מכויל: ההסתברות שהסגמנט יתקבל בלי עריכה מהותית, כשהמנוע כויל
     pub confidence: f64,
+    /// הציון של המנוע לפני הכיול; זה הערך שנרשם במנהל הלמידה לכיול הבא
+    #[serde(default)]
+    pub raw_confidence: f64,
     pub alternatives: Vec<String>,
     pub has_manual_edit: bool,
+    /// יישור מילים בין המקור לתרגום (אינדקסים של מילים מופרדות ברווח)
+    #[serde(default)]
+    pub alignments: Vec<WordAlignment>,
+    /// המנוע שממנו נבחר התרגום
+    #[serde(default)]
+    pub backend: Option<CandidateSource>,
+    /// שאר המועמדים, מדורגים מהטוב לגרוע, עם המנוע של כל אחד
+    #[serde(default)]
+    pub ranked_alternatives: Vec<ScoredCandidate>,
+}
+
+impl TranslationResult {
+    /// סגמנטים שהביטחון שלהם מתחת לסף ולא נערכו ידנית, לסינון הבדיקה בממשק
+    pub fn segments_for_review(&self, threshold: f64) -> impl Iterator<Item = (usize, &TranslationSegment)> {
+        self.segments
+            .iter()
+            .enumerate()
+            .filter(move |(_, segment)| !segment.has_manual_edit && segment.confidence < threshold)
+    }
+
+    /// הטקסט הסופי של כל סגמנט אחרי עריכת המסמך כולו. סגמנט שהתרגום שלו נמצא כמות שהוא משמש עוגן,
+    /// והטקסט שבין שני עוגנים מיוחס לסגמנטים שנערכו ביניהם
+    pub fn post_edits(&self, edited_text: &str) -> Vec<(&TranslationSegment, String)> {
+        let mut anchors: Vec<Option<(usize, usize)>> = Vec::with_capacity(self.segments.len());
+        let mut cursor = 0;
+        for segment in &self.segments {
+            let found = if segment.translated.is_empty() {
+                None
+            } else {
+                edited_text[cursor..].find(&segment.translated)
+            };
+            anchors.push(found.map(|offset| {
+                let start = cursor + offset;
+                cursor = start + segment.translated.len();
+                (start, cursor)
+            }));
+        }
+
+        self.segments
+            .iter()
+            .enumerate()
+            .map(|(index, segment)| {
+                let (start, end) = anchors[index].unwrap_or_else(|| {
+                    let start = anchors[..index].iter().rev().find_map(|a| a.map(|(_, end)| end)).unwrap_or(0);
+                    let end = anchors[index + 1..].iter().find_map(|a| a.map(|(start, _)| start)).unwrap_or(edited_text.len());
+                    (start, end)
+                });
+                (segment, edited_text[start..end].trim().to_string())
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -41,6 +198,21 @@ pub struct TranslationEngine {
     translation_memory: HashMap<String, String>,
     custom_dictionary: HashMap<String, String>,
     manual_edits: HashMap<String, String>,
+    neural_backends: HashMap<(Language, Language), Arc<dyn TranslationBackend>>,
+    /// כיוונים שהמודל שלהם רץ דרך מתזמן האצוות, משותף לכל הבקשות המקבילות
+    batch_schedulers: HashMap<(Language, Language), BatchScheduler>,
+    combiner: SystemCombiner,
+    domain_model: DomainModel,
+    style_model: StyleModel,
+    calibrator: Option<ConfidenceCalibrator>,
+    /// משלב הפרויקט: רמת הפורמליות וכללי התחום שלפיהם נבחרים הווריאנטים הלקסיקליים
+    style_guide: Option<StyleGuide>,
+    /// התחום של הפרויקט, אותו ערך שנמסר ל-QualityController::with_domain; בוחר את כללי התחום של StyleGuide
+    domain: Option<String>,
+    /// מספר הסגמנטים הקודמים שמועברים למודל כהקשר
+    context_window: usize,
+    /// המונחים המאושרים שהמודל הנוירוני חייב לשלב בתרגום מרוסית לעברית
+    terms: Option<Arc<TermsDatabase>>,
 }
 
 impl TranslationEngine {
@@ -49,22 +221,149 @@ impl TranslationEngine {
             translation_memory: HashMap::new(),
             custom_dictionary: Self::load_custom_dictionary(),
             manual_edits: HashMap::new(),
+            neural_backends: HashMap::new(),
+            batch_schedulers: HashMap::new(),
+            combiner: SystemCombiner::default(),
+            domain_model: DomainModel::new(),
+            style_model: StyleModel::new(),
+            calibrator: None,
+            style_guide: None,
+            domain: None,
+            context_window: 3,
+            terms: None,
+        }
+    }
+
+    /// יצירת מנוע עם טעינת המודלים שהוגדרו לכל כיוון; כיוון בלי מודל מתורגם בכללים בלבד
+    pub fn with_config(config: &EngineConfig) -> Result<Self> {
+        let mut engine = Self::new();
+
+        for (source, target) in [(Language::Russian, Language::Hebrew), (Language::Hebrew, Language::Russian)] {
+            let backend: Arc<dyn TranslationBackend> = match config.backend_for(source, target) {
+                BackendConfig::Rules => continue,
+                BackendConfig::Onnx { model_dir, intra_threads } => {
+                    Arc::new(OnnxTranslator::load_with_threads(&model_dir, intra_threads)?)
+                }
+                BackendConfig::Quantized { model_path } => Arc::new(QuantizedTranslator::load(&model_path)?),
+                BackendConfig::Registry { storage_dir, name, vocabulary_path } => {
+                    Arc::new(load_production_model(&storage_dir, &name, &vocabulary_path)?)
+                }
+            };
+            engine.register_backend(source, target, backend);
         }
+
+        Ok(engine)
+    }
+
+    pub fn with_context_window(mut self, context_window: usize) -> Self {
+        self.context_window = context_window;
+        self
+    }
+
+    /// רישום מנוע נוירוני לכיוון תרגום; הפלט שלו מתחרה במועמדים של שאר המנועים
+    pub fn register_backend(&mut self, source: Language, target: Language, backend: Arc<dyn TranslationBackend>) {
+        self.batch_schedulers.remove(&(source, target));
+        self.neural_backends.insert((source, target), backend);
+    }
+
+    /// המנוע הנוירוני שנרשם לכיוון, למשל לתרגום חוזר בכיוון ההפוך
+    pub fn backend(&self, source: Language, target: Language) -> Option<Arc<dyn TranslationBackend>> {
+        self.neural_backends.get(&(source, target)).cloned()
+    }
+
+    /// רישום מנוע נוירוני שרץ דרך מתזמן אצוות: סגמנטים מבקשות מקבילות מורצים יחד,
+    /// עם אותה אכיפת מונחים ואותו יישור מילים כמו במנוע שנרשם ישירות
+    pub fn register_batched_backend(&mut self, source: Language, target: Language, scheduler: BatchScheduler) {
+        self.neural_backends.remove(&(source, target));
+        self.batch_schedulers.insert((source, target), scheduler);
+    }
+
+    /// כיול ביטחון הסגמנטים לפי מנוע, מ-ConfidenceCalibrator::fit_from_events
+    pub fn with_calibrator(mut self, calibrator: ConfidenceCalibrator) -> Self {
+        self.calibrator = Some(calibrator);
+        self
+    }
+
+    pub fn with_style_guide(mut self, style_guide: StyleGuide) -> Self {
+        self.style_guide = Some(style_guide);
+        self
+    }
+
+    pub fn with_domain(mut self, domain: impl Into<String>) -> Self {
+        self.domain = Some(domain.into());
+        self
+    }
+
+    pub fn with_combiner(mut self, combiner: SystemCombiner) -> Self {
+        self.combiner = match &self.terms {
+            Some(terms) => combiner.with_terms(terms.clone()),
+            None => combiner,
+        };
+        self
+    }
+
+    /// מאגר המונחים שנאכף בפענוח של המודל הנוירוני ומשמש את המדרג לכיסוי מונחים
+    pub fn with_terms(mut self, terms: Arc<TermsDatabase>) -> Self {
+        self.combiner = std::mem::take(&mut self.combiner).with_terms(terms.clone());
+        self.terms = Some(terms);
+        self
     }
 
     pub async fn translate(&self, request: TranslationRequest) -> Result<TranslationResult> {
         let segments = self.split_into_segments(&request.text);
         let mut translated_segments = Vec::new();
+
+        // תחום וסגנון מזוהים פעם אחת לכל המסמך
+        let mut context = TranslationContext::detect(&request.text, &self.domain_model, &self.style_model)
+            .unwrap_or_else(|_| TranslationContext::new(Domain::General, Style::Formal, Formality::Medium));
+        // הפורמליות שהפרויקט הצהיר עליה גוברת על זו שזוהתה מהמקור
+        if let Some(style_guide) = &self.style_guide {
+            context.formality = match style_guide.formality_level() {
+                FormalityLevel::Formal => Formality::High,
+                FormalityLevel::SemiFormal => Formality::Medium,
+                FormalityLevel::Informal => Formality::Low,
+            };
+        }
+        let mut document_translations: HashMap<String, TranslationSegment> = HashMap::new();
         
-        for segment in segments {
-            let mut translated = self.translate_segment(&segment, &request)?;
-            
-            // בדיקה אם קיים תרגום ידני
-            if let Some(manual_edit) = self.manual_edits.get(&segment) {
-                translated.translated = manual_edit.clone();
+        for (index, segment) in segments.into_iter().enumerate() {
+            // תגיות פורמט מוסרות לפני התרגום ומוחזרות לפי יישור המילים
+            let (plain, tags) = strip_inline_tags(&segment);
+
+            // סגמנט מקור שחוזר באותו מסמך מקבל את אותו תרגום
+            let mut translated = match document_translations.get(&segment) {
+                Some(previous) => previous.clone(),
+                None => {
+                    let mut translated = self.translate_segment(&plain, &request, &context).await?;
+
+                    // בדיקה אם קיים תרגום ידני
+                    if let Some(manual_edit) = self.manual_edits.get(&segment) {
+                        translated.translated = manual_edit.clone();
+                        translated.has_manual_edit = true;
+                        translated.alignments.clear();
+                    } else if !tags.is_empty() {
+                        translated.translated = project_inline_tags(
+                            &translated.translated,
+                            &tags,
+                            plain.split_whitespace().count(),
+                            &translated.alignments,
+                        );
+                    }
+                    translated.original = segment.clone();
+                    translated
+                }
+            };
+
+            // תרגום שהמשתמש קבע למופע מסוים לא משנה את שאר המופעים
+            if let Some(override_text) = request.overrides.get(&index) {
+                translated.translated = override_text.clone();
                 translated.has_manual_edit = true;
+                translated.alignments.clear();
+            } else {
+                document_translations.entry(segment).or_insert_with(|| translated.clone());
             }
-            
+
+            context.push_segment(&plain, &translated.translated, self.context_window);
             translated_segments.push(translated);
         }
         
@@ -72,6 +371,11 @@ impl TranslationEngine {
             .map(|s| s.translated.clone())
             .collect::<Vec<_>>()
             .join(" ");
+        let status = if translated_segments.iter().any(|s| s.has_manual_edit) {
+            TranslationStatus::ManuallyEdited
+        } else {
+            TranslationStatus::Automatic
+        };
             
         Ok(TranslationResult {
             original_text: request.text,
@@ -80,11 +384,7 @@ impl TranslationEngine {
             target_language: request.target_language,
             segments: translated_segments,
             manual_edits: self.manual_edits.clone(),
-            status: if translated_segments.iter().any(|s| s.has_manual_edit) {
-                TranslationStatus::ManuallyEdited
-            } else {
-                TranslationStatus::Automatic
-            },
+            status,
         })
     }
     
@@ -92,6 +392,8 @@ impl TranslationEngine {
         if original.trim().is_empty() {
             return Err(anyhow!("Original text cannot be empty"));
         }
+        // תרגום שאושר ידנית הוא גם דוגמה לטקסט שוטף בשפת היעד
+        self.combiner.language_model_mut().train([edited.as_str()]);
         self.manual_edits.insert(original, edited);
         Ok(())
     }
@@ -124,44 +426,117 @@ impl TranslationEngine {
             .collect()
     }
     
-    fn translate_segment(&self, text: &str, request: &TranslationRequest) -> Result<TranslationSegment> {
-        // ניסיון למצוא תרגום בזיכרון התרגום
-        if let Some(cached) = self.translation_memory.get(text) {
-            return Ok(TranslationSegment {
-                original: text.to_string(),
-                translated: cached.clone(),
-                confidence: 1.0,
-                alternatives: self.get_translation_alternatives(text, 3),
-                has_manual_edit: false,
-            });
-        }
-        
-        // ניסיון למצוא תרגום במילון המותאם אישית
-        if let Some(custom) = self.custom_dictionary.get(text) {
-            return Ok(TranslationSegment {
-                original: text.to_string(),
-                translated: custom.clone(),
-                confidence: 0.9,
-                alternatives: self.get_translation_alternatives(text, 3),
-                has_manual_edit: false,
-            });
-        }
-        
-        // תרגום אוטומטי
-        let translated = match (request.source_language, request.target_language) {
-            (Language::Hebrew, Language::Russian) => self.translate_hebrew_to_russian(text)?,
-            (Language::Russian, Language::Hebrew) => self.translate_russian_to_hebrew(text)?,
-            _ => return Err(anyhow!("Unsupported language pair")),
+    async fn translate_segment(
+        &self,
+        text: &str,
+        request: &TranslationRequest,
+        context: &TranslationContext,
+    ) -> Result<TranslationSegment> {
+        let candidates = self.candidates(text, request, context).await?;
+        let mut ranked = self.combiner.rank(text, request.target_language, candidates).into_iter();
+        let best = ranked
+            .next()
+            .ok_or_else(|| anyhow!("No translation candidates for segment"))?;
+        let ranked_alternatives: Vec<ScoredCandidate> = ranked.collect();
+
+        let mut alternatives: Vec<String> = ranked_alternatives.iter().map(|c| c.text.clone()).collect();
+        alternatives.extend(self.generate_variations(text));
+        alternatives.truncate(3);
+
+        let confidence = match &self.calibrator {
+            Some(calibrator) => calibrator.calibrate(Some(best.source), best.score),
+            None => best.score,
         };
-        
+
+        // תרגום מזיכרון התרגום כבר אושר, ולכן אינו משוכתב
+        let translated = match &self.style_guide {
+            Some(style_guide) if best.source != CandidateSource::TranslationMemory => {
+                style_guide.apply_register(&best.text, request.target_language, self.domain.as_deref())
+            }
+            _ => best.text.clone(),
+        };
+        // היישור מתייחס למילים של המועמד; החלפה לקסיקלית מזיזה אותן
+        let alignments = if translated == best.text { best.alignments } else { Vec::new() };
+
         Ok(TranslationSegment {
             original: text.to_string(),
             translated,
-            confidence: 0.7,
-            alternatives: self.get_translation_alternatives(text, 3),
+            confidence,
+            raw_confidence: best.score,
+            alternatives,
             has_manual_edit: false,
+            alignments,
+            backend: Some(best.source),
+            ranked_alternatives,
         })
     }
+
+    /// מועמד מכל מנוע זמין: זיכרון תרגום, מילון מותאם, כללים ומודל נוירוני
+    async fn candidates(
+        &self,
+        text: &str,
+        request: &TranslationRequest,
+        context: &TranslationContext,
+    ) -> Result<Vec<Candidate>> {
+        let mut candidates = Vec::new();
+
+        if let Some(cached) = self.translation_memory.get(text) {
+            candidates.push(Candidate::new(cached.clone(), CandidateSource::TranslationMemory).with_score(1.0));
+        }
+
+        if let Some(custom) = self.custom_dictionary.get(text) {
+            candidates.push(Candidate::new(custom.clone(), CandidateSource::Dictionary));
+        }
+
+        let rules = match (request.source_language, request.target_language) {
+            (Language::Hebrew, Language::Russian) => Some(self.translate_hebrew_to_russian(text)?),
+            (Language::Russian, Language::Hebrew) => Some(self.translate_russian_to_hebrew(text)?),
+            _ => None,
+        };
+        if let Some(rules) = rules {
+            candidates.push(Candidate::new(rules, CandidateSource::Rules));
+        }
+
+        let direction = (request.source_language, request.target_language);
+        // המונחים נאכפים רק מרוסית לעברית, הכיוון שבו המאגר מזהה צורות נטויות במקור
+        let terms = self.terms.clone().filter(|_| direction == (Language::Russian, Language::Hebrew));
+        let neural = if let Some(scheduler) = self.batch_schedulers.get(&direction) {
+            Some(scheduler.translate_aligned(text, context, terms).await)
+        } else if let Some(backend) = self.neural_backends.get(&direction) {
+            // ההסקה (ONNX, int8 או tch) חוסמת, ולכן רצה מחוץ ל-executor
+            let backend = backend.clone();
+            let input = text.to_string();
+            let context = context.clone();
+            let result = tokio::task::spawn_blocking(move || match terms {
+                Some(terms) => backend.translate_constrained(&input, &context, &terms),
+                None => backend.translate_aligned(&input, &context),
+            })
+                .await
+                .unwrap_or_else(|e| Err(TranslationError::ModelError(format!("הרצת המנוע נכשלה: {}", e))));
+            Some(result)
+        } else {
+            None
+        };
+        if let Some(neural) = neural {
+            // כשל של המודל לא מפיל את הסגמנט; נשארים המועמדים של שאר המנועים
+            match neural {
+                Ok((output, probability)) => {
+                    let candidate = Candidate::new(output.text, CandidateSource::Neural)
+                        .with_alignments(output.alignments);
+                    candidates.push(match probability {
+                        Some(probability) => candidate.with_score(probability),
+                        None => candidate,
+                    });
+                }
+                Err(e) => log::warn!("המנוע הנוירוני נכשל בסגמנט: {}", e),
+            }
+        }
+
+        if candidates.is_empty() {
+            return Err(anyhow!("Unsupported language pair"));
+        }
+        Ok(candidates)
+    }
     
     fn translate_hebrew_to_russian(&self, text: &str) -> Result<String> {
         // כאן יש להוסיף את הלוגיקה של התרגום מעברית לרוסית
@@ -193,4 +568,353 @@ impl TranslationEngine {
         // כאן יש להוסיף את הלוגיקה של טעינת המילון המותאם אישית
         HashMap::new() // זמני
     }
-} 
\ No newline at end of file
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use crate::evaluation::calibration::CalibrationMap;
+    use crate::neural::AlignedTranslation;
+    use crate::neural::batching::BatchingConfig;
+    use crate::neural::testing::RecordingBackend;
+
+    /// מנוע בדיקה שמחזיר תרגום שונה בכל קריאה ורושם את ההקשר שקיבל
+    struct CountingBackend {
+        contexts: Mutex<Vec<usize>>,
+    }
+
+    impl TranslationBackend for CountingBackend {
+        fn translate(&self, input: &[String]) -> Result<Vec<String>, TranslationError> {
+            Ok(input.iter().map(|text| text.to_string()).collect())
+        }
+
+        fn translate_in_context(&self, input: &str, context: &TranslationContext) -> Result<(String, Option<f64>), TranslationError> {
+            let mut contexts = self.contexts.lock().unwrap();
+            contexts.push(context.previous_segments.len());
+            let marker = char::from_u32('א' as u32 + contexts.len() as u32 - 1).unwrap();
+            Ok((format!("תרגום {}", marker), Some(1.0)))
+        }
+    }
+
+    /// מנוע בדיקה שמחזיר תרגום קבוע עם הסתברות נתונה, או בלי הסתברות כמו מודל tch
+    struct FixedBackend {
+        output: &'static str,
+        probability: Option<f64>,
+    }
+
+    impl TranslationBackend for FixedBackend {
+        fn translate(&self, input: &[String]) -> Result<Vec<String>, TranslationError> {
+            Ok(input.iter().map(|_| self.output.to_string()).collect())
+        }
+
+        fn translate_in_context(&self, _input: &str, _context: &TranslationContext) -> Result<(String, Option<f64>), TranslationError> {
+            Ok((self.output.to_string(), self.probability))
+        }
+    }
+
+    fn engine() -> (TranslationEngine, Arc<CountingBackend>) {
+        let backend = Arc::new(CountingBackend { contexts: Mutex::new(Vec::new()) });
+        let mut engine = TranslationEngine::new().with_context_window(2);
+        engine.register_backend(Language::Russian, Language::Hebrew, backend.clone());
+        (engine, backend)
+    }
+
+    fn request(text: &str) -> TranslationRequest {
+        TranslationRequest {
+            text: text.to_string(),
+            source_language: Language::Russian,
+            target_language: Language::Hebrew,
+            overrides: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_repeated_segments_get_identical_translations() {
+        let (engine, backend) = engine();
+
+        let result = engine.translate(request("Закрыть клапан. Открыть насос. Закрыть клапан. Проверить давление.")).await.unwrap();
+
+        assert_eq!(result.segments[0].translated, result.segments[2].translated);
+        // הסגמנט החוזר לא נשלח שוב למודל, וחלון ההקשר מוגבל לשני סגמנטים
+        assert_eq!(*backend.contexts.lock().unwrap(), vec![0, 1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_override_applies_to_single_occurrence() {
+        let (engine, _) = engine();
+        let mut request = request("Закрыть клапан. Закрыть клапан. Закрыть клапан.");
+        request.overrides.insert(1, "לסגור את המגוף".to_string());
+
+        let result = engine.translate(request).await.unwrap();
+
+        assert_eq!(result.segments[1].translated, "לסגור את המגוף");
+        assert!(result.segments[1].has_manual_edit);
+        assert_eq!(result.segments[0].translated, result.segments[2].translated);
+        assert_eq!(result.status, TranslationStatus::ManuallyEdited);
+    }
+
+    /// מנוע בדיקה שמחליף את סדר שתי המילים ומחזיר את היישור המתאים
+    struct SwappingBackend;
+
+    impl TranslationBackend for SwappingBackend {
+        fn translate(&self, input: &[String]) -> Result<Vec<String>, TranslationError> {
+            Ok(input.to_vec())
+        }
+
+        fn translate_aligned(&self, _input: &str, _context: &TranslationContext) -> Result<(AlignedTranslation, Option<f64>), TranslationError> {
+            Ok((
+                AlignedTranslation {
+                    text: "המגוף לסגור".to_string(),
+                    alignments: vec![
+                        WordAlignment { source: 0, target: 1, weight: 0.9 },
+                        WordAlignment { source: 1, target: 0, weight: 0.9 },
+                    ],
+                },
+                Some(1.0),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_inline_tags_follow_neural_alignment() {
+        let mut engine = TranslationEngine::new();
+        engine.register_backend(Language::Russian, Language::Hebrew, Arc::new(SwappingBackend));
+
+        let result = engine.translate(request("<b>Закрыть</b> клапан")).await.unwrap();
+
+        assert_eq!(result.segments[0].alignments.len(), 2);
+        // בלי היישור התגית הייתה נצמדת למילה הראשונה באופן יחסי
+        assert_eq!(result.segments[0].translated, "המגוף <b>לסגור</b>");
+    }
+
+    #[tokio::test]
+    async fn test_post_edits_follow_unchanged_segments() {
+        let (engine, _) = engine();
+        let result = engine.translate(request("Закрыть клапан. Открыть насос. Проверить давление.")).await.unwrap();
+        let [first, second, third] = [0, 1, 2].map(|i| result.segments[i].translated.clone());
+
+        let edited = format!("{} יש להפעיל את המשאבה {}", first, third);
+        let post_edits = result.post_edits(&edited);
+
+        assert_eq!(post_edits[0].1, first);
+        assert_eq!(post_edits[1].1, "יש להפעיל את המשאבה");
+        assert_ne!(post_edits[1].1, second);
+        assert_eq!(post_edits[2].1, third);
+    }
+
+    #[tokio::test]
+    async fn test_calibrated_confidence_drives_review_filter() {
+        let (engine, _) = engine();
+        let calibrator = ConfidenceCalibrator {
+            fallback: Some(CalibrationMap::Isotonic { points: vec![(0.0, 0.0), (1.0, 0.4)] }),
+            ..Default::default()
+        };
+        let engine = engine.with_calibrator(calibrator);
+
+        let result = engine.translate(request("Закрыть клапан. Открыть насос.")).await.unwrap();
+
+        let segment = &result.segments[0];
+        assert!(segment.confidence < segment.raw_confidence);
+        assert!((segment.confidence - 0.4 * segment.raw_confidence).abs() < 1e-9);
+        assert_eq!(result.segments_for_review(0.5).count(), 2);
+        assert_eq!(result.segments_for_review(0.1).count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_batched_backend_serves_concurrent_requests() {
+        let backend = Arc::new(CountingBackend { contexts: Mutex::new(Vec::new()) });
+        let scheduler = BatchScheduler::spawn(backend, BatchingConfig {
+            max_batch_size: 8,
+            max_latency_ms: 100,
+            ..Default::default()
+        });
+        let mut engine = TranslationEngine::new();
+        engine.register_batched_backend(Language::Russian, Language::Hebrew, scheduler.clone());
+
+        let (first, second) = tokio::join!(
+            engine.translate(request("Закрыть клапан.")),
+            engine.translate(request("Открыть насос.")),
+        );
+
+        assert!(first.is_ok() && second.is_ok());
+        // שני הסגמנטים, מבקשות שונות, רצו באותה אצווה
+        let snapshot = scheduler.metrics().snapshot();
+        assert_eq!((snapshot.batches, snapshot.segments), (1, 2));
+    }
+
+    #[tokio::test]
+    async fn test_style_guide_selects_register() {
+        let mut engine = TranslationEngine::new();
+        engine.register_backend(Language::Russian, Language::Hebrew, Arc::new(FixedBackend {
+            output: "עכשיו על כבאי לסגור את המגוף",
+            probability: Some(1.0),
+        }));
+        let mut style_guide = StyleGuide::new(FormalityLevel::Formal);
+        style_guide.add_domain_rule("fire_protection".to_string(), vec!["כבאי => לוחם אש".to_string()]);
+        let engine = engine.with_style_guide(style_guide).with_domain("fire_protection");
+
+        let result = engine.translate(request("Сейчас пожарный должен закрыть задвижку.")).await.unwrap();
+
+        assert!(result.segments[0].translated.starts_with("כעת על לוחם אש לסגור את המגוף"));
+    }
+
+    #[tokio::test]
+    async fn test_style_guide_leaves_memory_hits() {
+        let (mut engine, _) = engine();
+        engine.translation_memory.insert("Закрыть клапан".to_string(), "עכשיו לסגור את המגוף".to_string());
+        let engine = engine.with_style_guide(StyleGuide::new(FormalityLevel::Formal));
+
+        let result = engine.translate(request("Закрыть клапан. Открыть насос.")).await.unwrap();
+
+        assert_eq!(result.segments[0].translated, "עכשיו לסגור את המגוף");
+        assert_eq!(result.segments[0].backend, Some(CandidateSource::TranslationMemory));
+    }
+
+    #[tokio::test]
+    async fn test_neural_candidate_beats_untranslated_rules_echo() {
+        for probability in [None, Some(0.6)] {
+            let mut engine = TranslationEngine::new();
+            engine.register_backend(Language::Russian, Language::Hebrew, Arc::new(FixedBackend {
+                output: "לסגור את המגוף",
+                probability,
+            }));
+
+            let result = engine.translate(request("Закрыть задвижку.")).await.unwrap();
+
+            assert_eq!(result.segments[0].translated, "לסגור את המגוף");
+            assert_eq!(result.segments[0].backend, Some(CandidateSource::Neural));
+        }
+    }
+
+    #[test]
+    fn test_engine_config_selects_backend_per_direction() {
+        let config: EngineConfig = toml::from_str(
+            "[backends.ru-he]\ntype = \"onnx\"\nmodel_dir = \"models/onnx\"\n",
+        ).unwrap();
+
+        assert!(matches!(
+            config.backend_for(Language::Russian, Language::Hebrew),
+            BackendConfig::Onnx { intra_threads: None, .. }
+        ));
+        assert!(matches!(config.backend_for(Language::Hebrew, Language::Russian), BackendConfig::Rules));
+
+        let engine = TranslationEngine::with_config(&EngineConfig::default()).unwrap();
+        assert!(engine.neural_backends.is_empty());
+    }
+
+    #[test]
+    fn test_registry_backend_requires_production_version() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut config = EngineConfig::default();
+        config.set_backend(Language::Russian, Language::Hebrew, BackendConfig::Registry {
+            storage_dir: temp_dir.path().join("models"),
+            name: "ru-he".to_string(),
+            vocabulary_path: temp_dir.path().join("vocab.json"),
+        });
+
+        let error = TranslationEngine::with_config(&config).err().unwrap();
+        assert!(error.to_string().contains("אין גרסת ייצור"));
+    }
+
+    #[tokio::test]
+    async fn test_neural_backend_runs_off_the_executor() {
+        let backend = Arc::new(RecordingBackend::fixed("לסגור את המגוף"));
+        let mut engine = TranslationEngine::new();
+        engine.register_backend(Language::Russian, Language::Hebrew, backend.clone());
+
+        let result = engine.translate(request("Закрыть задвижку.")).await.unwrap();
+
+        assert_eq!(result.segments[0].backend, Some(CandidateSource::Neural));
+        assert_eq!(backend.threads().len(), 1);
+        assert_ne!(backend.threads()[0], std::thread::current().id());
+    }
+
+    /// מנוע בדיקה שמתרגם את המונח כמאושר, עם יישור, רק כשהמונחים נמסרים לו
+    struct TermAwareBackend;
+
+    impl TranslationBackend for TermAwareBackend {
+        fn translate(&self, input: &[String]) -> Result<Vec<String>, TranslationError> {
+            Ok(input.iter().map(|_| "לסגור את השסתום.".to_string()).collect())
+        }
+
+        fn translate_constrained(
+            &self,
+            _input: &str,
+            _context: &TranslationContext,
+            _terms: &TermsDatabase,
+        ) -> Result<(AlignedTranslation, Option<f64>), TranslationError> {
+            Ok((
+                AlignedTranslation {
+                    text: "לסגור את המגוף.".to_string(),
+                    alignments: vec![
+                        WordAlignment { source: 0, target: 0, weight: 0.9 },
+                        WordAlignment { source: 1, target: 2, weight: 0.9 },
+                    ],
+                },
+                None,
+            ))
+        }
+    }
+
+    fn terms() -> Arc<TermsDatabase> {
+        let mut terms = TermsDatabase::new();
+        terms.add_term(crate::technical_terms::TechnicalTerm {
+            term_he: "מגוף".to_string(),
+            term_ru: "задвижка".to_string(),
+            domain: "fire_protection".to_string(),
+            context: String::new(),
+            examples: Vec::new(),
+            synonyms: Vec::new(),
+            source: String::new(),
+            confidence: 1.0,
+        });
+        Arc::new(terms)
+    }
+
+    #[tokio::test]
+    async fn test_neural_candidate_is_decoded_with_terms() {
+        let mut engine = TranslationEngine::new().with_terms(terms());
+        engine.register_backend(Language::Russian, Language::Hebrew, Arc::new(TermAwareBackend));
+
+        let result = engine.translate(request("Закрыть задвижку.")).await.unwrap();
+
+        assert_eq!(result.segments[0].backend, Some(CandidateSource::Neural));
+        assert_eq!(result.segments[0].translated, "לסגור את המגוף.");
+    }
+
+    #[tokio::test]
+    async fn test_candidate_missing_term_ranks_lower() {
+        let mut engine = TranslationEngine::new().with_terms(terms());
+        engine.register_backend(Language::Russian, Language::Hebrew, Arc::new(FixedBackend {
+            output: "לסגור את השסתום",
+            probability: Some(1.0),
+        }));
+        engine.custom_dictionary.insert("Закрыть задвижку".to_string(), "לסגור את המגוף".to_string());
+
+        let result = engine.translate(request("Закрыть задвижку.")).await.unwrap();
+        let segment = &result.segments[0];
+
+        assert_eq!(segment.backend, Some(CandidateSource::Dictionary));
+        assert_eq!(segment.translated, "לסגור את המגוף");
+        let neural = segment
+            .ranked_alternatives
+            .iter()
+            .find(|candidate| candidate.source == CandidateSource::Neural)
+            .unwrap();
+        assert_eq!(neural.features.term_coverage, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_batched_backend_keeps_terms_and_alignments() {
+        let scheduler = BatchScheduler::spawn(Arc::new(TermAwareBackend), BatchingConfig::default());
+        let mut engine = TranslationEngine::new().with_terms(terms());
+        engine.register_batched_backend(Language::Russian, Language::Hebrew, scheduler);
+
+        let result = engine.translate(request("Закрыть задвижку.")).await.unwrap();
+
+        assert_eq!(result.segments[0].backend, Some(CandidateSource::Neural));
+        assert_eq!(result.segments[0].translated, "לסגור את המגוף.");
+        assert_eq!(result.segments[0].alignments.len(), 2);
+    }
+}
@@ -1,39 +0,0 @@
-use anyhow::Result;
-use std::sync::Arc;
-use tokio::sync::Mutex;
-use crate::morphology::{
-    HebrewAnalyzer, RussianAnalyzer,
-    MorphologyAnalysis, MorphologyError,
-    Gender, Number,
-};
-
-#[derive(Debug)]
-pub struct TranslationEngine {
-    hebrew_analyzer: Arc<HebrewAnalyzer>,
-    russian_analyzer: Arc<RussianAnalyzer>,
-}
-
-impl TranslationEngine {
-    pub fn new() -> Self {
-        Self {
-            hebrew_analyzer: Arc::new(HebrewAnalyzer::new()),
-            russian_analyzer: Arc::new(RussianAnalyzer::new()),
-        }
-    }
-
-    pub async fn translate(&self, text: &str, source_lang: &str, target_lang: &str) -> Result<String> {
-        let morphological_analysis = match source_lang {
-            "he" => self.hebrew_analyzer.analyze(text)?,
-            "ru" => self.russian_analyzer.analyze(text)?,
-            _ => return Err(anyhow::anyhow!("שפת מקור לא נתמכת")),
-        };
-
-        let translated = self.apply_translation(text, &morphological_analysis, source_lang, target_lang)?;
-        Ok(translated)
-    }
-
-    fn apply_translation(&self, text: &str, analysis: &MorphologyAnalysis, source_lang: &str, target_lang: &str) -> Result<String> {
-        // כאן יבוא מימוש התרגום האמיתי
-        Ok(format!("תרגום של {} מ-{} ל-{}", text, source_lang, target_lang))
-    }
-} 
\ No newline at end of file
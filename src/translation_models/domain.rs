@@ -28,6 +28,17 @@ impl DomainModel {
             "ברז".to_string(),
             "מתזים".to_string(),
             "ספרינקלרים".to_string(),
+            // המקור ברוסית: גזעים, כדי לתפוס את כל צורות הנטייה
+            "систем".to_string(),
+            "монтаж".to_string(),
+            "трубопровод".to_string(),
+            "насос".to_string(),
+            "давлени".to_string(),
+            "расход".to_string(),
+            "задвижк".to_string(),
+            "клапан".to_string(),
+            "оросител".to_string(),
+            "спринклер".to_string(),
         ]);
         
         // אתחול מונחים משפטיים
@@ -40,6 +51,14 @@ impl DomainModel {
             "הסכם".to_string(),
             "התחייבות".to_string(),
             "אחריות".to_string(),
+            "договор".to_string(),
+            "постановлени".to_string(),
+            "гост".to_string(),
+            "разрешени".to_string(),
+            "лицензи".to_string(),
+            "соглашени".to_string(),
+            "обязательств".to_string(),
+            "ответственност".to_string(),
         ]);
         
         // אתחול משקולות
@@ -18,6 +18,50 @@ pub struct TranslationContext {
     /// רמת פורמליות
     pub formality: Formality,
     pub metadata: HashMap<String, String>,
+    /// הסגמנטים הקודמים במסמך עם התרגום שנבחר להם, מהישן לחדש
+    pub previous_segments: Vec<ContextSegment>,
+}
+
+/// סגמנט קודם במסמך
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContextSegment {
+    pub source: String,
+    pub target: String,
+}
+
+impl TranslationContext {
+    pub fn new(domain: Domain, style: Style, formality: Formality) -> Self {
+        Self {
+            domain,
+            style,
+            formality,
+            metadata: HashMap::new(),
+            previous_segments: Vec::new(),
+        }
+    }
+
+    /// הקשר למסמך חדש, עם תחום וסגנון שזוהו מהטקסט המלא
+    pub fn detect(text: &str, domain_model: &DomainModel, style_model: &StyleModel) -> Result<Self, TranslationError> {
+        let domain = domain_model.detect(text)?;
+        let style = style_model.detect(text)?;
+        let formality = match style {
+            Style::Formal | Style::Technical => Formality::High,
+            Style::Professional => Formality::Medium,
+            Style::Casual | Style::Informal => Formality::Low,
+            Style::Custom(_) => Formality::Medium,
+        };
+        Ok(Self::new(domain, style, formality))
+    }
+
+    /// הוספת סגמנט מתורגם לחלון ההקשר; נשמרים רק window הסגמנטים האחרונים
+    pub fn push_segment(&mut self, source: &str, target: &str, window: usize) {
+        self.previous_segments.push(ContextSegment {
+            source: source.to_string(),
+            target: target.to_string(),
+        });
+        let excess = self.previous_segments.len().saturating_sub(window);
+        self.previous_segments.drain(..excess);
+    }
 }
 
 /// תחומי תרגום
@@ -131,12 +175,7 @@ impl TranslationCache {
         Self {
             source: String::new(),
             target: String::new(),
-            context: TranslationContext {
-                domain: Domain::Technical,
-                style: Style::Formal,
-                formality: Formality::High,
-                metadata: HashMap::new(),
-            },
+            context: TranslationContext::new(Domain::Technical, Style::Formal, Formality::High),
             quality_score: 0.0,
         }
     }
@@ -155,4 +194,27 @@ pub trait TermAnalyzer {
 pub trait LearningModel {
     fn train(&mut self, source: &str, target: &str, context: &TranslationContext) -> Result<(), TranslationError>;
     fn evaluate(&self, source: &str, target: &str) -> Result<f64, TranslationError>;
-} 
\ No newline at end of file
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_on_russian_source() {
+        let context = TranslationContext::detect(
+            "Закрыть задвижку и проверить давление в трубопроводе насоса.",
+            &DomainModel::new(),
+            &StyleModel::new(),
+        )
+        .unwrap();
+        assert_eq!(context.domain, Domain::Technical);
+
+        let context = TranslationContext::detect(
+            "Договор и соглашение определяют ответственность сторон.",
+            &DomainModel::new(),
+            &StyleModel::new(),
+        )
+        .unwrap();
+        assert_eq!(context.domain, Domain::Legal);
+    }
+}
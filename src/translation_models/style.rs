@@ -36,11 +36,18 @@ impl StyleModel {
                 "כדלקמן".to_string(),
                 "באמצעות".to_string(),
                 "בהתייחס".to_string(),
+                "нижеследующ".to_string(),
+                "в соответствии".to_string(),
+                "следовательно".to_string(),
+                "посредством".to_string(),
+                "надлежит".to_string(),
             ],
             syntax_patterns: vec![
                 "יש לציין כי".to_string(),
                 "ניתן לקבוע כי".to_string(),
                 "בהתאם לאמור".to_string(),
+                "следует отметить".to_string(),
+                "с учетом изложенного".to_string(),
             ],
             formality_level: 0.9,
         });
@@ -54,11 +61,18 @@ impl StyleModel {
                 "נתונים".to_string(),
                 "ביצועים".to_string(),
                 "יעילות".to_string(),
+                "систем".to_string(),
+                "спецификаци".to_string(),
+                "стандарт".to_string(),
+                "данны".to_string(),
+                "производительност".to_string(),
             ],
             syntax_patterns: vec![
                 "בהתאם למפרט".to_string(),
                 "על פי התקן".to_string(),
                 "בהתאם לדרישות".to_string(),
+                "согласно спецификации".to_string(),
+                "согласно требованиям".to_string(),
             ],
             formality_level: 0.7,
         });
@@ -72,11 +86,17 @@ impl StyleModel {
                 "רגיל".to_string(),
                 "כזה".to_string(),
                 "ככה".to_string(),
+                "примерно".to_string(),
+                "нормально".to_string(),
+                "просто".to_string(),
+                "такой".to_string(),
             ],
             syntax_patterns: vec![
                 "אפשר גם".to_string(),
                 "זה בסדר".to_string(),
                 "פשוט צריך".to_string(),
+                "можно и".to_string(),
+                "просто надо".to_string(),
             ],
             formality_level: 0.3,
         });